@@ -0,0 +1,44 @@
+use std::hint::black_box;
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use seeing_noise::bench::{ALGORITHMS, frame, sampler};
+
+const OCTAVES: [u32; 3] = [1, 4, 8];
+const PIXELS_PER_FRAME: u64 = 400 * 400;
+
+fn per_pixel(c: &mut Criterion) {
+    let mut group = c.benchmark_group("per_pixel");
+    group.throughput(Throughput::Elements(1));
+    for algorithm in ALGORITHMS {
+        for octaves in OCTAVES {
+            let noise = sampler(algorithm, octaves);
+            group.bench_with_input(BenchmarkId::new(algorithm, octaves), &octaves, |b, _| {
+                // Walk across the plane so lattice lookups do not stay in one cell.
+                let mut x = 0.0;
+                b.iter(|| {
+                    x += 0.173;
+                    noise.sample(black_box(x), black_box(x * 0.618))
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+fn full_frame(c: &mut Criterion) {
+    let mut group = c.benchmark_group("full_frame");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(PIXELS_PER_FRAME));
+    for algorithm in ALGORITHMS {
+        for octaves in OCTAVES {
+            let noise = sampler(algorithm, octaves);
+            group.bench_with_input(BenchmarkId::new(algorithm, octaves), &octaves, |b, _| {
+                b.iter(|| frame(noise.as_ref()))
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, per_pixel, full_frame);
+criterion_main!(benches);
@@ -0,0 +1,114 @@
+//! Samples fewer pixels while a slider is dragged, as many as keep the frames fast enough.
+
+use std::cell::{Cell, LazyCell};
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{Event, HtmlElement, HtmlInputElement};
+
+use crate::{supersampling::SMOOTH_FRAME_MS, *};
+
+/// Pixels along each side of the sampled blocks, from full resolution down.
+const STEPS: [usize; 4] = [1, 2, 4, 8];
+
+thread_local! {
+    /// Side of the blocks the next frame samples once each.
+    static STEP: Cell<usize> = const { Cell::new(1) };
+    /// What the last frame would have taken at full resolution, in milliseconds.
+    static FULL_FRAME_MS: Cell<f64> = const { Cell::new(0.) };
+}
+
+elements!(
+    (adaptive_resolution, HtmlInputElement),
+    (adaptive_readout, HtmlElement),
+);
+define_event_closure!(adaptive_drag, Event, on_input);
+define_event_closure!(adaptive_release, Event, on_change);
+
+/// Inputs and changes bubble up to the document, a slider fires the first while it is dragged and
+/// the second once it is let go.
+pub fn setup() {
+    add_callback!(document, "input", adaptive_drag);
+    add_callback!(document, "change", adaptive_release);
+}
+
+pub fn step() -> usize {
+    STEP.with(Cell::get)
+}
+
+fn is_slider(event: &Event) -> bool {
+    event
+        .target()
+        .and_then(|t| t.dyn_into::<HtmlInputElement>().ok())
+        .is_some_and(|input| input.type_() == "range")
+}
+
+/// The finest step whose frames still fit in `budget_ms`, the coarsest when none does.
+fn step_for(full_frame_ms: f64, budget_ms: f64) -> usize {
+    STEPS
+        .into_iter()
+        .find(|&step| full_frame_ms / (step * step) as f64 <= budget_ms)
+        .unwrap_or(STEPS[STEPS.len() - 1])
+}
+
+/// Spreads the samples at the start of `field`, one per `step`×`step` block of a `size`×`size`
+/// image, over their blocks. Every pixel reads a sample at or before it, so going backwards
+/// never overwrites a sample still to be read.
+pub fn expand(field: &mut [f64], step: usize, size: usize) {
+    if step == 1 {
+        return;
+    }
+    let columns = size.div_ceil(step);
+    for i in (0..size * size).rev() {
+        field[i] = field[(i / size / step) * columns + i % size / step];
+    }
+}
+
+/// The slider's own handler has drawn its frame by now, the ones after it follow the drag.
+fn on_input(event: Event) {
+    if !is_slider(&event) || !is_checked!(adaptive_resolution) {
+        return;
+    }
+    let step = step_for(FULL_FRAME_MS.with(Cell::get), SMOOTH_FRAME_MS);
+    STEP.with(|s| s.set(step));
+    ADAPTIVE_READOUT.with(|r| {
+        r.set_inner_text(&match step {
+            1 => String::new(),
+            _ => format!("Dragging at 1/{step} resolution"),
+        })
+    });
+}
+
+fn on_change(event: Event) {
+    if !is_slider(&event) || STEP.with(|s| s.replace(1)) == 1 {
+        return;
+    }
+    ADAPTIVE_READOUT.with(|r| r.set_inner_text(""));
+    update_current_noise();
+}
+
+/// Runs after the frame time is taken, the frame sampled `step` times fewer pixels along each side.
+pub fn on_frame_drawn() {
+    let step = step() as f64;
+    FULL_FRAME_MS.with(|f| f.set(supersampling::frame_ms() * step * step));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slow_frames_are_sampled_in_blocks() {
+        assert_eq!(step_for(10., 33.), 1);
+        assert_eq!(step_for(100., 33.), 2);
+        assert_eq!(step_for(500., 33.), 4);
+        assert_eq!(step_for(10_000., 33.), 8);
+
+        let mut field = [1., 2., 3., 4.];
+        expand(&mut field, 1, 2);
+        assert_eq!(field, [1., 2., 3., 4.]);
+        // Three pixels wide in blocks of two, the last blocks are cut short.
+        let mut field = [1., 2., 3., 4., 0., 0., 0., 0., 0.];
+        expand(&mut field, 2, 3);
+        assert_eq!(field, [1., 1., 2., 1., 1., 2., 3., 3., 4.]);
+    }
+}
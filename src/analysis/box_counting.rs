@@ -0,0 +1,153 @@
+use std::cell::LazyCell;
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlElement, HtmlInputElement};
+
+use crate::{
+    analysis::variogram::fit_line,
+    charts::amplitude_spectrum,
+    drawer::{RESOLUTION, draw_circle, draw_line, draw_text, fill_polygon, stroke_polygon},
+    *,
+};
+
+const SIZE: usize = RESOLUTION as usize;
+/// Box sides in pixels. Boxes much larger than the features all hold a piece of the contour, so
+/// the largest side is left out of the fit.
+const BOX_SIZES: [usize; 7] = [1, 2, 4, 8, 16, 32, 64];
+const FIT_SIZES: usize = 6;
+const INSET_WIDTH: f64 = 180.;
+const INSET_HEIGHT: f64 = 120.;
+
+elements!(
+    (show_box_counting, HtmlInputElement),
+    (box_counting_readout, HtmlElement),
+);
+define_closure!(box_counting_change, on_box_counting_change);
+
+pub fn setup() {
+    add_callback!(show_box_counting, "input", box_counting_change);
+}
+
+fn on_box_counting_change() {
+    BOX_COUNTING_READOUT.with(|r| r.set_inner_text(""));
+    update_current_noise();
+}
+
+/// Pixels where the field crosses `level` towards their right or lower neighbour.
+fn contour(field: &[f64], level: f64) -> Vec<bool> {
+    (0..SIZE * SIZE)
+        .map(|i| {
+            let (x, y) = (i % SIZE, i / SIZE);
+            let above = field[i] > level;
+            (x + 1 < SIZE && (field[i + 1] > level) != above)
+                || (y + 1 < SIZE && (field[i + SIZE] > level) != above)
+        })
+        .collect()
+}
+
+/// Number of boxes of side `size` holding at least one contour pixel.
+fn occupied_boxes(contour: &[bool], size: usize) -> usize {
+    let boxes = SIZE.div_ceil(size);
+    let mut occupied = vec![false; boxes * boxes];
+    for (i, _) in contour.iter().enumerate().filter(|(_, c)| **c) {
+        occupied[(i / SIZE / size) * boxes + i % SIZE / size] = true;
+    }
+    occupied.iter().filter(|o| **o).count()
+}
+
+/// Points of log2 box count against log2 of the inverse box size, their slope is the dimension.
+fn box_counts(contour: &[bool]) -> Vec<(f64, f64)> {
+    BOX_SIZES
+        .iter()
+        .map(|&size| {
+            let count = occupied_boxes(contour, size).max(1);
+            (-(size as f64).log2(), (count as f64).log2())
+        })
+        .collect()
+}
+
+/// The log-log plot in the top left corner, with the fitted line.
+fn draw_fit(points: &[(f64, f64)], fit: Option<(f64, f64)>) {
+    let (left, top) = (4., 4.);
+    let frame = [
+        (left, top),
+        (left + INSET_WIDTH, top),
+        (left + INSET_WIDTH, top + INSET_HEIGHT),
+        (left, top + INSET_HEIGHT),
+    ];
+    fill_polygon(&frame, "rgba(255, 255, 255, 0.85)");
+    stroke_polygon(&frame, "#444444");
+
+    let high = points.iter().fold(0f64, |h, p| h.max(p.1));
+    let widest = BOX_SIZES[BOX_SIZES.len() - 1].ilog2() as f64;
+    let to_inset = |(x, y): (f64, f64)| {
+        (
+            left + 8. + (x + widest) / widest * (INSET_WIDTH - 16.),
+            top + 8. + (high - y) / high.max(1.) * (INSET_HEIGHT - 28.),
+        )
+    };
+    for &point in points {
+        let (x, y) = to_inset(point);
+        draw_circle(x, y, 2.5, "#1f77b4");
+    }
+    if let Some((slope, intercept)) = fit {
+        let ends =
+            [points[FIT_SIZES - 1].0, points[0].0].map(|x| to_inset((x, slope * x + intercept)));
+        draw_line(ends[0].0, ends[0].1, ends[1].0, ends[1].1, "#1f77b4");
+    }
+    draw_text(
+        "log2 boxes by log2 1/size",
+        left + 4.,
+        top + INSET_HEIGHT - 6.,
+        "#444444",
+    );
+}
+
+pub fn enabled() -> bool {
+    is_checked!(show_box_counting)
+}
+
+/// Takes the contour through the median of the field, so it splits the image in half, and
+/// estimates its dimension by counting the boxes it passes through at every box size.
+pub fn on_frame_drawn(field: &[f64]) {
+    let mut sorted = field.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let contour = contour(field, sorted[sorted.len() / 2]);
+
+    let points = box_counts(&contour);
+    let fit = contour
+        .contains(&true)
+        .then(|| fit_line(&points[..FIT_SIZES]))
+        .flatten();
+    draw_fit(&points, fit);
+
+    let expected = match amplitude_spectrum::hurst_exponent() {
+        Some(h) => format!(
+            ", fBm with H = {h:.2} has contours of dimension {:.2}",
+            2. - h.clamp(0., 1.)
+        ),
+        None => String::new(),
+    };
+    let text = match fit {
+        Some((dimension, _)) => format!("Contour dimension \u{2248} {dimension:.2}{expected}"),
+        None => "The field has no contour".to_string(),
+    };
+    BOX_COUNTING_READOUT.with(|r| r.set_inner_text(&text));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_straight_edge_has_dimension_one() {
+        let field: Vec<f64> = (0..SIZE * SIZE)
+            .map(|i| if i % SIZE < SIZE / 3 { -1. } else { 1. })
+            .collect();
+        let contour = contour(&field, 0.);
+        assert_eq!(contour.iter().filter(|c| **c).count(), SIZE);
+        let points = box_counts(&contour);
+        let (dimension, _) = fit_line(&points[..FIT_SIZES]).unwrap();
+        assert!((dimension - 1.).abs() < 0.05, "dimension {dimension}");
+    }
+}
@@ -0,0 +1,106 @@
+use std::f64::consts::PI;
+
+use crate::drawer::{HALF_RESOLUTION, RESOLUTION};
+
+/// Side of the square crop of the field that spectral analysis works on.
+pub const SPECTRUM_SIZE: usize = 256;
+
+/// In-place iterative radix-2 FFT. `re` and `im` must have the same power of two length.
+pub fn fft(re: &mut [f64], im: &mut [f64], inverse: bool) {
+    let n = re.len();
+    assert!(n.is_power_of_two() && im.len() == n);
+
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let sign = if inverse { 1. } else { -1. };
+    let mut length = 2;
+    while length <= n {
+        let angle = sign * 2. * PI / length as f64;
+        let (w_im, w_re) = angle.sin_cos();
+        for start in (0..n).step_by(length) {
+            let (mut t_re, mut t_im) = (1., 0.);
+            for k in 0..length / 2 {
+                let (a, b) = (start + k, start + k + length / 2);
+                let u_re = re[b] * t_re - im[b] * t_im;
+                let u_im = re[b] * t_im + im[b] * t_re;
+                re[b] = re[a] - u_re;
+                im[b] = im[a] - u_im;
+                re[a] += u_re;
+                im[a] += u_im;
+                (t_re, t_im) = (t_re * w_re - t_im * w_im, t_re * w_im + t_im * w_re);
+            }
+        }
+        length <<= 1;
+    }
+
+    if inverse {
+        re.iter_mut().for_each(|v| *v /= n as f64);
+        im.iter_mut().for_each(|v| *v /= n as f64);
+    }
+}
+
+/// 2D FFT of a `size` x `size` row-major grid, rows first and then columns.
+pub fn fft_2d(re: &mut [f64], im: &mut [f64], size: usize, inverse: bool) {
+    for row in 0..size {
+        let range = row * size..(row + 1) * size;
+        fft(&mut re[range.clone()], &mut im[range], inverse);
+    }
+
+    let (mut column_re, mut column_im) = (vec![0.; size], vec![0.; size]);
+    for column in 0..size {
+        for row in 0..size {
+            column_re[row] = re[row * size + column];
+            column_im[row] = im[row * size + column];
+        }
+        fft(&mut column_re, &mut column_im, inverse);
+        for row in 0..size {
+            re[row * size + column] = column_re[row];
+            im[row * size + column] = column_im[row];
+        }
+    }
+}
+
+/// Power spectrum of the centered `SPECTRUM_SIZE` crop of the field. The crop has its mean
+/// removed and a Hann window applied, so the crop edges do not leak energy onto the axes.
+/// Frequencies are returned unshifted, use `frequency` to find the frequency of an index.
+pub fn power_spectrum(field: &[f64]) -> Vec<f64> {
+    let offset = HALF_RESOLUTION as usize - SPECTRUM_SIZE / 2;
+    let crop: Vec<f64> = (0..SPECTRUM_SIZE * SPECTRUM_SIZE)
+        .map(|i| {
+            field[(offset + i / SPECTRUM_SIZE) * RESOLUTION as usize + offset + i % SPECTRUM_SIZE]
+        })
+        .collect();
+    let mean = crop.iter().sum::<f64>() / crop.len() as f64;
+
+    let hann = |i: usize| 0.5 - 0.5 * (2. * PI * i as f64 / (SPECTRUM_SIZE - 1) as f64).cos();
+    let mut re: Vec<f64> = crop
+        .iter()
+        .enumerate()
+        .map(|(i, v)| (v - mean) * hann(i % SPECTRUM_SIZE) * hann(i / SPECTRUM_SIZE))
+        .collect();
+    let mut im = vec![0.; re.len()];
+
+    fft_2d(&mut re, &mut im, SPECTRUM_SIZE, false);
+    re.iter().zip(&im).map(|(r, i)| r * r + i * i).collect()
+}
+
+/// Signed frequency, in cycles per crop, of an unshifted spectrum index.
+pub fn frequency(index: usize) -> i32 {
+    if index < SPECTRUM_SIZE / 2 {
+        index as i32
+    } else {
+        index as i32 - SPECTRUM_SIZE as i32
+    }
+}
@@ -0,0 +1,182 @@
+use std::cell::LazyCell;
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlElement, HtmlInputElement};
+
+use crate::{
+    drawer::{RESOLUTION, clear_canvas, draw_circle, draw_text},
+    field::draw_field,
+    noises::{helpers::shuffle, noise::Noise},
+    *,
+};
+
+const AVALANCHE_SAMPLES: u32 = 2000;
+const SCATTER_SAMPLES: u32 = 4000;
+
+elements!((hash_readout, HtmlElement));
+
+/// One of the hash building blocks, with how many input and output bits it has.
+struct HashQualityImpl {
+    function: HashFunction,
+    permutation: [usize; 256],
+    seed: u32,
+}
+
+impl HashQualityImpl {
+    pub fn new(function: HashFunction, seed: u32) -> Self {
+        let mut permutation: [usize; 256] = std::array::from_fn(|i| i);
+        shuffle(&mut permutation, seed);
+
+        HashQualityImpl {
+            function,
+            permutation,
+            seed,
+        }
+    }
+
+    fn input_bits(&self) -> u32 {
+        match self.function {
+            HashFunction::PermutationTable => 16,
+            HashFunction::SquirrelNoise | HashFunction::MultiplyAdd => 32,
+        }
+    }
+
+    fn output_bits(&self) -> u32 {
+        match self.function {
+            HashFunction::PermutationTable => 8,
+            HashFunction::SquirrelNoise | HashFunction::MultiplyAdd => 32,
+        }
+    }
+
+    fn hash(&self, input: u32) -> u32 {
+        match self.function {
+            // The 2D lattice hash of the gradient noises, x in the low byte and y in the high byte.
+            HashFunction::PermutationTable => {
+                let x = (input & 255) as usize;
+                let y = ((input >> 8) & 255) as usize;
+                self.permutation[(self.permutation[x] + y) & 255] as u32
+            }
+            HashFunction::SquirrelNoise => squirrel_noise5::squirrel_noise5(input, self.seed),
+            // A multiply-add on its own only carries changes towards the higher bits.
+            HashFunction::MultiplyAdd => input.wrapping_mul(1_103_515_245).wrapping_add(self.seed),
+        }
+    }
+
+    fn mask(bits: u32) -> u32 {
+        if bits == 32 {
+            u32::MAX
+        } else {
+            (1 << bits) - 1
+        }
+    }
+
+    /// Probability that flipping input bit `i` flips output bit `o`, indexed `[i][o]`.
+    /// A good hash sits at 0.5 everywhere.
+    pub fn avalanche(&self) -> Vec<Vec<f64>> {
+        let (input_bits, output_bits) = (self.input_bits(), self.output_bits());
+        let mut flips = vec![vec![0u32; output_bits as usize]; input_bits as usize];
+
+        for sample in 0..AVALANCHE_SAMPLES {
+            let input = squirrel_noise5::squirrel_noise5(sample, 0x5eed) & Self::mask(input_bits);
+            let output = self.hash(input);
+            for (i, row) in flips.iter_mut().enumerate() {
+                let changed = output ^ self.hash(input ^ (1 << i));
+                for (o, count) in row.iter_mut().enumerate() {
+                    *count += (changed >> o) & 1;
+                }
+            }
+        }
+
+        flips
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|&c| c as f64 / AVALANCHE_SAMPLES as f64)
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Pairs of consecutive outputs scaled to [0, 1], any visible structure is a correlation.
+    pub fn consecutive_pairs(&self) -> Vec<(f64, f64)> {
+        let input_mask = Self::mask(self.input_bits());
+        let output_range = Self::mask(self.output_bits()) as f64;
+        (0..SCATTER_SAMPLES)
+            .map(|n| {
+                let a = self.hash(n & input_mask) as f64 / output_range;
+                let b = self.hash((n + 1) & input_mask) as f64 / output_range;
+                (a, b)
+            })
+            .collect()
+    }
+}
+
+impl HashQualityNoise {
+    fn on_setup() {}
+
+    fn on_update() {}
+
+    fn draw_avalanche(hash: &HashQualityImpl) {
+        let matrix = hash.avalanche();
+        let (inputs, outputs) = (matrix.len(), matrix[0].len());
+
+        // Each cell shows the deviation from the ideal 0.5, white is perfect.
+        let field = (0..(RESOLUTION * RESOLUTION) as usize)
+            .map(|i| {
+                let x = i % RESOLUTION as usize * inputs / RESOLUTION as usize;
+                let y = i / RESOLUTION as usize * outputs / RESOLUTION as usize;
+                (matrix[x][y] - 0.5) * 2.
+            })
+            .collect();
+        draw_field(field);
+        draw_text("input bit \u{2192}", 6., RESOLUTION as f64 - 6., "#000000");
+
+        let bias = matrix
+            .iter()
+            .flatten()
+            .map(|p| (p - 0.5).abs())
+            .sum::<f64>()
+            / (inputs * outputs) as f64;
+        HASH_READOUT.with(|r| {
+            r.set_inner_text(&format!(
+                "Average avalanche bias over {inputs} input and {outputs} output bits: {bias:.3} (0 is ideal, 0.5 means some bits never change)"
+            ))
+        });
+    }
+
+    fn draw_scatter(hash: &HashQualityImpl) {
+        clear_canvas("#ffffff");
+        for (a, b) in hash.consecutive_pairs() {
+            draw_circle(
+                a * (RESOLUTION - 1) as f64,
+                (1. - b) * (RESOLUTION - 1) as f64,
+                1.5,
+                "#000000",
+            );
+        }
+        HASH_READOUT.with(|r| {
+            r.set_inner_text(&format!(
+                "Hash of n against hash of n + 1 for {SCATTER_SAMPLES} consecutive inputs"
+            ))
+        });
+    }
+
+    fn generate_and_draw(settings: HashQualityNoiseSettings) {
+        let hash = HashQualityImpl::new(settings.hash_function, settings.seed.value());
+        match settings.hash_test {
+            HashTest::Avalanche => Self::draw_avalanche(&hash),
+            HashTest::ConsecutiveScatter => Self::draw_scatter(&hash),
+        }
+    }
+}
+
+define_noise!(hash_quality,
+    sliders:[
+        (seed, u32, 0., 42., 1000.)
+    ];
+    radios:[
+        (hash_function, (permutation_table), (squirrel_noise), (multiply_add)),
+        (hash_test, (avalanche), (consecutive_scatter))
+    ];
+    checkboxes:[];
+);
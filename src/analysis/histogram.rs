@@ -0,0 +1,115 @@
+use std::cell::LazyCell;
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlElement, HtmlInputElement};
+
+use crate::{
+    drawer::{RESOLUTION, draw_line, draw_text, fill_polygon, stroke_polygon},
+    image_import, *,
+};
+
+pub const BINS: usize = 64;
+const INSET_WIDTH: f64 = 180.;
+const INSET_HEIGHT: f64 = 100.;
+const FIELD_STYLE: &str = "#1f77b4";
+const IMAGE_STYLE: &str = "#ff7f0e";
+
+elements!(
+    (compare_histogram, HtmlInputElement),
+    (histogram_readout, HtmlElement),
+);
+define_closure!(histogram_change, on_histogram_change);
+
+pub fn setup() {
+    add_callback!(compare_histogram, "input", histogram_change);
+}
+
+fn on_histogram_change() {
+    HISTOGRAM_READOUT.with(|r| r.set_inner_text(""));
+    update_current_noise();
+}
+
+/// Fraction of the values in each of `BINS` equal bins over [-1, 1].
+pub fn histogram(values: &[f64]) -> [f64; BINS] {
+    let mut bins = [0.; BINS];
+    for value in values {
+        let bin = ((value + 1.) / 2. * BINS as f64).clamp(0., (BINS - 1) as f64) as usize;
+        bins[bin] += 1.;
+    }
+    bins.iter_mut()
+        .for_each(|b| *b /= values.len().max(1) as f64);
+    bins
+}
+
+pub fn mean_and_deviation(values: &[f64]) -> (f64, f64) {
+    let count = values.len().max(1) as f64;
+    let mean = values.iter().sum::<f64>() / count;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / count;
+    (mean, variance.sqrt())
+}
+
+/// Shared area of two histograms, 1 when the values are distributed identically.
+fn overlap(a: &[f64; BINS], b: &[f64; BINS]) -> f64 {
+    a.iter().zip(b).map(|(a, b)| a.min(*b)).sum()
+}
+
+/// Draws the histograms as outlines in the bottom left corner, on a common vertical scale.
+fn draw_histograms(histograms: &[(&[f64; BINS], &str)]) {
+    let (left, top) = (4., RESOLUTION as f64 - INSET_HEIGHT - 4.);
+    let frame = [
+        (left, top),
+        (left + INSET_WIDTH, top),
+        (left + INSET_WIDTH, top + INSET_HEIGHT),
+        (left, top + INSET_HEIGHT),
+    ];
+    fill_polygon(&frame, "rgba(255, 255, 255, 0.85)");
+    stroke_polygon(&frame, "#444444");
+
+    let max = histograms
+        .iter()
+        .flat_map(|(bins, _)| bins.iter().copied())
+        .fold(0., f64::max)
+        .max(1e-12);
+    let bin_width = (INSET_WIDTH - 8.) / BINS as f64;
+    let bottom = top + INSET_HEIGHT - 4.;
+    for (bins, style) in histograms {
+        let mut previous = (left + 4., bottom);
+        for (i, fraction) in bins.iter().enumerate() {
+            let y = bottom - fraction / max * (INSET_HEIGHT - 20.);
+            let x = left + 4. + i as f64 * bin_width;
+            draw_line(previous.0, previous.1, x, y, style);
+            draw_line(x, y, x + bin_width, y, style);
+            previous = (x + bin_width, y);
+        }
+        draw_line(previous.0, previous.1, previous.0, bottom, style);
+    }
+    draw_text("-1", left + 4., top + 12., "#444444");
+    draw_text("1", left + INSET_WIDTH - 12., top + 12., "#444444");
+}
+
+pub fn enabled() -> bool {
+    is_checked!(compare_histogram)
+}
+
+pub fn on_frame_drawn(field: &[f64]) {
+    let field_bins = histogram(field);
+    let (field_mean, field_deviation) = mean_and_deviation(field);
+    let summary = format!("Field (blue): mean {field_mean:.3}, deviation {field_deviation:.3}.");
+
+    let Some(image) = image_import::image() else {
+        draw_histograms(&[(&field_bins, FIELD_STYLE)]);
+        HISTOGRAM_READOUT
+            .with(|r| r.set_inner_text(&format!("{summary} Import an image to compare with it.")));
+        return;
+    };
+
+    let image_bins = histogram(&image);
+    let (image_mean, image_deviation) = mean_and_deviation(&image);
+    draw_histograms(&[(&field_bins, FIELD_STYLE), (&image_bins, IMAGE_STYLE)]);
+    HISTOGRAM_READOUT.with(|r| {
+        r.set_inner_text(&format!(
+            "{summary} Image (orange): mean {image_mean:.3}, deviation {image_deviation:.3}. The histograms overlap by {:.0}%.",
+            overlap(&field_bins, &image_bins) * 100.
+        ))
+    });
+}
@@ -0,0 +1,58 @@
+use crate::{drawer::RESOLUTION, field::FIELD, view};
+
+mod box_counting;
+pub mod fft;
+pub mod hash_quality;
+pub mod histogram;
+mod periodicity;
+mod randomness;
+mod rose;
+pub mod similarity;
+mod variogram;
+mod watershed;
+
+pub fn setup() {
+    periodicity::setup();
+    histogram::setup();
+    rose::setup();
+    variogram::setup();
+    box_counting::setup();
+    randomness::setup();
+    watershed::setup();
+}
+
+/// Whether a pass is switched on, and the pass itself.
+type Pass = (fn() -> bool, fn(&[f64]));
+
+/// The passes in drawing order. The basins replace the image, the other passes draw over them.
+const PASSES: [Pass; 7] = [
+    (watershed::enabled, watershed::on_frame_drawn),
+    (periodicity::enabled, periodicity::on_frame_drawn),
+    (rose::enabled, rose::on_frame_drawn),
+    (variogram::enabled, variogram::on_frame_drawn),
+    (box_counting::enabled, box_counting::on_frame_drawn),
+    (histogram::enabled, histogram::on_frame_drawn),
+    (randomness::enabled, randomness::on_frame_drawn),
+];
+
+/// Called after every noise redraw, runs the enabled analysis passes over the retained field.
+/// Their overlays are drawn in canvas space, so they are skipped outside of the flat view.
+pub fn on_frame_drawn() {
+    if !view::is_flat() {
+        return;
+    }
+    let mut passes = PASSES.iter().filter(|(enabled, _)| enabled()).peekable();
+    if passes.peek().is_none() {
+        return;
+    }
+
+    FIELD.with(|f| {
+        let field = f.borrow();
+        if field.len() != (RESOLUTION * RESOLUTION) as usize {
+            return;
+        }
+        for (_, pass) in passes {
+            pass(&field);
+        }
+    });
+}
@@ -0,0 +1,128 @@
+use std::cell::LazyCell;
+
+use rayon::prelude::*;
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlElement, HtmlInputElement};
+
+use crate::{
+    drawer::{RESOLUTION, draw_line, draw_text},
+    *,
+};
+
+/// Lags with less overlap than this are too noisy to trust.
+const MIN_OVERLAP: usize = 64;
+const MAX_LAG: usize = RESOLUTION as usize - MIN_OVERLAP;
+/// Only every fourth row (or column) is correlated, which is plenty for a 400 pixel field.
+const LINE_STRIDE: usize = 4;
+/// The field has to decorrelate below this before a later peak counts as a repetition.
+const DECORRELATED: f64 = 0.3;
+const PERIOD_THRESHOLD: f64 = 0.9;
+
+#[derive(Copy, Clone)]
+enum Axis {
+    Horizontal,
+    Vertical,
+}
+
+elements!(
+    (detect_periodicity, HtmlInputElement),
+    (periodicity_readout, HtmlElement),
+);
+define_closure!(periodicity_change, on_periodicity_change);
+
+pub fn setup() {
+    add_callback!(detect_periodicity, "input", periodicity_change);
+}
+
+fn on_periodicity_change() {
+    PERIODICITY_READOUT.with(|r| r.set_inner_text(""));
+    update_current_noise();
+}
+
+/// Pearson correlation between the field and itself shifted by `lag` pixels along `axis`.
+fn autocorrelation(field: &[f64], lag: usize, axis: Axis) -> f64 {
+    let size = RESOLUTION as usize;
+    let at = |along: usize, across: usize| match axis {
+        Axis::Horizontal => field[across * size + along],
+        Axis::Vertical => field[along * size + across],
+    };
+
+    let (mut sum_a, mut sum_b, mut sum_aa, mut sum_bb, mut sum_ab, mut count) =
+        (0., 0., 0., 0., 0., 0.);
+    for across in (0..size).step_by(LINE_STRIDE) {
+        for along in 0..size - lag {
+            let (a, b) = (at(along, across), at(along + lag, across));
+            sum_a += a;
+            sum_b += b;
+            sum_aa += a * a;
+            sum_bb += b * b;
+            sum_ab += a * b;
+            count += 1.;
+        }
+    }
+
+    let covariance = sum_ab / count - sum_a / count * sum_b / count;
+    let variance_a = sum_aa / count - (sum_a / count).powi(2);
+    let variance_b = sum_bb / count - (sum_b / count).powi(2);
+    covariance / (variance_a * variance_b).sqrt().max(1e-12)
+}
+
+/// Finds the strongest repetition along `axis`: the best correlated lag after the field has
+/// first decorrelated from itself. Returns the lag in pixels and its correlation.
+fn detect_period(field: &[f64], axis: Axis) -> Option<(usize, f64)> {
+    let correlations: Vec<f64> = (0..MAX_LAG)
+        .into_par_iter()
+        .map(|lag| autocorrelation(field, lag, axis))
+        .collect();
+
+    let decorrelated_at = correlations.iter().position(|&r| r < DECORRELATED)?;
+    correlations[decorrelated_at..]
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.total_cmp(b.1))
+        .map(|(i, &r)| (decorrelated_at + i, r))
+        .filter(|&(_, r)| r > PERIOD_THRESHOLD)
+}
+
+/// Draws lines every `period` pixels. The phase is taken from the canvas center, where
+/// every noise places its origin, so lattice wrap-arounds line up with the drawn boundaries.
+fn draw_boundaries(period: usize, axis: Axis) {
+    let size = RESOLUTION as f64;
+    let center = HALF_RESOLUTION as f64;
+    let first = center - (center / period as f64).floor() * period as f64;
+
+    let mut offset = first;
+    while offset < size {
+        match axis {
+            Axis::Horizontal => draw_line(offset, 0., offset, size, "#ff0000"),
+            Axis::Vertical => draw_line(0., offset, size, offset, "#ff0000"),
+        }
+        offset += period as f64;
+    }
+}
+
+pub fn enabled() -> bool {
+    is_checked!(detect_periodicity)
+}
+
+pub fn on_frame_drawn(field: &[f64]) {
+    let mut report = Vec::new();
+    for (axis, name) in [
+        (Axis::Horizontal, "Horizontal"),
+        (Axis::Vertical, "Vertical"),
+    ] {
+        match detect_period(field, axis) {
+            Some((period, correlation)) => {
+                draw_boundaries(period, axis);
+                report.push(format!(
+                    "{name} period: {period} px (correlation {correlation:.3})"
+                ));
+            }
+            None => report.push(format!("{name}: no repetition within {MAX_LAG} px")),
+        }
+    }
+
+    draw_text(&report[0], 6., 16., "#ff0000");
+    draw_text(&report[1], 6., 32., "#ff0000");
+    PERIODICITY_READOUT.with(|r| r.set_inner_text(&report.join("\n")));
+}
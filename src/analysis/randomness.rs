@@ -0,0 +1,228 @@
+//! Quick checks on the raw field, passed or flagged against what the noise should do.
+
+use std::cell::LazyCell;
+use std::f64::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlElement, HtmlInputElement, HtmlSelectElement};
+
+use crate::{
+    analysis::{
+        fft::{SPECTRUM_SIZE, frequency, power_spectrum},
+        histogram::{BINS, histogram, mean_and_deviation},
+    },
+    *,
+};
+
+/// Largest distance of the mean from 0 for noises centered on 0.
+const MEAN_TOLERANCE: f64 = 0.1;
+/// Smallest deviation before the field counts as nearly constant.
+const MIN_DEVIATION: f64 = 0.05;
+/// Largest share of the values clipped at -1 or 1.
+const MAX_CLIPPED: f64 = 0.01;
+/// Largest chi-square distance, per pixel, of the histogram from a normal curve. Neighbouring
+/// pixels are far from independent, so the plain statistic would fail every noise.
+const MAX_CHI_SQUARE: f64 = 0.1;
+/// Largest excess of the power along the axes over the power along the diagonals.
+const MAX_ALIGNMENT: f64 = 0.3;
+/// Half width of the wedges around the axes and the diagonals.
+const WEDGE: f64 = PI / 18.;
+/// Frequencies closer to the center than this mostly measure the window, not the noise.
+const MIN_RADIUS: f64 = 2.;
+
+elements!(
+    (show_randomness_report, HtmlInputElement),
+    (randomness_report, HtmlElement),
+    (noise_select, HtmlSelectElement),
+);
+define_closure!(randomness_change, on_randomness_change);
+
+pub fn setup() {
+    add_callback!(show_randomness_report, "input", randomness_change);
+}
+
+fn on_randomness_change() {
+    RANDOMNESS_REPORT.with(|r| r.set_inner_text(""));
+    update_current_noise();
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Verdict {
+    Pass,
+    Warn,
+    /// The noise is not meant to pass this test.
+    Skipped,
+}
+
+impl Verdict {
+    fn of(pass: bool) -> Self {
+        if pass { Verdict::Pass } else { Verdict::Warn }
+    }
+
+    fn symbol(&self) -> &'static str {
+        match self {
+            Verdict::Pass => "\u{2713}",
+            Verdict::Warn => "\u{26a0}",
+            Verdict::Skipped => "\u{2013}",
+        }
+    }
+}
+
+/// What a noise algorithm should look like.
+#[derive(Clone, Copy)]
+struct Expectation {
+    /// Values spread evenly around 0.
+    centered: bool,
+    /// Values with a bell shaped histogram, the sum of many random contributions.
+    bell_shaped: bool,
+    /// The same in every direction.
+    isotropic: bool,
+}
+
+fn expectation(noise: &str) -> Expectation {
+    let (centered, bell_shaped, isotropic) = match noise {
+        "perlin" | "simplex" | "wavelet" => (true, true, true),
+        "gabor" | "anisotropic" => (true, true, false),
+        "worley" => (false, false, true),
+        _ => (false, false, false),
+    };
+    Expectation {
+        centered,
+        bell_shaped,
+        isotropic,
+    }
+}
+
+/// Chi-square distance of the histogram from a normal curve with the same mean and deviation,
+/// divided by the number of values.
+fn normal_chi_square(field: &[f64], mean: f64, deviation: f64) -> f64 {
+    let width = 2. / BINS as f64;
+    let density = |x: f64| (-0.5 * ((x - mean) / deviation).powi(2)).exp();
+    let expected: Vec<f64> = (0..BINS)
+        .map(|b| density(-1. + (b as f64 + 0.5) * width))
+        .collect();
+    let total: f64 = expected.iter().sum();
+    histogram(field)
+        .iter()
+        .zip(&expected)
+        .map(|(observed, expected)| (observed, expected / total))
+        .filter(|(_, expected)| *expected > 1e-9)
+        .map(|(observed, expected)| (observed - expected).powi(2) / expected)
+        .sum()
+}
+
+/// How much more power the spectrum holds along the axes than along the diagonals, 0 when the
+/// field does not care about the lattice directions.
+fn axis_alignment(spectrum: &[f64]) -> f64 {
+    let max_radius = (SPECTRUM_SIZE / 2) as f64;
+    let (mut axes, mut diagonals) = (0., 0.);
+    for (i, power) in spectrum.iter().enumerate() {
+        let fx = frequency(i % SPECTRUM_SIZE) as f64;
+        let fy = frequency(i / SPECTRUM_SIZE) as f64;
+        if !(MIN_RADIUS..=max_radius).contains(&fx.hypot(fy)) {
+            continue;
+        }
+        let angle = fy.atan2(fx).rem_euclid(FRAC_PI_2);
+        if angle < WEDGE || angle > FRAC_PI_2 - WEDGE {
+            axes += power;
+        } else if (angle - FRAC_PI_4).abs() < WEDGE {
+            diagonals += power;
+        }
+    }
+    axes / f64::max(diagonals, 1e-12) - 1.
+}
+
+/// Runs every check on the field, with what the noise is expected to do deciding the verdicts.
+fn checks(field: &[f64], expectation: Expectation) -> Vec<(Verdict, String)> {
+    let (mean, deviation) = mean_and_deviation(field);
+    let clipped = field.iter().filter(|v| v.abs() >= 1.).count() as f64 / field.len() as f64;
+    let mut checks = Vec::new();
+
+    checks.push(if expectation.centered {
+        (
+            Verdict::of(mean.abs() < MEAN_TOLERANCE),
+            format!("Mean {mean:.3}, expected 0 \u{b1} {MEAN_TOLERANCE}"),
+        )
+    } else {
+        (
+            Verdict::Skipped,
+            format!("Mean {mean:.3}, this noise is not centered on 0"),
+        )
+    });
+
+    checks.push((
+        Verdict::of(deviation > MIN_DEVIATION && clipped < MAX_CLIPPED),
+        format!(
+            "Deviation {deviation:.3}, {:.1}% of the values clipped at \u{b1}1",
+            clipped * 100.
+        ),
+    ));
+
+    let chi_square = normal_chi_square(field, mean, deviation.max(1e-9));
+    checks.push(if expectation.bell_shaped {
+        (
+            Verdict::of(chi_square < MAX_CHI_SQUARE),
+            format!("Histogram \u{3c7}\u{b2}/N {chi_square:.3} from a normal curve"),
+        )
+    } else {
+        (
+            Verdict::Skipped,
+            format!("Histogram \u{3c7}\u{b2}/N {chi_square:.3}, this noise is not bell shaped"),
+        )
+    });
+
+    let alignment = axis_alignment(&power_spectrum(field));
+    checks.push(if expectation.isotropic {
+        (
+            Verdict::of(alignment < MAX_ALIGNMENT),
+            format!("Lattice alignment {alignment:.2}, power along the axes over the diagonals"),
+        )
+    } else {
+        (
+            Verdict::Skipped,
+            format!("Lattice alignment {alignment:.2}, this noise has a direction by design"),
+        )
+    });
+    checks
+}
+
+pub fn enabled() -> bool {
+    is_checked!(show_randomness_report)
+}
+
+pub fn on_frame_drawn(field: &[f64]) {
+    let noise = parse_value!(noise_select, String);
+    let lines: Vec<String> = checks(field, expectation(&noise))
+        .iter()
+        .map(|(verdict, text)| format!("{} {text}", verdict.symbol()))
+        .collect();
+    RANDOMNESS_REPORT.with(|r| r.set_inner_text(&lines.join("\n")));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::drawer::RESOLUTION;
+
+    #[test]
+    fn stripes_fail_the_lattice_check_and_rings_pass() {
+        let size = RESOLUTION as usize;
+        let center = size as f64 / 2.;
+        let stripes: Vec<f64> = (0..size * size)
+            .map(|i| 0.5 * ((i % size) as f64 * 0.3).sin())
+            .collect();
+        let rings: Vec<f64> = (0..size * size)
+            .map(|i| {
+                let (x, y) = ((i % size) as f64 - center, (i / size) as f64 - center);
+                0.5 * (x.hypot(y) * 0.3).sin()
+            })
+            .collect();
+        let isotropic = expectation("perlin");
+
+        let stripes = checks(&stripes, isotropic);
+        assert_eq!(stripes[0].0, Verdict::Pass);
+        assert_eq!(stripes[3].0, Verdict::Warn);
+        assert_eq!(checks(&rings, isotropic)[3].0, Verdict::Pass);
+        assert_eq!(checks(&rings, expectation("image"))[3].0, Verdict::Skipped);
+    }
+}
@@ -0,0 +1,132 @@
+use std::cell::LazyCell;
+use std::f64::consts::PI;
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlElement, HtmlInputElement};
+
+use crate::{
+    analysis::fft::{SPECTRUM_SIZE, frequency, power_spectrum},
+    drawer::{RESOLUTION, draw_circle, draw_line, draw_text, fill_polygon, stroke_polygon},
+    *,
+};
+
+/// Number of direction bins over half a turn, the spectrum of a real field is symmetric.
+const ROSE_BINS: usize = 36;
+/// Frequencies closer to the center than this mostly measure the window, not the noise.
+const MIN_RADIUS: f64 = 2.;
+const INSET_SIZE: f64 = 150.;
+
+elements!((show_rose, HtmlInputElement), (rose_readout, HtmlElement),);
+define_closure!(rose_change, on_rose_change);
+
+pub fn setup() {
+    add_callback!(show_rose, "input", rose_change);
+}
+
+fn on_rose_change() {
+    ROSE_READOUT.with(|r| r.set_inner_text(""));
+    update_current_noise();
+}
+
+/// Total spectral power in each direction bin, angles measured counter-clockwise from +x
+/// with y pointing up, as in the rest of the explanations.
+fn directional_energy(spectrum: &[f64]) -> [f64; ROSE_BINS] {
+    let mut bins = [0.; ROSE_BINS];
+    let max_radius = (SPECTRUM_SIZE / 2) as f64;
+
+    for (i, power) in spectrum.iter().enumerate() {
+        let fx = frequency(i % SPECTRUM_SIZE) as f64;
+        let fy = -frequency(i / SPECTRUM_SIZE) as f64;
+        let radius = (fx * fx + fy * fy).sqrt();
+        if !(MIN_RADIUS..=max_radius).contains(&radius) {
+            continue;
+        }
+
+        let angle = fy.atan2(fx).rem_euclid(PI);
+        bins[((angle / PI * ROSE_BINS as f64) as usize).min(ROSE_BINS - 1)] += power;
+    }
+    bins
+}
+
+/// 0 for a perfectly isotropic field, approaching 1 when all energy points one way.
+fn anisotropy(bins: &[f64; ROSE_BINS]) -> f64 {
+    let max = bins.iter().copied().fold(0., f64::max);
+    let min = bins.iter().copied().fold(f64::MAX, f64::min);
+    (max - min) / (max + min).max(1e-12)
+}
+
+/// Draws the rose as a filled polar plot in the top right corner, mirrored to a full turn.
+fn draw_rose(bins: &[f64; ROSE_BINS]) {
+    let max = bins.iter().copied().fold(0., f64::max).max(1e-12);
+    let left = RESOLUTION as f64 - INSET_SIZE - 4.;
+    let (center_x, center_y) = (left + INSET_SIZE / 2., 4. + INSET_SIZE / 2.);
+    let radius = INSET_SIZE / 2. - 6.;
+
+    let frame = [
+        (left, 4.),
+        (left + INSET_SIZE, 4.),
+        (left + INSET_SIZE, 4. + INSET_SIZE),
+        (left, 4. + INSET_SIZE),
+    ];
+    fill_polygon(&frame, "rgba(255, 255, 255, 0.85)");
+    stroke_polygon(&frame, "#444444");
+    draw_line(
+        center_x - radius,
+        center_y,
+        center_x + radius,
+        center_y,
+        "#bbbbbb",
+    );
+    draw_line(
+        center_x,
+        center_y - radius,
+        center_x,
+        center_y + radius,
+        "#bbbbbb",
+    );
+
+    let bin_width = PI / ROSE_BINS as f64;
+    for half in 0..2 {
+        for (b, energy) in bins.iter().enumerate() {
+            let length = radius * energy / max;
+            let start = b as f64 * bin_width + half as f64 * PI;
+            let end = start + bin_width;
+            fill_polygon(
+                &[
+                    (center_x, center_y),
+                    (
+                        center_x + length * start.cos(),
+                        center_y - length * start.sin(),
+                    ),
+                    (center_x + length * end.cos(), center_y - length * end.sin()),
+                ],
+                "#1f77b4",
+            );
+        }
+    }
+    draw_circle(center_x, center_y, 2., "#000000");
+    draw_text("spectrum rose", left + 4., 4. + INSET_SIZE - 6., "#444444");
+}
+
+pub fn enabled() -> bool {
+    is_checked!(show_rose)
+}
+
+pub fn on_frame_drawn(field: &[f64]) {
+    let bins = directional_energy(&power_spectrum(field));
+    draw_rose(&bins);
+
+    let (strongest, _) = bins
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.total_cmp(b.1))
+        .unwrap_or((0, &0.));
+    let direction = (strongest as f64 + 0.5) * 180. / ROSE_BINS as f64;
+    let stripes = (direction + 90.) % 180.;
+    ROSE_READOUT.with(|r| {
+        r.set_inner_text(&format!(
+            "Anisotropy: {:.2} (0 is the same in every direction). Most energy varies along {direction:.0}\u{b0}, so features are stretched along {stripes:.0}\u{b0}.",
+            anisotropy(&bins)
+        ))
+    });
+}
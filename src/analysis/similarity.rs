@@ -0,0 +1,86 @@
+//! Pixel difference metrics between two fields of the same size.
+
+use crate::drawer::RESOLUTION;
+
+/// Side of the square windows SSIM compares.
+const WINDOW: usize = 8;
+
+pub fn rmse(a: &[f64], b: &[f64]) -> f64 {
+    let sum: f64 = a.iter().zip(b).map(|(a, b)| (a - b).powi(2)).sum();
+    (sum / a.len().max(1) as f64).sqrt()
+}
+
+pub fn max_abs_diff(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(a, b)| (a - b).abs()).fold(0., f64::max)
+}
+
+/// Mean structural similarity over non-overlapping windows, 1 for identical fields. The usual
+/// definition slides a gaussian window over every pixel, plain blocks are close enough to rank
+/// two renders and much cheaper. The fields are compared as intensities in [0, 1], SSIM assumes
+/// values that can not be negative.
+pub fn ssim(a: &[f64], b: &[f64]) -> f64 {
+    let size = RESOLUTION as usize;
+    let c1 = 0.01_f64.powi(2);
+    let c2 = 0.03_f64.powi(2);
+    let count = (WINDOW * WINDOW) as f64;
+    let intensity = |v: f64| (v + 1.) / 2.;
+
+    let mut total = 0.;
+    let mut windows = 0;
+    for top in (0..=size - WINDOW).step_by(WINDOW) {
+        for left in (0..=size - WINDOW).step_by(WINDOW) {
+            let pixels = || {
+                (top..top + WINDOW)
+                    .flat_map(move |y| (left..left + WINDOW).map(move |x| y * size + x))
+            };
+            let mean_a = pixels().map(|i| intensity(a[i])).sum::<f64>() / count;
+            let mean_b = pixels().map(|i| intensity(b[i])).sum::<f64>() / count;
+            let (mut variance_a, mut variance_b, mut covariance) = (0., 0., 0.);
+            for i in pixels() {
+                let (da, db) = (intensity(a[i]) - mean_a, intensity(b[i]) - mean_b);
+                variance_a += da * da;
+                variance_b += db * db;
+                covariance += da * db;
+            }
+            let (variance_a, variance_b, covariance) =
+                (variance_a / count, variance_b / count, covariance / count);
+
+            total += (2. * mean_a * mean_b + c1) * (2. * covariance + c2)
+                / ((mean_a * mean_a + mean_b * mean_b + c1) * (variance_a + variance_b + c2));
+            windows += 1;
+        }
+    }
+    total / windows as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gradient() -> Vec<f64> {
+        let size = RESOLUTION as usize;
+        (0..size * size)
+            .map(|i| ((i % size) as f64 * 0.1).sin() * ((i / size) as f64 * 0.07).cos())
+            .collect()
+    }
+
+    #[test]
+    fn identical_fields_do_not_differ() {
+        let field = gradient();
+        assert_eq!(rmse(&field, &field), 0.);
+        assert_eq!(max_abs_diff(&field, &field), 0.);
+        assert!((ssim(&field, &field) - 1.).abs() < 1e-12);
+    }
+
+    #[test]
+    fn metrics_grow_with_the_difference() {
+        let field = gradient();
+        let shifted: Vec<f64> = field.iter().map(|v| v + 0.1).collect();
+        let inverted: Vec<f64> = field.iter().map(|v| -v).collect();
+
+        assert!((rmse(&field, &shifted) - 0.1).abs() < 1e-12);
+        assert!((max_abs_diff(&field, &shifted) - 0.1).abs() < 1e-12);
+        assert!(ssim(&field, &shifted) > 0.9);
+        assert!(ssim(&field, &inverted) < 0.);
+    }
+}
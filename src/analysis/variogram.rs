@@ -0,0 +1,177 @@
+use std::cell::LazyCell;
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlElement, HtmlInputElement};
+
+use crate::{
+    charts::amplitude_spectrum,
+    drawer::{RESOLUTION, draw_circle, draw_line, draw_text, fill_polygon, stroke_polygon},
+    *,
+};
+
+const SIZE: usize = RESOLUTION as usize;
+/// Lags in pixels, doubling up to a sixth of the canvas.
+const LAGS: [usize; 7] = [1, 2, 4, 8, 16, 32, 64];
+/// Lags used for the fit. The smallest ones fall under the finest octave, where every smooth
+/// noise has a slope of 2, and the largest ones level off once the lowest octave repeats.
+const FIT_LAGS: std::ops::RangeInclusive<usize> = 2..=32;
+const INSET_WIDTH: f64 = 180.;
+const INSET_HEIGHT: f64 = 120.;
+
+elements!(
+    (show_variogram, HtmlInputElement),
+    (variogram_readout, HtmlElement),
+);
+define_closure!(variogram_change, on_variogram_change);
+
+pub fn setup() {
+    add_callback!(show_variogram, "input", variogram_change);
+}
+
+fn on_variogram_change() {
+    VARIOGRAM_READOUT.with(|r| r.set_inner_text(""));
+    update_current_noise();
+}
+
+/// Mean squared difference between pixels `lag` apart, along x and y together.
+fn structure_function(field: &[f64], lag: usize) -> f64 {
+    let (mut sum, mut count) = (0., 0);
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let value = field[y * SIZE + x];
+            if x + lag < SIZE {
+                sum += (field[y * SIZE + x + lag] - value).powi(2);
+                count += 1;
+            }
+            if y + lag < SIZE {
+                sum += (field[(y + lag) * SIZE + x] - value).powi(2);
+                count += 1;
+            }
+        }
+    }
+    sum / count.max(1) as f64
+}
+
+/// Least squares slope and intercept of the points.
+pub fn fit_line(points: &[(f64, f64)]) -> Option<(f64, f64)> {
+    let count = points.len() as f64;
+    let mean_x = points.iter().map(|p| p.0).sum::<f64>() / count;
+    let mean_y = points.iter().map(|p| p.1).sum::<f64>() / count;
+    let covariance: f64 = points
+        .iter()
+        .map(|(x, y)| (x - mean_x) * (y - mean_y))
+        .sum();
+    let variance: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+    let slope = covariance / variance;
+    slope
+        .is_finite()
+        .then_some((slope, mean_y - slope * mean_x))
+}
+
+/// Plots log2 of the structure function against log2 of the lag in the bottom right corner,
+/// with the fitted line over the lags it was fitted to.
+fn draw_variogram(points: &[(f64, f64)], fit: Option<(f64, f64)>) {
+    let left = RESOLUTION as f64 - INSET_WIDTH - 4.;
+    let top = RESOLUTION as f64 - INSET_HEIGHT - 4.;
+    let frame = [
+        (left, top),
+        (left + INSET_WIDTH, top),
+        (left + INSET_WIDTH, top + INSET_HEIGHT),
+        (left, top + INSET_HEIGHT),
+    ];
+    fill_polygon(&frame, "rgba(255, 255, 255, 0.85)");
+    stroke_polygon(&frame, "#444444");
+
+    let (low, high) = points
+        .iter()
+        .fold((f64::MAX, f64::MIN), |(l, h), p| (l.min(p.1), h.max(p.1)));
+    let span = (high - low).max(1e-9);
+    let last = LAGS[LAGS.len() - 1].ilog2() as f64;
+    let to_inset = |(x, y): (f64, f64)| {
+        (
+            left + 8. + x / last * (INSET_WIDTH - 16.),
+            top + 8. + (high - y) / span * (INSET_HEIGHT - 28.),
+        )
+    };
+    for pair in points.windows(2) {
+        let ((x0, y0), (x1, y1)) = (to_inset(pair[0]), to_inset(pair[1]));
+        draw_line(x0, y0, x1, y1, "#1f77b4");
+    }
+    for &point in points {
+        let (x, y) = to_inset(point);
+        draw_circle(x, y, 2.5, "#1f77b4");
+    }
+    if let Some((slope, intercept)) = fit {
+        let ends = [FIT_LAGS.start(), FIT_LAGS.end()].map(|&lag| {
+            let x = lag.ilog2() as f64;
+            to_inset((x, slope * x + intercept))
+        });
+        draw_line(ends[0].0, ends[0].1, ends[1].0, ends[1].1, "#d62728");
+    }
+    draw_text(
+        "log2 variogram by log2 lag",
+        left + 4.,
+        top + INSET_HEIGHT - 6.,
+        "#444444",
+    );
+}
+
+pub fn enabled() -> bool {
+    is_checked!(show_variogram)
+}
+
+/// Fits the slope of the variogram, twice the Hurst exponent for fBm, and compares it with the
+/// one the fBm controls ask for.
+pub fn on_frame_drawn(field: &[f64]) {
+    let points: Vec<(f64, f64)> = LAGS
+        .iter()
+        .map(|&lag| {
+            let gamma = structure_function(field, lag).max(f64::MIN_POSITIVE);
+            (lag.ilog2() as f64, gamma.log2())
+        })
+        .collect();
+    let fitted: Vec<(f64, f64)> = LAGS
+        .iter()
+        .zip(&points)
+        .filter(|(lag, _)| FIT_LAGS.contains(lag))
+        .map(|(_, &point)| point)
+        .collect();
+    let fit = fit_line(&fitted);
+    draw_variogram(&points, fit);
+
+    let Some((slope, _)) = fit else {
+        VARIOGRAM_READOUT.with(|r| r.set_inner_text("The variogram is flat"));
+        return;
+    };
+    let requested = match amplitude_spectrum::hurst_exponent() {
+        Some(h) => format!(", the octave amplitudes ask for H = {h:.2}"),
+        None => String::new(),
+    };
+    VARIOGRAM_READOUT.with(|r| {
+        r.set_inner_text(&format!(
+            "Variogram slope {slope:.2} over lags of {} to {} pixels, so H \u{2248} {:.2}{requested}",
+            FIT_LAGS.start(),
+            FIT_LAGS.end(),
+            slope / 2.
+        ))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_ramp_grows_with_the_square_of_the_lag() {
+        let ramp: Vec<f64> = (0..SIZE * SIZE)
+            .map(|i| (i % SIZE) as f64 * 0.001)
+            .collect();
+        let points: Vec<(f64, f64)> = FIT_LAGS
+            .filter(|lag| lag.is_power_of_two())
+            .map(|lag| ((lag as f64).log2(), structure_function(&ramp, lag).log2()))
+            .collect();
+        let (slope, _) = fit_line(&points).unwrap();
+        assert!((slope - 2.).abs() < 0.05, "slope {slope}");
+        assert!(fit_line(&[(1., 0.)]).is_none());
+    }
+}
@@ -0,0 +1,164 @@
+use std::{cell::LazyCell, cmp::Ordering, collections::BinaryHeap};
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlElement, HtmlInputElement};
+
+use crate::{
+    drawer::{RESOLUTION, draw_image},
+    *,
+};
+
+const SIZE: usize = RESOLUTION as usize;
+const NEIGHBOURS: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+const COLOR_SEED: u32 = 0xba5;
+
+elements!(
+    (show_watershed, HtmlInputElement),
+    (watershed_readout, HtmlElement),
+);
+define_closure!(watershed_change, on_watershed_change);
+
+pub fn setup() {
+    add_callback!(show_watershed, "input", watershed_change);
+}
+
+fn on_watershed_change() {
+    WATERSHED_READOUT.with(|r| r.set_inner_text(""));
+    update_current_noise();
+}
+
+/// A pixel waiting to be flooded, the lowest one comes out of the heap first.
+struct Flooding {
+    height: f64,
+    index: usize,
+}
+
+impl PartialEq for Flooding {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Flooding {}
+
+impl PartialOrd for Flooding {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Flooding {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.height.total_cmp(&self.height)
+    }
+}
+
+fn neighbours(index: usize) -> impl Iterator<Item = usize> {
+    let (x, y) = ((index % SIZE) as isize, (index / SIZE) as isize);
+    NEIGHBOURS.iter().filter_map(move |(dx, dy)| {
+        let (nx, ny) = (x + dx, y + dy);
+        let inside = (0..SIZE as isize).contains(&nx) && (0..SIZE as isize).contains(&ny);
+        inside.then(|| ny as usize * SIZE + nx as usize)
+    })
+}
+
+/// The basin of every pixel and the number of basins. Every local minimum, with the equally high
+/// minima it touches, starts a basin, and the water rises from all of them at once: the lowest
+/// pixel next to a basin joins it, so the basins meet along the ridges between them.
+fn basins(field: &[f64]) -> (Vec<usize>, usize) {
+    let mut labels = vec![usize::MAX; field.len()];
+    let mut heap = BinaryHeap::new();
+    let is_minimum = |i: usize| neighbours(i).all(|n| field[n] >= field[i]);
+    let mut count = 0;
+    for start in 0..field.len() {
+        if labels[start] != usize::MAX || !is_minimum(start) {
+            continue;
+        }
+        labels[start] = count;
+        let mut plateau = vec![start];
+        while let Some(i) = plateau.pop() {
+            heap.push(Flooding {
+                height: field[i],
+                index: i,
+            });
+            for n in neighbours(i) {
+                if labels[n] == usize::MAX && field[n] == field[i] && is_minimum(n) {
+                    labels[n] = count;
+                    plateau.push(n);
+                }
+            }
+        }
+        count += 1;
+    }
+
+    while let Some(Flooding { index, .. }) = heap.pop() {
+        for n in neighbours(index) {
+            if labels[n] == usize::MAX {
+                labels[n] = labels[index];
+                heap.push(Flooding {
+                    height: field[n],
+                    index: n,
+                });
+            }
+        }
+    }
+    (labels, count)
+}
+
+pub fn enabled() -> bool {
+    is_checked!(show_watershed)
+}
+
+/// Paints every basin in its own color, shaded by the height of the field, with the ridges
+/// between basins in black.
+pub fn on_frame_drawn(field: &[f64]) {
+    let (labels, count) = basins(field);
+    let rng = rng::current();
+    let mut pixels = Vec::with_capacity(field.len() * 4);
+    for (i, (&label, &value)) in labels.iter().zip(field).enumerate() {
+        if neighbours(i).any(|n| labels[n] != label) {
+            pixels.extend_from_slice(&[0, 0, 0, 255]);
+            continue;
+        }
+        let shade = 0.5 + 0.25 * (value.clamp(-1., 1.) + 1.);
+        let [r, g, b, _] = rng.bits(label as u32, COLOR_SEED).to_le_bytes();
+        let channel = |c: u8| ((64. + c as f64 * 0.75) * shade) as u8;
+        pixels.extend_from_slice(&[channel(r), channel(g), channel(b), 255]);
+    }
+    draw_image(&pixels, RESOLUTION, RESOLUTION, 0., 0.);
+    WATERSHED_READOUT.with(|r| r.set_inner_text(&format!("{count} basins")));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn two_pits_split_along_the_ridge_between_them() {
+        let pits = [(100., 200.), (300., 200.)];
+        let field: Vec<f64> = (0..SIZE * SIZE)
+            .map(|i| {
+                let (x, y) = ((i % SIZE) as f64, (i / SIZE) as f64);
+                pits.iter()
+                    .map(|(px, py)| (x - px).hypot(y - py))
+                    .fold(f64::MAX, f64::min)
+            })
+            .collect();
+        let (labels, count) = basins(&field);
+        assert_eq!(count, 2);
+        for y in 0..SIZE {
+            assert_eq!(labels[y * SIZE + 10], labels[200 * SIZE + 100]);
+            assert_eq!(labels[y * SIZE + 390], labels[200 * SIZE + 300]);
+        }
+        assert_ne!(labels[200 * SIZE + 100], labels[200 * SIZE + 300]);
+    }
+}
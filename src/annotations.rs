@@ -0,0 +1,264 @@
+//! Arrows, circles and labels drawn over the canvas on a layer of their own.
+
+use std::{
+    cell::{LazyCell, RefCell},
+    f64::consts::PI,
+};
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{
+    CanvasRenderingContext2d, HtmlCanvasElement, HtmlElement, HtmlInputElement, HtmlSelectElement,
+    MouseEvent,
+};
+
+use crate::{drawer::CANVAS, *};
+
+/// Arrow head length as a fraction of the canvas height.
+const HEAD_LENGTH: f64 = 0.035;
+/// Half the angle between the two strokes of an arrow head.
+const HEAD_ANGLE: f64 = PI / 7.;
+/// Stroke width and label height as fractions of the canvas height.
+const LINE_WIDTH: f64 = 0.008;
+const FONT_SIZE: f64 = 0.045;
+
+type Point = (f64, f64);
+
+#[derive(Clone)]
+enum Shape {
+    Arrow { from: Point, to: Point },
+    Circle { center: Point, rim: Point },
+    Label { at: Point, text: String },
+}
+
+#[derive(Clone)]
+struct Annotation {
+    shape: Shape,
+    color: String,
+}
+
+thread_local! {
+    static ANNOTATIONS: RefCell<Vec<Annotation>> = const { RefCell::new(Vec::new()) };
+    /// Where the arrow or circle being dragged out started.
+    static DRAG_START: RefCell<Option<Point>> = const { RefCell::new(None) };
+}
+
+elements!(
+    (annotation_layer, HtmlCanvasElement),
+    (annotation_tool, HtmlSelectElement),
+    (annotation_text, HtmlInputElement),
+    (annotation_color, HtmlInputElement),
+    (annotation_undo, HtmlElement),
+    (annotation_clear, HtmlElement),
+);
+define_closure!(annotation_tool_input, on_tool_change);
+define_closure!(annotation_undo_click, undo);
+define_closure!(annotation_clear_click, clear);
+define_event_closure!(annotation_press, MouseEvent, on_press);
+define_event_closure!(annotation_drag, MouseEvent, on_drag);
+define_event_closure!(annotation_release, MouseEvent, on_release);
+
+pub fn setup() {
+    add_callback!(annotation_tool, "input", annotation_tool_input);
+    add_callback!(annotation_undo, "click", annotation_undo_click);
+    add_callback!(annotation_clear, "click", annotation_clear_click);
+    add_callback!(annotation_layer, "mousedown", annotation_press);
+    add_callback!(annotation_layer, "mousemove", annotation_drag);
+    add_callback!(annotation_layer, "mouseup", annotation_release);
+    add_callback!(annotation_layer, "mouseleave", annotation_release);
+    on_tool_change();
+}
+
+/// The two strokes of the head of an arrow pointing from `from` to `to`.
+fn arrow_head(from: Point, to: Point, length: f64) -> [Point; 2] {
+    let angle = (to.1 - from.1).atan2(to.0 - from.0);
+    [angle - HEAD_ANGLE, angle + HEAD_ANGLE]
+        .map(|side| (to.0 - length * side.cos(), to.1 - length * side.sin()))
+}
+
+fn context(canvas: &HtmlCanvasElement) -> Option<CanvasRenderingContext2d> {
+    canvas.get_context("2d").ok()??.dyn_into().ok()
+}
+
+/// Draws the annotations onto a `width` x `height` context, on top of whatever it holds.
+fn draw(context: &CanvasRenderingContext2d, annotations: &[Annotation], width: f64, height: f64) {
+    let at = |(x, y): Point| (x * width, y * height);
+    context.set_line_width(LINE_WIDTH * height);
+    context.set_line_cap("round");
+    context.set_font(&format!(
+        "bold {}px sans-serif",
+        (FONT_SIZE * height).round()
+    ));
+
+    for annotation in annotations {
+        context.set_stroke_style_str(&annotation.color);
+        context.set_fill_style_str(&annotation.color);
+        context.begin_path();
+        match &annotation.shape {
+            Shape::Arrow { from, to } => {
+                let (from, to) = (at(*from), at(*to));
+                context.move_to(from.0, from.1);
+                context.line_to(to.0, to.1);
+                for side in arrow_head(from, to, HEAD_LENGTH * height) {
+                    context.move_to(to.0, to.1);
+                    context.line_to(side.0, side.1);
+                }
+                context.stroke();
+            }
+            Shape::Circle { center, rim } => {
+                let (center, rim) = (at(*center), at(*rim));
+                let radius = (rim.0 - center.0).hypot(rim.1 - center.1);
+                let _ = context.arc(center.0, center.1, radius, 0., 2. * PI);
+                context.stroke();
+            }
+            Shape::Label { at: point, text } => {
+                let point = at(*point);
+                let _ = context.fill_text(text, point.0, point.1);
+            }
+        }
+    }
+}
+
+/// Redraws the layer, with `pending` on top while an arrow or circle is being dragged out.
+fn redraw(pending: Option<Annotation>) {
+    let (width, height) = CANVAS.with(|c| (c.width(), c.height()));
+    ANNOTATION_LAYER.with(|layer| {
+        if layer.width() != width || layer.height() != height {
+            layer.set_width(width);
+            layer.set_height(height);
+        }
+        let Some(context) = context(layer) else {
+            return;
+        };
+        context.clear_rect(0., 0., width as f64, height as f64);
+        let mut annotations = ANNOTATIONS.with(|a| a.borrow().clone());
+        annotations.extend(pending);
+        draw(&context, &annotations, width as f64, height as f64);
+    });
+}
+
+/// The noise canvas with the annotations drawn over it, `None` when there are none.
+pub fn flatten() -> Option<HtmlCanvasElement> {
+    let annotations = ANNOTATIONS.with(|a| a.borrow().clone());
+    if annotations.is_empty() {
+        return None;
+    }
+    let flat = DOCUMENT
+        .with(|d| d.create_element("canvas"))
+        .ok()?
+        .dyn_into::<HtmlCanvasElement>()
+        .ok()?;
+    let (width, height) = CANVAS.with(|c| (c.width(), c.height()));
+    flat.set_width(width);
+    flat.set_height(height);
+    let context = context(&flat)?;
+    CANVAS
+        .with(|c| context.draw_image_with_html_canvas_element(c, 0., 0.))
+        .ok()?;
+    draw(&context, &annotations, width as f64, height as f64);
+    Some(flat)
+}
+
+/// The layer only catches the mouse while a tool is picked, otherwise clicks reach the canvas.
+fn on_tool_change() {
+    let drawing = parse_value!(annotation_tool, String) != "none";
+    ANNOTATION_LAYER.with(|l| {
+        l.set_class_name(if drawing {
+            "annotation-layer drawing"
+        } else {
+            "annotation-layer"
+        })
+    });
+    redraw(None);
+}
+
+fn position(event: &MouseEvent) -> Point {
+    ANNOTATION_LAYER.with(|l| {
+        (
+            event.offset_x() as f64 / l.client_width().max(1) as f64,
+            event.offset_y() as f64 / l.client_height().max(1) as f64,
+        )
+    })
+}
+
+/// The shape the current tool makes from a drag between two points.
+fn shape(from: Point, to: Point) -> Option<Shape> {
+    match parse_value!(annotation_tool, String).as_str() {
+        "arrow" => Some(Shape::Arrow { from, to }),
+        "circle" => Some(Shape::Circle {
+            center: from,
+            rim: to,
+        }),
+        _ => None,
+    }
+}
+
+fn annotation(shape: Shape) -> Annotation {
+    Annotation {
+        shape,
+        color: ANNOTATION_COLOR.with(|c| c.value()),
+    }
+}
+
+fn on_press(event: MouseEvent) {
+    let point = position(&event);
+    if parse_value!(annotation_tool, String) == "label" {
+        let text = ANNOTATION_TEXT.with(|t| t.value().trim().to_string());
+        if !text.is_empty() {
+            ANNOTATIONS.with(|a| {
+                a.borrow_mut()
+                    .push(annotation(Shape::Label { at: point, text }))
+            });
+            redraw(None);
+        }
+        return;
+    }
+    DRAG_START.with(|d| d.replace(Some(point)));
+}
+
+fn on_drag(event: MouseEvent) {
+    let Some(start) = DRAG_START.with(|d| *d.borrow()) else {
+        return;
+    };
+    redraw(shape(start, position(&event)).map(annotation));
+}
+
+fn on_release(event: MouseEvent) {
+    let Some(start) = DRAG_START.with(|d| d.take()) else {
+        return;
+    };
+    let end = position(&event);
+    let shape = if start == end {
+        None
+    } else {
+        shape(start, end)
+    };
+    if let Some(shape) = shape {
+        ANNOTATIONS.with(|a| a.borrow_mut().push(annotation(shape)));
+    }
+    redraw(None);
+}
+
+fn undo() {
+    ANNOTATIONS.with(|a| a.borrow_mut().pop());
+    redraw(None);
+}
+
+fn clear() {
+    ANNOTATIONS.with(|a| a.borrow_mut().clear());
+    redraw(None);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arrow_heads_point_back_along_the_arrow() {
+        let [left, right] = arrow_head((0., 0.), (10., 0.), 2.);
+        for side in [left, right] {
+            assert!(side.0 < 10.);
+            assert!(((side.0 - 10.).hypot(side.1) - 2.).abs() < 1e-9);
+        }
+        assert!((left.1 + right.1).abs() < 1e-9);
+    }
+}
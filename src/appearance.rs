@@ -0,0 +1,38 @@
+use std::cell::Cell;
+
+use web_sys::HtmlInputElement;
+
+use crate::*;
+
+thread_local! {
+    /// Pixels spanned by one lattice cell, carried over from the last lattice noise that was shown.
+    static CELL_PIXELS: Cell<Option<f64>> = const { Cell::new(None) };
+}
+
+elements!(
+    (normalize_appearance, HtmlInputElement),
+    (scale, HtmlInputElement),
+);
+
+/// Remembers how many pixels one lattice cell of the outgoing noise spans.
+pub fn capture(cell_size: Option<f64>) {
+    if let Some(cell) = cell_size {
+        let scale = SCALE.with(|s| s.value_as_number());
+        CELL_PIXELS.with(|c| c.set(Some(scale * cell)));
+    }
+}
+
+/// Rescales the freshly selected noise so its lattice cell spans the remembered pixel size.
+/// Returns whether the scale slider was changed and the noise needs to be redrawn.
+pub fn apply(cell_size: Option<f64>) -> bool {
+    if !is_checked!(normalize_appearance) {
+        return false;
+    }
+    let (Some(cell), Some(cell_pixels)) = (cell_size, CELL_PIXELS.with(|c| c.get()))
+    else {
+        return false;
+    };
+
+    SCALE.with(|s| s.set_value_as_number(cell_pixels / cell));
+    true
+}
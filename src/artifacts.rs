@@ -0,0 +1,140 @@
+//! A guided comparison of the lattice artifacts of the gradient noises.
+
+use std::cell::{Cell, LazyCell, RefCell};
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::HtmlElement;
+
+use crate::*;
+
+const ANIMATION_NAME: &str = "artifacts";
+/// Milliseconds every noise stays on screen.
+const SHOWN_MS: i32 = 8000;
+/// Settings all noises share during the comparison. One octave keeps the artifacts of the
+/// lattice from being covered by the finer octaves.
+const LOCKED: &str = concat!(
+    "seed=7\nscale=40\noctaves=1\n",
+    "view_mode=flat\nshow_rose=true\nshow_randomness_report=true\n"
+);
+
+struct Scenario {
+    /// State lines selecting the noise.
+    noise: &'static str,
+    caption: &'static str,
+}
+
+const SCENARIOS: &[Scenario] = &[
+    Scenario {
+        noise: "noise_select=perlin\n",
+        caption: "Perlin noise: the square lattice shows through. The rose has petals along the axes \
+            and the diagonals, and every lattice corner sits at exactly 0, which leaves a faint grid \
+            of gray dots.",
+    },
+    Scenario {
+        noise: "noise_select=simplex\n",
+        caption: "Simplex noise: the triangular lattice spreads the energy more evenly over the \
+            directions, so the rose is rounder. What is left lines up in steps of 60\u{b0} instead \
+            of 45\u{b0}, and the features look slightly sharper than Perlin's.",
+    },
+    #[cfg(feature = "noise-rs")]
+    Scenario {
+        noise: "noise_select=external\nopen_simplex=true\n",
+        caption: "OpenSimplex noise: a stretched lattice with a wider kernel that was designed to \
+            avoid the simplex patent. Its rose is the roundest, at the price of a smoother, \
+            blurrier look and a faint diagonal bias in the spectrum.",
+    },
+];
+
+struct Tour {
+    /// Settings from before the comparison.
+    state: String,
+    next: usize,
+}
+
+thread_local! {
+    static TOUR: RefCell<Option<Tour>> = const { RefCell::new(None) };
+    /// Compare slots filled so far, the first pass through the noises fills them.
+    static CAPTURED: Cell<usize> = const { Cell::new(0) };
+}
+
+elements!(
+    (artifacts_start, HtmlElement),
+    (artifacts_caption, HtmlElement),
+);
+define_closure!(artifacts_start_click, toggle);
+
+pub fn setup() {
+    add_callback!(artifacts_start, "click", artifacts_start_click);
+}
+
+/// State lines showing the `index`th noise with the locked settings. The noise goes first, so
+/// selecting it resets its controls before the locked ones are set.
+fn scenario_state(index: usize) -> String {
+    format!("{}{LOCKED}", SCENARIOS[index].noise)
+}
+
+fn toggle() {
+    if TOUR.with(|t| t.borrow().is_some()) {
+        stop();
+    } else {
+        start();
+    }
+}
+
+fn start() {
+    TOUR.with(|t| {
+        t.replace(Some(Tour {
+            state: state::serialize(),
+            next: 0,
+        }))
+    });
+    CAPTURED.with(|c| c.set(0));
+    ARTIFACTS_START.with(|b| b.set_inner_text("Stop comparison"));
+    set_hidden!(artifacts_caption, false);
+    show_next();
+    scheduler::start(ANIMATION_NAME, SHOWN_MS, show_next);
+}
+
+fn stop() {
+    scheduler::stop(ANIMATION_NAME);
+    ARTIFACTS_START.with(|b| b.set_inner_text("Compare gradient noises"));
+    set_hidden!(artifacts_caption, true);
+    if let Some(tour) = TOUR.with(|t| t.take()) {
+        state::apply(&tour.state);
+    }
+}
+
+fn show_next() {
+    let Some(index) = TOUR.with(|t| {
+        let mut tour = t.borrow_mut();
+        let tour = tour.as_mut()?;
+        let index = tour.next;
+        tour.next = (index + 1) % SCENARIOS.len();
+        Some(index)
+    }) else {
+        return;
+    };
+
+    state::apply(&scenario_state(index));
+    ARTIFACTS_CAPTION.with(|c| c.set_inner_text(SCENARIOS[index].caption));
+    let captured = CAPTURED.with(Cell::get);
+    if captured < 2 && index == captured {
+        compare::capture(captured);
+        CAPTURED.with(|c| c.set(captured + 1));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_scenario_selects_its_noise_first() {
+        for index in 0..SCENARIOS.len() {
+            let state = scenario_state(index);
+            assert!(state.starts_with("noise_select="));
+            assert!(state.lines().all(|l| l.contains('=')));
+            assert!(state.contains("octaves=1\n"));
+        }
+    }
+}
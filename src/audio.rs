@@ -0,0 +1,165 @@
+use std::cell::{LazyCell, RefCell};
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{AudioBufferSourceNode, AudioContext, GainNode, HtmlElement, HtmlInputElement};
+
+use crate::{drawer::RESOLUTION, field::FIELD, *};
+
+/// Pitch at which one canvas row is played back. Every noise feature across the row becomes
+/// a harmonic of this frequency, so each octave adds content an octave higher.
+const ROW_FREQUENCY: f32 = 55.0;
+
+struct AudioState {
+    context: AudioContext,
+    gain: GainNode,
+    source: Option<AudioBufferSourceNode>,
+}
+
+thread_local! {
+    static AUDIO: RefCell<Option<AudioState>> = const { RefCell::new(None) };
+}
+
+elements!(
+    (audio_enabled, HtmlInputElement),
+    (audio_volume, HtmlInputElement),
+    (audio_volume_display, HtmlElement),
+);
+define_closure!(audio_toggle, toggle);
+define_closure!(audio_volume_change, update_volume);
+
+pub fn setup() {
+    add_callback!(audio_enabled, "input", audio_toggle);
+    add_callback!(audio_volume, "input", audio_volume_change);
+    update_volume();
+}
+
+/// Called after every redraw, so the sound always follows the visible noise.
+pub fn on_field_changed() {
+    if AUDIO.with(|a| a.borrow().is_some()) {
+        play_middle_row();
+    }
+}
+
+fn toggle() {
+    if is_checked!(audio_enabled) {
+        start();
+    } else {
+        stop();
+    }
+}
+
+fn update_volume() {
+    let volume = parse_value!(audio_volume, f32);
+    AUDIO_VOLUME_DISPLAY.with(|d| d.set_inner_text(&volume.to_string()));
+    AUDIO.with(|a| {
+        if let Some(state) = a.borrow().as_ref() {
+            state.gain.gain().set_value(volume);
+        }
+    });
+}
+
+fn start() {
+    let context = AudioContext::new()
+        .map_err(|_| console_log!("Failed to create audio context"))
+        .unwrap();
+    let gain = context
+        .create_gain()
+        .map_err(|_| console_log!("Failed to create gain node"))
+        .unwrap();
+    gain.connect_with_audio_node(&context.destination())
+        .map_err(|_| console_log!("Failed to connect gain node"))
+        .unwrap();
+
+    AUDIO.with(|a| {
+        a.replace(Some(AudioState {
+            context,
+            gain,
+            source: None,
+        }))
+    });
+    update_volume();
+    play_middle_row();
+}
+
+fn stop() {
+    if let Some(state) = AUDIO.with(|a| a.take()) {
+        let _ = state.context.close();
+    }
+}
+
+/// Loops the middle row of the current field as a single waveform period.
+fn play_middle_row() {
+    let row: Vec<f64> = FIELD.with(|f| {
+        let field = f.borrow();
+        let start = (RESOLUTION / 2 * RESOLUTION) as usize;
+        field
+            .get(start..start + RESOLUTION as usize)
+            .map(|row| row.to_vec())
+            .unwrap_or_default()
+    });
+    if row.is_empty() {
+        return;
+    }
+
+    AUDIO.with(|a| {
+        let mut audio = a.borrow_mut();
+        let Some(state) = audio.as_mut() else {
+            return;
+        };
+
+        let sample_rate = state.context.sample_rate();
+        let period = (sample_rate / ROW_FREQUENCY) as usize;
+        let samples = resample_row(&row, period);
+
+        let buffer = state
+            .context
+            .create_buffer(1, period as u32, sample_rate)
+            .map_err(|_| console_log!("Failed to create audio buffer"))
+            .unwrap();
+        buffer
+            .copy_to_channel(&samples, 0)
+            .map_err(|_| console_log!("Failed to fill audio buffer"))
+            .unwrap();
+
+        let source = state
+            .context
+            .create_buffer_source()
+            .map_err(|_| console_log!("Failed to create buffer source"))
+            .unwrap();
+        source.set_buffer(Some(&buffer));
+        source.set_loop(true);
+        source
+            .connect_with_audio_node(&state.gain)
+            .map_err(|_| console_log!("Failed to connect buffer source"))
+            .unwrap();
+
+        if let Some(old) = state.source.take() {
+            #[allow(deprecated)]
+            let _ = old.stop();
+        }
+        #[allow(deprecated)]
+        let _ = source.start();
+        state.source = Some(source);
+    });
+}
+
+/// Stretches the row to `length` samples, removing its DC offset and normalizing the peak to 1.
+fn resample_row(row: &[f64], length: usize) -> Vec<f32> {
+    let mean = row.iter().sum::<f64>() / row.len() as f64;
+    let peak = row
+        .iter()
+        .map(|v| (v - mean).abs())
+        .fold(0.0, f64::max)
+        .max(1e-6);
+
+    (0..length)
+        .map(|i| {
+            let position = i as f64 * row.len() as f64 / length as f64;
+            let left = position.floor() as usize;
+            let right = (left + 1) % row.len();
+            let t = position - left as f64;
+            let value = row[left] + t * (row[right] - row[left]);
+            ((value - mean) / peak) as f32
+        })
+        .collect()
+}
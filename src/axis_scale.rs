@@ -0,0 +1,104 @@
+//! Separate scales along x and y on top of the noise's own scale.
+
+use std::cell::Cell;
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlElement, HtmlInputElement};
+
+use crate::{drawer::HALF_RESOLUTION, *};
+
+/// Stretch factors of the canvas axes, captured once per frame so the parallel sampling does not
+/// touch the page.
+#[derive(Clone, Copy)]
+pub struct AxisScale {
+    pub x: f64,
+    pub y: f64,
+}
+
+thread_local! {
+    static AXIS_SCALE: Cell<AxisScale> = const { Cell::new(AxisScale { x: 1., y: 1. }) };
+}
+
+elements!(
+    (scale_x, HtmlInputElement),
+    (scale_x_display, HtmlElement),
+    (scale_y, HtmlInputElement),
+    (scale_y_display, HtmlElement),
+    (scale_lock, HtmlInputElement),
+);
+define_closure!(scale_x_change, on_scale_x_change);
+define_closure!(scale_y_change, on_scale_y_change);
+define_closure!(scale_lock_change, on_scale_lock_change);
+
+pub fn setup() {
+    add_callback!(scale_x, "input", scale_x_change);
+    add_callback!(scale_y, "input", scale_y_change);
+    add_callback!(scale_lock, "input", scale_lock_change);
+    read_controls();
+}
+
+fn on_scale_x_change() {
+    if is_checked!(scale_lock) {
+        SCALE_Y.with(|y| SCALE_X.with(|x| y.set_value(&x.value())));
+    }
+    read_controls();
+    update_current_noise();
+}
+
+fn on_scale_y_change() {
+    if is_checked!(scale_lock) {
+        SCALE_X.with(|x| SCALE_Y.with(|y| x.set_value(&y.value())));
+    }
+    read_controls();
+    update_current_noise();
+}
+
+/// Locking evens the axes out at the x scale.
+fn on_scale_lock_change() {
+    on_scale_x_change();
+}
+
+/// Picks up the controls from the page, also after a saved state has been applied.
+pub fn read_controls() {
+    let (x, y) = (parse_value!(scale_x, f64), parse_value!(scale_y, f64));
+    set_text!(scale_x, &format!("{x:.2}"));
+    set_text!(scale_y, &format!("{y:.2}"));
+    AXIS_SCALE.with(|s| s.set(AxisScale { x, y }));
+}
+
+impl AxisScale {
+    pub fn current() -> Self {
+        AXIS_SCALE.with(Cell::get)
+    }
+
+    /// The canvas position (x, y), in pixels, stretched about the center of the canvas. A factor
+    /// of 2 makes the features twice as large along that axis.
+    pub fn apply(&self, x: f64, y: f64) -> (f64, f64) {
+        let half = HALF_RESOLUTION as f64;
+        (half + (x - half) / self.x, half + (y - half) / self.y)
+    }
+
+    /// Undoes `apply`.
+    pub fn invert(&self, x: f64, y: f64) -> (f64, f64) {
+        let half = HALF_RESOLUTION as f64;
+        (half + (x - half) * self.x, half + (y - half) * self.y)
+    }
+
+    pub fn is_identity(&self) -> bool {
+        self.x == 1. && self.y == 1.
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stretches_about_the_canvas_center() {
+        let half = HALF_RESOLUTION as f64;
+        let scale = AxisScale { x: 2., y: 0.5 };
+        assert_eq!(scale.apply(half, half), (half, half));
+        assert_eq!(scale.apply(half + 10., half + 10.), (half + 5., half + 20.));
+        assert_eq!(AxisScale { x: 1., y: 1. }.apply(3., 7.), (3., 7.));
+    }
+}
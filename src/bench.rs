@@ -0,0 +1,30 @@
+//! Native entry points for the criterion benches in `benches/`, enabled by the `bench` feature.
+
+use crate::{
+    core::{self, DEFAULT_SCALE, Sampler},
+    field::sample_field,
+};
+
+pub const ALGORITHMS: [&str; 6] = [
+    "perlin",
+    "simplex",
+    "wavelet",
+    "gabor",
+    "anisotropic",
+    "worley",
+];
+
+/// `algorithm` at its default settings with `octaves` octaves.
+pub fn sampler(algorithm: &str, octaves: u32) -> Box<dyn Sampler> {
+    let pairs = [
+        ("noise", algorithm.to_string()),
+        ("octaves", octaves.to_string()),
+    ]
+    .map(|(key, value)| (key.to_string(), value));
+    core::from_settings(&pairs).unwrap_or_else(|e| panic!("{e}"))
+}
+
+/// One full canvas frame at the default scale, sampled the same way the page samples it.
+pub fn frame(sampler: &dyn Sampler) -> Vec<f64> {
+    sample_field(DEFAULT_SCALE, |x, y| sampler.sample(x, y))
+}
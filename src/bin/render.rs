@@ -0,0 +1,39 @@
+//! Renders a settings file to a PNG, or to raw `f32`s for `.raw` outputs, without a browser.
+
+use std::{env, fs, process::ExitCode};
+
+use seeing_noise::render::{parse_settings, render, to_png, to_raw};
+
+/// The size of the image written.
+fn run(settings_path: &str, output_path: &str) -> Result<(u32, u32), String> {
+    let text = fs::read_to_string(settings_path)
+        .map_err(|e| format!("Failed to read {settings_path}: {e}"))?;
+    let (width, height, field) = render(&parse_settings(&text)?)?;
+
+    let bytes = if output_path.ends_with(".raw") {
+        to_raw(&field)
+    } else {
+        to_png(width, height, &field)
+    };
+    fs::write(output_path, bytes).map_err(|e| format!("Failed to write {output_path}: {e}"))?;
+    Ok((width, height))
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().collect();
+    let [_, settings_path, output_path] = args.as_slice() else {
+        eprintln!("Usage: render <settings.toml|settings.json> <output.png|output.raw>");
+        return ExitCode::FAILURE;
+    };
+
+    match run(settings_path, output_path) {
+        Ok((width, height)) => {
+            println!("Rendered {width}x{height} to {output_path}");
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
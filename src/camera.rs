@@ -0,0 +1,72 @@
+use crate::drawer::{HALF_RESOLUTION, RESOLUTION};
+
+pub type Vec3 = [f64; 3];
+
+/// Orbit camera looking at the origin from `distance` units away.
+pub struct OrbitCamera {
+    pub yaw: f64,
+    pub pitch: f64,
+    pub distance: f64,
+}
+
+impl OrbitCamera {
+    /// Builds a camera from angles given in degrees, as the sliders show them.
+    pub fn from_degrees(yaw: f64, pitch: f64, distance: f64) -> Self {
+        OrbitCamera {
+            yaw: yaw.to_radians(),
+            pitch: pitch.to_radians(),
+            distance,
+        }
+    }
+
+    /// Moves a world-space point into view space, where the camera looks down +z.
+    /// Positive pitch lifts the camera above the ground plane.
+    pub fn to_view(&self, [x, y, z]: Vec3) -> Vec3 {
+        let (sin_yaw, cos_yaw) = self.yaw.sin_cos();
+        let (sin_pitch, cos_pitch) = self.pitch.sin_cos();
+
+        let x1 = x * cos_yaw - z * sin_yaw;
+        let z1 = x * sin_yaw + z * cos_yaw;
+
+        let y2 = y * cos_pitch + z1 * sin_pitch;
+        let z2 = z1 * cos_pitch - y * sin_pitch;
+        [x1, y2, z2 + self.distance]
+    }
+
+    /// Perspective projection of a view-space point onto the canvas.
+    pub fn project([x, y, z]: Vec3) -> (f64, f64) {
+        let focal = RESOLUTION as f64 * 0.9;
+        (
+            HALF_RESOLUTION as f64 + focal * x / z,
+            HALF_RESOLUTION as f64 - focal * y / z,
+        )
+    }
+}
+
+pub fn sub(a: Vec3, b: Vec3) -> Vec3 {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+pub fn dot(a: Vec3, b: Vec3) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+pub fn cross(a: Vec3, b: Vec3) -> Vec3 {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+pub fn normalize(v: Vec3) -> Vec3 {
+    let length = dot(v, v).sqrt().max(1e-12);
+    [v[0] / length, v[1] / length, v[2] / length]
+}
+
+/// Two-sided lambert term of a view-space triangle under a fixed light coming from above the camera.
+pub fn triangle_lighting(view: &[Vec3; 3]) -> f64 {
+    let light = normalize([0.4, 0.6, -1.0]);
+    let normal = normalize(cross(sub(view[1], view[0]), sub(view[2], view[0])));
+    dot(normal, light).abs()
+}
@@ -0,0 +1,234 @@
+//! Packs three fields into the red, green and blue channels of the image.
+
+use std::cell::{Cell, RefCell};
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+
+use crate::{drawer::RESOLUTION, flow::Source, *};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Mode {
+    Off,
+    /// The noise with its own seed in red, with the next two seeds in green and blue.
+    Seeds,
+    /// The noise in red, its slope along x in green and along y in blue.
+    Derivatives,
+    /// A vector field from the noise, drawn as arrows.
+    FlowArrows,
+    /// The same vector field with line integral convolution.
+    FlowLic,
+}
+
+thread_local! {
+    static MODE: Cell<Mode> = const { Cell::new(Mode::Off) };
+    static SOURCE: Cell<Source> = const { Cell::new(Source::Seeds) };
+    static ENABLED: Cell<[bool; 3]> = const { Cell::new([true; 3]) };
+    /// Channel the next drawn field goes to instead of the canvas.
+    static CAPTURING: Cell<Option<usize>> = const { Cell::new(None) };
+    /// Fields of the green and blue channels of the frame being drawn.
+    static CAPTURED: RefCell<[Option<Vec<f64>>; 2]> = const { RefCell::new([None, None]) };
+}
+
+elements!(
+    (channel_mode, HtmlSelectElement),
+    (flow_source, HtmlSelectElement),
+    (channel_r, HtmlInputElement),
+    (channel_g, HtmlInputElement),
+    (channel_b, HtmlInputElement),
+    (seed, HtmlInputElement),
+);
+define_closure!(channel_change, on_channel_change);
+
+pub fn setup() {
+    add_callback!(channel_mode, "input", channel_change);
+    add_callback!(flow_source, "input", channel_change);
+    add_callback!(channel_r, "input", channel_change);
+    add_callback!(channel_g, "input", channel_change);
+    add_callback!(channel_b, "input", channel_change);
+}
+
+fn on_channel_change() {
+    read_controls();
+    update_current_noise();
+}
+
+/// Picks up the controls from the page, also after a saved state has been applied.
+pub fn read_controls() {
+    let mode = match parse_value!(channel_mode, String).as_str() {
+        "seeds" => Mode::Seeds,
+        "derivatives" => Mode::Derivatives,
+        "flow_arrows" => Mode::FlowArrows,
+        "flow_lic" => Mode::FlowLic,
+        _ => Mode::Off,
+    };
+    MODE.with(|m| m.set(mode));
+    let source = match parse_value!(flow_source, String).as_str() {
+        "gradient" => Source::Gradient,
+        "curl" => Source::Curl,
+        _ => Source::Seeds,
+    };
+    SOURCE.with(|s| s.set(source));
+    let enabled = [
+        is_checked!(channel_r),
+        is_checked!(channel_g),
+        is_checked!(channel_b),
+    ];
+    ENABLED.with(|e| e.set(enabled));
+}
+
+/// The green and blue channels to draw before the noise itself, with their seeds. Empty unless
+/// the channels hold independent seeds or a vector field takes its y from a second seed.
+pub fn extra_passes() -> Vec<(usize, u32)> {
+    CAPTURED.with(|c| *c.borrow_mut() = [None, None]);
+    let channels = match MODE.with(Cell::get) {
+        Mode::Off | Mode::Derivatives => return Vec::new(),
+        Mode::Seeds => {
+            let enabled = ENABLED.with(Cell::get);
+            (1..3).filter(|&channel| enabled[channel]).collect()
+        }
+        Mode::FlowArrows | Mode::FlowLic if SOURCE.with(Cell::get) == Source::Seeds => vec![1],
+        Mode::FlowArrows | Mode::FlowLic => return Vec::new(),
+    };
+    let seed = SEED.with(|s| s.value()).parse::<u32>().unwrap_or(0);
+    channels
+        .into_iter()
+        .map(|channel| (channel, seed.wrapping_add(channel as u32)))
+        .collect()
+}
+
+pub fn capture_next(channel: usize) {
+    CAPTURING.with(|c| c.set(Some(channel)));
+}
+
+pub fn end_capture() {
+    CAPTURING.with(|c| c.set(None));
+}
+
+/// Keeps `field` for its channel while an extra channel is drawn, or hands it back to be drawn.
+pub fn capture(field: Vec<f64>) -> Option<Vec<f64>> {
+    match CAPTURING.with(Cell::take) {
+        Some(channel) => {
+            CAPTURED.with(|c| c.borrow_mut()[channel - 1] = Some(field));
+            None
+        }
+        None => Some(field),
+    }
+}
+
+/// Rgba pixels of `field` with the kept channels packed in, none when the noise is drawn in its
+/// usual colors.
+pub fn pack(field: &[f64]) -> Option<Vec<u8>> {
+    let mode = MODE.with(Cell::get);
+    let enabled = ENABLED.with(Cell::get);
+    CAPTURED.with(|captured| {
+        let [green, blue] = &*captured.borrow();
+        if mode == Mode::FlowLic {
+            let source = SOURCE.with(Cell::get);
+            let (u, v) = flow::vectors(source, field, green.as_deref())?;
+            let lic = flow::line_integral_convolution(&u, &v, rng::current());
+            return Some(lic.iter().flat_map(|&v| gray(v)).collect());
+        }
+        pack_with(mode, enabled, field, [green.as_deref(), blue.as_deref()])
+    })
+}
+
+/// Puts the arrows of the vector field over the drawn noise.
+pub fn on_field_drawn(field: &[f64]) {
+    if MODE.with(Cell::get) != Mode::FlowArrows {
+        return;
+    }
+    let source = SOURCE.with(Cell::get);
+    CAPTURED.with(|captured| {
+        let [green, _] = &*captured.borrow();
+        if let Some((u, v)) = flow::vectors(source, field, green.as_deref()) {
+            flow::draw_arrows(&u, &v);
+        }
+    });
+}
+
+fn gray(value: f64) -> [u8; 4] {
+    let byte = to_byte(value);
+    [byte, byte, byte, 255]
+}
+
+/// A value in [-1, 1] as a channel byte.
+fn to_byte(value: f64) -> u8 {
+    ((value.clamp(-1., 1.) + 1.) / 2. * 255.).round() as u8
+}
+
+/// Central differences of `field` along x and y, stretched together so the steepest slope reaches
+/// ±1. The edges of the canvas use one sided differences.
+pub fn derivatives(field: &[f64]) -> (Vec<f64>, Vec<f64>) {
+    let width = RESOLUTION as usize;
+    let height = field.len() / width;
+    let at = |x: usize, y: usize| field[y * width + x];
+    let slope = |before: f64, after: f64, span: usize| (after - before) / span as f64;
+    let mut dx = Vec::with_capacity(field.len());
+    let mut dy = Vec::with_capacity(field.len());
+    for y in 0..height {
+        for x in 0..width {
+            let (left, right) = (x.saturating_sub(1), (x + 1).min(width - 1));
+            let (up, down) = (y.saturating_sub(1), (y + 1).min(height - 1));
+            dx.push(slope(at(left, y), at(right, y), right - left));
+            dy.push(slope(at(x, up), at(x, down), down - up));
+        }
+    }
+    let steepest = dx.iter().chain(&dy).fold(0f64, |m, d| m.max(d.abs()));
+    if steepest > 0. {
+        dx.iter_mut()
+            .chain(dy.iter_mut())
+            .for_each(|d| *d /= steepest);
+    }
+    (dx, dy)
+}
+
+fn pack_with(
+    mode: Mode,
+    enabled: [bool; 3],
+    field: &[f64],
+    captured: [Option<&[f64]>; 2],
+) -> Option<Vec<u8>> {
+    let slopes;
+    let [green, blue] = match mode {
+        Mode::Off | Mode::FlowArrows | Mode::FlowLic => return None,
+        Mode::Seeds => captured,
+        Mode::Derivatives => {
+            slopes = derivatives(field);
+            [Some(slopes.0.as_slice()), Some(slopes.1.as_slice())]
+        }
+    };
+    let channels = [Some(field), green, blue];
+    let mut pixels = vec![255; field.len() * 4];
+    for (channel, values) in channels.iter().enumerate() {
+        for (i, pixel) in pixels.as_chunks_mut::<4>().0.iter_mut().enumerate() {
+            pixel[channel] = match values {
+                Some(values) if enabled[channel] => to_byte(values[i]),
+                _ => 0,
+            };
+        }
+    }
+    Some(pixels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn derivatives_of_a_ramp_are_flat() {
+        let width = RESOLUTION as usize;
+        let ramp: Vec<f64> = (0..width * 4).map(|i| (i % width) as f64 * 0.001).collect();
+        let pixels =
+            pack_with(Mode::Derivatives, [true, true, false], &ramp, [None, None]).unwrap();
+        for pixel in pixels.as_chunks::<4>().0 {
+            assert_eq!(pixel[1], 255);
+            assert_eq!(pixel[2], 0);
+        }
+        assert_eq!(pixels[0], 128);
+
+        assert!(pack_with(Mode::Off, [true; 3], &ramp, [None, None]).is_none());
+        let pixels = pack_with(Mode::Seeds, [true; 3], &ramp, [Some(&ramp), None]).unwrap();
+        assert_eq!(&pixels[..4], &[128, 128, 0, 255]);
+    }
+}
@@ -0,0 +1,103 @@
+use std::cell::LazyCell;
+
+use web_sys::{HtmlCanvasElement, HtmlElement, HtmlInputElement};
+
+use crate::{charts::Chart, *};
+
+const MARGIN: f64 = 24.;
+/// The plot always spans this many powers of two on both axes, so slopes can be compared.
+const DECADES: f64 = 10.;
+
+elements!(
+    (amplitude_chart, HtmlCanvasElement),
+    (gain_control, HtmlElement),
+    (h_exponent_control, HtmlElement),
+    (octaves, HtmlInputElement),
+    (gain, HtmlInputElement),
+    (lacunarity, HtmlInputElement),
+    (h_exponent, HtmlInputElement),
+    (spectral_h, HtmlInputElement),
+);
+
+/// Frequency and amplitude of every octave, as the fBm loops of the noises compute them.
+fn octave_amplitudes() -> Vec<(f64, f64)> {
+    let octaves = parse_value!(octaves, u32);
+    let lacunarity = parse_value!(lacunarity, f64);
+    let h_exponent = parse_value!(h_exponent, f64);
+    let gain = if is_checked!(spectral_h) {
+        lacunarity.powf(-h_exponent)
+    } else if !H_EXPONENT_CONTROL.with(|c| c.hidden()) {
+        parse_value!(gain, f64).powf(h_exponent)
+    } else {
+        parse_value!(gain, f64)
+    };
+
+    (0..octaves)
+        .map(|i| (lacunarity.powi(i as i32), gain.powi(i as i32)))
+        .collect()
+}
+
+/// The Hurst exponent the fBm controls ask for, the falloff of the octave amplitudes with their
+/// frequency. None when the selected noise has no octaves or only one.
+pub fn hurst_exponent() -> Option<f64> {
+    if GAIN_CONTROL.with(|c| c.hidden()) {
+        return None;
+    }
+    match octave_amplitudes().as_slice() {
+        [(f0, a0), .., (f1, a1)] => {
+            Some(-(a1 / a0).log2() / (f1 / f0).log2()).filter(|h| h.is_finite())
+        }
+        _ => None,
+    }
+}
+
+/// Plots log2 amplitude against log2 frequency. fBm with a constant gain is a straight line
+/// there, and its slope is the exponent of the 1/f^beta amplitude falloff.
+pub fn draw() {
+    if GAIN_CONTROL.with(|c| c.hidden()) {
+        return;
+    }
+
+    let chart = AMPLITUDE_CHART.with(|c| Chart::new(c));
+    let octaves = octave_amplitudes();
+
+    chart.clear("#ffffff");
+    let (left, bottom) = (MARGIN, chart.height - MARGIN);
+    let (plot_width, plot_height) = (chart.width - 2. * MARGIN, chart.height - 2. * MARGIN);
+    chart.line((left, MARGIN), (left, bottom), "#888888");
+    chart.line((left, bottom), (left + plot_width, bottom), "#888888");
+    chart.text(
+        "log2 frequency",
+        left + plot_width - 70.,
+        chart.height - 8.,
+        "#444444",
+    );
+    chart.text("log2 amplitude", 4., 12., "#444444");
+
+    let to_chart = |(frequency, amplitude): (f64, f64)| {
+        let x = frequency.max(f64::MIN_POSITIVE).log2() / DECADES;
+        let y = -amplitude.max(f64::MIN_POSITIVE).log2() / DECADES;
+        (
+            left + x.clamp(0., 1.) * plot_width,
+            MARGIN + y.clamp(0., 1.) * plot_height,
+        )
+    };
+
+    let points: Vec<_> = octaves.iter().copied().map(to_chart).collect();
+    chart.polyline(&points, "#1f77b4");
+    for &point in &points {
+        chart.circle(point, 3., "#1f77b4");
+    }
+
+    if let [(f0, a0), .., (f1, a1)] = octaves.as_slice() {
+        let slope = (a1 / a0).log2() / (f1 / f0).log2();
+        if slope.is_finite() {
+            chart.text(
+                &format!("amplitude \u{221d} 1/f^{:.2}", -slope),
+                left + plot_width - 110.,
+                MARGIN,
+                "#1f77b4",
+            );
+        }
+    }
+}
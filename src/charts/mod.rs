@@ -0,0 +1,85 @@
+use std::f64::consts::PI;
+
+use wasm_bindgen::{Clamped, JsCast};
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, ImageData};
+
+pub mod amplitude_spectrum;
+
+/// A small secondary canvas next to the controls, with its own drawing helpers.
+pub struct Chart {
+    context: CanvasRenderingContext2d,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Chart {
+    pub fn new(canvas: &HtmlCanvasElement) -> Self {
+        let context = canvas
+            .get_context("2d")
+            .unwrap()
+            .unwrap()
+            .dyn_into::<CanvasRenderingContext2d>()
+            .unwrap();
+
+        Chart {
+            context,
+            width: canvas.width() as f64,
+            height: canvas.height() as f64,
+        }
+    }
+
+    pub fn clear(&self, fill_style: &str) {
+        self.context.set_fill_style_str(fill_style);
+        self.context.fill_rect(0., 0., self.width, self.height);
+    }
+
+    pub fn line(&self, from: (f64, f64), to: (f64, f64), stroke_style: &str) {
+        self.polyline(&[from, to], stroke_style);
+    }
+
+    pub fn polyline(&self, points: &[(f64, f64)], stroke_style: &str) {
+        self.context.set_stroke_style_str(stroke_style);
+        self.context.begin_path();
+        for (i, &(x, y)) in points.iter().enumerate() {
+            if i == 0 {
+                self.context.move_to(x, y);
+            } else {
+                self.context.line_to(x, y);
+            }
+        }
+        self.context.stroke();
+    }
+
+    pub fn circle(&self, center: (f64, f64), radius: f64, fill_style: &str) {
+        self.context.set_fill_style_str(fill_style);
+        self.context.begin_path();
+        let _ = self.context.arc(center.0, center.1, radius, 0., 2. * PI);
+        self.context.fill();
+    }
+
+    pub fn rect(&self, x: f64, y: f64, width: f64, height: f64, fill_style: &str) {
+        self.context.set_fill_style_str(fill_style);
+        self.context.fill_rect(x, y, width, height);
+    }
+
+    /// Puts rgba pixels of a `width` x `height` image with its top left corner at (x, y).
+    pub fn image(&self, data: &[u8], width: u32, height: u32, x: f64, y: f64) {
+        let Ok(image_data) =
+            ImageData::new_with_u8_clamped_array_and_sh(Clamped(data), width, height)
+        else {
+            return;
+        };
+        let _ = self.context.put_image_data(&image_data, x, y);
+    }
+
+    pub fn text(&self, text: &str, x: f64, y: f64, fill_style: &str) {
+        self.context.set_fill_style_str(fill_style);
+        self.context.set_font("11px sans-serif");
+        let _ = self.context.fill_text(text, x, y);
+    }
+}
+
+/// Called after every noise redraw, so charts follow the sliders of the selected noise.
+pub fn on_frame_drawn() {
+    amplitude_spectrum::draw();
+}
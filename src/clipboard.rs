@@ -0,0 +1,71 @@
+use std::cell::LazyCell;
+
+use js_sys::{Array, Function, Object, Promise, Reflect};
+use wasm_bindgen::{JsCast, JsValue, prelude::Closure};
+use web_sys::{ClipboardItem, HtmlElement};
+
+use crate::{drawer::CANVAS, *};
+
+const FALLBACK_MESSAGE: &str =
+    "This browser can't copy images, right-click the canvas and choose \"Copy image\" instead";
+
+thread_local! {
+    static COPY_SUCCEEDED: LazyCell<Closure<dyn FnMut(JsValue)>> = LazyCell::new(|| {
+        Closure::new(|_| set_status("Copied to clipboard"))
+    });
+    static COPY_FAILED: LazyCell<Closure<dyn FnMut(JsValue)>> = LazyCell::new(|| {
+        Closure::new(|error: JsValue| {
+            console_log!("Failed to copy the canvas: {error:?}");
+            set_status(FALLBACK_MESSAGE);
+        })
+    });
+}
+
+elements!((copy_image, HtmlElement), (copy_image_status, HtmlElement),);
+define_closure!(copy_image_click, copy_image);
+
+pub fn setup() {
+    add_callback!(copy_image, "click", copy_image_click);
+}
+
+fn set_status(text: &str) {
+    COPY_IMAGE_STATUS.with(|s| s.set_inner_text(text));
+}
+
+/// Resolves with the canvas encoded as a png blob, annotations included.
+fn canvas_blob() -> Promise {
+    Promise::new(&mut |resolve: Function, reject: Function| {
+        let blob = match annotations::flatten() {
+            Some(flat) => flat.to_blob(&resolve),
+            None => CANVAS.with(|c| c.to_blob(&resolve)),
+        };
+        if let Err(error) = blob {
+            let _ = reject.call1(&JsValue::NULL, &error);
+        }
+    })
+}
+
+/// The clipboard item is built right inside the click handler with a promised blob, Safari only
+/// allows clipboard writes that start synchronously from a user gesture.
+fn copy_image() {
+    let navigator = web_sys::window().unwrap().navigator();
+    if !Reflect::has(&navigator, &"clipboard".into()).unwrap_or(false) {
+        set_status(FALLBACK_MESSAGE);
+        return;
+    }
+
+    let items = Object::new();
+    let _ = Reflect::set(&items, &"image/png".into(), &canvas_blob());
+    let Ok(item) = ClipboardItem::new_with_record_from_str_to_blob_promise(&items) else {
+        set_status(FALLBACK_MESSAGE);
+        return;
+    };
+
+    set_status("Copying...");
+    let written = navigator.clipboard().write(&Array::of1(&item));
+    COPY_SUCCEEDED.with(|succeeded| {
+        COPY_FAILED.with(|failed| {
+            let _ = written.then2(succeeded, failed);
+        })
+    });
+}
@@ -0,0 +1,162 @@
+//! A/B comparison of two captured configurations, flickered and measured.
+
+use std::cell::{Cell, LazyCell, RefCell};
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlElement, HtmlInputElement};
+
+use crate::{
+    analysis::similarity::{max_abs_diff, rmse, ssim},
+    drawer::{CANVAS_CONTEXT, RESOLUTION, draw_noise, draw_text, fill_polygon},
+    field::FIELD,
+    scheduler, *,
+};
+
+const ANIMATION_NAME: &str = "compare_flicker";
+const LABELS: [&str; 2] = ["A", "B"];
+
+/// One side of the comparison.
+pub struct Capture {
+    pub noise: String,
+    /// Canvas pixels as rgba, the way they were drawn.
+    pub pixels: Vec<u8>,
+    pub field: Vec<f64>,
+    /// The controls on screen when it was captured, see `state::visible_parameters`.
+    pub parameters: Vec<(String, String)>,
+}
+
+thread_local! {
+    static CAPTURES: RefCell<[Option<Capture>; 2]> = const { RefCell::new([None, None]) };
+    /// The capture the flicker shows next.
+    static SHOWN: Cell<usize> = const { Cell::new(0) };
+}
+
+elements!(
+    (compare_set_a, HtmlElement),
+    (compare_set_b, HtmlElement),
+    (compare_flicker, HtmlInputElement),
+    (compare_blink_rate, HtmlInputElement),
+    (compare_blink_rate_display, HtmlElement),
+    (compare_status, HtmlElement),
+    (compare_metrics, HtmlElement),
+);
+define_closure!(compare_set_a_click, set_a);
+define_closure!(compare_set_b_click, set_b);
+define_closure!(compare_change, on_compare_change);
+
+pub fn setup() {
+    add_callback!(compare_set_a, "click", compare_set_a_click);
+    add_callback!(compare_set_b, "click", compare_set_b_click);
+    add_callback!(compare_flicker, "input", compare_change);
+    add_callback!(compare_blink_rate, "input", compare_change);
+    update_displays();
+}
+
+fn update_displays() {
+    set_text!(
+        compare_blink_rate,
+        &parse_value!(compare_blink_rate, f64).to_string()
+    );
+    let status = CAPTURES.with(|c| {
+        let captures = c.borrow();
+        LABELS
+            .iter()
+            .zip(captures.iter())
+            .map(|(label, capture)| match capture {
+                Some(capture) => format!("{label}: {}", capture.noise),
+                None => format!("{label}: not set"),
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    });
+    COMPARE_STATUS.with(|s| s.set_inner_text(&status));
+
+    let metrics = with_captures(|a, b| {
+        if a.field.len() != b.field.len() || a.field.is_empty() {
+            return "One of the captures has no field to compare.".to_string();
+        }
+        format!(
+            "RMSE {:.4}, largest difference {:.4}, SSIM {:.4}",
+            rmse(&a.field, &b.field),
+            max_abs_diff(&a.field, &b.field),
+            ssim(&a.field, &b.field)
+        )
+    });
+    COMPARE_METRICS.with(|m| m.set_inner_text(&metrics.unwrap_or_default()));
+}
+
+/// Runs `f` with both captures, or returns `None` while either slot is empty.
+pub fn with_captures<R>(f: impl FnOnce(&Capture, &Capture) -> R) -> Option<R> {
+    CAPTURES.with(|c| match &*c.borrow() {
+        [Some(a), Some(b)] => Some(f(a, b)),
+        _ => None,
+    })
+}
+
+/// Keeps what the canvas shows in slot `slot`, 0 for A and 1 for B.
+pub fn capture(slot: usize) {
+    let field = FIELD.with(|f| f.borrow().clone());
+    let pixels = CANVAS_CONTEXT
+        .with(|ctx| ctx.get_image_data(0., 0., RESOLUTION as f64, RESOLUTION as f64))
+        .map_err(|_| console_log!("Reading canvas image data failed"))
+        .ok();
+    let Some(pixels) = pixels else {
+        return;
+    };
+
+    let capture = Capture {
+        noise: CURRENT_NOISE.lock().unwrap().clone(),
+        pixels: pixels.data().0,
+        field,
+        parameters: state::visible_parameters(),
+    };
+    CAPTURES.with(|c| c.borrow_mut()[slot] = Some(capture));
+    update_displays();
+    restart_flicker();
+}
+
+fn set_a() {
+    capture(0);
+}
+
+fn set_b() {
+    capture(1);
+}
+
+fn on_compare_change() {
+    update_displays();
+    restart_flicker();
+}
+
+/// Starts the flicker at the current blink rate, or stops it and redraws the noise when it is
+/// turned off or a slot is still empty.
+fn restart_flicker() {
+    let was_running = scheduler::is_running(ANIMATION_NAME);
+    scheduler::stop(ANIMATION_NAME);
+
+    let ready = with_captures(|_, _| ()).is_some();
+    if is_checked!(compare_flicker) && ready {
+        let period = 1000. / parse_value!(compare_blink_rate, f64).max(0.1);
+        scheduler::start(ANIMATION_NAME, period as i32, tick);
+        tick();
+    } else if was_running {
+        update_current_noise();
+    }
+}
+
+fn tick() {
+    let shown = SHOWN.with(|s| s.replace(1 - s.get()));
+    let drawn = CAPTURES.with(|c| match &c.borrow()[shown] {
+        Some(capture) => {
+            draw_noise(&capture.pixels);
+            true
+        }
+        None => false,
+    });
+    if !drawn {
+        return;
+    }
+
+    fill_polygon(&[(4., 4.), (24., 4.), (24., 22.), (4., 22.)], "#ffffff");
+    draw_text(LABELS[shown], 9., 18., "#000000");
+}
@@ -0,0 +1,172 @@
+//! Builds fBm up on screen one octave per second.
+
+use std::cell::{LazyCell, RefCell};
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlElement, HtmlInputElement, HtmlSelectElement};
+
+use crate::{
+    field::{FIELD, draw_field},
+    *,
+};
+
+const ANIMATION_NAME: &str = "construction";
+/// Half of every second shows the new octave faintly, the other half in full.
+const ANIMATION_PERIOD_MS: i32 = 500;
+/// Weight of the new octave while it is faint.
+const FAINT: f64 = 0.35;
+
+struct Build {
+    /// Settings from before the animation.
+    state: String,
+    noise: String,
+    octave: u32,
+    octaves: u32,
+    faint: bool,
+    /// The sum of the octaves already in, `None` before the first.
+    previous: Option<Vec<f64>>,
+}
+
+thread_local! {
+    static BUILD: RefCell<Option<Build>> = const { RefCell::new(None) };
+}
+
+elements!(
+    (construction_play, HtmlElement),
+    (construction_readout, HtmlElement),
+    (accumulated_octaves_control, HtmlElement),
+    (octaves, HtmlInputElement),
+    (noise_select, HtmlSelectElement),
+);
+define_closure!(construction_play_click, toggle);
+
+pub fn setup() {
+    add_callback!(construction_play, "click", construction_play_click);
+}
+
+fn set_readout(text: &str) {
+    CONSTRUCTION_READOUT.with(|r| r.set_inner_text(text));
+}
+
+fn toggle() {
+    if BUILD.with(|b| b.borrow().is_some()) {
+        stop();
+    } else {
+        start();
+    }
+}
+
+fn start() {
+    if ACCUMULATED_OCTAVES_CONTROL.with(|c| c.hidden()) {
+        set_readout("The selected noise has no octaves to build up");
+        return;
+    }
+    BUILD.with(|b| {
+        b.replace(Some(Build {
+            state: state::serialize(),
+            noise: parse_value!(noise_select, String),
+            octave: 1,
+            octaves: parse_value!(octaves, u32),
+            faint: true,
+            previous: None,
+        }))
+    });
+    CONSTRUCTION_PLAY.with(|b| b.set_inner_text("Stop"));
+    show_step();
+    scheduler::start(ANIMATION_NAME, ANIMATION_PERIOD_MS, tick);
+}
+
+fn stop() {
+    scheduler::stop(ANIMATION_NAME);
+    CONSTRUCTION_PLAY.with(|b| b.set_inner_text("Build up octaves"));
+    set_readout("");
+    if let Some(build) = BUILD.with(|b| b.take()) {
+        // Another noise has been picked in the meantime, its settings are left alone.
+        if build.noise == parse_value!(noise_select, String) {
+            state::apply(&build.state);
+        }
+    }
+}
+
+/// Shows the current octave over the sum of the ones before it.
+fn show_step() {
+    let Some((octave, octaves)) =
+        BUILD.with(|b| b.borrow().as_ref().map(|b| (b.octave, b.octaves)))
+    else {
+        return;
+    };
+    set_readout(&format!("Octave {octave} of {octaves}"));
+    state::apply_changes(&format!("accumulated_octaves=true\nshow_octave={octave}\n"));
+}
+
+/// Turns the faint octave into a full one, or moves on to the next faint one.
+fn tick() {
+    let noise = parse_value!(noise_select, String);
+    let next = BUILD.with(|b| {
+        let mut build = b.borrow_mut();
+        let build = build.as_mut()?;
+        if build.noise != noise {
+            return None;
+        }
+        if build.faint {
+            build.faint = false;
+        } else {
+            build.faint = true;
+            build.octave += 1;
+        }
+        Some(build.octave <= build.octaves)
+    });
+    match next {
+        Some(true) => show_step(),
+        _ => stop(),
+    }
+}
+
+/// `previous` with `weight` of the way towards `current`.
+fn blend(previous: &[f64], current: &[f64], weight: f64) -> Vec<f64> {
+    previous
+        .iter()
+        .zip(current)
+        .map(|(p, c)| p + (c - p) * weight)
+        .collect()
+}
+
+/// Runs right after the noise is drawn, before the passes that restyle its field.
+pub fn on_frame_drawn() {
+    let faint = BUILD.with(|b| {
+        let mut build = b.borrow_mut();
+        let build = build.as_mut()?;
+        let field = FIELD.with(|f| f.borrow().clone());
+        if field.is_empty() {
+            return None;
+        }
+        if build.faint {
+            let previous = build
+                .previous
+                .clone()
+                .unwrap_or_else(|| vec![0.; field.len()]);
+            Some(blend(&previous, &field, FAINT))
+        } else {
+            build.previous = Some(field);
+            None
+        }
+    });
+    if let Some(faint) = faint {
+        draw_field(faint);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_new_octave_fades_in_over_the_sum() {
+        let previous = [0.5, -0.5, 0.];
+        let current = [1., -1., 0.4];
+        assert_eq!(blend(&previous, &current, 0.), previous);
+        assert_eq!(blend(&previous, &current, 1.), current);
+        let faint = blend(&previous, &current, 0.5);
+        assert_eq!(faint, [0.75, -0.75, 0.2]);
+    }
+}
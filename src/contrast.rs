@@ -0,0 +1,111 @@
+//! Stretches the value range a calibration frame measured to the full palette.
+
+use std::cell::{Cell, LazyCell, RefCell};
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlElement, HtmlInputElement, HtmlSelectElement};
+
+use crate::{
+    field::{FIELD, draw_field},
+    *,
+};
+
+thread_local! {
+    /// Smallest and largest value of the calibration frame.
+    static RANGE: Cell<Option<(f64, f64)>> = const { Cell::new(None) };
+    /// Noise the range was measured on.
+    static CALIBRATED_NOISE: RefCell<String> = const { RefCell::new(String::new()) };
+}
+
+elements!(
+    (auto_contrast, HtmlInputElement),
+    (contrast_calibrate, HtmlElement),
+    (contrast_readout, HtmlElement),
+    (noise_select, HtmlSelectElement),
+);
+define_closure!(auto_contrast_change, recalibrate);
+define_closure!(contrast_calibrate_click, recalibrate);
+
+pub fn setup() {
+    add_callback!(auto_contrast, "input", auto_contrast_change);
+    add_callback!(contrast_calibrate, "click", contrast_calibrate_click);
+}
+
+/// Forgets the range, the next frame measures a new one.
+fn recalibrate() {
+    RANGE.with(|r| r.set(None));
+    CONTRAST_READOUT.with(|r| r.set_inner_text(""));
+    update_current_noise();
+}
+
+/// Smallest and largest finite value of `field`, `None` when it holds no finite values.
+fn measure(field: &[f64]) -> Option<(f64, f64)> {
+    field
+        .iter()
+        .filter(|v| v.is_finite())
+        .fold(None, |range, &v| match range {
+            None => Some((v, v)),
+            Some((min, max)) => Some((f64::min(min, v), f64::max(max, v))),
+        })
+}
+
+/// Maps `min` to -1 and `max` to 1. A flat range is left alone, there is nothing to stretch.
+fn stretch(value: f64, (min, max): (f64, f64)) -> f64 {
+    if max - min < 1e-12 {
+        return value;
+    }
+    ((value - min) / (max - min) * 2. - 1.).clamp(-1., 1.)
+}
+
+/// Runs before the other passes, which then see the stretched field.
+pub fn on_frame_drawn() {
+    if !is_checked!(auto_contrast) {
+        return;
+    }
+    let field = FIELD.with(|f| f.borrow().clone());
+    if field.is_empty() {
+        return;
+    }
+
+    let noise = parse_value!(noise_select, String);
+    let same_noise = CALIBRATED_NOISE.with(|n| *n.borrow() == noise);
+    let range = match RANGE.with(Cell::get) {
+        Some(range) if same_noise => range,
+        _ => {
+            let Some(range) = measure(&field) else {
+                return;
+            };
+            RANGE.with(|r| r.set(Some(range)));
+            CALIBRATED_NOISE.with(|n| n.replace(noise));
+            CONTRAST_READOUT.with(|r| {
+                r.set_inner_text(&format!(
+                    "Calibrated on {} samples, [{:.3}, {:.3}] is stretched to [-1, 1]",
+                    field.len(),
+                    range.0,
+                    range.1
+                ))
+            });
+            range
+        }
+    };
+    draw_field(field.iter().map(|&v| stretch(v, range)).collect());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_measured_range_fills_the_palette() {
+        let field = [0.1, 0.4, f64::NAN, 0.25, 0.1];
+        let range = measure(&field).unwrap();
+        assert_eq!(range, (0.1, 0.4));
+        assert_eq!(stretch(0.1, range), -1.);
+        assert_eq!(stretch(0.4, range), 1.);
+        assert!(stretch(0.25, range).abs() < 1e-12);
+        // Later frames may leave the calibrated range, they are clipped.
+        assert_eq!(stretch(0.9, range), 1.);
+        assert_eq!(stretch(0.3, (0.3, 0.3)), 0.3);
+        assert_eq!(measure(&[f64::NAN]), None);
+    }
+}
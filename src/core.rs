@@ -0,0 +1,302 @@
+//! The noise algorithms of the visualizer as a plain Rust library, without the page around them:
+//!
+//! ```
+//! use seeing_noise::core::{Fractal, Perlin, Sampler, Viewport};
+//!
+//! let perlin = Perlin::new(42).octaves(5).gain(0.6).fractal(Fractal::Ridge);
+//! let height = perlin.sample(0.5, 1.25);
+//!
+//! let mut pixels = vec![0.0; 64 * 64];
+//! perlin.fill(&mut pixels, &Viewport::new(0.0, 0.0, 4.0, 4.0, 64, 64));
+//! ```
+
+use rayon::prelude::*;
+
+pub use crate::noises::{
+    anisotropic_noise::Anisotropic, gabor_noise::Gabor, perlin_noise::Perlin,
+    simplex_noise::Simplex, wavelet_noise::Wavelet, worley_noise::Worley,
+};
+pub use crate::rng::Generator;
+
+/// How the octaves of Perlin, Simplex and Wavelet noise are combined.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fractal {
+    Standard,
+    Turbulence,
+    Ridge,
+    DomainWarp,
+}
+
+/// The lattice Perlin noise puts its gradients on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Lattice {
+    Square,
+    Hexagonal,
+    Triangular,
+}
+
+/// The gradients Simplex noise picks from at its corners.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Gradients {
+    /// The 8 directions of Perlin noise, axes and diagonals.
+    Eight,
+    /// The 12 gradients of 3D simplex noise projected to 2D, as in reference implementations.
+    Twelve,
+    /// A direction of its own for every hash value.
+    Random,
+}
+
+/// How the octaves of Gabor noise are combined.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GaborFractal {
+    Standard,
+    Turbulence,
+    Anisotropic,
+    DomainWarp,
+}
+
+/// How the octaves of anisotropic noise are combined.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AnisotropicFractal {
+    Standard,
+    Turbulence,
+    Ridge,
+    Directional,
+}
+
+/// Which feature distances Worley noise turns into a value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WorleyFeature {
+    F1,
+    F2MinusF1,
+    Crackle,
+    DomainWarp,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Metric {
+    Euclidean,
+    Manhattan,
+    Chebyshev,
+    Minkowski,
+}
+
+/// How Wavelet noise reads between the samples of its tile.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Interpolation {
+    Bilinear,
+    Quintic,
+    Bicubic,
+}
+
+/// A rectangle of noise space split into a grid of buffer cells.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    pub columns: usize,
+    pub rows: usize,
+}
+
+impl Viewport {
+    pub fn new(x: f64, y: f64, width: f64, height: f64, columns: usize, rows: usize) -> Self {
+        Viewport {
+            x,
+            y,
+            width,
+            height,
+            columns,
+            rows,
+        }
+    }
+
+    /// Noise coordinates of the top-left corner of a cell, the page maps its pixels the same way.
+    pub fn point(&self, column: usize, row: usize) -> (f64, f64) {
+        (
+            self.x + column as f64 * self.width / self.columns as f64,
+            self.y + row as f64 * self.height / self.rows as f64,
+        )
+    }
+}
+
+pub trait Sampler: Sync {
+    /// The noise value at a point of noise space, roughly in `[-1, 1]`.
+    fn sample(&self, x: f64, y: f64) -> f64;
+
+    /// Samples every cell of the viewport into `buffer`, in row-major order.
+    fn fill(&self, buffer: &mut [f64], viewport: &Viewport) {
+        assert_eq!(
+            buffer.len(),
+            viewport.columns * viewport.rows,
+            "buffer does not match the viewport"
+        );
+        if buffer.is_empty() {
+            return;
+        }
+        buffer
+            .par_chunks_mut(viewport.columns)
+            .enumerate()
+            .for_each(|(row, line)| {
+                for (column, value) in line.iter_mut().enumerate() {
+                    let (x, y) = viewport.point(column, row);
+                    *value = self.sample(x, y);
+                }
+            });
+    }
+}
+
+/// The noise a flat settings file describes, as the `render` binary reads them: `noise` names the
+/// algorithm and the other keys are the ids of its controls.
+pub fn from_settings(pairs: &[(String, String)]) -> Result<Box<dyn Sampler>, String> {
+    let noise = pairs
+        .iter()
+        .find(|(key, _)| key == "noise")
+        .map(|(_, value)| value.as_str())
+        .ok_or("The settings do not name a noise")?;
+    let settings: Vec<_> = pairs
+        .iter()
+        .filter(|(key, _)| key != "noise")
+        .cloned()
+        .collect();
+
+    Ok(match noise {
+        "perlin" => Box::new(Perlin::from_pairs(&settings)?),
+        "simplex" => Box::new(Simplex::from_pairs(&settings)?),
+        "wavelet" => Box::new(Wavelet::from_pairs(&settings)?),
+        "gabor" => Box::new(Gabor::from_pairs(&settings)?),
+        "anisotropic" => Box::new(Anisotropic::from_pairs(&settings)?),
+        "worley" => Box::new(Worley::from_pairs(&settings)?),
+        e => return Err(format!("Unknown noise {e}")),
+    })
+}
+
+/// Every noise starts at this scale, settings without one are drawn at it.
+pub const DEFAULT_SCALE: f64 = 50.;
+
+/// The pixels per noise unit a flat settings file asks for.
+pub fn scale(pairs: &[(String, String)]) -> Result<f64, String> {
+    match pairs.iter().find(|(key, _)| key == "scale") {
+        Some((_, scale)) => scale
+            .parse()
+            .map_err(|_| format!("Invalid value {scale} for scale")),
+        None => Ok(DEFAULT_SCALE),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fill_samples_every_cell() {
+        let noise = Worley::new(3).octaves(2).metric(Metric::Manhattan);
+        let viewport = Viewport::new(-2.0, 1.0, 6.0, 3.0, 24, 12);
+        let mut buffer = vec![0.0; 24 * 12];
+        noise.fill(&mut buffer, &viewport);
+
+        for row in 0..viewport.rows {
+            for column in 0..viewport.columns {
+                let (x, y) = viewport.point(column, row);
+                assert_eq!(buffer[row * viewport.columns + column], noise.sample(x, y));
+            }
+        }
+    }
+
+    #[test]
+    fn builders_start_from_the_page_defaults() {
+        let field = |noise: &dyn Sampler| -> Vec<f64> {
+            (0..100)
+                .map(|i| noise.sample(i as f64 * 0.37, i as f64 * -0.21))
+                .collect()
+        };
+        assert_eq!(
+            field(&Perlin::new(42)),
+            field(&Perlin::new(42).octaves(1).gain(0.5))
+        );
+        assert_ne!(
+            field(&Perlin::new(42)),
+            field(&Perlin::new(42).fractal(Fractal::Ridge))
+        );
+        assert_ne!(
+            field(&Wavelet::new(42)),
+            field(&Wavelet::new(42).tile_size_power(6))
+        );
+    }
+
+    #[test]
+    fn builders_ignore_the_page_generator_and_table() {
+        let fields = || -> Vec<Vec<f64>> {
+            let noises: [Box<dyn Sampler>; 6] = [
+                Box::new(Perlin::new(42)),
+                Box::new(Simplex::new(42)),
+                Box::new(Wavelet::new(42)),
+                Box::new(Gabor::new(42)),
+                Box::new(Anisotropic::new(42)),
+                Box::new(Worley::new(42)),
+            ];
+            noises
+                .iter()
+                .map(|noise| {
+                    (0..50)
+                        .map(|i| noise.sample(i as f64 * 0.37, i as f64 * -0.21))
+                        .collect()
+                })
+                .collect()
+        };
+        let defaults = fields();
+
+        let reversed: [usize; 256] = std::array::from_fn(|i| 255 - i);
+        crate::rng::set_generator(Generator::Pcg32);
+        crate::permutation::set_table(Some(reversed));
+        let on_a_changed_page = fields();
+        crate::rng::set_generator(Generator::SquirrelNoise5);
+        crate::permutation::set_table(None);
+        assert_eq!(defaults, on_a_changed_page);
+
+        let sample = |noise: &dyn Sampler| noise.sample(1.3, -0.7);
+        assert_ne!(
+            sample(&Worley::new(42)),
+            sample(&Worley::new(42).generator(Generator::Pcg32))
+        );
+        assert_ne!(
+            sample(&Perlin::new(42)),
+            sample(&Perlin::new(42).permutation_table(reversed))
+        );
+    }
+
+    #[test]
+    fn fill_leaves_an_empty_viewport_alone() {
+        let mut buffer = [];
+        Perlin::new(42).fill(&mut buffer, &Viewport::new(0.0, 0.0, 1.0, 1.0, 0, 0));
+        Perlin::new(42).fill(&mut buffer, &Viewport::new(0.0, 0.0, 1.0, 1.0, 0, 8));
+    }
+
+    #[test]
+    fn no_octaves_means_one() {
+        for x in 0..20 {
+            let (x, y) = (x as f64 * 0.31, 0.5);
+            assert_eq!(
+                Perlin::new(42).octaves(0).sample(x, y),
+                Perlin::new(42).octaves(1).sample(x, y)
+            );
+            assert!(Worley::new(42).octaves(0).sample(x, y).is_finite());
+        }
+    }
+
+    #[test]
+    fn settings_files_match_the_builders() {
+        let pairs: Vec<_> = [("noise", "perlin"), ("seed", "7"), ("octaves", "3")]
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        let noise = from_settings(&pairs).unwrap();
+        let built = Perlin::new(7).octaves(3);
+        for i in 0..50 {
+            let (x, y) = (i as f64 * 0.29, i as f64 * -0.17);
+            assert_eq!(noise.sample(x, y), built.sample(x, y));
+        }
+        assert!(from_settings(&pairs[1..]).is_err());
+    }
+}
@@ -0,0 +1,315 @@
+//! A curves tone remap of the retained field, applied after the noise is drawn.
+
+use std::cell::{Cell, LazyCell};
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlCanvasElement, HtmlElement, HtmlInputElement, MouseEvent};
+
+use crate::{
+    charts::Chart,
+    field::{FIELD, draw_field},
+    *,
+};
+
+/// Entries of the lookup table the curve is baked into, spread evenly over [-1, 1].
+const LUT_SIZE: usize = 256;
+/// Distance in editor pixels within which a click grabs a control point.
+const GRAB_RADIUS: f64 = 8.;
+/// Closest two control points may get horizontally, so the curve stays a function.
+const MIN_SPACING: f64 = 0.02;
+const IDENTITY: [(f64, f64); 2] = [(-1., -1.), (1., 1.)];
+
+thread_local! {
+    /// Index of the control point being dragged.
+    static DRAGGED: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+elements!(
+    (curve_enabled, HtmlInputElement),
+    (curve_points, HtmlInputElement),
+    (curve_editor, HtmlCanvasElement),
+    (curve_reset, HtmlElement),
+);
+define_closure!(curve_enabled_change, update_current_noise);
+define_closure!(curve_reset_click, reset);
+define_closure!(curve_release, release);
+define_event_closure!(curve_press, MouseEvent, on_press);
+define_event_closure!(curve_drag, MouseEvent, on_drag);
+define_event_closure!(curve_double_click, MouseEvent, on_double_click);
+
+pub fn setup() {
+    add_callback!(curve_enabled, "input", curve_enabled_change);
+    add_callback!(curve_reset, "click", curve_reset_click);
+    add_callback!(curve_editor, "mousedown", curve_press);
+    add_callback!(curve_editor, "mousemove", curve_drag);
+    add_callback!(curve_editor, "mouseup", curve_release);
+    add_callback!(curve_editor, "mouseleave", curve_release);
+    add_callback!(curve_editor, "dblclick", curve_double_click);
+    draw_editor();
+}
+
+/// Control points written as `x:y` pairs separated by spaces, sorted by x.
+fn parse_points(text: &str) -> Vec<(f64, f64)> {
+    let points: Vec<(f64, f64)> = text
+        .split_whitespace()
+        .filter_map(|pair| {
+            let (x, y) = pair.split_once(':')?;
+            Some((x.parse().ok()?, y.parse().ok()?))
+        })
+        .collect();
+    if points.len() < 2 {
+        IDENTITY.to_vec()
+    } else {
+        points
+    }
+}
+
+fn format_points(points: &[(f64, f64)]) -> String {
+    points
+        .iter()
+        .map(|(x, y)| format!("{x:.3}:{y:.3}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn points() -> Vec<(f64, f64)> {
+    parse_points(&CURVE_POINTS.with(|p| p.value()))
+}
+
+fn set_points(points: &[(f64, f64)]) {
+    CURVE_POINTS.with(|p| p.set_value(&format_points(points)));
+    draw_editor();
+    if is_checked!(curve_enabled) {
+        update_current_noise();
+    }
+}
+
+/// Monotone cubic interpolation through the points (Fritsch-Carlson), which never overshoots,
+/// so a curve drawn between two levels stays between them.
+fn spline(points: &[(f64, f64)]) -> impl Fn(f64) -> f64 + '_ {
+    let n = points.len();
+    let secants: Vec<f64> = points
+        .windows(2)
+        .map(|w| (w[1].1 - w[0].1) / (w[1].0 - w[0].0))
+        .collect();
+    let mut tangents: Vec<f64> = (0..n)
+        .map(|i| match i {
+            0 => secants[0],
+            i if i == n - 1 => secants[n - 2],
+            i if secants[i - 1] * secants[i] <= 0. => 0.,
+            i => (secants[i - 1] + secants[i]) / 2.,
+        })
+        .collect();
+    for (i, &secant) in secants.iter().enumerate() {
+        if secant == 0. {
+            tangents[i] = 0.;
+            tangents[i + 1] = 0.;
+            continue;
+        }
+        let (a, b) = (tangents[i] / secant, tangents[i + 1] / secant);
+        let length = a.hypot(b);
+        if length > 3. {
+            tangents[i] = 3. / length * a * secant;
+            tangents[i + 1] = 3. / length * b * secant;
+        }
+    }
+
+    move |x: f64| {
+        let x = x.clamp(points[0].0, points[n - 1].0);
+        let i = points.windows(2).position(|w| x <= w[1].0).unwrap_or(n - 2);
+        let ((x0, y0), (x1, y1)) = (points[i], points[i + 1]);
+        let width = x1 - x0;
+        let t = (x - x0) / width;
+        let (t2, t3) = (t * t, t * t * t);
+        (2. * t3 - 3. * t2 + 1.) * y0
+            + (t3 - 2. * t2 + t) * width * tangents[i]
+            + (-2. * t3 + 3. * t2) * y1
+            + (t3 - t2) * width * tangents[i + 1]
+    }
+}
+
+fn lut(points: &[(f64, f64)]) -> Vec<f64> {
+    let curve = spline(points);
+    (0..LUT_SIZE)
+        .map(|i| curve(i as f64 / (LUT_SIZE - 1) as f64 * 2. - 1.).clamp(-1., 1.))
+        .collect()
+}
+
+/// Looks a value up in the table, interpolating between neighbouring entries.
+fn remap(lut: &[f64], value: f64) -> f64 {
+    let position = ((value.clamp(-1., 1.) + 1.) / 2.) * (LUT_SIZE - 1) as f64;
+    let i = (position as usize).min(LUT_SIZE - 2);
+    let t = position - i as f64;
+    lut[i] + (lut[i + 1] - lut[i]) * t
+}
+
+/// Editor pixels of a point on the curve, x to the right and y upwards.
+fn to_editor(chart: &Chart, (x, y): (f64, f64)) -> (f64, f64) {
+    ((x + 1.) / 2. * chart.width, (1. - y) / 2. * chart.height)
+}
+
+fn from_editor(editor: &HtmlCanvasElement, event: &MouseEvent) -> (f64, f64) {
+    let x = event.offset_x() as f64 / editor.client_width().max(1) as f64 * 2. - 1.;
+    let y = 1. - event.offset_y() as f64 / editor.client_height().max(1) as f64 * 2.;
+    (x.clamp(-1., 1.), y.clamp(-1., 1.))
+}
+
+fn draw_editor() {
+    let chart = CURVE_EDITOR.with(|e| Chart::new(e));
+    let points = points();
+    let (width, height) = (chart.width, chart.height);
+
+    chart.clear("#ffffff");
+    for quarter in 1..4 {
+        let t = quarter as f64 / 4.;
+        chart.line((t * width, 0.), (t * width, height), "#eeeeee");
+        chart.line((0., t * height), (width, t * height), "#eeeeee");
+    }
+    chart.line((0., height), (width, 0.), "#cccccc");
+
+    let curve = spline(&points);
+    let samples: Vec<(f64, f64)> = (0..=width as usize)
+        .map(|px| {
+            let x = px as f64 / width * 2. - 1.;
+            to_editor(&chart, (x, curve(x).clamp(-1., 1.)))
+        })
+        .collect();
+    chart.polyline(&samples, "#1f77b4");
+    for &point in &points {
+        chart.circle(to_editor(&chart, point), 4., "#1f77b4");
+    }
+}
+
+/// Index of the control point within grabbing distance of the cursor.
+fn grabbed(points: &[(f64, f64)], editor: &HtmlCanvasElement, event: &MouseEvent) -> Option<usize> {
+    let scale = editor.client_width().max(1) as f64 / 2.;
+    let (x, y) = from_editor(editor, event);
+    points
+        .iter()
+        .enumerate()
+        .map(|(i, p)| (i, (p.0 - x).hypot(p.1 - y) * scale))
+        .filter(|(_, distance)| *distance <= GRAB_RADIUS)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(i, _)| i)
+}
+
+/// Grabs the control point under the cursor, or adds one there and grabs that.
+fn on_press(event: MouseEvent) {
+    let mut points = points();
+    let index = CURVE_EDITOR.with(|editor| {
+        grabbed(&points, editor, &event).or_else(|| {
+            let (x, y) = from_editor(editor, &event);
+            let i = points.iter().position(|p| p.0 > x)?;
+            let spaced = (x - points[i - 1].0).min(points[i].0 - x) >= MIN_SPACING;
+            spaced.then(|| {
+                points.insert(i, (x, y));
+                i
+            })
+        })
+    });
+    DRAGGED.with(|d| d.set(index));
+    set_points(&points);
+}
+
+/// Moves the grabbed point, the end points only up and down and the others no further than
+/// their neighbours.
+fn on_drag(event: MouseEvent) {
+    let Some(i) = DRAGGED.with(|d| d.get()) else {
+        return;
+    };
+    if event.buttons() & 1 == 0 {
+        release();
+        return;
+    }
+
+    let mut points = points();
+    if i >= points.len() {
+        return;
+    }
+    let (x, y) = CURVE_EDITOR.with(|editor| from_editor(editor, &event));
+    let last = points.len() - 1;
+    points[i] = match i {
+        0 => (-1., y),
+        i if i == last => (1., y),
+        i => (
+            x.clamp(points[i - 1].0 + MIN_SPACING, points[i + 1].0 - MIN_SPACING),
+            y,
+        ),
+    };
+    set_points(&points);
+}
+
+fn release() {
+    if DRAGGED.with(|d| d.take()).is_some() {
+        state::save();
+    }
+}
+
+/// Removes the control point under the cursor, the end points stay.
+fn on_double_click(event: MouseEvent) {
+    let mut points = points();
+    let Some(i) = CURVE_EDITOR.with(|editor| grabbed(&points, editor, &event)) else {
+        return;
+    };
+    if i == 0 || i == points.len() - 1 {
+        return;
+    }
+    points.remove(i);
+    set_points(&points);
+    state::save();
+}
+
+fn reset() {
+    set_points(&IDENTITY);
+    state::save();
+}
+
+/// Runs after erosion and before the views, so every view shows the remapped field. Erosion
+/// keeps its own heights, the remap never compounds over its frames.
+pub fn on_frame_drawn() {
+    draw_editor();
+    if !is_checked!(curve_enabled) {
+        return;
+    }
+
+    let field = FIELD.with(|f| f.borrow().clone());
+    if field.is_empty() {
+        return;
+    }
+    let lut = lut(&points());
+    draw_field(field.iter().map(|&v| remap(&lut, v)).collect());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identity_curve_changes_nothing() {
+        let lut = lut(&IDENTITY);
+        for value in [-1., -0.5, 0., 0.3, 1.] {
+            assert!((remap(&lut, value) - value).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn curve_passes_through_its_points_without_overshooting() {
+        let points = [(-1., -1.), (-0.2, -0.9), (0.2, 0.9), (1., 1.)];
+        let curve = spline(&points);
+        for &(x, y) in &points {
+            assert!((curve(x) - y).abs() < 1e-9);
+        }
+        for i in 0..=100 {
+            let x = i as f64 / 50. - 1.;
+            assert!((-1. ..=1.).contains(&curve(x)));
+        }
+        assert!(curve(-0.6) < -0.9 && curve(0.6) > 0.9);
+    }
+
+    #[test]
+    fn points_round_trip_through_text() {
+        let points = vec![(-1., -1.), (0.25, 0.5), (1., 0.75)];
+        assert_eq!(parse_points(&format_points(&points)), points);
+        assert_eq!(parse_points("garbage"), IDENTITY.to_vec());
+    }
+}
@@ -0,0 +1,173 @@
+use std::cell::LazyCell;
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlElement, HtmlInputElement};
+
+use crate::{
+    core::{Perlin, Sampler},
+    drawer::{draw_image, draw_noise, draw_text, stroke_rect},
+    field::{draw_field, sample_field},
+    noises::noise::Noise,
+    *,
+};
+
+/// Side of the lookup table inset in pixels.
+const TABLE_SIZE: u32 = 120;
+const TABLE_MARGIN: f64 = 10.;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Biome {
+    Tundra,
+    Taiga,
+    Snow,
+    Grassland,
+    Shrubland,
+    TemperateForest,
+    TemperateRainforest,
+    Desert,
+    Savanna,
+    TropicalForest,
+    TropicalRainforest,
+}
+
+impl Biome {
+    fn color(self) -> [u8; 4] {
+        match self {
+            Biome::Tundra => [150, 160, 140, 255],
+            Biome::Taiga => [90, 120, 90, 255],
+            Biome::Snow => [240, 245, 250, 255],
+            Biome::Grassland => [170, 200, 100, 255],
+            Biome::Shrubland => [180, 170, 110, 255],
+            Biome::TemperateForest => [70, 140, 60, 255],
+            Biome::TemperateRainforest => [40, 110, 70, 255],
+            Biome::Desert => [230, 200, 130, 255],
+            Biome::Savanna => [200, 180, 80, 255],
+            Biome::TropicalForest => [60, 160, 40, 255],
+            Biome::TropicalRainforest => [20, 120, 40, 255],
+        }
+    }
+}
+
+/// A coarse Whittaker diagram, rows go from cold to hot and columns from dry to wet.
+const WHITTAKER: [[Biome; 4]; 4] = {
+    use Biome::*;
+    [
+        [Tundra, Tundra, Snow, Snow],
+        [Grassland, Shrubland, Taiga, Taiga],
+        [Grassland, Shrubland, TemperateForest, TemperateRainforest],
+        [Desert, Savanna, TropicalForest, TropicalRainforest],
+    ]
+};
+
+/// Looks up the biome for a temperature and moisture in [0, 1].
+fn biome(temperature: f64, moisture: f64) -> Biome {
+    let band = |v: f64| ((v.clamp(0., 1.) * 4.) as usize).min(3);
+    WHITTAKER[band(temperature)][band(moisture)]
+}
+
+/// Temperature and moisture in [0, 1], two Perlin fields with unrelated seeds.
+fn climate_fields(settings: &BiomesNoiseSettings) -> (Vec<f64>, Vec<f64>) {
+    let seed = settings.seed.value();
+    let octaves = settings.octaves.value();
+    let scale = settings.scale.value();
+    let channel = |seed: u32, bias: f64| {
+        let noise = Perlin::new(seed).octaves(octaves);
+        sample_field(scale, |x, y| ((noise.sample(x, y) + 1.) / 2. + bias).clamp(0., 1.))
+    };
+    (
+        channel(seed, settings.temperature_bias.value()),
+        channel(seed.wrapping_add(7919), settings.moisture_bias.value()),
+    )
+}
+
+impl BiomesNoise {
+    fn on_setup() {}
+
+    fn on_update() {}
+
+    /// Draws the lookup table in the top left corner, temperature rising downwards and moisture
+    /// to the right.
+    fn draw_table() {
+        let pixels: Vec<u8> = (0..TABLE_SIZE * TABLE_SIZE)
+            .flat_map(|i| {
+                let moisture = (i % TABLE_SIZE) as f64 / TABLE_SIZE as f64;
+                let temperature = (i / TABLE_SIZE) as f64 / TABLE_SIZE as f64;
+                biome(temperature, moisture).color()
+            })
+            .collect();
+        draw_image(&pixels, TABLE_SIZE, TABLE_SIZE, TABLE_MARGIN, TABLE_MARGIN);
+        stroke_rect(
+            TABLE_MARGIN,
+            TABLE_MARGIN,
+            TABLE_SIZE as f64,
+            TABLE_SIZE as f64,
+            "#000000",
+        );
+        draw_text(
+            "wet →",
+            TABLE_MARGIN + TABLE_SIZE as f64 - 36.,
+            TABLE_MARGIN + TABLE_SIZE as f64 + 14.,
+            "#000000",
+        );
+        draw_text(
+            "hot ↓",
+            TABLE_MARGIN,
+            TABLE_MARGIN + TABLE_SIZE as f64 + 14.,
+            "#000000",
+        );
+    }
+
+    fn generate_and_draw(settings: BiomesNoiseSettings) {
+        let (temperature, moisture) = climate_fields(&settings);
+        let to_field =
+            |values: Vec<f64>| -> Vec<f64> { values.iter().map(|v| v * 2. - 1.).collect() };
+
+        match settings.biome_layer {
+            BiomeLayer::BiomeMap => {
+                let pixels: Vec<u8> = temperature
+                    .iter()
+                    .zip(&moisture)
+                    .flat_map(|(&t, &m)| biome(t, m).color())
+                    .collect();
+                // The biome map has no single value per pixel, views and analysis keep working
+                // on the temperature.
+                draw_field(to_field(temperature));
+                draw_noise(&pixels);
+            }
+            BiomeLayer::TemperatureLayer => draw_field(to_field(temperature)),
+            BiomeLayer::MoistureLayer => draw_field(to_field(moisture)),
+        }
+
+        if settings.show_lookup.value() {
+            Self::draw_table();
+        }
+    }
+}
+
+define_noise!(biomes,
+    sliders:[
+        (seed, u32, 0., 42., 1000.),
+        (scale, f64, 20., 150., 400.),
+        (octaves, u32, 1., 4., 8.),
+        (temperature_bias, f64, -0.5, 0., 0.5),
+        (moisture_bias, f64, -0.5, 0., 0.5)
+    ];
+    radios:[
+        (biome_layer, (biome_map), (temperature_layer), (moisture_layer))
+    ];
+    checkboxes:[show_lookup];
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookup_follows_the_whittaker_diagram() {
+        assert_eq!(biome(0., 0.), Biome::Tundra);
+        assert_eq!(biome(0.05, 1.), Biome::Snow);
+        assert_eq!(biome(1., 0.), Biome::Desert);
+        assert_eq!(biome(0.99, 0.99), Biome::TropicalRainforest);
+        assert_eq!(biome(0.6, 0.6), Biome::TemperateForest);
+    }
+}
@@ -0,0 +1,194 @@
+use std::cell::{LazyCell, RefCell};
+use std::collections::VecDeque;
+use std::f64::consts::PI;
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlElement, HtmlInputElement};
+
+use crate::{
+    drawer::{RESOLUTION, clear_canvas, draw_circle, draw_polyline},
+    field::draw_field,
+    noises::noise::Noise,
+    scheduler, *,
+};
+
+const ANIMATION_NAME: &str = "brownian_motion";
+const ANIMATION_PERIOD_MS: i32 = 30;
+const TRAIL_LENGTH: usize = 300;
+const WALKER_COLORS: [&str; 6] = [
+    "#d62728", "#1f77b4", "#2ca02c", "#9467bd", "#ff7f0e", "#17becf",
+];
+
+struct Walker {
+    trail: VecDeque<(f64, f64)>,
+}
+
+/// Walkers taking independent gaussian steps, and how often each pixel has been visited.
+struct BrownianMotionImpl {
+    seed: u32,
+    steps: u32,
+    walkers: Vec<Walker>,
+    visits: Vec<u32>,
+}
+
+thread_local! {
+    static SIMULATION: RefCell<Option<BrownianMotionImpl>> = const { RefCell::new(None) };
+}
+
+impl BrownianMotionImpl {
+    /// All walkers start at the center of the canvas.
+    pub fn new(seed: u32, walker_count: u32) -> Self {
+        let center = (HALF_RESOLUTION as f64, HALF_RESOLUTION as f64);
+        BrownianMotionImpl {
+            seed,
+            steps: 0,
+            walkers: (0..walker_count)
+                .map(|_| Walker {
+                    trail: VecDeque::from([center]),
+                })
+                .collect(),
+            visits: vec![0; (RESOLUTION * RESOLUTION) as usize],
+        }
+    }
+
+    /// Standard normal sample via Box-Muller, indexed so every step of every walker is reproducible.
+    fn gaussian(&self, index: i32) -> (f64, f64) {
+        let u1 = (squirrel_noise5::f32_zero_to_one_1d(index.wrapping_mul(2), self.seed as i32)
+            as f64)
+            .max(1e-9);
+        let u2 = squirrel_noise5::f32_zero_to_one_1d(
+            index.wrapping_mul(2).wrapping_add(1),
+            self.seed as i32,
+        ) as f64;
+        let radius = (-2. * u1.ln()).sqrt();
+        (radius * (2. * PI * u2).cos(), radius * (2. * PI * u2).sin())
+    }
+
+    /// Moves every walker once, reflecting it back from the canvas edges.
+    fn step(&mut self, step_size: f64) {
+        let walker_count = self.walkers.len() as i32;
+        let limit = (RESOLUTION - 1) as f64;
+        let reflect = |v: f64| {
+            if v < 0. {
+                -v
+            } else if v > limit {
+                2. * limit - v
+            } else {
+                v
+            }
+        };
+
+        for w in 0..self.walkers.len() {
+            let (dx, dy) = self.gaussian(
+                (self.steps as i32)
+                    .wrapping_mul(walker_count)
+                    .wrapping_add(w as i32),
+            );
+            let trail = &mut self.walkers[w].trail;
+            let &(x, y) = trail.back().unwrap();
+            let next = (reflect(x + dx * step_size), reflect(y + dy * step_size));
+
+            trail.push_back(next);
+            if trail.len() > TRAIL_LENGTH {
+                trail.pop_front();
+            }
+            self.visits[next.1 as usize * RESOLUTION as usize + next.0 as usize] += 1;
+        }
+        self.steps += 1;
+    }
+
+    /// Log-scaled visit counts, so rarely visited pixels are still visible next to the start point.
+    fn generate_field(&self) -> Vec<f64> {
+        let max = self.visits.iter().copied().max().unwrap_or(0).max(1) as f64;
+        self.visits
+            .iter()
+            .map(|&count| (count as f64).ln_1p() / max.ln_1p() * 2. - 1.)
+            .collect()
+    }
+}
+
+impl BrownianMotionNoise {
+    fn on_setup() {}
+
+    fn on_update() {
+        let label = if scheduler::is_running(ANIMATION_NAME) {
+            "Pause"
+        } else {
+            "Play"
+        };
+        PLAY_PAUSE.with(|b| b.set_inner_text(label));
+    }
+
+    fn on_play_pause() {
+        if scheduler::is_running(ANIMATION_NAME) {
+            scheduler::stop(ANIMATION_NAME);
+        } else {
+            scheduler::start(ANIMATION_NAME, ANIMATION_PERIOD_MS, Self::tick);
+        }
+        Self::update();
+    }
+
+    fn on_restart() {
+        SIMULATION.with(|s| s.replace(None));
+        Self::update();
+    }
+
+    fn tick() {
+        if BROWNIAN_MOTION.with(|e| e.hidden()) {
+            scheduler::stop(ANIMATION_NAME);
+            return;
+        }
+
+        let settings = BrownianMotionNoiseSettings::parse();
+        SIMULATION.with(|s| {
+            if let Some(simulation) = s.borrow_mut().as_mut() {
+                for _ in 0..settings.steps_per_frame.value() {
+                    simulation.step(settings.step_size.value());
+                }
+            }
+        });
+        Self::update();
+    }
+
+    fn generate_and_draw(settings: BrownianMotionNoiseSettings) {
+        let seed = settings.seed.value();
+        let walker_count = settings.walker_count.value();
+
+        SIMULATION.with(|s| {
+            let mut simulation = s.borrow_mut();
+            if simulation
+                .as_ref()
+                .is_none_or(|sim| sim.seed != seed || sim.walkers.len() != walker_count as usize)
+            {
+                simulation.replace(BrownianMotionImpl::new(seed, walker_count));
+            }
+            let simulation = simulation.as_ref().unwrap();
+
+            if settings.show_density.value() {
+                draw_field(simulation.generate_field());
+            } else {
+                clear_canvas("#ffffff");
+            }
+
+            for (w, walker) in simulation.walkers.iter().enumerate() {
+                let color = WALKER_COLORS[w % WALKER_COLORS.len()];
+                let points: Vec<_> = walker.trail.iter().copied().collect();
+                draw_polyline(&points, color, 1.5);
+                let &(x, y) = walker.trail.back().unwrap();
+                draw_circle(x, y, 3., color);
+            }
+        });
+    }
+}
+
+define_noise!(brownian_motion,
+    sliders:[
+        (seed, u32, 0., 42., 1000.),
+        (walker_count, u32, 1., 6., 50.),
+        (step_size, f64, 0.5, 3., 10.),
+        (steps_per_frame, u32, 1., 10., 40.)
+    ];
+    radios:[];
+    checkboxes:[show_density];
+    buttons:[play_pause, restart];
+);
@@ -0,0 +1,221 @@
+use std::cell::LazyCell;
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlElement, HtmlInputElement};
+
+use crate::{
+    core::{Perlin, Sampler, Worley},
+    drawer::{HALF_RESOLUTION, RESOLUTION, draw_noise},
+    field::draw_field,
+    noises::{helpers::lerp, noise::Noise},
+    *,
+};
+
+const WALL_COLOR: [u8; 4] = [59, 48, 38, 255];
+const FLOOR_COLOR: [u8; 4] = [217, 199, 163, 255];
+const POCKET_COLOR: [u8; 4] = [150, 115, 80, 255];
+/// Brightness of the pixels along the top and left edge of every tile.
+const TILE_EDGE_SHADE: f64 = 0.85;
+
+elements!((caves_readout, HtmlElement));
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Tile {
+    Wall,
+    Floor,
+    /// Floor that the flood fill could not reach from the main cave.
+    Pocket,
+}
+
+/// A grid of tiles, the noise is sampled once at the center of every tile.
+struct CaveMap {
+    tiles: Vec<Tile>,
+    columns: usize,
+    rows: usize,
+}
+
+impl CaveMap {
+    /// Everything where inverted Worley noise mixed with fBm stays below the threshold is floor,
+    /// so the floor forms rooms around the Worley feature points joined by fBm tunnels.
+    fn generate(settings: &CavesNoiseSettings) -> Self {
+        let seed = settings.seed.value();
+        let perlin = Perlin::new(seed).octaves(settings.octaves.value());
+        let worley = Worley::new(seed);
+        let scale = settings.scale.value();
+        let tile_size = settings.tile_size.value() as usize;
+        let (columns, rows) = (
+            RESOLUTION as usize / tile_size,
+            RESOLUTION as usize / tile_size,
+        );
+
+        let tiles = (0..columns * rows)
+            .map(|i| {
+                let to_noise = |tile: usize| {
+                    ((tile * tile_size) as f64 + tile_size as f64 / 2. - HALF_RESOLUTION as f64)
+                        / scale
+                };
+                let (x, y) = (to_noise(i % columns), to_noise(i / columns));
+                let value = lerp(
+                    settings.worley_mix.value(),
+                    perlin.sample(x, y),
+                    -worley.sample(x, y),
+                );
+                if value > settings.threshold.value() {
+                    Tile::Wall
+                } else {
+                    Tile::Floor
+                }
+            })
+            .collect();
+        CaveMap {
+            tiles,
+            columns,
+            rows,
+        }
+    }
+
+    /// Marks every floor region except the largest as a pocket, with a 4-connected flood fill so
+    /// the player can walk everywhere that stays floor. Returns the number of pockets.
+    fn discard_pockets(&mut self) -> usize {
+        let mut region = vec![usize::MAX; self.tiles.len()];
+        let mut sizes = Vec::new();
+        for start in 0..self.tiles.len() {
+            if self.tiles[start] != Tile::Floor || region[start] != usize::MAX {
+                continue;
+            }
+            let id = sizes.len();
+            let mut size = 0;
+            let mut stack = vec![start];
+            region[start] = id;
+            while let Some(i) = stack.pop() {
+                size += 1;
+                let (x, y) = (i % self.columns, i / self.columns);
+                let neighbours = [
+                    (x > 0).then(|| i - 1),
+                    (x + 1 < self.columns).then(|| i + 1),
+                    (y > 0).then(|| i - self.columns),
+                    (y + 1 < self.rows).then(|| i + self.columns),
+                ];
+                for neighbour in neighbours.into_iter().flatten() {
+                    if self.tiles[neighbour] == Tile::Floor && region[neighbour] == usize::MAX {
+                        region[neighbour] = id;
+                        stack.push(neighbour);
+                    }
+                }
+            }
+            sizes.push(size);
+        }
+
+        let Some(largest) = (0..sizes.len()).max_by_key(|&id| sizes[id]) else {
+            return 0;
+        };
+        for (tile, &id) in self.tiles.iter_mut().zip(&region) {
+            if *tile == Tile::Floor && id != largest {
+                *tile = Tile::Pocket;
+            }
+        }
+        sizes.len() - 1
+    }
+
+    fn tile_at_pixel(&self, x: usize, y: usize) -> Option<Tile> {
+        let tile_size = RESOLUTION as usize / self.columns;
+        let (column, row) = (x / tile_size, y / tile_size);
+        (column < self.columns && row < self.rows).then(|| self.tiles[row * self.columns + column])
+    }
+
+    /// Walls are 1 and floor -1 in the retained field, pockets count as walls since the cave
+    /// fills them in.
+    fn generate_field(&self) -> Vec<f64> {
+        (0..(RESOLUTION * RESOLUTION) as usize)
+            .map(|i| {
+                match self.tile_at_pixel(i % RESOLUTION as usize, i / RESOLUTION as usize) {
+                    Some(Tile::Floor) => -1.,
+                    _ => 1.,
+                }
+            })
+            .collect()
+    }
+
+    fn colorize(&self, show_pockets: bool) -> Vec<u8> {
+        let tile_size = RESOLUTION as usize / self.columns;
+        (0..(RESOLUTION * RESOLUTION) as usize)
+            .flat_map(|i| {
+                let (x, y) = (i % RESOLUTION as usize, i / RESOLUTION as usize);
+                let color = match self.tile_at_pixel(x, y) {
+                    Some(Tile::Floor) => FLOOR_COLOR,
+                    Some(Tile::Pocket) if show_pockets => POCKET_COLOR,
+                    _ => WALL_COLOR,
+                };
+                // A darker line along the top and left of every tile keeps the grid readable.
+                let [r, g, b, a] = color;
+                if tile_size > 2 && (x % tile_size == 0 || y % tile_size == 0) {
+                    let shade = |c: u8| (c as f64 * TILE_EDGE_SHADE) as u8;
+                    [shade(r), shade(g), shade(b), a]
+                } else {
+                    color
+                }
+            })
+            .collect()
+    }
+}
+
+impl CavesNoise {
+    fn on_setup() {}
+
+    fn on_update() {}
+
+    fn generate_and_draw(settings: CavesNoiseSettings) {
+        let mut map = CaveMap::generate(&settings);
+        let pockets = map.discard_pockets();
+        let floor = map.tiles.iter().filter(|&&t| t == Tile::Floor).count();
+
+        draw_field(map.generate_field());
+        draw_noise(&map.colorize(settings.show_pockets.value()));
+        CAVES_READOUT.with(|r| {
+            r.set_inner_text(&format!(
+                "{}x{} tiles, {:.0}% floor, {pockets} disconnected pockets filled in",
+                map.columns,
+                map.rows,
+                floor as f64 / map.tiles.len() as f64 * 100.
+            ))
+        });
+    }
+}
+
+define_noise!(caves,
+    sliders:[
+        (seed, u32, 0., 42., 1000.),
+        (scale, f64, 20., 80., 200.),
+        (octaves, u32, 1., 3., 8.),
+        (worley_mix, f64, 0., 0.6, 1.),
+        (threshold, f64, -1., 0., 1.),
+        (tile_size, u32, 2., 8., 20.)
+    ];
+    radios:[];
+    checkboxes:[show_pockets];
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_the_largest_region_stays_floor() {
+        use Tile::{Floor as F, Wall as W};
+        let mut map = CaveMap {
+            tiles: vec![
+                F, F, W, F, //
+                F, F, W, W, //
+                W, W, W, F, //
+                F, W, W, F, //
+            ],
+            columns: 4,
+            rows: 4,
+        };
+        assert_eq!(map.discard_pockets(), 3);
+        let floor: Vec<usize> = (0..16).filter(|&i| map.tiles[i] == F).collect();
+        assert_eq!(floor, vec![0, 1, 4, 5]);
+        assert_eq!(map.tiles[3], Tile::Pocket);
+        assert_eq!(map.tiles[11], Tile::Pocket);
+    }
+}
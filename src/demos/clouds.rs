@@ -0,0 +1,147 @@
+use std::cell::LazyCell;
+
+use rayon::prelude::*;
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlElement, HtmlInputElement};
+
+use crate::{
+    core::{Perlin, Sampler, Worley},
+    drawer::{RESOLUTION, draw_noise},
+    field::{draw_field, sample_field},
+    noises::{helpers::lerp, noise::Noise},
+    *,
+};
+
+/// Number of density samples taken on the way from a pixel towards the sun.
+const LIGHT_STEPS: usize = 12;
+/// Distance between two light samples, in pixels.
+const LIGHT_STEP_PIXELS: f64 = 3.;
+/// How much thicker the clouds look to the light than to the eye, so the shading shows even where
+/// the clouds are thin enough to see through.
+const SHADOW_DENSITY: f64 = 2.;
+const SKY_TOP: [f64; 3] = [70., 130., 210.];
+const SKY_BOTTOM: [f64; 3] = [165., 205., 240.];
+const SUN_COLOR: [f64; 3] = [255., 248., 230.];
+const AMBIENT_COLOR: [f64; 3] = [95., 105., 125.];
+
+/// Cloud density in [0, 1] for every pixel: fBm Perlin noise for the overall shape mixed with
+/// Worley noise for the billows, with everything below the coverage cut away.
+fn density_field(settings: &CloudsNoiseSettings) -> Vec<f64> {
+    let seed = settings.seed.value();
+    let octaves = settings.octaves.value();
+    let perlin = Perlin::new(seed).octaves(octaves);
+    let worley = Worley::new(seed).octaves(octaves);
+    let worley_mix = settings.worley_mix.value();
+    let coverage = settings.coverage.value();
+
+    sample_field(settings.scale.value(), |x, y| {
+        let shape = (perlin.sample(x, y) + 1.) / 2.;
+        let billows = (worley.sample(x * 2., y * 2.) + 1.) / 2.;
+        let value = lerp(worley_mix, shape, billows);
+        ((value - (1. - coverage)) / coverage).clamp(0., 1.)
+    })
+}
+
+/// Fraction of sunlight that reaches every pixel through the clouds between it and the sun, a
+/// single scattering pass marched in screen space.
+fn transmittance(density: &[f64], sun_angle: f64, absorption: f64) -> Vec<f64> {
+    let size = RESOLUTION as usize;
+    let (dx, dy) = (sun_angle.to_radians().cos(), -sun_angle.to_radians().sin());
+    (0..size * size)
+        .into_par_iter()
+        .map(|i| {
+            let (x, y) = ((i % size) as f64, (i / size) as f64);
+            let depth: f64 = (1..=LIGHT_STEPS)
+                .map(|step| {
+                    let distance = step as f64 * LIGHT_STEP_PIXELS;
+                    let sx = (x + dx * distance).round();
+                    let sy = (y + dy * distance).round();
+                    if sx < 0. || sy < 0. || sx >= size as f64 || sy >= size as f64 {
+                        0.
+                    } else {
+                        density[sy as usize * size + sx as usize]
+                    }
+                })
+                .sum();
+            (-absorption * SHADOW_DENSITY * depth * LIGHT_STEP_PIXELS / LIGHT_STEPS as f64).exp()
+        })
+        .collect()
+}
+
+/// Composites the lit clouds over a sky gradient, the opacity of a pixel follows Beer's law of
+/// its own density.
+fn shade(density: &[f64], light: &[f64], absorption: f64) -> Vec<u8> {
+    let size = RESOLUTION as usize;
+    density
+        .iter()
+        .zip(light)
+        .enumerate()
+        .flat_map(|(i, (&density, &light))| {
+            let height = (i / size) as f64 / size as f64;
+            let opacity = 1. - (-absorption * density).exp();
+            let channel = |c: usize| {
+                let sky = lerp(height, SKY_TOP[c], SKY_BOTTOM[c]);
+                let cloud = AMBIENT_COLOR[c] + (SUN_COLOR[c] - AMBIENT_COLOR[c]) * light;
+                lerp(opacity, sky, cloud) as u8
+            };
+            [channel(0), channel(1), channel(2), 255]
+        })
+        .collect()
+}
+
+impl CloudsNoise {
+    fn on_setup() {}
+
+    fn on_update() {}
+
+    fn generate_and_draw(settings: CloudsNoiseSettings) {
+        let density = density_field(&settings);
+        let absorption = settings.absorption.value();
+        let light = transmittance(&density, settings.sun_angle.value(), absorption);
+        let pixels = shade(&density, &light, absorption);
+
+        // The density stays the retained field for views and analysis, the flat view shows the
+        // lit clouds instead of the palette.
+        draw_field(density.iter().map(|d| d * 2. - 1.).collect());
+        draw_noise(&pixels);
+    }
+}
+
+define_noise!(clouds,
+    sliders:[
+        (seed, u32, 0., 42., 1000.),
+        (scale, f64, 20., 120., 400.),
+        (octaves, u32, 1., 5., 8.),
+        (worley_mix, f64, 0., 0.4, 1.),
+        (coverage, f64, 0.05, 0.5, 1.),
+        (absorption, f64, 0.5, 3., 10.),
+        (sun_angle, f64, 0., 45., 360., Degrees)
+    ];
+    radios:[];
+    checkboxes:[];
+    dials:[sun_angle];
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clouds_shadow_their_far_side() {
+        let size = RESOLUTION as usize;
+        let center = size as f64 / 2.;
+        let density: Vec<f64> = (0..size * size)
+            .map(|i| {
+                let (x, y) = ((i % size) as f64 - center, (i / size) as f64 - center);
+                if x * x + y * y < 40. * 40. { 1. } else { 0. }
+            })
+            .collect();
+        // The sun shines from the right, at zero degrees.
+        let light = transmittance(&density, 0., 3.);
+        let at = |x: f64| light[size / 2 * size + (center + x) as usize];
+
+        assert!(at(38.) > at(-38.), "lit {} against shadowed {}", at(38.), at(-38.));
+        assert_eq!(at(100.), 1.);
+        assert!(at(-45.) < 1., "the cloud casts a shadow to its left");
+    }
+}
@@ -0,0 +1,250 @@
+use std::cell::LazyCell;
+
+use rayon::prelude::*;
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlElement, HtmlInputElement, MouseEvent};
+
+use crate::{
+    core::{Perlin, Sampler},
+    drawer::{
+        CANVAS, RESOLUTION, canvas_coordinates, draw_circle, draw_line, draw_text, restore_frame,
+        save_frame, stroke_rect,
+    },
+    field::draw_field,
+    noises::noise::Noise,
+    *,
+};
+
+/// Side of both views in pixels.
+const PANEL_SIZE: f64 = 190.;
+const PANEL_TOP: f64 = 70.;
+const PANEL_LEFTS: [f64; 2] = [5., 205.];
+const LABELS: [&str; 2] = ["View A", "View B"];
+const WINDOW_STYLE: &str = "#d62728";
+
+elements!((determinism_readout, HtmlElement));
+
+/// Maps one panel onto the plane: the world point at the panel center and pixels per world unit.
+#[derive(Clone, Copy, Debug)]
+struct View {
+    center: (f64, f64),
+    scale: f64,
+}
+
+impl View {
+    fn to_world(self, panel_x: f64, panel_y: f64) -> (f64, f64) {
+        (
+            self.center.0 + (panel_x - PANEL_SIZE / 2.) / self.scale,
+            self.center.1 + (panel_y - PANEL_SIZE / 2.) / self.scale,
+        )
+    }
+
+    fn to_panel(self, x: f64, y: f64) -> (f64, f64) {
+        (
+            (x - self.center.0) * self.scale + PANEL_SIZE / 2.,
+            (y - self.center.1) * self.scale + PANEL_SIZE / 2.,
+        )
+    }
+
+    /// World space rectangle the panel shows, as (left, top, right, bottom).
+    fn window(self) -> (f64, f64, f64, f64) {
+        let (left, top) = self.to_world(0., 0.);
+        let (right, bottom) = self.to_world(PANEL_SIZE, PANEL_SIZE);
+        (left, top, right, bottom)
+    }
+}
+
+/// View A looks at the origin, view B is zoomed in and moved by the offset.
+fn views(settings: &DeterminismNoiseSettings) -> [View; 2] {
+    let scale = settings.scale.value();
+    [
+        View {
+            center: (0., 0.),
+            scale,
+        },
+        View {
+            center: (settings.offset_x.value(), settings.offset_y.value()),
+            scale: scale * settings.zoom.value(),
+        },
+    ]
+}
+
+/// Part of the plane both views show, if they overlap at all.
+fn shared_window(views: &[View; 2]) -> Option<(f64, f64, f64, f64)> {
+    let (a, b) = (views[0].window(), views[1].window());
+    let window = (a.0.max(b.0), a.1.max(b.1), a.2.min(b.2), a.3.min(b.3));
+    (window.0 < window.2 && window.1 < window.3).then_some(window)
+}
+
+/// The panel under a canvas point, and the point relative to the panel's top left corner.
+fn panel_at(x: f64, y: f64) -> Option<(usize, f64, f64)> {
+    PANEL_LEFTS.iter().enumerate().find_map(|(i, left)| {
+        let (panel_x, panel_y) = (x - left, y - PANEL_TOP);
+        let inside = (0. ..PANEL_SIZE).contains(&panel_x) && (0. ..PANEL_SIZE).contains(&panel_y);
+        inside.then_some((i, panel_x, panel_y))
+    })
+}
+
+define_event_closure!(
+    determinism_canvas_move,
+    MouseEvent,
+    DeterminismNoise::on_canvas_move
+);
+define_closure!(determinism_canvas_leave, DeterminismNoise::on_canvas_leave);
+
+impl DeterminismNoise {
+    fn on_setup() {
+        add_callback!(canvas, "mousemove", determinism_canvas_move);
+        add_callback!(canvas, "mouseleave", determinism_canvas_leave);
+    }
+
+    fn on_update() {}
+
+    fn is_shown() -> bool {
+        !DETERMINISM.with(|e| e.hidden()) && view::is_flat()
+    }
+
+    fn on_canvas_leave() {
+        if Self::is_shown() {
+            restore_frame();
+        }
+    }
+
+    /// Marks the world point under the cursor in both views. The noise is a function of the
+    /// world position alone, so both crosshairs sit on the same feature.
+    fn on_canvas_move(event: MouseEvent) {
+        if !Self::is_shown() {
+            return;
+        }
+        restore_frame();
+
+        let (x, y) = canvas_coordinates(&event);
+        let Some((panel, panel_x, panel_y)) = panel_at(x, y) else {
+            return;
+        };
+        let settings = DeterminismNoiseSettings::parse();
+        let views = views(&settings);
+        let (world_x, world_y) = views[panel].to_world(panel_x, panel_y);
+
+        let mut shown_in = 0;
+        for (i, view) in views.iter().enumerate() {
+            let (px, py) = view.to_panel(world_x, world_y);
+            if !(0. ..PANEL_SIZE).contains(&px) || !(0. ..PANEL_SIZE).contains(&py) {
+                continue;
+            }
+            let (cx, cy) = (PANEL_LEFTS[i] + px, PANEL_TOP + py);
+            draw_line(PANEL_LEFTS[i], cy, PANEL_LEFTS[i] + PANEL_SIZE, cy, "#000000");
+            draw_line(cx, PANEL_TOP, cx, PANEL_TOP + PANEL_SIZE, "#000000");
+            draw_circle(cx, cy, 3., "#000000");
+            shown_in += 1;
+        }
+
+        let noise = Perlin::new(settings.seed.value()).octaves(settings.octaves.value());
+        let value = noise.sample(world_x, world_y);
+        let readout = if shown_in == 2 {
+            format!(
+                "At ({world_x:.3}, {world_y:.3}) the noise is {value:.4} in both views, whatever their zoom and offset."
+            )
+        } else {
+            format!(
+                "At ({world_x:.3}, {world_y:.3}) the noise is {value:.4}. Only {} shows this point, move inside the red window to see it in both.",
+                LABELS[panel]
+            )
+        };
+        DETERMINISM_READOUT.with(|r| r.set_inner_text(&readout));
+    }
+
+    fn generate_and_draw(settings: DeterminismNoiseSettings) {
+        let noise = Perlin::new(settings.seed.value()).octaves(settings.octaves.value());
+        let views = views(&settings);
+
+        // Outside the panels the field is 0, which the colormap leaves white.
+        let size = RESOLUTION as usize;
+        let field = (0..size * size)
+            .into_par_iter()
+            .map(|i| {
+                let (x, y) = ((i % size) as f64 + 0.5, (i / size) as f64 + 0.5);
+                panel_at(x, y).map_or(0., |(panel, panel_x, panel_y)| {
+                    let (world_x, world_y) = views[panel].to_world(panel_x, panel_y);
+                    noise.sample(world_x, world_y)
+                })
+            })
+            .collect();
+        draw_field(field);
+
+        let window = shared_window(&views);
+        for (i, view) in views.iter().enumerate() {
+            let left = PANEL_LEFTS[i];
+            stroke_rect(left, PANEL_TOP, PANEL_SIZE, PANEL_SIZE, "#444444");
+            draw_text(LABELS[i], left, PANEL_TOP - 24., "#000000");
+            draw_text(
+                &format!(
+                    "({:.2}, {:.2}) at {:.0} px/unit",
+                    view.center.0, view.center.1, view.scale
+                ),
+                left,
+                PANEL_TOP - 8.,
+                "#444444",
+            );
+
+            if let Some((window_left, window_top, window_right, window_bottom)) = window {
+                let (x0, y0) = view.to_panel(window_left, window_top);
+                let (x1, y1) = view.to_panel(window_right, window_bottom);
+                stroke_rect(left + x0, PANEL_TOP + y0, x1 - x0, y1 - y0, WINDOW_STYLE);
+            }
+        }
+
+        DETERMINISM_READOUT.with(|r| {
+            r.set_inner_text(if window.is_some() {
+                "The red window is the same part of the plane in both views. Hover either view to mark a point in both."
+            } else {
+                "The views do not overlap, bring the offset closer to 0."
+            })
+        });
+        save_frame();
+    }
+}
+
+define_noise!(determinism,
+    sliders:[
+        (seed, u32, 0., 42., 1000.),
+        (scale, f64, 10., 40., 100.),
+        (octaves, u32, 1., 3., 8.),
+        (zoom, f64, 0.25, 2., 4.),
+        (offset_x, f64, -4., 1., 4.),
+        (offset_y, f64, -4., 0.5, 4.)
+    ];
+    radios:[];
+    checkboxes:[];
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn views_agree_on_world_points() {
+        let views = [
+            View {
+                center: (0., 0.),
+                scale: 40.,
+            },
+            View {
+                center: (1., 0.5),
+                scale: 80.,
+            },
+        ];
+        let (x, y) = views[1].to_world(30., 150.);
+        let (px, py) = views[1].to_panel(x, y);
+        assert!((px - 30.).abs() < 1e-9 && (py - 150.).abs() < 1e-9);
+
+        let (left, top, right, bottom) = shared_window(&views).unwrap();
+        assert_eq!((left, top, right, bottom), views[1].window());
+
+        let far = View {
+            center: (20., 0.),
+            scale: 40.,
+        };
+        assert_eq!(shared_window(&[views[0], far]), None);
+    }
+}
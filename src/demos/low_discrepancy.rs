@@ -0,0 +1,152 @@
+use std::cell::LazyCell;
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlElement, HtmlInputElement};
+
+use crate::{
+    drawer::{RESOLUTION, clear_canvas, draw_circle, draw_grid},
+    noises::noise::Noise,
+    *,
+};
+
+/// The plastic number, the 2D analogue of the golden ratio used by the R2 sequence.
+const PLASTIC_NUMBER: f64 = 1.324_717_957_244_746;
+const GRID_CELLS: f64 = 8.;
+
+elements!((discrepancy_readout, HtmlElement));
+
+struct LowDiscrepancyImpl;
+
+impl LowDiscrepancyImpl {
+    /// Van der Corput radical inverse: mirrors the digits of `index` in `base` around the decimal point.
+    fn radical_inverse(mut index: u32, base: u32) -> f64 {
+        let mut result = 0.;
+        let mut fraction = 1. / base as f64;
+        while index > 0 {
+            result += (index % base) as f64 * fraction;
+            index /= base;
+            fraction /= base as f64;
+        }
+        result
+    }
+
+    fn halton(index: u32) -> (f64, f64) {
+        (
+            Self::radical_inverse(index + 1, 2),
+            Self::radical_inverse(index + 1, 3),
+        )
+    }
+
+    /// First two Sobol dimensions. The first is the base 2 radical inverse, the second uses
+    /// the direction numbers of the primitive polynomial x + 1.
+    fn sobol(index: u32) -> (f64, f64) {
+        let mut direction = 1u32 << 31;
+        let mut y = 0u32;
+        let mut bits = index;
+        while bits > 0 {
+            if bits & 1 == 1 {
+                y ^= direction;
+            }
+            direction ^= direction >> 1;
+            bits >>= 1;
+        }
+        let scale = 1. / 4_294_967_296.;
+        (index.reverse_bits() as f64 * scale, y as f64 * scale)
+    }
+
+    fn r2(index: u32) -> (f64, f64) {
+        let a1 = 1. / PLASTIC_NUMBER;
+        let a2 = 1. / (PLASTIC_NUMBER * PLASTIC_NUMBER);
+        (
+            (0.5 + a1 * index as f64).fract(),
+            (0.5 + a2 * index as f64).fract(),
+        )
+    }
+
+    fn uniform_random(index: u32, seed: u32) -> (f64, f64) {
+        let random = |channel: u32| {
+            squirrel_noise5::f32_zero_to_one_1d((index * 2 + channel) as i32, seed as i32) as f64
+        };
+        (random(0), random(1))
+    }
+
+    pub fn generate(sequence: Sequence, count: u32, seed: u32) -> Vec<(f64, f64)> {
+        (0..count)
+            .map(|i| match sequence {
+                Sequence::Halton => Self::halton(i),
+                Sequence::Sobol => Self::sobol(i),
+                Sequence::R2 => Self::r2(i),
+                Sequence::UniformRandom => Self::uniform_random(i, seed),
+            })
+            .collect()
+    }
+
+    /// L2-star discrepancy from Warnock's closed form. It measures how far the fraction of
+    /// points inside boxes anchored at the origin strays from the area of those boxes.
+    pub fn l2_star_discrepancy(points: &[(f64, f64)]) -> f64 {
+        let n = points.len() as f64;
+        if points.is_empty() {
+            return 0.;
+        }
+
+        let single: f64 = points
+            .iter()
+            .map(|&(x, y)| (1. - x * x) * (1. - y * y) / 4.)
+            .sum();
+        let pairs: f64 = points
+            .iter()
+            .map(|&(xi, yi)| {
+                points
+                    .iter()
+                    .map(|&(xj, yj)| (1. - xi.max(xj)) * (1. - yi.max(yj)))
+                    .sum::<f64>()
+            })
+            .sum();
+
+        (1. / 9. - 2. / n * single + pairs / (n * n)).max(0.).sqrt()
+    }
+}
+
+impl LowDiscrepancyNoise {
+    fn on_setup() {}
+
+    fn on_update() {}
+
+    fn generate_and_draw(settings: LowDiscrepancyNoiseSettings) {
+        let points = LowDiscrepancyImpl::generate(
+            settings.sequence,
+            settings.point_count.value(),
+            settings.seed.value(),
+        );
+
+        clear_canvas("#ffffff");
+        if settings.show_grid.value() {
+            draw_grid(RESOLUTION as f64 / GRID_CELLS, "#dddddd");
+        }
+
+        let last = points.len().saturating_sub(1);
+        for (i, &(x, y)) in points.iter().enumerate() {
+            let style = if i == last { "#d62728" } else { "#000000" };
+            draw_circle(x * RESOLUTION as f64, y * RESOLUTION as f64, 2.5, style);
+        }
+
+        let discrepancy = LowDiscrepancyImpl::l2_star_discrepancy(&points);
+        DISCREPANCY_READOUT.with(|r| {
+            r.set_inner_text(&format!(
+                "L2-star discrepancy of {} points: {discrepancy:.5}",
+                points.len()
+            ))
+        });
+    }
+}
+
+define_noise!(low_discrepancy,
+    sliders:[
+        (seed, u32, 0., 42., 1000.),
+        (point_count, u32, 1., 256., 2000.)
+    ];
+    radios:[
+        (sequence, (halton, hide:[seed]), (sobol, hide:[seed]), (r2, hide:[seed]), (uniform_random))
+    ];
+    checkboxes:[show_grid];
+);
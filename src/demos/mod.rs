@@ -0,0 +1,9 @@
+pub mod reaction_diffusion;
+pub mod brownian_motion;
+pub mod low_discrepancy;
+pub mod quilting;
+pub mod smoke;
+pub mod clouds;
+pub mod caves;
+pub mod biomes;
+pub mod determinism;
@@ -0,0 +1,264 @@
+use std::cell::LazyCell;
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlElement, HtmlInputElement};
+
+use crate::{
+    drawer::{RESOLUTION, draw_image, stroke_rect},
+    field::{colorize, draw_field},
+    image_import,
+    noises::noise::Noise,
+    *,
+};
+
+const SIZE: usize = RESOLUTION as usize;
+/// Exemplar positions tried for every patch, searching all of them is too slow to follow a slider.
+const CANDIDATES: i32 = 300;
+/// Patches whose overlap error is within this fraction of the best one are equally good choices,
+/// picking randomly among them keeps the output from repeating the same patch.
+const TOLERANCE: f64 = 0.1;
+
+/// Image quilting (Efros and Freeman, 2001): the output is tiled with square patches copied from
+/// the exemplar, each one chosen to match the patches already placed in the strips where they
+/// overlap, and joined to them along the cheapest seam through that overlap.
+struct Quilt<'a> {
+    exemplar: &'a [f64],
+    exemplar_size: usize,
+    patch: usize,
+    overlap: usize,
+    seed: u32,
+    output: Vec<f64>,
+}
+
+impl<'a> Quilt<'a> {
+    fn new(
+        exemplar: &'a [f64],
+        exemplar_size: usize,
+        patch: usize,
+        overlap: usize,
+        seed: u32,
+    ) -> Self {
+        Quilt {
+            exemplar,
+            exemplar_size,
+            patch: patch.min(exemplar_size),
+            overlap: overlap.min(patch / 2),
+            seed,
+            output: vec![0.; SIZE * SIZE],
+        }
+    }
+
+    #[inline]
+    fn exemplar_at(&self, x: usize, y: usize) -> f64 {
+        self.exemplar[y * self.exemplar_size + x]
+    }
+
+    fn random(&self, index: i32) -> f64 {
+        squirrel_noise5::f32_zero_to_one_1d(index, self.seed as i32) as f64
+    }
+
+    /// Squared difference between the output and the patch of the exemplar at (cx, cy), for every
+    /// pixel of the patch placed at (x0, y0). Pixels outside the overlap or the canvas are zero.
+    fn overlap_error(&self, x0: usize, y0: usize, cx: usize, cy: usize) -> Vec<f64> {
+        let mut error = vec![0.; self.patch * self.patch];
+        for y in 0..self.patch.min(SIZE - y0) {
+            for x in 0..self.patch.min(SIZE - x0) {
+                let in_left = x0 > 0 && x < self.overlap;
+                let in_top = y0 > 0 && y < self.overlap;
+                if in_left || in_top {
+                    let difference =
+                        self.output[(y0 + y) * SIZE + x0 + x] - self.exemplar_at(cx + x, cy + y);
+                    error[y * self.patch + x] = difference * difference;
+                }
+            }
+        }
+        error
+    }
+
+    /// Picks the exemplar position of the next patch among `CANDIDATES` random ones.
+    fn choose_patch(&self, index: i32, x0: usize, y0: usize) -> (usize, usize, Vec<f64>) {
+        let range = (self.exemplar_size - self.patch + 1) as f64;
+        let mut candidates: Vec<(usize, usize, f64, Vec<f64>)> = (0..CANDIDATES)
+            .map(|candidate| {
+                let random_index = (index * CANDIDATES + candidate) * 2;
+                let cx = (self.random(random_index) * range) as usize;
+                let cy = (self.random(random_index + 1) * range) as usize;
+                let error = self.overlap_error(x0, y0, cx, cy);
+                (cx, cy, error.iter().sum(), error)
+            })
+            .collect();
+
+        let best = candidates.iter().map(|c| c.2).fold(f64::MAX, f64::min);
+        candidates.retain(|c| c.2 <= best * (1. + TOLERANCE) + f64::EPSILON);
+        let pick = (self.random(-index - 1) * candidates.len() as f64) as usize;
+        let (cx, cy, _, error) = candidates.swap_remove(pick.min(candidates.len() - 1));
+        (cx, cy, error)
+    }
+
+    /// For every row of the left overlap, the first column taken from the new patch, along the
+    /// path of least error from the top of the overlap to the bottom.
+    fn vertical_cut(&self, error: &[f64], rows: usize) -> Vec<usize> {
+        let width = self.overlap;
+        let mut cost = error[..width].to_vec();
+        let mut from = vec![0; rows * width];
+        for y in 1..rows {
+            let mut next = vec![0.; width];
+            for x in 0..width {
+                let (best, previous) = (x.saturating_sub(1)..=(x + 1).min(width - 1))
+                    .map(|px| (cost[px], px))
+                    .fold((f64::MAX, x), |a, b| if b.0 < a.0 { b } else { a });
+                next[x] = best + error[y * self.patch + x];
+                from[y * width + x] = previous;
+            }
+            cost = next;
+        }
+
+        let mut x = (0..width)
+            .min_by(|a, b| cost[*a].total_cmp(&cost[*b]))
+            .unwrap_or(0);
+        let mut cut = vec![0; rows];
+        for y in (0..rows).rev() {
+            cut[y] = x;
+            x = from[y * width + x];
+        }
+        cut
+    }
+
+    /// The same cut through the top overlap, by cutting the transposed error.
+    fn horizontal_cut(&self, error: &[f64], columns: usize) -> Vec<usize> {
+        let mut transposed = vec![0.; self.patch * self.patch];
+        for y in 0..self.patch {
+            for x in 0..self.patch {
+                transposed[x * self.patch + y] = error[y * self.patch + x];
+            }
+        }
+        self.vertical_cut(&transposed, columns)
+    }
+
+    fn synthesize(mut self, boundary_cut: bool) -> Vec<f64> {
+        let step = self.patch - self.overlap;
+        let mut index = 0;
+        for y0 in (0..SIZE).step_by(step) {
+            for x0 in (0..SIZE).step_by(step) {
+                let (cx, cy, error) = if x0 == 0 && y0 == 0 {
+                    let range = (self.exemplar_size - self.patch + 1) as f64;
+                    let cx = (self.random(-1_000_000) * range) as usize;
+                    let cy = (self.random(-1_000_001) * range) as usize;
+                    (cx, cy, Vec::new())
+                } else {
+                    self.choose_patch(index, x0, y0)
+                };
+                index += 1;
+
+                let (rows, columns) = (self.patch.min(SIZE - y0), self.patch.min(SIZE - x0));
+                let left_cut = if boundary_cut && x0 > 0 {
+                    self.vertical_cut(&error, rows)
+                } else {
+                    vec![0; rows]
+                };
+                let top_cut = if boundary_cut && y0 > 0 {
+                    self.horizontal_cut(&error, columns)
+                } else {
+                    vec![0; columns]
+                };
+
+                for (y, &left) in left_cut.iter().enumerate() {
+                    for (x, &top) in top_cut.iter().enumerate() {
+                        if x >= left && y >= top {
+                            self.output[(y0 + y) * SIZE + x0 + x] =
+                                self.exemplar_at(cx + x, cy + y);
+                        }
+                    }
+                }
+            }
+        }
+        self.output
+    }
+}
+
+/// The centered square of the imported image the texture is synthesized from.
+fn crop_exemplar(image: &[f64], size: usize) -> Vec<f64> {
+    let offset = (SIZE - size) / 2;
+    (0..size * size)
+        .map(|i| image[(offset + i / size) * SIZE + offset + i % size])
+        .collect()
+}
+
+elements!((quilting_placeholder, HtmlElement));
+
+impl QuiltingNoise {
+    fn on_setup() {}
+
+    fn on_update() {
+        let patch_size = PatchSize::parse().value();
+        PATCH_OVERLAP.with(|e| e.set_max(&(patch_size / 2).to_string()));
+    }
+
+    /// Without an imported image there is nothing to learn from, so the canvas stays blank.
+    fn generate_and_draw(settings: QuiltingNoiseSettings) {
+        let image = image_import::image();
+        QUILTING_PLACEHOLDER.with(|p| p.set_hidden(image.is_some()));
+        let Some(image) = image else {
+            draw_field(vec![0.; SIZE * SIZE]);
+            return;
+        };
+
+        let exemplar_size = settings.exemplar_size.value() as usize;
+        let exemplar = crop_exemplar(&image, exemplar_size);
+        let quilt = Quilt::new(
+            &exemplar,
+            exemplar_size,
+            settings.patch_size.value() as usize,
+            settings.patch_overlap.value() as usize,
+            settings.seed.value(),
+        );
+        draw_field(quilt.synthesize(!settings.straight_seams.value()));
+
+        if settings.show_exemplar.value() {
+            draw_image(
+                &colorize(&exemplar),
+                exemplar_size as u32,
+                exemplar_size as u32,
+                4.,
+                4.,
+            );
+            stroke_rect(
+                4.,
+                4.,
+                exemplar_size as f64,
+                exemplar_size as f64,
+                "#000000",
+            );
+        }
+    }
+}
+
+define_noise!(quilting,
+    sliders:[
+        (seed, u32, 0., 42., 1000.),
+        (exemplar_size, u32, 48., 96., 200.),
+        (patch_size, u32, 8., 32., 64.),
+        (patch_overlap, u32, 1., 6., 16.)
+    ];
+    radios:[];
+    checkboxes:[straight_seams, show_exemplar];
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quilting_a_flat_exemplar_is_flat() {
+        let exemplar = vec![0.25; 64 * 64];
+        let output = Quilt::new(&exemplar, 64, 24, 6, 1).synthesize(true);
+        assert!(output.iter().all(|&v| v == 0.25));
+    }
+
+    #[test]
+    fn every_pixel_comes_from_the_exemplar() {
+        let exemplar: Vec<f64> = (0..48 * 48).map(|i| i as f64).collect();
+        let output = Quilt::new(&exemplar, 48, 16, 4, 7).synthesize(true);
+        assert!(output.iter().all(|v| exemplar.contains(v)));
+    }
+}
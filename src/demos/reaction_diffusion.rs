@@ -0,0 +1,163 @@
+use std::cell::{LazyCell, RefCell};
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlElement, HtmlInputElement};
+
+use crate::{drawer::RESOLUTION, field::draw_field, noises::noise::Noise, scheduler, *};
+
+/// The simulation runs at half the canvas resolution, each cell covers 2x2 pixels.
+const GRID_SIZE: usize = (RESOLUTION / 2) as usize;
+const DIFFUSION_U: f64 = 1.0;
+const DIFFUSION_V: f64 = 0.5;
+const ANIMATION_NAME: &str = "reaction_diffusion";
+const ANIMATION_PERIOD_MS: i32 = 30;
+const SEED_SQUARES: i32 = 12;
+const SEED_SQUARE_SIZE: usize = 8;
+
+/// Gray-Scott model: chemical V consumes U to reproduce, U is fed in at `feed`
+/// and V is removed at `feed + kill`.
+struct ReactionDiffusionImpl {
+    seed: u32,
+    u: Vec<f64>,
+    v: Vec<f64>,
+}
+
+thread_local! {
+    static SIMULATION: RefCell<Option<ReactionDiffusionImpl>> = const { RefCell::new(None) };
+}
+
+impl ReactionDiffusionImpl {
+    /// Fills the grid with U and drops a few random squares of V to start the reaction.
+    pub fn new(seed: u32) -> Self {
+        let mut u = vec![1.; GRID_SIZE * GRID_SIZE];
+        let mut v = vec![0.; GRID_SIZE * GRID_SIZE];
+
+        for square in 0..SEED_SQUARES {
+            let random = |channel: i32| {
+                squirrel_noise5::f32_zero_to_one_1d(square * 2 + channel, seed as i32) as f64
+            };
+            let x0 = (random(0) * (GRID_SIZE - SEED_SQUARE_SIZE) as f64) as usize;
+            let y0 = (random(1) * (GRID_SIZE - SEED_SQUARE_SIZE) as f64) as usize;
+            for y in y0..y0 + SEED_SQUARE_SIZE {
+                for x in x0..x0 + SEED_SQUARE_SIZE {
+                    u[y * GRID_SIZE + x] = 0.5;
+                    v[y * GRID_SIZE + x] = 0.25;
+                }
+            }
+        }
+
+        ReactionDiffusionImpl { seed, u, v }
+    }
+
+    /// 3x3 laplacian with wrap-around edges.
+    #[inline]
+    fn laplacian(grid: &[f64], x: usize, y: usize) -> f64 {
+        let at = |dx: isize, dy: isize| {
+            let nx = (x as isize + dx).rem_euclid(GRID_SIZE as isize) as usize;
+            let ny = (y as isize + dy).rem_euclid(GRID_SIZE as isize) as usize;
+            grid[ny * GRID_SIZE + nx]
+        };
+        0.2 * (at(-1, 0) + at(1, 0) + at(0, -1) + at(0, 1))
+            + 0.05 * (at(-1, -1) + at(1, -1) + at(-1, 1) + at(1, 1))
+            - at(0, 0)
+    }
+
+    fn step(&mut self, feed: f64, kill: f64) {
+        let mut next_u = self.u.clone();
+        let mut next_v = self.v.clone();
+
+        for y in 0..GRID_SIZE {
+            for x in 0..GRID_SIZE {
+                let i = y * GRID_SIZE + x;
+                let (u, v) = (self.u[i], self.v[i]);
+                let reaction = u * v * v;
+                next_u[i] =
+                    u + DIFFUSION_U * Self::laplacian(&self.u, x, y) - reaction + feed * (1. - u);
+                next_v[i] =
+                    v + DIFFUSION_V * Self::laplacian(&self.v, x, y) + reaction - (feed + kill) * v;
+            }
+        }
+
+        self.u = next_u;
+        self.v = next_v;
+    }
+
+    /// Maps the concentration of V onto the [-1, 1] range every noise draws with.
+    fn generate_field(&self) -> Vec<f64> {
+        (0..(RESOLUTION * RESOLUTION) as usize)
+            .map(|i| {
+                let x = i % RESOLUTION as usize * GRID_SIZE / RESOLUTION as usize;
+                let y = i / RESOLUTION as usize * GRID_SIZE / RESOLUTION as usize;
+                (self.v[y * GRID_SIZE + x] * 5. - 1.).clamp(-1., 1.)
+            })
+            .collect()
+    }
+}
+
+impl ReactionDiffusionNoise {
+    fn on_setup() {}
+
+    fn on_update() {
+        let label = if scheduler::is_running(ANIMATION_NAME) {
+            "Pause"
+        } else {
+            "Play"
+        };
+        PLAY_PAUSE.with(|b| b.set_inner_text(label));
+    }
+
+    fn on_play_pause() {
+        if scheduler::is_running(ANIMATION_NAME) {
+            scheduler::stop(ANIMATION_NAME);
+        } else {
+            scheduler::start(ANIMATION_NAME, ANIMATION_PERIOD_MS, Self::tick);
+        }
+        Self::update();
+    }
+
+    fn on_restart() {
+        SIMULATION.with(|s| s.replace(None));
+        Self::update();
+    }
+
+    fn tick() {
+        if REACTION_DIFFUSION.with(|e| e.hidden()) {
+            scheduler::stop(ANIMATION_NAME);
+            return;
+        }
+
+        let settings = ReactionDiffusionNoiseSettings::parse();
+        SIMULATION.with(|s| {
+            if let Some(simulation) = s.borrow_mut().as_mut() {
+                for _ in 0..settings.steps_per_frame.value() {
+                    simulation.step(settings.feed.value(), settings.kill.value());
+                }
+            }
+        });
+        Self::update();
+    }
+
+    fn generate_and_draw(settings: ReactionDiffusionNoiseSettings) {
+        let seed = settings.seed.value();
+        let field = SIMULATION.with(|s| {
+            let mut simulation = s.borrow_mut();
+            if simulation.as_ref().is_none_or(|sim| sim.seed != seed) {
+                simulation.replace(ReactionDiffusionImpl::new(seed));
+            }
+            simulation.as_ref().unwrap().generate_field()
+        });
+        draw_field(field);
+    }
+}
+
+define_noise!(reaction_diffusion,
+    sliders:[
+        (seed, u32, 0., 42., 1000.),
+        (feed, f64, 0.01, 0.055, 0.1, Decimals(3)),
+        (kill, f64, 0.04, 0.062, 0.07, Decimals(3)),
+        (steps_per_frame, u32, 1., 10., 40.)
+    ];
+    radios:[];
+    checkboxes:[];
+    buttons:[play_pause, restart];
+);
@@ -0,0 +1,263 @@
+use std::cell::{LazyCell, RefCell};
+
+use rayon::prelude::*;
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlElement, HtmlInputElement};
+
+use crate::{
+    core::{Perlin, Sampler},
+    drawer::{RESOLUTION, draw_circle, draw_line},
+    field::draw_field,
+    noises::{helpers::lerp, noise::Noise},
+    scheduler, *,
+};
+
+/// The dye is simulated at half the canvas resolution, each cell covers 2x2 pixels.
+const GRID_SIZE: usize = (RESOLUTION / 2) as usize;
+const CELL_PIXELS: f64 = RESOLUTION as f64 / GRID_SIZE as f64;
+const ANIMATION_NAME: &str = "smoke";
+const ANIMATION_PERIOD_MS: i32 = 30;
+/// Step of the finite differences the curl is taken with, in noise units.
+const EPSILON: f64 = 1e-3;
+/// How far the potential drifts through noise space per step when the flow evolves.
+const EVOLUTION_SPEED: f64 = 0.004;
+const SOURCE_RADIUS: f64 = 10.;
+const ARROW_SPACING: usize = 16;
+
+/// Dye carried around by a divergence-free velocity field, the curl of a Perlin noise potential.
+/// Every step moves the dye with a semi-Lagrangian step: each cell traces the velocity back by
+/// one step and takes the dye that was there.
+struct SmokeImpl {
+    seed: u32,
+    dye: Vec<f64>,
+    time: f64,
+}
+
+thread_local! {
+    static SIMULATION: RefCell<Option<SmokeImpl>> = const { RefCell::new(None) };
+}
+
+/// Velocity in cells per step at the center of every cell.
+fn velocity_field(settings: &SmokeNoiseSettings, time: f64) -> Vec<(f64, f64)> {
+    let potential = Perlin::new(settings.seed.value()).octaves(settings.octaves.value());
+    let cells_per_unit = settings.scale.value() / CELL_PIXELS;
+    let speed = settings.flow_speed.value();
+    let rise = settings.rise.value();
+    let drift = time * EVOLUTION_SPEED;
+
+    (0..GRID_SIZE * GRID_SIZE)
+        .into_par_iter()
+        .map(|i| {
+            let x = (i % GRID_SIZE) as f64 / cells_per_unit + drift;
+            let y = (i / GRID_SIZE) as f64 / cells_per_unit + drift * 0.7;
+            let dx = (potential.sample(x + EPSILON, y) - potential.sample(x - EPSILON, y))
+                / (2. * EPSILON);
+            let dy = (potential.sample(x, y + EPSILON) - potential.sample(x, y - EPSILON))
+                / (2. * EPSILON);
+            // The curl of a scalar potential, (dψ/dy, -dψ/dx), has zero divergence everywhere.
+            (speed * dy, -speed * dx - rise)
+        })
+        .collect()
+}
+
+impl SmokeImpl {
+    pub fn new(seed: u32) -> Self {
+        SmokeImpl {
+            seed,
+            dye: vec![0.; GRID_SIZE * GRID_SIZE],
+            time: 0.,
+        }
+    }
+
+    /// Bilinear lookup with clamped edges.
+    fn dye_at(&self, x: f64, y: f64) -> f64 {
+        let max = (GRID_SIZE - 1) as f64;
+        let (x, y) = (x.clamp(0., max), y.clamp(0., max));
+        let (x0, y0) = (x.floor() as usize, y.floor() as usize);
+        let (x1, y1) = ((x0 + 1).min(GRID_SIZE - 1), (y0 + 1).min(GRID_SIZE - 1));
+        let (tx, ty) = (x - x0 as f64, y - y0 as f64);
+        let top = lerp(
+            tx,
+            self.dye[y0 * GRID_SIZE + x0],
+            self.dye[y0 * GRID_SIZE + x1],
+        );
+        let bottom = lerp(
+            tx,
+            self.dye[y1 * GRID_SIZE + x0],
+            self.dye[y1 * GRID_SIZE + x1],
+        );
+        lerp(ty, top, bottom)
+    }
+
+    /// Pours dye into a disc near the bottom of the canvas.
+    fn emit(&mut self) {
+        let (center_x, center_y) = (GRID_SIZE as f64 / 2., GRID_SIZE as f64 - SOURCE_RADIUS * 2.);
+        for (i, dye) in self.dye.iter_mut().enumerate() {
+            let dx = (i % GRID_SIZE) as f64 - center_x;
+            let dy = (i / GRID_SIZE) as f64 - center_y;
+            if dx * dx + dy * dy < SOURCE_RADIUS * SOURCE_RADIUS {
+                *dye = 1.;
+            }
+        }
+    }
+
+    fn step(&mut self, velocity: &[(f64, f64)], dissipation: f64) {
+        self.emit();
+        let dye = (0..GRID_SIZE * GRID_SIZE)
+            .into_par_iter()
+            .map(|i| {
+                let (vx, vy) = velocity[i];
+                let x = (i % GRID_SIZE) as f64 - vx;
+                let y = (i / GRID_SIZE) as f64 - vy;
+                self.dye_at(x, y) * (1. - dissipation)
+            })
+            .collect();
+        self.dye = dye;
+        self.time += 1.;
+    }
+
+    /// Maps the dye density onto the [-1, 1] range every noise draws with.
+    fn generate_field(&self) -> Vec<f64> {
+        (0..(RESOLUTION * RESOLUTION) as usize)
+            .map(|i| {
+                let x = i % RESOLUTION as usize * GRID_SIZE / RESOLUTION as usize;
+                let y = i / RESOLUTION as usize * GRID_SIZE / RESOLUTION as usize;
+                self.dye[y * GRID_SIZE + x].clamp(0., 1.) * 2. - 1.
+            })
+            .collect()
+    }
+}
+
+impl SmokeNoise {
+    fn on_setup() {}
+
+    fn on_update() {
+        let label = if scheduler::is_running(ANIMATION_NAME) {
+            "Pause"
+        } else {
+            "Play"
+        };
+        PLAY_PAUSE.with(|b| b.set_inner_text(label));
+    }
+
+    fn on_play_pause() {
+        if scheduler::is_running(ANIMATION_NAME) {
+            scheduler::stop(ANIMATION_NAME);
+        } else {
+            scheduler::start(ANIMATION_NAME, ANIMATION_PERIOD_MS, Self::tick);
+        }
+        Self::update();
+    }
+
+    fn on_restart() {
+        SIMULATION.with(|s| s.replace(None));
+        Self::update();
+    }
+
+    fn tick() {
+        if SMOKE.with(|e| e.hidden()) {
+            scheduler::stop(ANIMATION_NAME);
+            return;
+        }
+
+        let settings = SmokeNoiseSettings::parse();
+        SIMULATION.with(|s| {
+            if let Some(simulation) = s.borrow_mut().as_mut() {
+                let time = if settings.evolve_flow.value() {
+                    simulation.time
+                } else {
+                    0.
+                };
+                let velocity = velocity_field(&settings, time);
+                for _ in 0..settings.steps_per_frame.value() {
+                    simulation.step(&velocity, settings.dissipation.value());
+                }
+            }
+        });
+        Self::update();
+    }
+
+    /// Draws a short line along the velocity from a dot every few cells, scaled up so slow flows
+    /// stay visible.
+    fn draw_flow(velocity: &[(f64, f64)]) {
+        for y in (ARROW_SPACING / 2..GRID_SIZE).step_by(ARROW_SPACING) {
+            for x in (ARROW_SPACING / 2..GRID_SIZE).step_by(ARROW_SPACING) {
+                let (vx, vy) = velocity[y * GRID_SIZE + x];
+                let (px, py) = (x as f64 * CELL_PIXELS, y as f64 * CELL_PIXELS);
+                draw_circle(px, py, 1.5, "#000000");
+                draw_line(
+                    px,
+                    py,
+                    px + vx * CELL_PIXELS * 6.,
+                    py + vy * CELL_PIXELS * 6.,
+                    "#000000",
+                );
+            }
+        }
+    }
+
+    fn generate_and_draw(settings: SmokeNoiseSettings) {
+        let seed = settings.seed.value();
+        let (field, time) = SIMULATION.with(|s| {
+            let mut simulation = s.borrow_mut();
+            if simulation.as_ref().is_none_or(|sim| sim.seed != seed) {
+                simulation.replace(SmokeImpl::new(seed));
+            }
+            let simulation = simulation.as_ref().unwrap();
+            (simulation.generate_field(), simulation.time)
+        });
+        draw_field(field);
+
+        if settings.show_flow.value() {
+            let time = if settings.evolve_flow.value() {
+                time
+            } else {
+                0.
+            };
+            Self::draw_flow(&velocity_field(&settings, time));
+        }
+    }
+}
+
+define_noise!(smoke,
+    sliders:[
+        (seed, u32, 0., 42., 1000.),
+        (scale, f64, 20., 80., 200.),
+        (octaves, u32, 1., 2., 4.),
+        (flow_speed, f64, 0.1, 1., 3.),
+        (rise, f64, 0., 0.3, 1.),
+        (dissipation, f64, 0., 0.005, 0.05, Decimals(3)),
+        (steps_per_frame, u32, 1., 2., 8.)
+    ];
+    radios:[];
+    checkboxes:[evolve_flow, show_flow];
+    buttons:[play_pause, restart];
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn curl_velocity_is_divergence_free() {
+        let mut settings = SmokeNoiseSettings::defaults();
+        settings.rise = Rise(0.);
+        let velocity = velocity_field(&settings, 0.);
+        let at = |x: usize, y: usize| velocity[y * GRID_SIZE + x];
+
+        let mut total = 0.;
+        let mut magnitude = 0.;
+        for y in 1..GRID_SIZE - 1 {
+            for x in 1..GRID_SIZE - 1 {
+                let divergence =
+                    (at(x + 1, y).0 - at(x - 1, y).0) + (at(x, y + 1).1 - at(x, y - 1).1);
+                total += divergence.abs();
+                magnitude += at(x, y).0.abs() + at(x, y).1.abs();
+            }
+        }
+        assert!(
+            total < 0.05 * magnitude,
+            "divergence {total} against speed {magnitude}"
+        );
+    }
+}
@@ -0,0 +1,82 @@
+//! Circular controls for angle sliders, generated by `define_noise!` for its `dials`.
+
+use std::f64::consts::PI;
+
+use web_sys::{HtmlCanvasElement, HtmlInputElement, MouseEvent};
+
+use crate::charts::Chart;
+
+/// Wraps an angle into the turn starting at `min`. Sliders that span less than a full turn stop
+/// at whichever end is closer to the angle.
+fn to_range(degrees: f64, min: f64, max: f64) -> f64 {
+    let wrapped = min + (degrees - min).rem_euclid(360.);
+    if wrapped <= max {
+        wrapped
+    } else if wrapped - max < min + 360. - wrapped {
+        max
+    } else {
+        min
+    }
+}
+
+fn range(slider: &HtmlInputElement) -> (f64, f64) {
+    (
+        slider.min().parse().unwrap_or(0.),
+        slider.max().parse().unwrap_or(360.),
+    )
+}
+
+/// Points the slider at the cursor while the left button is held, returns whether it moved.
+pub fn drag(dial: &HtmlCanvasElement, slider: &HtmlInputElement, event: &MouseEvent) -> bool {
+    if event.buttons() & 1 == 0 {
+        return false;
+    }
+    let dx = event.offset_x() as f64 - dial.client_width() as f64 / 2.;
+    let dy = event.offset_y() as f64 - dial.client_height() as f64 / 2.;
+    if dx == 0. && dy == 0. {
+        return false;
+    }
+
+    let (min, max) = range(slider);
+    let previous = slider.value_as_number();
+    slider.set_value_as_number(to_range((-dy).atan2(dx).to_degrees(), min, max));
+    slider.value_as_number() != previous
+}
+
+pub fn draw(dial: &HtmlCanvasElement, slider: &HtmlInputElement) {
+    let chart = Chart::new(dial);
+    let center = (chart.width / 2., chart.height / 2.);
+    let radius = chart.width.min(chart.height) / 2. - 1.;
+
+    chart.clear("#ffffff");
+    chart.circle(center, radius, "#cccccc");
+    chart.circle(center, radius - 3., "#ffffff");
+    chart.line(
+        (center.0 + radius - 8., center.1),
+        (center.0 + radius - 3., center.1),
+        "#888888",
+    );
+
+    let angle = slider.value_as_number() * PI / 180.;
+    let tip = (
+        center.0 + angle.cos() * (radius - 8.),
+        center.1 - angle.sin() * (radius - 8.),
+    );
+    chart.line(center, tip, "#1f77b4");
+    chart.circle(tip, 4., "#1f77b4");
+    chart.circle(center, 2., "#444444");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn angles_wrap_into_the_slider_range() {
+        assert_eq!(to_range(-90., 0., 360.), 270.);
+        assert_eq!(to_range(45., 0., 360.), 45.);
+        assert_eq!(to_range(-170., -90., 90.), -90.);
+        assert_eq!(to_range(-45., -90., 90.), -45.);
+        assert_eq!(to_range(135., -90., 90.), 90.);
+    }
+}
@@ -0,0 +1,144 @@
+//! Domain repetition, the sample coordinates wrapped into tiles before the noise sees them.
+
+use std::cell::Cell;
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlElement, HtmlInputElement};
+
+use crate::{rng::Generator, *};
+
+/// The random tile offsets are spread over this many noise units in each direction.
+const OFFSET_RANGE: f64 = 1000.;
+const OFFSET_SEED: u32 = 0x7117;
+const OFFSET_X_STREAM: u32 = 0;
+const OFFSET_Y_STREAM: u32 = 1;
+
+/// How the sample coordinates are wrapped, captured once per frame so the parallel sampling does
+/// not touch the page.
+#[derive(Clone, Copy)]
+pub struct Repeat {
+    /// Side of a tile in noise units, zero leaves the coordinates alone.
+    pub period: f64,
+    /// Whether every other row of tiles is shifted by half a tile.
+    pub brick: bool,
+    /// Whether every tile reads the noise at its own random offset.
+    pub variation: bool,
+    /// The generator picked on the page, which draws the offsets.
+    pub generator: Generator,
+}
+
+thread_local! {
+    static REPEAT: Cell<Repeat> = const {
+        Cell::new(Repeat {
+            period: 0.,
+            brick: false,
+            variation: false,
+            generator: Generator::SquirrelNoise5,
+        })
+    };
+}
+
+elements!(
+    (repeat_period, HtmlInputElement),
+    (repeat_period_display, HtmlElement),
+    (repeat_brick, HtmlInputElement),
+    (repeat_variation, HtmlInputElement),
+);
+define_closure!(repeat_input_change, on_input_change);
+
+pub fn setup() {
+    add_callback!(repeat_period, "input", repeat_input_change);
+    add_callback!(repeat_brick, "input", repeat_input_change);
+    add_callback!(repeat_variation, "input", repeat_input_change);
+    read_controls();
+}
+
+fn on_input_change() {
+    read_controls();
+    update_current_noise();
+}
+
+/// Picks up the controls from the page, also after a saved state has been applied.
+pub fn read_controls() {
+    let period = parse_value!(repeat_period, f64).max(0.);
+    let text = if period == 0. {
+        "Off".to_string()
+    } else {
+        format!("{period}")
+    };
+    set_text!(repeat_period, &text);
+    REPEAT.with(|r| {
+        r.set(Repeat {
+            period,
+            brick: is_checked!(repeat_brick),
+            variation: is_checked!(repeat_variation),
+            generator: rng::generator(),
+        })
+    });
+}
+
+impl Repeat {
+    pub fn current() -> Self {
+        Repeat {
+            generator: rng::generator(),
+            ..REPEAT.with(Cell::get)
+        }
+    }
+
+    /// The point of the tile that (x, y) falls in, plus the tile's offset when it has one.
+    pub fn apply(&self, x: f64, y: f64) -> (f64, f64) {
+        if self.period == 0. {
+            return (x, y);
+        }
+        let row = (y / self.period).floor();
+        let x = if self.brick && row.rem_euclid(2.) == 1. {
+            x + self.period / 2.
+        } else {
+            x
+        };
+        let column = (x / self.period).floor();
+        let (local_x, local_y) = (x - column * self.period, y - row * self.period);
+        if !self.variation {
+            return (local_x, local_y);
+        }
+        let rng = self.generator.rng();
+        let offset =
+            |stream| rng.cell(column as i32, row as i32, OFFSET_SEED, stream) * OFFSET_RANGE;
+        (
+            local_x + offset(OFFSET_X_STREAM),
+            local_y + offset(OFFSET_Y_STREAM),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tiles_repeat_and_bricks_shift_every_other_row() {
+        let tiles = Repeat {
+            period: 2.,
+            brick: false,
+            variation: false,
+            generator: Generator::SquirrelNoise5,
+        };
+        assert_eq!(tiles.apply(0.5, 0.25), (0.5, 0.25));
+        assert_eq!(tiles.apply(-3.5, 4.25), (0.5, 0.25));
+
+        let bricks = Repeat {
+            brick: true,
+            ..tiles
+        };
+        assert_eq!(bricks.apply(0.5, 2.25), (1.5, 0.25));
+        assert_eq!(bricks.apply(0.5, 4.25), (0.5, 0.25));
+
+        let varied = Repeat {
+            variation: true,
+            ..tiles
+        };
+        let step = varied.apply(1.5, 0.25).0 - varied.apply(0.5, 0.25).0;
+        assert!((step - 1.).abs() < 1e-9);
+        assert_ne!(varied.apply(0.5, 0.25), varied.apply(2.5, 0.25));
+    }
+}
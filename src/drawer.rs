@@ -1,11 +1,12 @@
-use std::cell::LazyCell;
+use std::cell::{LazyCell, RefCell};
 use std::f64::consts::PI;
 use wasm_bindgen::prelude::*;
 
-use web_sys::CanvasRenderingContext2d;
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, ImageData, MouseEvent};
 
 use crate::log;
 use crate::console_log;
+use crate::noises::helpers::lerp;
 
 pub const GRID_THICKNESS: u32 = 2;
 pub const HALF_GRID_THICKNESS: u32 = GRID_THICKNESS / 2;
@@ -14,7 +15,7 @@ pub const HALF_RESOLUTION: u32 = RESOLUTION / 2;
 pub const IMAGE_BYTES_COUNT: u32 = RESOLUTION * RESOLUTION * 4;
 
 thread_local! {
-    pub static CANVAS_CONTEXT: LazyCell<CanvasRenderingContext2d> = LazyCell::new(||{
+    pub static CANVAS: LazyCell<HtmlCanvasElement> = LazyCell::new(||{
         let document = web_sys::window().unwrap().document().unwrap();
         let canvas = document.get_element_by_id("canvas").unwrap();
         let canvas: web_sys::HtmlCanvasElement = canvas
@@ -23,14 +24,51 @@ thread_local! {
 
         canvas.set_width(RESOLUTION);
         canvas.set_height(RESOLUTION);
-
         canvas
-            .get_context("2d")
-            .unwrap()
-            .unwrap()
-            .dyn_into::<web_sys::CanvasRenderingContext2d>()
-            .unwrap()
     });
+    pub static CANVAS_CONTEXT: LazyCell<CanvasRenderingContext2d> = LazyCell::new(||{
+        CANVAS.with(|canvas| {
+            canvas
+                .get_context("2d")
+                .unwrap()
+                .unwrap()
+                .dyn_into::<web_sys::CanvasRenderingContext2d>()
+                .unwrap()
+        })
+    });
+}
+
+thread_local! {
+    static SAVED_FRAME: RefCell<Option<ImageData>> = const { RefCell::new(None) };
+}
+
+/// Remembers what is currently on the canvas, so hover overlays can be redrawn without regenerating noise.
+pub fn save_frame() {
+    let frame = CANVAS_CONTEXT
+        .with(|ctx| ctx.get_image_data(0., 0., RESOLUTION as f64, RESOLUTION as f64))
+        .map_err(|_| console_log!("Reading canvas image data failed"))
+        .ok();
+    SAVED_FRAME.with(|f| f.replace(frame));
+}
+
+pub fn restore_frame() {
+    SAVED_FRAME.with(|f| {
+        if let Some(frame) = f.borrow().as_ref() {
+            CANVAS_CONTEXT
+                .with(|ctx| ctx.put_image_data(frame, 0., 0.))
+                .map_err(|_| console_log!("Restoring saved frame failed"))
+                .unwrap();
+        }
+    });
+}
+
+/// Converts mouse event position into canvas pixel coordinates, accounting for css scaling of the canvas.
+pub fn canvas_coordinates(event: &MouseEvent) -> (f64, f64) {
+    CANVAS.with(|canvas| {
+        let x = event.offset_x() as f64 * RESOLUTION as f64 / canvas.client_width().max(1) as f64;
+        let y = event.offset_y() as f64 * RESOLUTION as f64 / canvas.client_height().max(1) as f64;
+        (x, y)
+    })
 }
 
 pub fn draw_noise(data: &[u8]) {
@@ -47,6 +85,18 @@ pub fn draw_noise(data: &[u8]) {
         .unwrap();
 }
 
+/// Draws rgba pixels of a `width` x `height` image with its top left corner at (x, y).
+pub fn draw_image(data: &[u8], width: u32, height: u32, x: f64, y: f64) {
+    let clamped = wasm_bindgen::Clamped(data);
+    let imagedata = web_sys::ImageData::new_with_u8_clamped_array_and_sh(clamped, width, height)
+        .map_err(|_| console_log!("Creating image data failed"))
+        .unwrap();
+    CANVAS_CONTEXT
+        .with(|ctx| ctx.put_image_data(&imagedata, x, y))
+        .map_err(|_| console_log!("Drawing image to canvas failed"))
+        .unwrap();
+}
+
 pub fn draw_grid(scale: f64, fill_style: &str) {
     CANVAS_CONTEXT.with(|context| {
         context.set_fill_style_str(fill_style);
@@ -98,3 +148,106 @@ pub fn draw_circle(x: f64, y: f64, radius: f64, fill_style: &str) {
         context.fill();
     });
 }
+
+pub fn draw_line(from_x: f64, from_y: f64, to_x: f64, to_y: f64, stroke_style: &str) {
+    CANVAS_CONTEXT.with(|context| {
+        context.set_stroke_style_str(stroke_style);
+        context.begin_path();
+        context.move_to(from_x, from_y);
+        context.line_to(to_x, to_y);
+        context.stroke();
+    });
+}
+
+pub fn draw_polyline(points: &[(f64, f64)], stroke_style: &str, line_width: f64) {
+    CANVAS_CONTEXT.with(|context| {
+        context.set_stroke_style_str(stroke_style);
+        context.set_line_width(line_width);
+        context.begin_path();
+        for (i, &(x, y)) in points.iter().enumerate() {
+            if i == 0 {
+                context.move_to(x, y);
+            } else {
+                context.line_to(x, y);
+            }
+        }
+        context.stroke();
+        context.set_line_width(1.0);
+    });
+}
+
+pub fn clear_canvas(fill_style: &str) {
+    CANVAS_CONTEXT.with(|context| {
+        context.set_fill_style_str(fill_style);
+        context.fill_rect(0., 0., RESOLUTION as f64, RESOLUTION as f64);
+    });
+}
+
+pub fn stroke_rect(x: f64, y: f64, width: f64, height: f64, stroke_style: &str) {
+    CANVAS_CONTEXT.with(|context| {
+        context.set_stroke_style_str(stroke_style);
+        context.stroke_rect(x, y, width, height);
+    });
+}
+
+pub fn stroke_polygon(points: &[(f64, f64)], stroke_style: &str) {
+    CANVAS_CONTEXT.with(|context| {
+        context.set_stroke_style_str(stroke_style);
+        trace_polygon(context, points);
+        context.stroke();
+    });
+}
+
+pub fn fill_polygon(points: &[(f64, f64)], fill_style: &str) {
+    CANVAS_CONTEXT.with(|context| {
+        context.set_fill_style_str(fill_style);
+        trace_polygon(context, points);
+        context.fill();
+    });
+}
+
+fn trace_polygon(context: &CanvasRenderingContext2d, points: &[(f64, f64)]) {
+    context.begin_path();
+    for (i, &(x, y)) in points.iter().enumerate() {
+        if i == 0 {
+            context.move_to(x, y);
+        } else {
+            context.line_to(x, y);
+        }
+    }
+    context.close_path();
+}
+
+pub fn draw_patch(data: &[u8], x: f64, y: f64, width: u32, height: u32) {
+    assert!(data.len() as u32 == width * height * 4);
+
+    let clamped = wasm_bindgen::Clamped(data);
+    let imagedata = web_sys::ImageData::new_with_u8_clamped_array_and_sh(clamped, width, height)
+        .map_err(|_| console_log!("Creating image data failed"))
+        .unwrap();
+    CANVAS_CONTEXT
+        .with(|ctx| ctx.put_image_data(&imagedata, x, y))
+        .map_err(|_| console_log!("Drawing patch to canvas failed"))
+        .unwrap();
+}
+
+pub fn value_to_color(noise_val: f64) -> [u8; 4] {
+    if noise_val < 0. {
+        let t = noise_val + 1.;
+        [255, lerp(t, 0.0, 255.0) as u8, 255, 255]
+    } else {
+        let t = noise_val;
+        [lerp(t, 255.0, 0.0) as u8, 255, lerp(t, 255.0, 0.0) as u8, 255]
+    }
+}
+
+pub fn draw_text(text: &str, x: f64, y: f64, fill_style: &str) {
+    CANVAS_CONTEXT.with(|context| {
+        context.set_fill_style_str(fill_style);
+        context.set_font("12px sans-serif");
+        context
+            .fill_text(text, x, y)
+            .map_err(|_| console_log!("Drawing text {text} failed"))
+            .unwrap();
+    });
+}
@@ -0,0 +1,124 @@
+//! Droplets rolled straight downhill over the field, their paths drawn over the noise.
+
+use std::cell::LazyCell;
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlElement, HtmlInputElement};
+
+use crate::{
+    drawer::{RESOLUTION, draw_circle, draw_polyline},
+    field::FIELD,
+    rng::Rng,
+    *,
+};
+
+const SIZE: usize = RESOLUTION as usize;
+const DROPLET_SEED: u32 = 0xd20;
+/// Pixels a droplet moves per step, whatever the slope.
+const STEP: f64 = 1.;
+const MAX_STEPS: usize = 400;
+/// Slope, in field units per pixel, below which a droplet counts as settled.
+const FLAT: f64 = 1e-6;
+
+elements!(
+    (droplets_enabled, HtmlInputElement),
+    (droplet_count, HtmlInputElement),
+    (droplet_count_display, HtmlElement),
+);
+define_closure!(droplets_change, on_droplets_change);
+
+pub fn setup() {
+    add_callback!(droplets_enabled, "input", droplets_change);
+    add_callback!(droplet_count, "input", droplets_change);
+    set_text!(droplet_count, &parse_value!(droplet_count, u32).to_string());
+}
+
+fn on_droplets_change() {
+    set_text!(droplet_count, &parse_value!(droplet_count, u32).to_string());
+    update_current_noise();
+}
+
+/// Bilinearly interpolated gradient of `field` inside the cell containing (x, y).
+fn gradient(field: &[f64], x: f64, y: f64) -> (f64, f64) {
+    let (cx, cy) = (x.floor() as usize, y.floor() as usize);
+    let (fx, fy) = (x - cx as f64, y - cy as f64);
+    let i = cy * SIZE + cx;
+    let (nw, ne, sw, se) = (field[i], field[i + 1], field[i + SIZE], field[i + SIZE + 1]);
+    (
+        (ne - nw) * (1. - fy) + (se - sw) * fy,
+        (sw - nw) * (1. - fx) + (se - ne) * fx,
+    )
+}
+
+/// The path of a droplet from (x, y), a step against the gradient at a time until it settles,
+/// starts to climb back out of a pit or leaves the canvas.
+fn path(field: &[f64], mut x: f64, mut y: f64) -> Vec<(f64, f64)> {
+    let limit = (SIZE - 1) as f64;
+    let mut points = vec![(x, y)];
+    let mut previous = (0., 0.);
+    for _ in 0..MAX_STEPS {
+        let (gx, gy) = gradient(field, x, y);
+        let slope = gx.hypot(gy);
+        // Turning back means the droplet stepped over the bottom of a valley.
+        if slope < FLAT || gx * previous.0 + gy * previous.1 < 0. {
+            break;
+        }
+        previous = (gx, gy);
+        (x, y) = (x - gx / slope * STEP, y - gy / slope * STEP);
+        if !(0. ..limit).contains(&x) || !(0. ..limit).contains(&y) {
+            break;
+        }
+        points.push((x, y));
+    }
+    points
+}
+
+/// The paths of `count` droplets dropped at random spots of the canvas.
+fn paths(field: &[f64], count: u32, rng: &dyn Rng) -> Vec<Vec<(f64, f64)>> {
+    let limit = (SIZE - 1) as f64;
+    (0..count)
+        .map(|i| {
+            let x = rng.unit(2 * i, DROPLET_SEED) * limit;
+            let y = rng.unit(2 * i + 1, DROPLET_SEED) * limit;
+            path(field, x.min(limit - 1e-9), y.min(limit - 1e-9))
+        })
+        .collect()
+}
+
+/// Draws the droplet paths over the flat view, with a dot where every droplet came to rest.
+pub fn on_frame_drawn() {
+    if !is_checked!(droplets_enabled) || !view::is_flat() {
+        return;
+    }
+    let field = FIELD.with(|f| f.borrow().clone());
+    if field.len() != SIZE * SIZE {
+        return;
+    }
+    for path in paths(&field, parse_value!(droplet_count, u32), rng::current()) {
+        draw_polyline(&path, "#1f3b99", 1.);
+        if let Some(&(x, y)) = path.last() {
+            draw_circle(x, y, 2., "#1f3b99");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::SquirrelNoise5;
+
+    #[test]
+    fn droplets_roll_into_the_bottom_of_a_bowl() {
+        let center = SIZE as f64 / 2.;
+        let bowl: Vec<f64> = (0..SIZE * SIZE)
+            .map(|i| {
+                let (x, y) = ((i % SIZE) as f64 - center, (i / SIZE) as f64 - center);
+                (x * x + y * y) / (center * center)
+            })
+            .collect();
+        for path in paths(&bowl, 50, &SquirrelNoise5) {
+            let &(x, y) = path.last().unwrap();
+            assert!((x - center).hypot(y - center) < 2., "settled at ({x}, {y})");
+        }
+    }
+}
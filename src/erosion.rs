@@ -0,0 +1,394 @@
+//! Erosion of the retained field, treated as terrain and worn down frame by frame.
+
+use std::cell::{LazyCell, RefCell};
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlElement, HtmlInputElement, HtmlSelectElement};
+
+use crate::{
+    drawer::RESOLUTION,
+    field::{FIELD, draw_field},
+    scheduler, *,
+};
+
+const SIZE: usize = RESOLUTION as usize;
+const ANIMATION_NAME: &str = "erosion";
+const ANIMATION_PERIOD_MS: i32 = 30;
+/// Field values are stretched to this many pixels of height, so slopes are measured in the same
+/// units as distances along the ground.
+const HEIGHT_SCALE: f64 = 50.;
+const DROPLETS_PER_FRAME: usize = 2000;
+const THERMAL_PASSES_PER_FRAME: usize = 4;
+const DROPLET_SEED: i32 = 0x5eed;
+
+const INERTIA: f64 = 0.05;
+const CAPACITY: f64 = 4.;
+const MIN_SLOPE: f64 = 0.01;
+const DEPOSITION_RATE: f64 = 0.3;
+const EROSION_RATE: f64 = 0.3;
+const EVAPORATION: f64 = 0.02;
+const GRAVITY: f64 = 4.;
+const MAX_LIFETIME: usize = 30;
+const BRUSH_RADIUS: i32 = 2;
+/// Steepest slope, in height per pixel, that loose material rests on without sliding down.
+const TALUS: f64 = 0.6;
+const THERMAL_RATE: f64 = 0.5;
+
+#[derive(Copy, Clone, PartialEq)]
+enum Mode {
+    Hydraulic,
+    Thermal,
+}
+
+struct Erosion {
+    /// The field the noise drew, the simulation restarts from it.
+    source: Vec<f64>,
+    /// Terrain heights in pixels.
+    heights: Vec<f64>,
+    /// The eroded field that was drawn last.
+    shown: Vec<f64>,
+    frames: u32,
+    droplets: i32,
+}
+
+thread_local! {
+    static EROSION: RefCell<Option<Erosion>> = const { RefCell::new(None) };
+    /// Offsets and weights of the cells a droplet erodes around its position.
+    static BRUSH: Vec<(i32, i32, f64)> = {
+        let mut brush = Vec::new();
+        for dy in -BRUSH_RADIUS..=BRUSH_RADIUS {
+            for dx in -BRUSH_RADIUS..=BRUSH_RADIUS {
+                let weight = BRUSH_RADIUS as f64 - ((dx * dx + dy * dy) as f64).sqrt();
+                if weight > 0. {
+                    brush.push((dx, dy, weight));
+                }
+            }
+        }
+        let total: f64 = brush.iter().map(|b| b.2).sum();
+        brush.iter().map(|&(dx, dy, w)| (dx, dy, w / total)).collect()
+    };
+}
+
+elements!(
+    (erosion_enabled, HtmlInputElement),
+    (erosion_mode, HtmlSelectElement),
+    (erosion_iterations, HtmlInputElement),
+    (erosion_iterations_display, HtmlElement),
+    (erosion_rain, HtmlInputElement),
+    (erosion_rain_display, HtmlElement),
+    (erosion_rain_control, HtmlElement),
+    (erosion_restart, HtmlElement),
+    (erosion_readout, HtmlElement),
+);
+define_closure!(erosion_change, on_erosion_change);
+define_closure!(erosion_restart_click, restart);
+
+pub fn setup() {
+    add_callback!(erosion_enabled, "input", erosion_change);
+    add_callback!(erosion_mode, "input", erosion_change);
+    add_callback!(erosion_iterations, "input", erosion_change);
+    add_callback!(erosion_rain, "input", erosion_change);
+    add_callback!(erosion_restart, "click", erosion_restart_click);
+    update_displays();
+}
+
+fn mode() -> Mode {
+    match parse_value!(erosion_mode, String).as_str() {
+        "thermal" => Mode::Thermal,
+        _ => Mode::Hydraulic,
+    }
+}
+
+fn update_displays() {
+    set_text!(
+        erosion_iterations,
+        &parse_value!(erosion_iterations, u32).to_string()
+    );
+    set_text!(erosion_rain, &parse_value!(erosion_rain, f64).to_string());
+    EROSION_RAIN_CONTROL.with(|c| c.set_hidden(mode() == Mode::Thermal));
+}
+
+/// Slider changes keep the progress, the next frame picks the new values up.
+fn on_erosion_change() {
+    update_displays();
+    if !is_checked!(erosion_enabled) {
+        scheduler::stop(ANIMATION_NAME);
+        EROSION.with(|e| e.replace(None));
+        EROSION_READOUT.with(|r| r.set_inner_text(""));
+    }
+    update_current_noise();
+}
+
+fn restart() {
+    EROSION.with(|e| {
+        if let Some(erosion) = e.borrow_mut().as_mut() {
+            *erosion = Erosion::new(erosion.source.clone());
+        }
+    });
+    show();
+    scheduler::start(ANIMATION_NAME, ANIMATION_PERIOD_MS, tick);
+    crate::on_frame_drawn();
+}
+
+impl Erosion {
+    fn new(source: Vec<f64>) -> Self {
+        let heights = source.iter().map(|v| v * HEIGHT_SCALE).collect();
+        Erosion {
+            source,
+            shown: Vec::new(),
+            heights,
+            frames: 0,
+            droplets: 0,
+        }
+    }
+
+    fn field(&self) -> Vec<f64> {
+        self.heights
+            .iter()
+            .map(|h| (h / HEIGHT_SCALE).clamp(-1., 1.))
+            .collect()
+    }
+
+    /// Bilinearly interpolated height and gradient inside the cell containing (x, y).
+    fn height_and_gradient(&self, x: f64, y: f64) -> (f64, f64, f64) {
+        let (cx, cy) = (x.floor() as usize, y.floor() as usize);
+        let (fx, fy) = (x - cx as f64, y - cy as f64);
+        let i = cy * SIZE + cx;
+        let (nw, ne, sw, se) = (
+            self.heights[i],
+            self.heights[i + 1],
+            self.heights[i + SIZE],
+            self.heights[i + SIZE + 1],
+        );
+        let gradient_x = (ne - nw) * (1. - fy) + (se - sw) * fy;
+        let gradient_y = (sw - nw) * (1. - fx) + (se - ne) * fx;
+        let height =
+            nw * (1. - fx) * (1. - fy) + ne * fx * (1. - fy) + sw * (1. - fx) * fy + se * fx * fy;
+        (height, gradient_x, gradient_y)
+    }
+
+    /// Spreads `amount` of sediment over the four corners of the cell containing (x, y).
+    fn deposit(&mut self, x: f64, y: f64, amount: f64) {
+        let (cx, cy) = (x.floor() as usize, y.floor() as usize);
+        let (fx, fy) = (x - cx as f64, y - cy as f64);
+        let i = cy * SIZE + cx;
+        self.heights[i] += amount * (1. - fx) * (1. - fy);
+        self.heights[i + 1] += amount * fx * (1. - fy);
+        self.heights[i + SIZE] += amount * (1. - fx) * fy;
+        self.heights[i + SIZE + 1] += amount * fx * fy;
+    }
+
+    fn erode(&mut self, x: f64, y: f64, amount: f64) {
+        let (cx, cy) = (x.floor() as i32, y.floor() as i32);
+        BRUSH.with(|brush| {
+            for &(dx, dy, weight) in brush {
+                let (bx, by) = (cx + dx, cy + dy);
+                if (0..SIZE as i32).contains(&bx) && (0..SIZE as i32).contains(&by) {
+                    self.heights[by as usize * SIZE + bx as usize] -= amount * weight;
+                }
+            }
+        });
+    }
+
+    /// Runs one rain droplet downhill, picking up sediment where it speeds up and dropping it
+    /// where it slows down or the water can not carry it anymore.
+    fn droplet(&mut self, mut x: f64, mut y: f64, mut water: f64) {
+        let (mut direction_x, mut direction_y) = (0., 0.);
+        let (mut speed, mut sediment) = (1., 0.);
+        let limit = (SIZE - 1) as f64;
+
+        for _ in 0..MAX_LIFETIME {
+            let (height, gradient_x, gradient_y) = self.height_and_gradient(x, y);
+            direction_x = direction_x * INERTIA - gradient_x * (1. - INERTIA);
+            direction_y = direction_y * INERTIA - gradient_y * (1. - INERTIA);
+            let length = (direction_x * direction_x + direction_y * direction_y).sqrt();
+            if length < 1e-9 {
+                break;
+            }
+            direction_x /= length;
+            direction_y /= length;
+
+            let (old_x, old_y) = (x, y);
+            x += direction_x;
+            y += direction_y;
+            if !(0. ..limit).contains(&x) || !(0. ..limit).contains(&y) {
+                break;
+            }
+
+            let delta = self.height_and_gradient(x, y).0 - height;
+            let capacity = (-delta).max(MIN_SLOPE) * speed * water * CAPACITY;
+            if delta > 0. || sediment > capacity {
+                let amount = if delta > 0. {
+                    delta.min(sediment)
+                } else {
+                    (sediment - capacity) * DEPOSITION_RATE
+                };
+                sediment -= amount;
+                self.deposit(old_x, old_y, amount);
+            } else {
+                let amount = ((capacity - sediment) * EROSION_RATE).min(-delta);
+                sediment += amount;
+                self.erode(old_x, old_y, amount);
+            }
+
+            speed = (speed * speed - delta * GRAVITY).max(0.).sqrt();
+            water *= 1. - EVAPORATION;
+        }
+    }
+
+    /// Moves material from every cell to its lower neighbours wherever the slope is steeper
+    /// than the talus angle.
+    fn thermal_pass(&mut self) {
+        let mut next = self.heights.clone();
+        for y in 1..SIZE - 1 {
+            for x in 1..SIZE - 1 {
+                let i = y * SIZE + x;
+                let height = self.heights[i];
+                let neighbours = [i - 1, i + 1, i - SIZE, i + SIZE];
+                let excess = neighbours.map(|n| (height - self.heights[n] - TALUS).max(0.));
+                let total: f64 = excess.iter().sum();
+                let steepest = excess.iter().copied().fold(0., f64::max);
+                if total <= 0. {
+                    continue;
+                }
+
+                let moved = steepest * THERMAL_RATE / 2.;
+                next[i] -= moved;
+                for (n, e) in neighbours.iter().zip(excess) {
+                    next[*n] += moved * e / total;
+                }
+            }
+        }
+        self.heights = next;
+    }
+
+    fn step(&mut self, mode: Mode, rain: f64) {
+        match mode {
+            Mode::Hydraulic => {
+                let limit = (SIZE - 1) as f64;
+                for _ in 0..DROPLETS_PER_FRAME {
+                    let random = |channel: i32| {
+                        squirrel_noise5::f32_zero_to_one_1d(
+                            self.droplets.wrapping_mul(2) + channel,
+                            DROPLET_SEED,
+                        ) as f64
+                    };
+                    let (x, y) = (random(0) * limit, random(1) * limit);
+                    self.droplets = self.droplets.wrapping_add(1);
+                    self.droplet(x, y, rain);
+                }
+            }
+            Mode::Thermal => {
+                for _ in 0..THERMAL_PASSES_PER_FRAME {
+                    self.thermal_pass();
+                }
+            }
+        }
+        self.frames += 1;
+    }
+}
+
+/// Draws the current state of the simulation as the field of this frame.
+fn show() {
+    let field = EROSION.with(|e| {
+        let mut erosion = e.borrow_mut();
+        let erosion = erosion.as_mut()?;
+        erosion.shown = erosion.field();
+        Some(erosion.shown.clone())
+    });
+    let Some(field) = field else {
+        return;
+    };
+    draw_field(field);
+
+    let frames = EROSION.with(|e| e.borrow().as_ref().map_or(0, |e| e.frames));
+    let total = parse_value!(erosion_iterations, u32);
+    EROSION_READOUT.with(|r| r.set_inner_text(&format!("Iteration {frames} of {total}")));
+}
+
+fn tick() {
+    let (mode, rain, total) = (
+        mode(),
+        parse_value!(erosion_rain, f64),
+        parse_value!(erosion_iterations, u32),
+    );
+    let finished = EROSION.with(|e| {
+        let mut erosion = e.borrow_mut();
+        let Some(erosion) = erosion.as_mut() else {
+            return true;
+        };
+        if erosion.frames >= total {
+            return true;
+        }
+        erosion.step(mode, rain);
+        false
+    });
+    if finished {
+        scheduler::stop(ANIMATION_NAME);
+        return;
+    }
+
+    show();
+    crate::on_frame_drawn();
+}
+
+/// Runs before every other pass over a new frame. A field the noise has just drawn restarts the
+/// simulation, unless it is the same field it already started from.
+pub fn on_frame_drawn() {
+    if !is_checked!(erosion_enabled) {
+        return;
+    }
+    let field = FIELD.with(|f| f.borrow().clone());
+    if field.len() != SIZE * SIZE {
+        scheduler::stop(ANIMATION_NAME);
+        return;
+    }
+
+    let already_shown = EROSION.with(|e| {
+        let mut erosion = e.borrow_mut();
+        match erosion.as_ref() {
+            Some(erosion) if erosion.shown == field => true,
+            Some(erosion) if erosion.source == field => false,
+            _ => {
+                erosion.replace(Erosion::new(field));
+                false
+            }
+        }
+    });
+    if already_shown {
+        return;
+    }
+
+    show();
+    scheduler::start(ANIMATION_NAME, ANIMATION_PERIOD_MS, tick);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slope() -> Vec<f64> {
+        (0..SIZE * SIZE)
+            .map(|i| ((i % SIZE) as f64 / SIZE as f64) * 2. - 1.)
+            .collect()
+    }
+
+    #[test]
+    fn flat_terrain_does_not_erode() {
+        let mut erosion = Erosion::new(vec![0.; SIZE * SIZE]);
+        erosion.step(Mode::Hydraulic, 1.);
+        erosion.step(Mode::Thermal, 1.);
+        assert!(erosion.heights.iter().all(|&h| h == 0.));
+    }
+
+    #[test]
+    fn thermal_erosion_keeps_the_material() {
+        let mut source = slope();
+        source[SIZE * SIZE / 2 + SIZE / 2] = 1.;
+        let mut erosion = Erosion::new(source);
+        let before: f64 = erosion.heights.iter().sum();
+        erosion.step(Mode::Thermal, 1.);
+        let after: f64 = erosion.heights.iter().sum();
+        assert!((before - after).abs() < 1e-6 * before.abs().max(1.));
+    }
+}
@@ -0,0 +1,222 @@
+use std::cell::{Cell, LazyCell, RefCell};
+
+use rayon::prelude::*;
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::HtmlSelectElement;
+
+use crate::{
+    adaptive, audio,
+    axis_scale::AxisScale,
+    channels,
+    domain_repeat::Repeat,
+    drawer::{
+        HALF_RESOLUTION, IMAGE_BYTES_COUNT, RESOLUTION, draw_grid, draw_image, draw_line,
+        value_to_color,
+    },
+    image_import::ImageInput,
+    overlay,
+    precision::{self, Precision},
+    rotation::Rotation,
+    scroll::Scroll,
+    supersampling, *,
+};
+
+thread_local! {
+    /// Noise values of the last drawn frame, one per canvas pixel in row-major order.
+    pub static FIELD: RefCell<Vec<f64>> = const { RefCell::new(Vec::new()) };
+    /// Rgba pixels of the last drawn frame, kept so slider drags do not allocate one per frame.
+    static FRAME: RefCell<Vec<u8>> = RefCell::new(vec![0; IMAGE_BYTES_COUNT as usize]);
+    /// Rows colorized and put on the canvas at a time.
+    static BAND_ROWS: Cell<usize> = const { Cell::new(RESOLUTION as usize) };
+}
+
+elements!((canvas_band_rows, HtmlSelectElement));
+define_closure!(canvas_band_rows_change, read_controls);
+
+pub fn setup() {
+    add_callback!(canvas_band_rows, "input", canvas_band_rows_change);
+}
+
+/// Picks up the band height from the page, also after a saved state has been applied. The next
+/// frame is drawn in the new bands, this one looks the same in any.
+pub fn read_controls() {
+    let rows = parse_value!(canvas_band_rows, usize).clamp(1, RESOLUTION as usize);
+    BAND_ROWS.with(|b| b.set(rows));
+}
+
+/// Samples `sample` once per canvas pixel, with the canvas center at the noise origin
+/// and `scale` pixels per noise unit. An imported image warps or masks the samples when enabled,
+/// the precision controls can move the view and sample it in f32, supersampling averages
+/// several samples per pixel, the rotation and the axis scales turn and stretch the canvas, the
+/// scroll offsets move it over the field and domain repetition wraps the coordinates into tiles.
+/// While a slow noise is dragged, only every few pixels are sampled and spread over their block.
+pub fn sample_field<F>(scale: f64, sample: F) -> Vec<f64>
+where
+    F: Fn(f64, f64) -> f64 + Sync,
+{
+    let image = ImageInput::current();
+    let precision = Precision::current();
+    let repeat = Repeat::current();
+    let axis_scale = AxisScale::current();
+    let rotation = Rotation::current();
+    let scroll = Scroll::current();
+    let offsets = supersampling::offsets(supersampling::grid());
+    let step = adaptive::step();
+    let size = RESOLUTION as usize;
+    let columns = size.div_ceil(step);
+    let fill = |field: &mut [f64], precision: Precision| {
+        field[..columns * columns]
+            .par_iter_mut()
+            .enumerate()
+            .for_each(|(i, value)| {
+                let x = i % columns * step;
+                let y = i / columns * step;
+                let total: f64 = offsets
+                    .iter()
+                    .map(|(dx, dy)| {
+                        let (px, py) = rotation.apply(x as f64 + dx, y as f64 + dy);
+                        let (px, py) = axis_scale.apply(px, py);
+                        let (nx, ny) = precision.coordinates(px, py, scale);
+                        let (nx, ny) = scroll.apply(nx, ny);
+                        let (nx, ny) = repeat.apply(nx, ny);
+                        match &image {
+                            Some(image) => image.sample(x, y, nx, ny, &sample),
+                            None => sample(nx, ny),
+                        }
+                    })
+                    .sum();
+                *value = precision.value(x, total / offsets.len() as f64);
+            });
+        adaptive::expand(field, step, size);
+    };
+    // The last frame's field is sampled over, so slider drags allocate none.
+    let mut field = FIELD.with(|f| f.take());
+    field.resize(size * size, 0.);
+    fill(&mut field, precision);
+    if precision.difference {
+        let mut reference = vec![0.; size * size];
+        fill(&mut reference, precision.double());
+        precision::difference(&mut field, &reference);
+    }
+    field
+}
+
+/// The noise coordinates `sample_field` samples at the canvas position (x, y), for the overlays
+/// that probe a single pixel.
+pub fn noise_coordinates(x: f64, y: f64, scale: f64) -> (f64, f64) {
+    let (nx, ny) = unwrapped_coordinates(x, y, scale);
+    Repeat::current().apply(nx, ny)
+}
+
+/// `noise_coordinates` before the domain repetition.
+fn unwrapped_coordinates(x: f64, y: f64, scale: f64) -> (f64, f64) {
+    let (x, y) = Rotation::current().apply(x, y);
+    let (x, y) = AxisScale::current().apply(x, y);
+    let (nx, ny) = Precision::current().double().coordinates(x, y, scale);
+    Scroll::current().apply(nx, ny)
+}
+
+/// The canvas position the noise point (x, y) is drawn at, undoing `unwrapped_coordinates`.
+pub fn canvas_position(x: f64, y: f64, scale: f64) -> (f64, f64) {
+    let (scroll, offset) = (Scroll::current(), Precision::current().offset);
+    let half = HALF_RESOLUTION as f64;
+    let (px, py) = (
+        half + (x - scroll.x - offset) * scale,
+        half + (y - scroll.y - offset) * scale,
+    );
+    let (px, py) = AxisScale::current().invert(px, py);
+    Rotation::current().invert(px, py)
+}
+
+/// Draws the lines between the unit cells of the noise, turned, stretched and moved along with
+/// the view.
+pub fn draw_noise_grid(scale: f64, style: &str) {
+    let (scroll, offset) = (Scroll::current(), Precision::current().offset);
+    let untransformed = Rotation::current().is_identity()
+        && AxisScale::current().is_identity()
+        && scroll.x.fract() == 0.
+        && scroll.y.fract() == 0.
+        && offset.fract() == 0.;
+    if untransformed {
+        draw_grid(scale, style);
+        return;
+    }
+
+    let size = RESOLUTION as f64;
+    let corners = [(0., 0.), (size, 0.), (0., size), (size, size)]
+        .map(|(x, y)| unwrapped_coordinates(x, y, scale));
+    let (min_x, max_x, min_y, max_y) = corners.iter().fold(
+        (f64::MAX, f64::MIN, f64::MAX, f64::MIN),
+        |(min_x, max_x, min_y, max_y), &(x, y)| {
+            (min_x.min(x), max_x.max(x), min_y.min(y), max_y.max(y))
+        },
+    );
+    let line = |(x0, y0): (f64, f64), (x1, y1): (f64, f64)| {
+        let ((sx0, sy0), (sx1, sy1)) = (
+            canvas_position(x0, y0, scale),
+            canvas_position(x1, y1, scale),
+        );
+        draw_line(sx0, sy0, sx1, sy1, style);
+    };
+    for x in min_x.floor() as i64..=max_x.ceil() as i64 {
+        line((x as f64, min_y), (x as f64, max_y));
+    }
+    for y in min_y.floor() as i64..=max_y.ceil() as i64 {
+        line((min_x, y as f64), (max_x, y as f64));
+    }
+}
+
+pub fn colorize(field: &[f64]) -> Vec<u8> {
+    let mut pixels = vec![0; field.len() * 4];
+    colorize_into(field, &mut pixels);
+    pixels
+}
+
+/// Writes the colors of `field` into `pixels`, which holds four bytes per value.
+pub fn colorize_into(field: &[f64], pixels: &mut [u8]) {
+    pixels
+        .par_chunks_exact_mut(4)
+        .zip(field)
+        .for_each(|(pixel, &v)| pixel.copy_from_slice(&value_to_color(v)));
+}
+
+/// Forgets the previous field, so views never show a stale field for noises that do not draw one.
+pub fn begin_frame() {
+    FIELD.with(|f| f.borrow_mut().clear());
+    supersampling::frame_started();
+}
+
+/// Colorizes `field` into the reused frame a band of rows at a time, each band put on the canvas
+/// as soon as it is colored. The field of an extra color channel is only kept, and the noise
+/// itself is drawn with the kept channels packed in. With only the overlays shown, the bands are
+/// plain background for the overlays to be drawn on.
+pub fn draw_field(field: Vec<f64>) {
+    let Some(field) = channels::capture(field) else {
+        return;
+    };
+    let packed = channels::pack(&field);
+    let band_rows = BAND_ROWS.with(Cell::get);
+    let overlays_only = overlay::overlays_only();
+    let width = RESOLUTION as usize;
+    FRAME.with(|frame| {
+        let mut frame = frame.borrow_mut();
+        let bands = frame
+            .chunks_mut(band_rows * width * 4)
+            .zip(field.chunks(band_rows * width));
+        for (band, (pixels, values)) in bands.enumerate() {
+            match &packed {
+                _ if overlays_only => pixels.as_chunks_mut::<4>().0.fill(overlay::BACKGROUND),
+                Some(packed) => {
+                    let start = band * band_rows * width * 4;
+                    pixels.copy_from_slice(&packed[start..start + pixels.len()]);
+                }
+                None => colorize_into(values, pixels),
+            }
+            let rows = (values.len() / width) as u32;
+            draw_image(pixels, RESOLUTION, rows, 0., (band * band_rows) as f64);
+        }
+    });
+    channels::on_field_drawn(&field);
+    FIELD.with(|f| f.replace(field));
+    audio::on_field_changed();
+}
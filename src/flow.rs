@@ -0,0 +1,157 @@
+//! A vector field made from noise, shown as arrows or with line integral convolution.
+
+use rayon::prelude::*;
+
+use crate::{
+    channels,
+    drawer::{RESOLUTION, draw_arrow},
+    rng::Rng,
+};
+
+/// Pixels stepped along the streamline in each direction when smearing a pixel.
+const STREAK_LENGTH: usize = 15;
+/// Pixels between the arrows.
+const ARROW_SPACING: usize = 20;
+const TEXTURE_SEED: u32 = 0x11c;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Source {
+    /// The noise as x and the noise with the next seed as y.
+    Seeds,
+    Gradient,
+    Curl,
+}
+
+/// The x and y components of the vector field of `field`, none when the second seed is missing.
+pub fn vectors(
+    source: Source,
+    field: &[f64],
+    second_seed: Option<&[f64]>,
+) -> Option<(Vec<f64>, Vec<f64>)> {
+    match source {
+        Source::Seeds => Some((field.to_vec(), second_seed?.to_vec())),
+        Source::Gradient => Some(channels::derivatives(field)),
+        Source::Curl => {
+            let (dx, dy) = channels::derivatives(field);
+            Some((dy, dx.iter().map(|d| -d).collect()))
+        }
+    }
+}
+
+/// The vector at pixel (x, y), or none outside the canvas.
+fn vector(u: &[f64], v: &[f64], x: f64, y: f64) -> Option<(f64, f64)> {
+    let size = RESOLUTION as usize;
+    if x < 0. || y < 0. || x >= size as f64 || y >= size as f64 {
+        return None;
+    }
+    let i = y as usize * size + x as usize;
+    Some((u[i], v[i]))
+}
+
+/// The average of the white noise texture along the streamline through every pixel, stretched
+/// back to about [-1, 1]. Streaks follow the flow, their length does not depend on its speed.
+pub fn line_integral_convolution(u: &[f64], v: &[f64], rng: &dyn Rng) -> Vec<f64> {
+    let size = RESOLUTION as usize;
+    let texture: Vec<f64> = (0..size * size)
+        .map(|i| rng.signed(i as u32, TEXTURE_SEED))
+        .collect();
+    // The average of n uniform values in [-1, 1] spreads like 1/√(3n).
+    let stretch = (3. * (2 * STREAK_LENGTH + 1) as f64).sqrt() / 3.;
+    (0..size * size)
+        .into_par_iter()
+        .map(|i| {
+            let start = ((i % size) as f64 + 0.5, (i / size) as f64 + 0.5);
+            let (mut sum, mut count) = (texture[i], 1);
+            for direction in [1., -1.] {
+                let (mut x, mut y) = start;
+                for _ in 0..STREAK_LENGTH {
+                    let Some((dx, dy)) = vector(u, v, x, y) else {
+                        break;
+                    };
+                    let length = dx.hypot(dy);
+                    if length == 0. {
+                        break;
+                    }
+                    x += direction * dx / length;
+                    y += direction * dy / length;
+                    if x < 0. || y < 0. || x >= size as f64 || y >= size as f64 {
+                        break;
+                    }
+                    sum += texture[y as usize * size + x as usize];
+                    count += 1;
+                }
+            }
+            (sum / count as f64 * stretch).clamp(-1., 1.)
+        })
+        .collect()
+}
+
+/// Arrows from the centers of a coarse grid of cells along the vectors there, the longest vector
+/// almost spanning a cell.
+pub fn arrows(u: &[f64], v: &[f64]) -> Vec<((f64, f64), (f64, f64))> {
+    let size = RESOLUTION as usize;
+    let longest = u.iter().zip(v).fold(0f64, |m, (x, y)| m.max(x.hypot(*y)));
+    if longest == 0. {
+        return Vec::new();
+    }
+    let reach = ARROW_SPACING as f64 * 0.9 / longest;
+    let centers = (ARROW_SPACING / 2..size).step_by(ARROW_SPACING);
+    centers
+        .clone()
+        .flat_map(|y| centers.clone().map(move |x| (x, y)))
+        .map(|(x, y)| {
+            let (dx, dy) = (u[y * size + x], v[y * size + x]);
+            let (x, y) = (x as f64, y as f64);
+            (
+                (x - dx * reach / 2., y - dy * reach / 2.),
+                (x + dx * reach / 2., y + dy * reach / 2.),
+            )
+        })
+        .collect()
+}
+
+pub fn draw_arrows(u: &[f64], v: &[f64]) {
+    for ((x0, y0), (x1, y1)) in arrows(u, v) {
+        draw_arrow(x0, y0, x1, y1, 4., "#000000");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::SquirrelNoise5;
+
+    #[test]
+    fn streaks_follow_the_flow() {
+        let size = RESOLUTION as usize;
+        let (u, v) = (vec![1.; size * size], vec![0.; size * size]);
+        let lic = line_integral_convolution(&u, &v, &SquirrelNoise5);
+        // Along a row the streaks share most of their texture, across rows they share none.
+        let along: f64 = (0..size - 1)
+            .map(|x| (lic[200 * size + x] - lic[200 * size + x + 1]).abs())
+            .sum();
+        let across: f64 = (0..size)
+            .map(|x| (lic[200 * size + x] - lic[201 * size + x]).abs())
+            .sum();
+        assert!(along * 4. < across, "{along} along, {across} across");
+
+        let arrows = arrows(&u, &v);
+        assert_eq!(arrows.len(), (size / ARROW_SPACING).pow(2));
+        let ((x0, y0), (x1, y1)) = arrows[0];
+        assert!(x1 > x0 && y0 == y1);
+    }
+
+    #[test]
+    fn curl_runs_along_the_contours() {
+        let size = RESOLUTION as usize;
+        let field: Vec<f64> = (0..size * size)
+            .map(|i| ((i % size) as f64 * 0.03).sin() * ((i / size) as f64 * 0.02).cos())
+            .collect();
+        let (gx, gy) = vectors(Source::Gradient, &field, None).unwrap();
+        let (cx, cy) = vectors(Source::Curl, &field, None).unwrap();
+        for i in 0..size * size {
+            assert!((gx[i] * cx[i] + gy[i] * cy[i]).abs() < 1e-12);
+        }
+        assert!(vectors(Source::Seeds, &field, None).is_none());
+    }
+}
@@ -0,0 +1,155 @@
+//! Renders the six core noises with the shared settings as one labeled contact sheet.
+
+use std::cell::LazyCell;
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, HtmlElement, HtmlSelectElement};
+
+use crate::{
+    drawer::{CANVAS, RESOLUTION},
+    *,
+};
+
+const FILE_NAME: &str = "noise_gallery.png";
+const NOISES: [&str; 6] = [
+    "perlin",
+    "simplex",
+    "wavelet",
+    "gabor",
+    "anisotropic",
+    "worley",
+];
+const COLUMNS: usize = 3;
+const MARGIN: f64 = 16.;
+const LABEL_HEIGHT: f64 = 24.;
+const LINE_HEIGHT: f64 = 14.;
+
+elements!(
+    (gallery_render, HtmlElement),
+    (gallery_status, HtmlElement),
+    (noise_select, HtmlSelectElement),
+);
+define_closure!(gallery_render_click, render);
+
+pub fn setup() {
+    add_callback!(gallery_render, "click", gallery_render_click);
+}
+
+fn set_status(text: &str) {
+    GALLERY_STATUS.with(|s| s.set_inner_text(text));
+}
+
+/// The settings on screen without the noise itself, as state lines every noise picks its own
+/// controls out of.
+fn shared_settings(parameters: &[(String, String)]) -> Vec<String> {
+    parameters
+        .iter()
+        .filter(|(id, _)| id != "noise_select")
+        .map(|(id, value)| format!("{id}={value}"))
+        .collect()
+}
+
+/// Top left corner of the image of the `index`th noise.
+fn cell_origin(index: usize) -> (f64, f64) {
+    let (column, row) = ((index % COLUMNS) as f64, (index / COLUMNS) as f64);
+    (
+        MARGIN + column * (RESOLUTION as f64 + MARGIN),
+        MARGIN + row * (LABEL_HEIGHT + RESOLUTION as f64 + MARGIN),
+    )
+}
+
+/// Name of a noise as the noise selector shows it.
+fn title(noise: &str) -> String {
+    NOISE_SELECT.with(|select| {
+        (0..select.length())
+            .filter_map(|i| select.item(i))
+            .find(|option| option.get_attribute("value").as_deref() == Some(noise))
+            .and_then(|option| option.text_content())
+            .unwrap_or_else(|| noise.to_string())
+    })
+}
+
+/// Selects every noise in turn with the shared settings, copies the canvas into the sheet and
+/// finally puts the original settings back.
+fn draw_sheet(shared: &[String]) -> Option<HtmlCanvasElement> {
+    let rows = NOISES.len().div_ceil(COLUMNS);
+    let (_, bottom) = cell_origin(rows * COLUMNS);
+    let width = MARGIN + COLUMNS as f64 * (RESOLUTION as f64 + MARGIN);
+    let height = bottom + shared.len().div_ceil(COLUMNS) as f64 * LINE_HEIGHT + MARGIN;
+
+    let sheet = DOCUMENT
+        .with(|d| d.create_element("canvas"))
+        .ok()?
+        .dyn_into::<HtmlCanvasElement>()
+        .ok()?;
+    sheet.set_width(width as u32);
+    sheet.set_height(height as u32);
+    let context = sheet
+        .get_context("2d")
+        .ok()??
+        .dyn_into::<CanvasRenderingContext2d>()
+        .ok()?;
+    context.set_fill_style_str("#ffffff");
+    context.fill_rect(0., 0., width, height);
+
+    let original = state::serialize();
+    for (i, noise) in NOISES.iter().enumerate() {
+        state::apply(&format!("noise_select={noise}\n{}", shared.join("\n")));
+        let (x, y) = cell_origin(i);
+        context.set_fill_style_str("#000000");
+        context.set_font("16px sans-serif");
+        let _ = context.fill_text(&title(noise), x, y + LABEL_HEIGHT - 8.);
+        let _ =
+            CANVAS.with(|c| context.draw_image_with_html_canvas_element(c, x, y + LABEL_HEIGHT));
+    }
+    state::apply(&original);
+
+    context.set_font("12px sans-serif");
+    context.set_fill_style_str("#444444");
+    for (i, setting) in shared.iter().enumerate() {
+        let (x, _) = cell_origin(i % COLUMNS);
+        let y = bottom + (i / COLUMNS) as f64 * LINE_HEIGHT;
+        let _ = context.fill_text(setting, x, y);
+    }
+    Some(sheet)
+}
+
+fn render() {
+    let shared = shared_settings(&state::visible_parameters());
+    let Some(url) = draw_sheet(&shared).and_then(|sheet| sheet.to_data_url().ok()) else {
+        set_status("Failed to render the gallery");
+        return;
+    };
+
+    let Some(link) = DOCUMENT
+        .with(|d| d.create_element("a"))
+        .ok()
+        .and_then(|a| a.dyn_into::<HtmlElement>().ok())
+    else {
+        return;
+    };
+    let _ = link.set_attribute("href", &url);
+    let _ = link.set_attribute("download", FILE_NAME);
+    link.click();
+    set_status(&format!("Saved {} noises as {FILE_NAME}", NOISES.len()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_sheet_keeps_the_shared_settings_and_lays_out_in_rows() {
+        let parameters = [
+            ("noise_select".to_string(), "perlin".to_string()),
+            ("scale".to_string(), "40".to_string()),
+            ("seed".to_string(), "7".to_string()),
+        ];
+        assert_eq!(shared_settings(&parameters), ["scale=40", "seed=7"]);
+
+        assert_eq!(cell_origin(0), (MARGIN, MARGIN));
+        let (x, y) = cell_origin(COLUMNS + 1);
+        assert_eq!(x, cell_origin(1).0);
+        assert!(y > RESOLUTION as f64);
+    }
+}
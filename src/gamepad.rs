@@ -0,0 +1,211 @@
+//! Gamepad control of the sliders and the noise selection, for kiosk setups.
+
+use std::{
+    cell::{LazyCell, RefCell},
+    collections::HashMap,
+};
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{Gamepad, GamepadButton, HtmlElement, HtmlInputElement};
+
+use crate::*;
+
+const POLL_NAME: &str = "gamepad";
+const POLL_PERIOD_MS: i32 = 50;
+/// Stick deflection ignored as drift of a stick at rest.
+const DEAD_ZONE: f64 = 0.15;
+/// Fraction of a slider's range a stick or trigger held all the way moves per second.
+const SPEED: f64 = 0.4;
+const PAN_SLIDERS: [&str; 2] = ["offset_x", "offset_y"];
+/// Tried in order, the first one on screen zooms.
+const ZOOM_SLIDERS: [&str; 2] = ["zoom", "scale"];
+
+const LEFT_STICK_X: usize = 0;
+const LEFT_STICK_Y: usize = 1;
+const RIGHT_STICK_Y: usize = 3;
+const PREVIOUS_NOISE: usize = 4;
+const NEXT_NOISE: usize = 5;
+const TURN_DOWN: usize = 6;
+const TURN_UP: usize = 7;
+const PREVIOUS_SLIDER: usize = 12;
+const NEXT_SLIDER: usize = 13;
+
+thread_local! {
+    /// Buttons held at the last poll, so a press acts once however long it is held.
+    static PRESSED: RefCell<Vec<bool>> = const { RefCell::new(Vec::new()) };
+    /// Movement each slider has not shown yet because it is smaller than the slider's step.
+    static CARRY: RefCell<HashMap<String, f64>> = RefCell::new(HashMap::new());
+}
+
+elements!((gamepad_status, HtmlElement));
+define_closure!(gamepad_connected, start_polling);
+
+/// The page only polls while a gamepad is connected, browsers announce the first one once a
+/// button on it is pressed.
+pub fn setup() {
+    let window = web_sys::window().unwrap();
+    GAMEPAD_CONNECTED.with(|c| {
+        let _ =
+            window.add_event_listener_with_callback("gamepadconnected", c.as_ref().unchecked_ref());
+    });
+}
+
+fn start_polling() {
+    scheduler::start(POLL_NAME, POLL_PERIOD_MS, poll);
+}
+
+fn set_status(text: &str) {
+    GAMEPAD_STATUS.with(|s| s.set_inner_text(text));
+}
+
+fn dead_zone(value: f64) -> f64 {
+    if value.abs() < DEAD_ZONE { 0. } else { value }
+}
+
+/// Index `step` places away from `index`, wrapping around both ends.
+fn cycle(index: usize, len: usize, step: isize) -> usize {
+    (index as isize + step).rem_euclid(len as isize) as usize
+}
+
+fn first_gamepad() -> Option<Gamepad> {
+    let gamepads = web_sys::window()?.navigator().get_gamepads().ok()?;
+    gamepads
+        .iter()
+        .filter_map(|g| g.dyn_into::<Gamepad>().ok())
+        .find(|g| g.connected())
+}
+
+fn visible_slider(id: &str) -> Option<HtmlInputElement> {
+    DOCUMENT
+        .with(|d| d.get_element_by_id(id))?
+        .dyn_into::<HtmlInputElement>()
+        .ok()
+        .filter(|s| s.offset_parent().is_some())
+}
+
+fn visible_sliders() -> Vec<HtmlInputElement> {
+    let Ok(nodes) = DOCUMENT.with(|d| d.query_selector_all(".left-column input[type=range][id]"))
+    else {
+        return Vec::new();
+    };
+    (0..nodes.length())
+        .filter_map(|i| nodes.item(i)?.dyn_into::<HtmlInputElement>().ok())
+        .filter(|s| s.offset_parent().is_some())
+        .collect()
+}
+
+/// Moves a slider by `amount` of its range, keeping what its step rounds away for the next poll.
+fn nudge(slider: &HtmlInputElement, amount: f64) {
+    if amount == 0. {
+        return;
+    }
+    let min: f64 = slider.min().parse().unwrap_or(0.);
+    let max: f64 = slider.max().parse().unwrap_or(100.);
+    let carry = CARRY.with(|c| c.borrow().get(&slider.id()).copied().unwrap_or(0.));
+    let wanted = (slider.value_as_number() + amount * (max - min) + carry).clamp(min, max);
+    state::set_slider(slider, wanted);
+    let left = wanted - slider.value_as_number();
+    CARRY.with(|c| c.borrow_mut().insert(slider.id(), left));
+}
+
+fn focused_slider() -> Option<HtmlInputElement> {
+    DOCUMENT
+        .with(|d| d.active_element())?
+        .dyn_into::<HtmlInputElement>()
+        .ok()
+        .filter(|s| s.type_() == "range")
+}
+
+fn focus_slider(step: isize) {
+    let sliders = visible_sliders();
+    if sliders.is_empty() {
+        return;
+    }
+    let focused = focused_slider().map(|f| f.id());
+    let index = match sliders.iter().position(|s| Some(s.id()) == focused) {
+        Some(index) => cycle(index, sliders.len(), step),
+        None => 0,
+    };
+    let _ = sliders[index].focus();
+}
+
+fn cycle_noise(step: isize) {
+    let len = NOISE_SELECT.with(|s| s.length()) as usize;
+    if len == 0 {
+        return;
+    }
+    let index = NOISE_SELECT.with(|s| s.selected_index()).max(0) as usize;
+    NOISE_SELECT.with(|s| s.set_selected_index(cycle(index, len, step) as i32));
+    change_noise();
+    state::save();
+}
+
+fn poll() {
+    let Some(gamepad) = first_gamepad() else {
+        scheduler::stop(POLL_NAME);
+        set_status("No gamepad connected");
+        return;
+    };
+    set_status(&format!("Connected: {}", gamepad.id()));
+
+    let axes: Vec<f64> = gamepad
+        .axes()
+        .iter()
+        .map(|a| a.as_f64().unwrap_or(0.))
+        .collect();
+    let buttons: Vec<GamepadButton> = gamepad
+        .buttons()
+        .iter()
+        .filter_map(|b| b.dyn_into::<GamepadButton>().ok())
+        .collect();
+    let axis = |i: usize| dead_zone(axes.get(i).copied().unwrap_or(0.));
+    let pull = |i: usize| buttons.get(i).map_or(0., |b| b.value());
+    let step = SPEED * POLL_PERIOD_MS as f64 / 1000.;
+
+    // Sticks report up as negative.
+    for (id, amount) in PAN_SLIDERS
+        .iter()
+        .zip([axis(LEFT_STICK_X), -axis(LEFT_STICK_Y)])
+    {
+        if let Some(slider) = visible_slider(id) {
+            nudge(&slider, amount * step);
+        }
+    }
+    if let Some(zoom) = ZOOM_SLIDERS.iter().find_map(|id| visible_slider(id)) {
+        nudge(&zoom, -axis(RIGHT_STICK_Y) * step);
+    }
+    if let Some(focused) = focused_slider() {
+        nudge(&focused, (pull(TURN_UP) - pull(TURN_DOWN)) * step);
+    }
+
+    let pressed: Vec<bool> = buttons.iter().map(|b| b.pressed()).collect();
+    let was_pressed = PRESSED.with(|p| p.replace(pressed.clone()));
+    let just_pressed =
+        |i: usize| pressed.get(i) == Some(&true) && was_pressed.get(i) != Some(&true);
+    if just_pressed(PREVIOUS_SLIDER) {
+        focus_slider(-1);
+    }
+    if just_pressed(NEXT_SLIDER) {
+        focus_slider(1);
+    }
+    if just_pressed(PREVIOUS_NOISE) {
+        cycle_noise(-1);
+    }
+    if just_pressed(NEXT_NOISE) {
+        cycle_noise(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sticks_rest_and_selections_wrap() {
+        assert_eq!(dead_zone(0.1), 0.);
+        assert_eq!(dead_zone(-0.5), -0.5);
+        assert_eq!(cycle(0, 5, -1), 4);
+        assert_eq!(cycle(4, 5, 1), 0);
+        assert_eq!(cycle(2, 5, 1), 3);
+    }
+}
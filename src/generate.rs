@@ -0,0 +1,121 @@
+//! Renders to a promise a band of rows at a time, so an abort signal cancels it between bands.
+
+use js_sys::{Float32Array, Function, Object, Promise, Reflect};
+use wasm_bindgen::{JsValue, prelude::wasm_bindgen};
+use web_sys::AbortSignal;
+
+use crate::{
+    core::{Sampler, Viewport},
+    tiles::{Tile, prepare, sample_tile},
+    *,
+};
+
+/// Rows computed between two checks of the signal.
+const BAND_ROWS: u32 = 64;
+
+struct Job {
+    sampler: Box<dyn Sampler>,
+    view: Viewport,
+    bands: Vec<Tile>,
+    done: usize,
+    width: u32,
+    height: u32,
+    /// The bands done so far, row by row.
+    values: Vec<f32>,
+    signal: Option<AbortSignal>,
+    resolve: Function,
+    reject: Function,
+}
+
+/// Bands of `BAND_ROWS` rows across the whole image from the top, the last takes what is left.
+fn bands(width: u32, height: u32) -> Vec<Tile> {
+    (0..height)
+        .step_by(BAND_ROWS as usize)
+        .map(|top| (0, top, width, BAND_ROWS.min(height - top)))
+        .collect()
+}
+
+/// Computes the next band, then leaves the following one to the scheduler.
+fn step(mut job: Job) {
+    if let Some(signal) = job.signal.as_ref().filter(|s| s.aborted()) {
+        let _ = job.reject.call1(&JsValue::NULL, &signal.reason());
+        return;
+    }
+    let Some(&band) = job.bands.get(job.done) else {
+        let image = Object::new();
+        for (key, value) in [
+            ("width", JsValue::from(job.width)),
+            ("height", JsValue::from(job.height)),
+            ("values", Float32Array::from(job.values.as_slice()).into()),
+        ] {
+            let _ = Reflect::set(&image, &key.into(), &value);
+        }
+        let _ = job.resolve.call1(&JsValue::NULL, &image);
+        return;
+    };
+    let values = sample_tile(job.sampler.as_ref(), &job.view, band);
+    job.values.extend_from_slice(&values);
+    job.done += 1;
+    scheduler::after(0, move || step(job));
+}
+
+/// Renders a `width` x `height` image of the noise `settings` describe, the same flat object
+/// `render_tiles` takes. The promise resolves with `{ width, height, values }`, the values row by
+/// row in a `Float32Array`, and rejects with the signal's reason once `signal` is aborted.
+#[wasm_bindgen]
+pub fn generate(
+    settings: &Object,
+    width: u32,
+    height: u32,
+    signal: Option<AbortSignal>,
+) -> Promise {
+    let mut prepared = Some(prepare(settings, width, height));
+    Promise::new(&mut |resolve: Function, reject: Function| {
+        // The executor runs once, right away.
+        let Some(prepared) = prepared.take() else {
+            return;
+        };
+        match prepared {
+            Ok((sampler, view)) => step(Job {
+                sampler,
+                view,
+                bands: bands(width, height),
+                done: 0,
+                width,
+                height,
+                values: Vec::with_capacity(width as usize * height as usize),
+                signal: signal.clone(),
+                resolve,
+                reject,
+            }),
+            Err(error) => {
+                let _ = reject.call1(&JsValue::NULL, &error);
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::from_settings;
+
+    #[test]
+    fn bands_add_up_to_the_whole_image() {
+        let (width, height) = (70, 150);
+        let pairs =
+            [("noise", "simplex"), ("seed", "3")].map(|(k, v)| (k.to_string(), v.to_string()));
+        let sampler = from_settings(&pairs).unwrap();
+        let view = Viewport::new(-2., -3., 4., 6., width as usize, height as usize);
+
+        let bands = bands(width, height);
+        assert_eq!(bands.last(), Some(&(0, 128, width, 22)));
+        let banded: Vec<f32> = bands
+            .into_iter()
+            .flat_map(|band| sample_tile(sampler.as_ref(), &view, band))
+            .collect();
+        let whole = sample_tile(sampler.as_ref(), &view, (0, 0, width, height));
+        assert_eq!(banded.len(), whole.len());
+        assert!(banded.iter().zip(&whole).all(|(b, w)| (b - w).abs() < 1e-5));
+    }
+}
@@ -0,0 +1,81 @@
+//! Golden image comparison for native tests, against the PNGs in `tests/golden`.
+
+use std::{fs, path::PathBuf};
+
+use crate::{drawer::RESOLUTION, field::colorize, png};
+
+/// Largest per-channel difference that still counts as the same pixel.
+const CHANNEL_TOLERANCE: u8 = 2;
+/// Fraction of pixels allowed to differ by more than `CHANNEL_TOLERANCE`.
+const PIXEL_TOLERANCE: f64 = 0.001;
+/// Side of the golden images. Every `RESOLUTION / GOLDEN_SIZE`th pixel of the canvas is kept,
+/// which keeps the images in the repository small.
+const GOLDEN_SIZE: u32 = 50;
+const STEP: u32 = RESOLUTION / GOLDEN_SIZE;
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{name}.png"))
+}
+
+/// The pixel in the middle of every `STEP` x `STEP` block of the colored canvas.
+fn thumbnail(rgba: &[u8]) -> Vec<u8> {
+    (0..GOLDEN_SIZE * GOLDEN_SIZE)
+        .flat_map(|i| {
+            let row = i / GOLDEN_SIZE * STEP + STEP / 2;
+            let column = i % GOLDEN_SIZE * STEP + STEP / 2;
+            let pixel = (row * RESOLUTION + column) as usize * 4;
+            rgba[pixel..pixel + 4].iter().copied()
+        })
+        .collect()
+}
+
+/// Compares the colored `field`, a whole canvas, with its golden image. `BLESS=1` writes the render as the new
+/// golden instead, for new cases and intended visual changes.
+pub fn assert_matches_golden(name: &str, field: &[f64]) {
+    let rgba = thumbnail(&colorize(field));
+    let path = golden_path(name);
+
+    if std::env::var("BLESS").is_ok_and(|bless| bless == "1") {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, png::encode(GOLDEN_SIZE, GOLDEN_SIZE, &rgba)).unwrap();
+        eprintln!("wrote golden image {}", path.display());
+        return;
+    }
+    assert!(
+        path.exists(),
+        "{name}: no golden image at {}, run the tests with BLESS=1 to write it",
+        path.display()
+    );
+
+    let (width, height, golden) = png::decode(&fs::read(&path).unwrap())
+        .unwrap_or_else(|e| panic!("{}: {e}", path.display()));
+    assert_eq!(
+        (width, height),
+        (GOLDEN_SIZE, GOLDEN_SIZE),
+        "{name}: golden image has a different size"
+    );
+
+    let differing = rgba
+        .as_chunks::<4>()
+        .0
+        .iter()
+        .zip(golden.as_chunks::<4>().0)
+        .filter(|(a, b)| {
+            a.iter()
+                .zip(*b)
+                .any(|(a, b)| a.abs_diff(*b) > CHANNEL_TOLERANCE)
+        })
+        .count();
+    let allowed = (PIXEL_TOLERANCE * (GOLDEN_SIZE * GOLDEN_SIZE) as f64) as usize;
+    if differing > allowed {
+        let actual = path.with_extension("actual.png");
+        fs::write(&actual, png::encode(GOLDEN_SIZE, GOLDEN_SIZE, &rgba)).unwrap();
+        panic!(
+            "{name}: {differing} pixels differ from the golden image (at most {allowed} may), \
+             the new render was written to {}",
+            actual.display()
+        );
+    }
+}
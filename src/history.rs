@@ -0,0 +1,171 @@
+//! Keeps the noise and its settings in the address and in the browser history.
+
+use std::cell::{LazyCell, RefCell};
+
+use wasm_bindgen::{JsCast, JsValue, prelude::Closure};
+
+use crate::*;
+
+thread_local! {
+    /// Noise of the current history entry.
+    static NOISE: RefCell<String> = const { RefCell::new(String::new()) };
+}
+
+define_closure!(history_change, on_change);
+define_closure!(history_pop, on_pop);
+
+/// Changes bubble up to the document once a control is let go, slider drags do not flood the
+/// history, which browsers limit.
+pub fn setup() {
+    add_callback!(document, "change", history_change);
+    let window = web_sys::window().unwrap();
+    HISTORY_POP.with(|p| {
+        let _ = window.add_event_listener_with_callback("popstate", p.as_ref().unchecked_ref());
+    });
+}
+
+/// Bytes outside the unreserved set of URLs as `%XX`.
+fn encode(text: &str) -> String {
+    text.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+fn decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| text.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// The settings as the fragment of the address, `#id=value&id=value`.
+fn to_fragment(parameters: &[(String, String)]) -> String {
+    let pairs: Vec<String> = parameters
+        .iter()
+        .map(|(id, value)| format!("{}={}", encode(id), encode(value)))
+        .collect();
+    format!("#{}", pairs.join("&"))
+}
+
+/// The fragment of the address as a serialized state, the format `state::apply` reads.
+fn from_fragment(fragment: &str) -> String {
+    fragment
+        .trim_start_matches('#')
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(id, value)| format!("{}={}\n", decode(id), decode(value)))
+        .collect()
+}
+
+/// The selected noise and the controls on screen.
+fn current_fragment() -> (String, String) {
+    let parameters = state::visible_parameters();
+    let noise = parameters
+        .iter()
+        .find(|(id, _)| id == "noise_select")
+        .map(|(_, noise)| noise.clone())
+        .unwrap_or_default();
+    (noise, to_fragment(&parameters))
+}
+
+/// Puts the current settings in the address, in a new history entry when `new_entry` is set.
+fn record(new_entry: bool) {
+    let Some(history) = web_sys::window().and_then(|w| w.history().ok()) else {
+        return;
+    };
+    let (noise, fragment) = current_fragment();
+    let result = if new_entry {
+        history.push_state_with_url(&JsValue::NULL, "", Some(&fragment))
+    } else {
+        history.replace_state_with_url(&JsValue::NULL, "", Some(&fragment))
+    };
+    if result.is_err() {
+        console_log!("Failed to put the settings in the address");
+    }
+    NOISE.with(|n| n.replace(noise));
+}
+
+/// Starts a new history entry, for changes that load a whole configuration.
+pub fn push() {
+    record(true);
+}
+
+/// A changed noise starts a new entry, any other change updates the current one.
+fn on_change() {
+    let noise = parse_value!(noise_select, String);
+    let new_entry = NOISE.with(|n| *n.borrow() != noise);
+    record(new_entry);
+}
+
+fn fragment() -> String {
+    web_sys::window()
+        .and_then(|w| w.location().hash().ok())
+        .unwrap_or_default()
+}
+
+/// Back and forward bring the settings of their entry back.
+fn on_pop() {
+    let state = from_fragment(&fragment());
+    if state.is_empty() {
+        return;
+    }
+    state::apply(&state);
+    state::save();
+    NOISE.with(|n| n.replace(parse_value!(noise_select, String)));
+}
+
+/// A link with settings wins over the ones saved from the last visit. Either way the address
+/// then shows what is on screen.
+pub fn restore() {
+    let state = from_fragment(&fragment());
+    if !state.is_empty() {
+        state::apply(&state);
+    }
+    record(false);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_round_trip_through_the_address() {
+        let parameters = vec![
+            ("noise_select".to_string(), "perlin".to_string()),
+            ("scale".to_string(), "2.5".to_string()),
+            ("annotation_text".to_string(), "a & b = 100%".to_string()),
+        ];
+        let fragment = to_fragment(&parameters);
+        assert_eq!(
+            fragment,
+            "#noise_select=perlin&scale=2.5&annotation_text=a%20%26%20b%20%3D%20100%25"
+        );
+        assert_eq!(
+            from_fragment(&fragment),
+            "noise_select=perlin\nscale=2.5\nannotation_text=a & b = 100%\n"
+        );
+        assert_eq!(decode("%E2%9C%93 %zz"), "\u{2713} %zz");
+        assert_eq!(from_fragment(""), "");
+    }
+}
@@ -0,0 +1,240 @@
+//! An uploaded image decoded to grayscale, to show or to warp and mask the noises with.
+
+use std::{
+    cell::{LazyCell, RefCell},
+    sync::Arc,
+};
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{
+    CanvasRenderingContext2d, HtmlCanvasElement, HtmlElement, HtmlImageElement, HtmlInputElement,
+    Url,
+};
+
+use crate::{core::Sampler, drawer::RESOLUTION, noises::helpers::lerp, *};
+
+/// Noise units the whole image spans at its native size, so the default scale of 50 shows it
+/// pixel for pixel.
+const IMAGE_UNITS: f64 = RESOLUTION as f64 / 50.;
+
+thread_local! {
+    /// Luminance of the imported image in [-1, 1], one value per canvas pixel in row-major order.
+    static IMAGE: RefCell<Option<Arc<Vec<f64>>>> = const { RefCell::new(None) };
+    static IMAGE_ELEMENT: LazyCell<HtmlImageElement> =
+        LazyCell::new(|| HtmlImageElement::new().unwrap());
+}
+
+elements!(
+    (image_file, HtmlInputElement),
+    (image_status, HtmlElement),
+    (image_warp, HtmlInputElement),
+    (image_warp_amount, HtmlInputElement),
+    (image_warp_amount_display, HtmlElement),
+    (image_mask, HtmlInputElement),
+);
+define_closure!(image_file_change, load_selected);
+define_closure!(image_element_load, on_image_loaded);
+define_closure!(image_element_error, on_image_error);
+define_closure!(image_input_change, on_input_change);
+
+pub fn setup() {
+    add_callback!(image_file, "change", image_file_change);
+    add_callback!(image_element, "load", image_element_load);
+    add_callback!(image_element, "error", image_element_error);
+    add_callback!(image_warp, "input", image_input_change);
+    add_callback!(image_warp_amount, "input", image_input_change);
+    add_callback!(image_mask, "input", image_input_change);
+    show_warp_amount();
+}
+
+pub fn image() -> Option<Arc<Vec<f64>>> {
+    IMAGE.with(|i| i.borrow().clone())
+}
+
+fn set_status(text: &str) {
+    IMAGE_STATUS.with(|s| s.set_inner_text(text));
+}
+
+fn show_warp_amount() {
+    let amount = IMAGE_WARP_AMOUNT.with(|a| a.value());
+    IMAGE_WARP_AMOUNT_DISPLAY.with(|d| d.set_inner_text(&amount));
+}
+
+fn on_input_change() {
+    show_warp_amount();
+    if IMAGE.with(|i| i.borrow().is_some()) {
+        update_current_noise();
+    }
+}
+
+/// The browser decodes the file, the pixels are read back once it has loaded.
+fn load_selected() {
+    let Some(file) = IMAGE_FILE
+        .with(|f| f.files())
+        .and_then(|files| files.get(0))
+    else {
+        return;
+    };
+    let Ok(url) = Url::create_object_url_with_blob(&file) else {
+        set_status("Failed to read the file");
+        return;
+    };
+    set_status("Loading...");
+    IMAGE_ELEMENT.with(|i| i.set_src(&url));
+}
+
+fn on_image_error() {
+    IMAGE_ELEMENT.with(|i| {
+        let _ = Url::revoke_object_url(&i.src());
+    });
+    set_status("This file is not an image the browser can decode");
+}
+
+fn on_image_loaded() {
+    let pixels = IMAGE_ELEMENT.with(|image| {
+        let _ = Url::revoke_object_url(&image.src());
+        decode(image)
+    });
+    let Some(pixels) = pixels else {
+        set_status("Failed to read the pixels of the image");
+        return;
+    };
+
+    IMAGE.with(|i| i.replace(Some(Arc::new(pixels))));
+    let (width, height) = IMAGE_ELEMENT.with(|i| (i.natural_width(), i.natural_height()));
+    set_status(&format!("Loaded a {width}x{height} image"));
+    update_current_noise();
+}
+
+/// Scales the largest centered square of the image to the canvas resolution and converts it to
+/// luminance, with black at -1 and white at 1.
+fn decode(image: &HtmlImageElement) -> Option<Vec<f64>> {
+    let canvas = DOCUMENT
+        .with(|d| d.create_element("canvas"))
+        .ok()?
+        .dyn_into::<HtmlCanvasElement>()
+        .ok()?;
+    canvas.set_width(RESOLUTION);
+    canvas.set_height(RESOLUTION);
+    let context = canvas
+        .get_context("2d")
+        .ok()??
+        .dyn_into::<CanvasRenderingContext2d>()
+        .ok()?;
+
+    let (width, height) = (image.natural_width() as f64, image.natural_height() as f64);
+    let side = width.min(height);
+    context
+        .draw_image_with_html_image_element_and_sw_and_sh_and_dx_and_dy_and_dw_and_dh(
+            image,
+            (width - side) / 2.,
+            (height - side) / 2.,
+            side,
+            side,
+            0.,
+            0.,
+            RESOLUTION as f64,
+            RESOLUTION as f64,
+        )
+        .ok()?;
+    let data = context
+        .get_image_data(0., 0., RESOLUTION as f64, RESOLUTION as f64)
+        .ok()?
+        .data();
+
+    Some(
+        data.as_chunks::<4>()
+            .0
+            .iter()
+            .map(|rgba| {
+                let luminance =
+                    (0.2126 * rgba[0] as f64 + 0.7152 * rgba[1] as f64 + 0.0722 * rgba[2] as f64)
+                        / 255.;
+                luminance * 2. - 1.
+            })
+            .collect(),
+    )
+}
+
+/// The imported image as a noise, tiled over the plane and bilinearly filtered between pixels.
+pub struct ImageSampler {
+    pixels: Arc<Vec<f64>>,
+}
+
+impl ImageSampler {
+    pub fn new(pixels: Arc<Vec<f64>>) -> Self {
+        ImageSampler { pixels }
+    }
+
+    fn pixel(&self, x: i64, y: i64) -> f64 {
+        let size = RESOLUTION as i64;
+        self.pixels[(y.rem_euclid(size) * size + x.rem_euclid(size)) as usize]
+    }
+}
+
+impl Sampler for ImageSampler {
+    fn sample(&self, x: f64, y: f64) -> f64 {
+        let pixels_per_unit = RESOLUTION as f64 / IMAGE_UNITS;
+        let px = x * pixels_per_unit + RESOLUTION as f64 / 2. - 0.5;
+        let py = y * pixels_per_unit + RESOLUTION as f64 / 2. - 0.5;
+        let (x0, y0) = (px.floor(), py.floor());
+        let (tx, ty) = (px - x0, py - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+
+        let top = lerp(tx, self.pixel(x0, y0), self.pixel(x0 + 1, y0));
+        let bottom = lerp(tx, self.pixel(x0, y0 + 1), self.pixel(x0 + 1, y0 + 1));
+        lerp(ty, top, bottom)
+    }
+}
+
+/// How the imported image feeds into the other noises, captured once per frame so the parallel
+/// sampling does not touch the page.
+pub struct ImageInput {
+    pixels: Arc<Vec<f64>>,
+    warp_amount: Option<f64>,
+    mask: bool,
+}
+
+impl ImageInput {
+    /// `None` unless an image was imported and is used as a warp or a mask.
+    pub fn current() -> Option<Self> {
+        let pixels = image()?;
+        let warp_amount = is_checked!(image_warp).then(|| parse_value!(image_warp_amount, f64));
+        let mask = is_checked!(image_mask);
+        (warp_amount.is_some() || mask).then_some(ImageInput {
+            pixels,
+            warp_amount,
+            mask,
+        })
+    }
+
+    /// Samples the noise at canvas pixel (x, y), whose noise coordinates are (nx, ny). The warp
+    /// reads the image at the pixel and half an image away, like the domain warp of the noises
+    /// reads their own fBm at two offsets.
+    pub fn sample(
+        &self,
+        x: usize,
+        y: usize,
+        nx: f64,
+        ny: f64,
+        sample: impl Fn(f64, f64) -> f64,
+    ) -> f64 {
+        let size = RESOLUTION as usize;
+        let value = self.pixels[y * size + x];
+
+        let (nx, ny) = match self.warp_amount {
+            Some(amount) => {
+                let shifted = self.pixels[(y + size / 2) % size * size + (x + size / 2) % size];
+                (nx + amount * value, ny + amount * shifted)
+            }
+            None => (nx, ny),
+        };
+
+        let noise = sample(nx, ny);
+        if self.mask {
+            noise * (value + 1.) / 2.
+        } else {
+            noise
+        }
+    }
+}
@@ -0,0 +1,195 @@
+//! Unattended demo that shows every noise in turn and sweeps its sliders.
+
+use std::{
+    cell::{LazyCell, RefCell},
+    f64::consts::TAU,
+};
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlElement, HtmlInputElement};
+
+use crate::*;
+
+const ANIMATION_NAME: &str = "kiosk";
+const ANIMATION_PERIOD_MS: i32 = 100;
+/// Sliders swept while a noise is shown, whichever of them the noise has.
+const SWEPT_SLIDERS: [&str; 6] = [
+    "scale",
+    "angle",
+    "sun_angle",
+    "camera_yaw",
+    "warp_amount",
+    "zoom",
+];
+/// Imported images need a file picked by hand, the demo passes them by.
+const SKIPPED_NOISES: [&str; 1] = ["image"];
+
+struct Tour {
+    /// Settings from before the demo.
+    state: String,
+    /// When the current noise came up, in milliseconds.
+    shown_at: f64,
+    swept: Vec<HtmlInputElement>,
+}
+
+thread_local! {
+    static TOUR: RefCell<Option<Tour>> = const { RefCell::new(None) };
+}
+
+elements!(
+    (kiosk_start, HtmlElement),
+    (kiosk_duration, HtmlInputElement),
+    (kiosk_duration_display, HtmlElement),
+    (kiosk_caption, HtmlElement),
+);
+define_closure!(kiosk_start_click, toggle);
+define_closure!(kiosk_duration_input, update_displays);
+
+pub fn setup() {
+    add_callback!(kiosk_start, "click", kiosk_start_click);
+    add_callback!(kiosk_duration, "input", kiosk_duration_input);
+    update_displays();
+}
+
+fn update_displays() {
+    set_text!(
+        kiosk_duration,
+        &format!("{} s", parse_value!(kiosk_duration, u32))
+    );
+}
+
+/// Position of a swept slider along its range at `t` turns into the sweep, staying within the
+/// middle half so the noise never reaches the extremes of a slider.
+fn sweep(t: f64, phase: f64) -> f64 {
+    0.5 + 0.25 * (TAU * (t + phase)).sin()
+}
+
+/// The first sentence of a description, or all of it when it has only one.
+fn first_sentence(text: &str) -> &str {
+    let text = text.trim();
+    match text.find(". ") {
+        Some(end) => &text[..=end],
+        None => text,
+    }
+}
+
+fn toggle() {
+    if TOUR.with(|t| t.borrow().is_some()) {
+        stop();
+    } else {
+        start();
+    }
+}
+
+fn start() {
+    TOUR.with(|t| {
+        t.replace(Some(Tour {
+            state: state::serialize(),
+            shown_at: 0.,
+            swept: Vec::new(),
+        }))
+    });
+    KIOSK_START.with(|b| b.set_inner_text("Stop demo"));
+    set_hidden!(kiosk_caption, false);
+    show(&parse_value!(noise_select, String));
+    scheduler::start(ANIMATION_NAME, ANIMATION_PERIOD_MS, tick);
+}
+
+fn stop() {
+    scheduler::stop(ANIMATION_NAME);
+    KIOSK_START.with(|b| b.set_inner_text("Start demo"));
+    set_hidden!(kiosk_caption, true);
+    if let Some(tour) = TOUR.with(|t| t.take()) {
+        state::apply(&tour.state);
+    }
+}
+
+/// Selects `noise` with its default settings and captions it.
+fn show(noise: &str) {
+    state::apply(&format!("noise_select={noise}\n"));
+
+    let description = DOCUMENT.with(|d| d.get_element_by_id(noise));
+    let title = description
+        .as_ref()
+        .and_then(|d| d.query_selector("h2").ok().flatten())
+        .and_then(|h| h.text_content())
+        .unwrap_or_else(|| noise.to_string());
+    let summary = description
+        .as_ref()
+        .and_then(|d| d.query_selector(".text-block").ok().flatten())
+        .and_then(|p| p.text_content())
+        .unwrap_or_default();
+    KIOSK_CAPTION.with(|c| {
+        c.set_inner_text(&format!("{title}\n{}", first_sentence(&summary)));
+    });
+
+    let swept = SWEPT_SLIDERS
+        .iter()
+        .filter_map(|id| DOCUMENT.with(|d| d.get_element_by_id(id)))
+        .filter_map(|s| s.dyn_into::<HtmlInputElement>().ok())
+        .filter(|s| s.offset_parent().is_some())
+        .collect();
+    TOUR.with(|t| {
+        if let Some(tour) = t.borrow_mut().as_mut() {
+            tour.shown_at = js_sys::Date::now();
+            tour.swept = swept;
+        }
+    });
+}
+
+fn next_noise() -> Option<String> {
+    NOISE_SELECT.with(|select| {
+        let len = select.length();
+        let current = select.selected_index().max(0) as u32;
+        (1..=len)
+            .filter_map(|step| select.item((current + step) % len)?.get_attribute("value"))
+            .find(|value| !SKIPPED_NOISES.contains(&value.as_str()))
+    })
+}
+
+fn tick() {
+    let Some((shown_at, swept)) =
+        TOUR.with(|t| t.borrow().as_ref().map(|t| (t.shown_at, t.swept.clone())))
+    else {
+        return;
+    };
+    let duration = parse_value!(kiosk_duration, f64) * 1000.;
+    let elapsed = js_sys::Date::now() - shown_at;
+    if elapsed >= duration {
+        if let Some(noise) = next_noise() {
+            show(&noise);
+        }
+        return;
+    }
+    if swept.is_empty() {
+        return;
+    }
+
+    // One sweep there and back per noise, every slider a little out of step with the others.
+    for (i, slider) in swept.iter().enumerate() {
+        let min: f64 = slider.min().parse().unwrap_or(0.);
+        let max: f64 = slider.max().parse().unwrap_or(100.);
+        let fraction = sweep(elapsed / duration, i as f64 / swept.len() as f64);
+        slider.set_value_as_number(min + fraction * (max - min));
+    }
+    update_current_noise();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sweeps_stay_in_the_middle_and_captions_stay_short() {
+        for i in 0..=20 {
+            let fraction = sweep(i as f64 / 20., 0.3);
+            assert!((0.25..=0.75).contains(&fraction));
+        }
+        assert_eq!(sweep(0., 0.), 0.5);
+        assert_eq!(
+            first_sentence("  Perlin noise is smooth. It was invented in 1983.  "),
+            "Perlin noise is smooth."
+        );
+        assert_eq!(first_sentence("One sentence"), "One sentence");
+    }
+}
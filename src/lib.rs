@@ -4,19 +4,95 @@ use std::{cell::LazyCell, sync::Mutex};
 
 use wasm_bindgen::prelude::*;
 mod noises;
+mod demos;
+mod analysis;
 use web_sys::{Document, Element, HtmlSelectElement};
 
 use crate::{
-    drawer::{HALF_RESOLUTION, RESOLUTION, draw_grid, draw_noise},
+    analysis::hash_quality::HashQualityNoise,
+    demos::{
+        brownian_motion::BrownianMotionNoise, low_discrepancy::LowDiscrepancyNoise,
+        quilting::QuiltingNoise, smoke::SmokeNoise, clouds::CloudsNoise,
+        caves::CavesNoise, biomes::BiomesNoise, determinism::DeterminismNoise,
+        reaction_diffusion::ReactionDiffusionNoise,
+    },
+    drawer::HALF_RESOLUTION,
     noises::{
         noise::Noise,
         anisotropic_noise::AnisotropicNoise, gabor_noise::GaborNoise, perlin_noise::PerlinNoise,
         simplex_noise::SimplexNoise, wavelet_noise::WaveletNoise, worley_noise::WorleyNoise,
+        line_noise::LineNoise, isosurface_noise::IsosurfaceNoise,
+        slices_noise::SlicesNoise, image_noise::ImageNoise,
     },
 };
+#[cfg(feature = "noise-rs")]
+use crate::noises::external_noise::ExternalNoise;
+mod adaptive;
+mod annotations;
+mod appearance;
+mod artifacts;
+mod audio;
+mod axis_scale;
+#[cfg(feature = "bench")]
+pub mod bench;
+mod camera;
+mod channels;
+mod charts;
+mod clipboard;
+mod compare;
+mod construction;
+mod contrast;
+mod curves;
+mod dial;
+pub mod core;
+mod domain_repeat;
 mod drawer;
+mod droplets;
+mod erosion;
+mod field;
+mod flow;
+mod gallery;
+mod gamepad;
+mod generate;
+#[cfg(test)]
+mod golden;
+mod history;
+mod image_import;
+mod kiosk;
 mod log;
 mod macros;
+mod midi;
+mod montage;
+#[cfg(feature = "noise-rs")]
+pub mod noise_rs;
+mod overlay;
+mod pad;
+mod permutation;
+#[cfg(any(test, feature = "render"))]
+mod png;
+mod precision;
+#[cfg(feature = "render")]
+pub mod render;
+mod report;
+mod rng;
+mod rotation;
+mod scheduler;
+mod schema;
+mod scroll;
+mod self_test;
+mod session;
+mod shader;
+mod slider_buttons;
+mod snapshots;
+mod snippet;
+mod state;
+mod supersampling;
+mod tiles;
+mod value_format;
+mod view;
+
+/// Starts the rayon workers the core's samplers, and with them [`tiles::render_tiles`], run on.
+pub use wasm_bindgen_rayon::init_thread_pool;
 
 thread_local! {
     pub static DOCUMENT: LazyCell<Document> = LazyCell::new(||{
@@ -35,9 +111,39 @@ pub fn get_element_by_id(id: &str) -> Element {
     })
 }
 
+/// Noise units spanned by one lattice cell of the named noise, if it has a lattice.
+fn cell_size(noise: &str) -> Option<f64> {
+    match noise {
+        "perlin" => PerlinNoise::cell_size(),
+        "simplex" => SimplexNoise::cell_size(),
+        "wavelet" => WaveletNoise::cell_size(),
+        "gabor" => GaborNoise::cell_size(),
+        "anisotropic" => AnisotropicNoise::cell_size(),
+        "worley" => WorleyNoise::cell_size(),
+        "line" => LineNoise::cell_size(),
+        "isosurface" => IsosurfaceNoise::cell_size(),
+        "slices" => SlicesNoise::cell_size(),
+        "reaction_diffusion" => ReactionDiffusionNoise::cell_size(),
+        "brownian_motion" => BrownianMotionNoise::cell_size(),
+        "low_discrepancy" => LowDiscrepancyNoise::cell_size(),
+        "hash_quality" => HashQualityNoise::cell_size(),
+        "image" => ImageNoise::cell_size(),
+        "quilting" => QuiltingNoise::cell_size(),
+        "smoke" => SmokeNoise::cell_size(),
+        "clouds" => CloudsNoise::cell_size(),
+        "caves" => CavesNoise::cell_size(),
+        "biomes" => BiomesNoise::cell_size(),
+        "determinism" => DeterminismNoise::cell_size(),
+        #[cfg(feature = "noise-rs")]
+        "external" => ExternalNoise::cell_size(),
+        _ => None,
+    }
+}
+
 fn change_noise() {
     let new_noise = parse_value!(noise_select, String);
     let mut current_noise = CURRENT_NOISE.lock().unwrap();
+    appearance::capture(cell_size(current_noise.as_str()));
 
     match current_noise.as_str() {
         "perlin" => PerlinNoise::deselect(),
@@ -46,6 +152,22 @@ fn change_noise() {
         "gabor" => GaborNoise::deselect(),
         "anisotropic" => AnisotropicNoise::deselect(),
         "worley" => WorleyNoise::deselect(),
+        "line" => LineNoise::deselect(),
+        "isosurface" => IsosurfaceNoise::deselect(),
+        "slices" => SlicesNoise::deselect(),
+        "reaction_diffusion" => ReactionDiffusionNoise::deselect(),
+        "brownian_motion" => BrownianMotionNoise::deselect(),
+        "low_discrepancy" => LowDiscrepancyNoise::deselect(),
+        "hash_quality" => HashQualityNoise::deselect(),
+        "image" => ImageNoise::deselect(),
+        "quilting" => QuiltingNoise::deselect(),
+        "smoke" => SmokeNoise::deselect(),
+        "clouds" => CloudsNoise::deselect(),
+        "caves" => CavesNoise::deselect(),
+        "biomes" => BiomesNoise::deselect(),
+        "determinism" => DeterminismNoise::deselect(),
+        #[cfg(feature = "noise-rs")]
+        "external" => ExternalNoise::deselect(),
         _ => (),
     }
 
@@ -56,6 +178,22 @@ fn change_noise() {
         "gabor" => GaborNoise::select(),
         "anisotropic" => AnisotropicNoise::select(),
         "worley" => WorleyNoise::select(),
+        "line" => LineNoise::select(),
+        "isosurface" => IsosurfaceNoise::select(),
+        "slices" => SlicesNoise::select(),
+        "reaction_diffusion" => ReactionDiffusionNoise::select(),
+        "brownian_motion" => BrownianMotionNoise::select(),
+        "low_discrepancy" => LowDiscrepancyNoise::select(),
+        "hash_quality" => HashQualityNoise::select(),
+        "image" => ImageNoise::select(),
+        "quilting" => QuiltingNoise::select(),
+        "smoke" => SmokeNoise::select(),
+        "clouds" => CloudsNoise::select(),
+        "caves" => CavesNoise::select(),
+        "biomes" => BiomesNoise::select(),
+        "determinism" => DeterminismNoise::select(),
+        #[cfg(feature = "noise-rs")]
+        "external" => ExternalNoise::select(),
         e => {
             console_log!("Unknown noise was selected: {e}");
             return;
@@ -63,16 +201,127 @@ fn change_noise() {
     }
     current_noise.clear();
     current_noise.push_str(new_noise.as_str());
+    drop(current_noise);
+
+    if appearance::apply(cell_size(new_noise.as_str())) {
+        update_current_noise();
+    }
 }
 define_closure!(change_noise, change_noise);
 
+/// Called by every noise after it has drawn its field, for the passes that follow the current frame.
+pub fn on_frame_drawn() {
+    supersampling::on_frame_drawn();
+    adaptive::on_frame_drawn();
+    construction::on_frame_drawn();
+    contrast::on_frame_drawn();
+    erosion::on_frame_drawn();
+    curves::on_frame_drawn();
+    view::on_frame_drawn();
+    precision::on_frame_drawn();
+    droplets::on_frame_drawn();
+    analysis::on_frame_drawn();
+    charts::on_frame_drawn();
+    permutation::on_frame_drawn();
+}
+
+/// Redraws the selected noise, for global controls that change how every noise is shown.
+pub fn update_current_noise() {
+    match CURRENT_NOISE.lock().unwrap().as_str() {
+        "perlin" => PerlinNoise::update(),
+        "simplex" => SimplexNoise::update(),
+        "wavelet" => WaveletNoise::update(),
+        "gabor" => GaborNoise::update(),
+        "anisotropic" => AnisotropicNoise::update(),
+        "worley" => WorleyNoise::update(),
+        "line" => LineNoise::update(),
+        "isosurface" => IsosurfaceNoise::update(),
+        "slices" => SlicesNoise::update(),
+        "reaction_diffusion" => ReactionDiffusionNoise::update(),
+        "brownian_motion" => BrownianMotionNoise::update(),
+        "low_discrepancy" => LowDiscrepancyNoise::update(),
+        "hash_quality" => HashQualityNoise::update(),
+        "image" => ImageNoise::update(),
+        "quilting" => QuiltingNoise::update(),
+        "smoke" => SmokeNoise::update(),
+        "clouds" => CloudsNoise::update(),
+        "caves" => CavesNoise::update(),
+        "biomes" => BiomesNoise::update(),
+        "determinism" => DeterminismNoise::update(),
+        #[cfg(feature = "noise-rs")]
+        "external" => ExternalNoise::update(),
+        _ => (),
+    }
+}
+
 #[wasm_bindgen(start)]
 fn start() {
+    // Workers load the same module to render tiles, only the page has controls to set up.
+    if web_sys::window().is_none() {
+        return;
+    }
     add_callback!(noise_select, "input", change_noise);
+    field::setup();
     PerlinNoise::setup();
     SimplexNoise::setup();
     WaveletNoise::setup();
     GaborNoise::setup();
     AnisotropicNoise::setup();
     WorleyNoise::setup();
+    LineNoise::setup();
+    IsosurfaceNoise::setup();
+    SlicesNoise::setup();
+    ReactionDiffusionNoise::setup();
+    BrownianMotionNoise::setup();
+    LowDiscrepancyNoise::setup();
+    HashQualityNoise::setup();
+    ImageNoise::setup();
+    QuiltingNoise::setup();
+    SmokeNoise::setup();
+    CloudsNoise::setup();
+    CavesNoise::setup();
+    BiomesNoise::setup();
+    DeterminismNoise::setup();
+    #[cfg(feature = "noise-rs")]
+    ExternalNoise::setup();
+    audio::setup();
+    clipboard::setup();
+    annotations::setup();
+    shader::setup();
+    snippet::setup();
+    image_import::setup();
+    precision::setup();
+    rng::setup();
+    overlay::setup();
+    permutation::setup();
+    supersampling::setup();
+    adaptive::setup();
+    axis_scale::setup();
+    scroll::setup();
+    rotation::setup();
+    channels::setup();
+    domain_repeat::setup();
+    view::setup();
+    droplets::setup();
+    erosion::setup();
+    contrast::setup();
+    construction::setup();
+    curves::setup();
+    analysis::setup();
+    state::setup();
+    history::setup();
+    slider_buttons::setup();
+    midi::setup();
+    gamepad::setup();
+    kiosk::setup();
+    artifacts::setup();
+    session::setup();
+    snapshots::setup();
+    compare::setup();
+    report::setup();
+    montage::setup();
+    gallery::setup();
+    self_test::setup();
+    state::restore();
+    history::restore();
 }
@@ -87,6 +87,21 @@ macro_rules! define_closure {
     };
 }
 
+#[macro_export]
+macro_rules! define_event_closure {
+    ($name:ident, $event:ty, $body:expr) => {
+        paste::paste!{
+            thread_local!{
+                    static [<$name:snake:upper>]: LazyCell<Closure<dyn Fn($event)>> = LazyCell::new(|| {
+                        Closure::new(|event: $event|{
+                        $body(event);
+                    })
+                });
+            }
+        }
+    };
+}
+
 #[macro_export]
 macro_rules! add_callback {
     ($var:ident, $callback:literal, $closure:expr) => {
@@ -131,6 +146,8 @@ macro_rules! remove_callback {
 macro_rules! radio {
     ($name:ident, ($default:ident, $($default_hide:ident),* $(,)?), $(($option:ident, $($option_hide:ident),* $(,)?)),* $(,)?) => {
         paste::paste! {
+            // Variants are named after the page's radio ids, which often share a suffix.
+            #[allow(clippy::enum_variant_names)]
             #[derive(Copy, Clone, PartialEq)]
             enum [<$name:camel>] {
                 [<$default:camel>],
@@ -219,6 +236,66 @@ macro_rules! checkbox {
     };
 }
 
+#[macro_export]
+macro_rules! button {
+    ($noise:ident, $name:ident) => {
+        paste::paste! {
+            elements!(
+                    ($name, HtmlElement),
+                    ([<$name _control>], HtmlElement)
+            );
+
+            define_closure!([<$name _click>], [<$noise:camel Noise>]::[<on_ $name>]);
+        }
+    };
+}
+
+/// A dial for the angle slider `$name`, dragging it moves the slider and redraws the noise.
+#[macro_export]
+macro_rules! dial {
+    ($noise:ident, $name:ident) => {
+        paste::paste! {
+            elements!(([<$name _dial>], web_sys::HtmlCanvasElement));
+
+            define_event_closure!([<$name _dial_drag>], web_sys::MouseEvent, |event: web_sys::MouseEvent| {
+                let moved = [<$name:snake:upper _DIAL>].with(|dial| {
+                    [<$name:snake:upper>].with(|slider| $crate::dial::drag(dial, slider, &event))
+                });
+                if moved {
+                    [<$noise:camel Noise>]::update();
+                    $crate::state::save();
+                }
+            });
+        }
+    };
+}
+
+/// A square that sets the sliders `$x` and `$y` together, dragging it redraws the noise. Its
+/// `_control` wrapper can be listed in radio `hide` lists like any slider.
+#[macro_export]
+macro_rules! pad {
+    ($noise:ident, $x:ident, $y:ident) => {
+        paste::paste! {
+            elements!(
+                ([<$x _ $y _pad>], web_sys::HtmlCanvasElement),
+                ([<$x _ $y _pad_control>], HtmlElement)
+            );
+
+            define_event_closure!([<$x _ $y _pad_drag>], web_sys::MouseEvent, |event: web_sys::MouseEvent| {
+                let moved = [<$x:upper _ $y:upper _PAD>].with(|pad| {
+                    [<$x:snake:upper>].with(|x| {
+                        [<$y:snake:upper>].with(|y| $crate::pad::drag(pad, x, y, &event))
+                    })
+                });
+                if moved {
+                    [<$noise:camel Noise>]::update();
+                    $crate::state::save();
+                }
+            });
+        }
+    };
+}
+
 #[macro_export]
 macro_rules! slider {
     ($name:ident, $type:ty, $default:literal) => {
@@ -256,17 +333,37 @@ macro_rules! set_hidden {
     };
 }
 
+/// The display format a slider row in `define_noise!` names, written without the
+/// `ValueFormat::` prefix, or `Auto` when the row has none.
+#[macro_export]
+macro_rules! slider_format {
+    () => {
+        $crate::value_format::ValueFormat::Auto
+    };
+    ($format:expr) => {{
+        use $crate::value_format::ValueFormat::*;
+        $format
+    }};
+}
+
 #[macro_export]
 macro_rules! define_noise {
     ($noise:ident,
-        sliders:[$(($slider_name:ident, $slider_type:ty, $slider_min:literal, $slider_default:literal, $slider_max:literal)),*] ;
+        sliders:[$(($slider_name:ident, $slider_type:ty, $slider_min:literal, $slider_default:literal, $slider_max:literal $(, $slider_format:expr)?)),*] ;
         radios:[$(($radio_name:ident, ($radio_default:ident $(, hide:[ $($radio_default_hide:ident),* $(,)? ])?), $(($radio_option:ident $(, hide:[ $($radio_option_hide:ident),* $(,)? ])?)),* $(,)?)),*] ;
         checkboxes:[$($checkbox_name:ident),*] $(;)?
+        $(buttons:[$($button_name:ident),*] $(;)?)?
+        $(dials:[$($dial_name:ident),*] $(;)?)?
+        $(pads:[$(($pad_x:ident, $pad_y:ident)),*] $(;)?)?
+        $(cell_size: $cell_size:expr $(;)?)?
     ) => {
         paste::paste! {
             $(slider!($slider_name, $slider_type, $slider_default);)*
             $(radio!($radio_name, ($radio_default, $($($radio_default_hide,)*)*), $(($radio_option, $($($radio_option_hide,)*)* ),)*);)*
             $(checkbox!($checkbox_name);)*
+            $($(button!($noise, $button_name);)*)?
+            $($(dial!($noise, $dial_name);)*)?
+            $($(pad!($noise, $pad_x, $pad_y);)*)?
 
             elements!(($noise, HtmlElement));
 
@@ -298,23 +395,133 @@ macro_rules! define_noise {
                         )*
                     }
                 }
+
+                /// The settings the controls reset to when the noise is selected, for everything
+                /// that renders without a page.
+                #[allow(dead_code)]
+                pub fn defaults() -> Self {
+                    Self {
+                        $(
+                            $slider_name: [<$slider_name:camel>]($slider_default as $slider_type),
+                        )*
+                        $(
+                            $radio_name: [<$radio_name:camel>]::[<$radio_default:camel>],
+                        )*
+                        $(
+                            $checkbox_name: [<$checkbox_name:camel>](false),
+                        )*
+                    }
+                }
+
+                /// The defaults with the listed settings replaced, radios take the id of the
+                /// chosen option.
+                #[allow(dead_code)]
+                pub fn from_pairs(pairs: &[(String, String)]) -> Result<Self, String> {
+                    let mut settings = Self::defaults();
+                    for (key, value) in pairs {
+                        settings.set(key, value)?;
+                    }
+                    Ok(settings)
+                }
+
+                /// Replaces the setting named `key`, a slider, radio or checkbox id.
+                pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+                    let invalid = || format!("Invalid value {value} for {key}");
+                    match key {
+                        $(
+                            stringify!($slider_name) => self.$slider_name =
+                                [<$slider_name:camel>](value.parse().map_err(|_| invalid())?),
+                        )*
+                        $(
+                            stringify!($radio_name) => self.$radio_name = match value {
+                                stringify!($radio_default) => [<$radio_name:camel>]::[<$radio_default:camel>],
+                                $( stringify!($radio_option) => [<$radio_name:camel>]::[<$radio_option:camel>], )*
+                                _ => return Err(invalid()),
+                            },
+                        )*
+                        $(
+                            stringify!($checkbox_name) => self.$checkbox_name =
+                                [<$checkbox_name:camel>](value.parse().map_err(|_| invalid())?),
+                        )*
+                        _ => return Err(format!("Unknown setting {key} for {} noise", stringify!($noise))),
+                    }
+                    Ok(())
+                }
             }
 
             pub struct [<$noise:camel Noise>];
+            impl [<$noise:camel Noise>] {
+                /// JSON Schema of the settings, one entry of `schema::parameter_schema`.
+                pub fn schema() -> String {
+                    $crate::schema::noise(stringify!($noise), &[
+                        $(
+                            $crate::schema::slider(
+                                stringify!($slider_name),
+                                !matches!(stringify!($slider_type), "f32" | "f64"),
+                                $slider_min as $slider_type as f64,
+                                $slider_default as $slider_type as f64,
+                                $slider_max as $slider_type as f64,
+                            ),
+                        )*
+                        $(
+                            $crate::schema::radio(
+                                stringify!($radio_name),
+                                &[stringify!($radio_default), $(stringify!($radio_option)),*],
+                            ),
+                        )*
+                        $(
+                            $crate::schema::checkbox(stringify!($checkbox_name)),
+                        )*
+                    ])
+                }
+            }
             impl Noise for [<$noise:camel Noise>] {
                 fn setup() {
                     [<$noise:camel Noise>]::on_setup();
                 }
 
+                $(
+                    fn cell_size() -> Option<f64> {
+                        Some($cell_size)
+                    }
+                )?
+
                 fn update() {
                     $( [<$radio_name:camel>]::update(); )*
 
                     [<$noise:camel Noise>]::on_update();
                     let settings = [<$noise:camel NoiseSettings>]::parse();
                     
-                    $( set_text!($slider_name, &settings.$slider_name.value().to_string()); )*
-
+                    $( set_text!($slider_name, &$crate::value_format::format_value(
+                        settings.$slider_name.value(),
+                        slider_format!($($slider_format)?),
+                    )); )*
+                    $($(
+                        [<$dial_name:snake:upper _DIAL>].with(|dial| {
+                            [<$dial_name:snake:upper>].with(|slider| $crate::dial::draw(dial, slider))
+                        });
+                    )*)?
+                    $($(
+                        [<$pad_x:upper _ $pad_y:upper _PAD>].with(|pad| {
+                            [<$pad_x:snake:upper>].with(|x| {
+                                [<$pad_y:snake:upper>].with(|y| $crate::pad::draw(pad, x, y))
+                            })
+                        });
+                    )*)?
+
+                    $crate::field::begin_frame();
+                    // The extra channels are drawn first and kept, the last draw packs them with
+                    // the noise itself and puts its overlays on top.
+                    for (channel, seed) in $crate::channels::extra_passes() {
+                        let mut channel_settings = settings.clone();
+                        if channel_settings.set("seed", &seed.to_string()).is_ok() {
+                            $crate::channels::capture_next(channel);
+                            [<$noise:camel Noise>]::generate_and_draw(channel_settings);
+                            $crate::channels::end_capture();
+                        }
+                    }
                     [<$noise:camel Noise>]::generate_and_draw(settings);
+                    $crate::on_frame_drawn();
                     $( [<$radio_name:camel>]::memorize([<$radio_name:camel>]::parse()); )*
                 }
 
@@ -330,6 +537,16 @@ macro_rules! define_noise {
                         $( add_callback!($radio_option, "input", update_noise); )*
                     )*
                     $( add_callback!($checkbox_name, "input", update_noise); )*
+                    $($( add_callback!($button_name, "click", [<$button_name _click>]); )*)?
+                    $($(
+                        add_callback!([<$dial_name _dial>], "mousedown", [<$dial_name _dial_drag>]);
+                        add_callback!([<$dial_name _dial>], "mousemove", [<$dial_name _dial_drag>]);
+                    )*)?
+                    $($(
+                        add_callback!([<$pad_x _ $pad_y _pad>], "mousedown", [<$pad_x _ $pad_y _pad_drag>]);
+                        add_callback!([<$pad_x _ $pad_y _pad>], "mousemove", [<$pad_x _ $pad_y _pad_drag>]);
+                        set_hidden!([<$pad_x _ $pad_y _pad_control>], false);
+                    )*)?
 
                     Self::reset();
                     $(
@@ -339,6 +556,9 @@ macro_rules! define_noise {
                     $(
                         set_hidden!([<$checkbox_name:camel _control>], false);
                     )*
+                    $($(
+                        set_hidden!([<$button_name:camel _control>], false);
+                    )*)?
                     set_hidden!($noise, false);
 
                     Self::update();
@@ -351,6 +571,16 @@ macro_rules! define_noise {
                         $( remove_callback!($radio_option, "input", update_noise); )*
                     )*
                     $( remove_callback!($checkbox_name, "input", update_noise); )*
+                    $($( remove_callback!($button_name, "click", [<$button_name _click>]); )*)?
+                    $($(
+                        remove_callback!([<$dial_name _dial>], "mousedown", [<$dial_name _dial_drag>]);
+                        remove_callback!([<$dial_name _dial>], "mousemove", [<$dial_name _dial_drag>]);
+                    )*)?
+                    $($(
+                        remove_callback!([<$pad_x _ $pad_y _pad>], "mousedown", [<$pad_x _ $pad_y _pad_drag>]);
+                        remove_callback!([<$pad_x _ $pad_y _pad>], "mousemove", [<$pad_x _ $pad_y _pad_drag>]);
+                        set_hidden!([<$pad_x _ $pad_y _pad_control>], true);
+                    )*)?
 
                     $(
                         set_hidden!([<$slider_name:camel _control>], true);
@@ -363,6 +593,9 @@ macro_rules! define_noise {
                     $(
                         set_hidden!([<$checkbox_name:camel _control>], true);
                     )*
+                    $($(
+                        set_hidden!([<$button_name:camel _control>], true);
+                    )*)?
 
                     set_hidden!($noise, true);
                 }
@@ -382,3 +615,43 @@ macro_rules! define_noise {
         }
     }
 }
+
+/// Builder methods for the random numbers and the permutation table of a core noise, which
+/// rebuilds itself with `rebuild` after either changes.
+#[macro_export]
+macro_rules! source_builder {
+    () => {
+        /// Draws the random numbers from `generator` instead of SquirrelNoise5.
+        pub fn generator(mut self, generator: $crate::core::Generator) -> Self {
+            self.source.generator = generator;
+            self.rebuild();
+            self
+        }
+
+        /// Hashes the cells with `table` instead of the table shuffled from the seed.
+        pub fn permutation_table(mut self, table: [usize; 256]) -> Self {
+            self.source.table = Some(table);
+            self.rebuild();
+            self
+        }
+    };
+}
+
+/// Builder methods of the public noise types in `core`, each replacing the value of one slider
+/// or checkbox in the wrapped settings.
+#[macro_export]
+macro_rules! settings_builder {
+    ($($name:ident: $type:ty $(= $min:literal..)?),* $(,)?) => {
+        paste::paste! {
+            $(
+                #[doc = concat!("Sets `", stringify!($name), "`, the same as the slider or checkbox on the page.")]
+                $(#[doc = concat!("\n\nValues below `", stringify!($min), "` are raised to it.")])?
+                pub fn $name(mut self, $name: $type) -> Self {
+                    $(let $name = $name.max($min);)?
+                    self.settings.$name = [<$name:camel>]($name);
+                    self
+                }
+            )*
+        }
+    };
+}
@@ -0,0 +1,248 @@
+//! Web MIDI control of the sliders, with knobs bound to them in learn mode.
+
+use std::cell::{Cell, LazyCell, RefCell};
+
+use wasm_bindgen::{JsCast, JsValue, prelude::Closure};
+use web_sys::{HtmlElement, HtmlInputElement, MidiAccess, MidiInput, MidiMessageEvent, MouseEvent};
+
+use crate::*;
+
+const STORAGE_KEY: &str = "seeing_noise_midi";
+/// Status byte of a control change message, the low four bits carry the channel.
+const CONTROL_CHANGE: u8 = 0xB0;
+const MAX_KNOB_VALUE: f64 = 127.;
+
+thread_local! {
+    /// Knob and slider id pairs, a knob is written as `channel:controller`.
+    static BINDINGS: RefCell<Vec<(String, String)>> = RefCell::new(load());
+    static LEARNING: Cell<bool> = const { Cell::new(false) };
+    /// The knob that moved in learn mode, waiting for a slider to be clicked.
+    static LEARNED_KNOB: RefCell<Option<String>> = const { RefCell::new(None) };
+    static ACCESS: RefCell<Option<MidiAccess>> = const { RefCell::new(None) };
+    static ACCESS_GRANTED: LazyCell<Closure<dyn FnMut(JsValue)>> = LazyCell::new(|| {
+        Closure::new(|access: JsValue| {
+            let Ok(access) = access.dyn_into::<MidiAccess>() else {
+                return;
+            };
+            ACCESS.with(|a| a.replace(Some(access)));
+            listen_to_inputs();
+        })
+    });
+    static ACCESS_DENIED: LazyCell<Closure<dyn FnMut(JsValue)>> = LazyCell::new(|| {
+        Closure::new(|error: JsValue| {
+            console_log!("MIDI access was refused: {error:?}");
+            set_status("MIDI access was refused");
+        })
+    });
+}
+
+elements!(
+    (midi_connect, HtmlElement),
+    (midi_learn, HtmlElement),
+    (midi_forget, HtmlElement),
+    (midi_status, HtmlElement),
+);
+define_closure!(midi_connect_click, connect);
+define_closure!(midi_learn_click, toggle_learning);
+define_closure!(midi_forget_click, forget);
+define_closure!(midi_ports_changed, listen_to_inputs);
+define_event_closure!(midi_message, MidiMessageEvent, on_message);
+define_event_closure!(midi_slider_click, MouseEvent, on_slider_click);
+
+pub fn setup() {
+    add_callback!(midi_connect, "click", midi_connect_click);
+    add_callback!(midi_learn, "click", midi_learn_click);
+    add_callback!(midi_forget, "click", midi_forget_click);
+    add_callback!(document, "click", midi_slider_click);
+    let bound = BINDINGS.with(|b| b.borrow().len());
+    if bound > 0 {
+        set_status(&format!("{bound} knobs bound, connect to use them"));
+    }
+}
+
+fn set_status(text: &str) {
+    MIDI_STATUS.with(|s| s.set_inner_text(text));
+}
+
+/// Bindings as `knob=slider` lines.
+fn parse_bindings(text: &str) -> Vec<(String, String)> {
+    text.lines()
+        .filter_map(|l| l.split_once('='))
+        .map(|(knob, slider)| (knob.to_string(), slider.to_string()))
+        .collect()
+}
+
+fn format_bindings(bindings: &[(String, String)]) -> String {
+    bindings
+        .iter()
+        .map(|(knob, slider)| format!("{knob}={slider}\n"))
+        .collect()
+}
+
+fn load() -> Vec<(String, String)> {
+    state::storage()
+        .and_then(|s| s.get_item(STORAGE_KEY).ok().flatten())
+        .map(|text| parse_bindings(&text))
+        .unwrap_or_default()
+}
+
+fn store() {
+    let Some(storage) = state::storage() else {
+        return;
+    };
+    let text = BINDINGS.with(|b| format_bindings(&b.borrow()));
+    if storage.set_item(STORAGE_KEY, &text).is_err() {
+        console_log!("Failed to save the MIDI bindings to local storage");
+    }
+}
+
+/// The knob and its position from a control change message, other messages give `None`.
+fn control_change(data: &[u8]) -> Option<(String, u8)> {
+    let [status, controller, value, ..] = *data else {
+        return None;
+    };
+    (status & 0xF0 == CONTROL_CHANGE).then(|| (format!("{}:{controller}", status & 0x0F), value))
+}
+
+/// Where a knob position lands on a slider, the knob's whole turn spans the slider.
+fn knob_to_slider(value: u8, min: f64, max: f64) -> f64 {
+    min + value as f64 / MAX_KNOB_VALUE * (max - min)
+}
+
+fn connect() {
+    let navigator = web_sys::window().unwrap().navigator();
+    let Ok(requested) = navigator.request_midi_access() else {
+        set_status("This browser has no Web MIDI support");
+        return;
+    };
+    set_status("Waiting for MIDI access...");
+    ACCESS_GRANTED.with(|granted| {
+        ACCESS_DENIED.with(|denied| {
+            let _ = requested.then2(granted, denied);
+        })
+    });
+}
+
+/// Hooks every connected input, called again whenever a device is plugged in or out.
+fn listen_to_inputs() {
+    let inputs = ACCESS.with(|a| {
+        let access = a.borrow();
+        let access = access.as_ref()?;
+        MIDI_PORTS_CHANGED.with(|c| access.set_onstatechange(Some(c.as_ref().unchecked_ref())));
+        Some(
+            access
+                .inputs()
+                .values()
+                .into_iter()
+                .filter_map(|input| input.ok()?.dyn_into::<MidiInput>().ok())
+                .collect::<Vec<_>>(),
+        )
+    });
+    let Some(inputs) = inputs else {
+        return;
+    };
+    MIDI_MESSAGE.with(|m| {
+        for input in &inputs {
+            input.set_onmidimessage(Some(m.as_ref().unchecked_ref()));
+        }
+    });
+    let bound = BINDINGS.with(|b| b.borrow().len());
+    set_status(&format!(
+        "{} MIDI inputs, {bound} knobs bound",
+        inputs.len()
+    ));
+}
+
+fn toggle_learning() {
+    let learning = !LEARNING.with(|l| l.get());
+    LEARNING.with(|l| l.set(learning));
+    LEARNED_KNOB.with(|k| k.take());
+    MIDI_LEARN.with(|b| b.set_inner_text(if learning { "Stop learning" } else { "Learn" }));
+    set_status(if learning {
+        "Move a knob, then click the slider it should drive"
+    } else {
+        "Stopped learning"
+    });
+}
+
+fn forget() {
+    BINDINGS.with(|b| b.borrow_mut().clear());
+    store();
+    set_status("Forgot every binding");
+}
+
+fn on_message(event: MidiMessageEvent) {
+    let Some((knob, value)) = event.data().ok().and_then(|data| control_change(&data)) else {
+        return;
+    };
+    if LEARNING.with(|l| l.get()) {
+        set_status(&format!("Knob {knob} moved, now click a slider"));
+        LEARNED_KNOB.with(|k| k.replace(Some(knob)));
+        return;
+    }
+
+    let Some(id) = BINDINGS.with(|b| {
+        b.borrow()
+            .iter()
+            .find(|(bound, _)| *bound == knob)
+            .map(|(_, slider)| slider.clone())
+    }) else {
+        return;
+    };
+    let Some(slider) = DOCUMENT
+        .with(|d| d.get_element_by_id(&id))
+        .and_then(|s| s.dyn_into::<HtmlInputElement>().ok())
+    else {
+        return;
+    };
+    // Sliders of the other noises keep their values, selecting a noise resets them anyway.
+    if slider.offset_parent().is_none() {
+        return;
+    }
+    let min = slider.min().parse().unwrap_or(0.);
+    let max = slider.max().parse().unwrap_or(100.);
+    state::set_slider(&slider, knob_to_slider(value, min, max));
+}
+
+/// Binds the knob that moved last in learn mode to the clicked slider.
+fn on_slider_click(event: MouseEvent) {
+    let Some(knob) = LEARNED_KNOB.with(|k| k.borrow().clone()) else {
+        return;
+    };
+    let Some(slider) = event
+        .target()
+        .and_then(|t| t.dyn_into::<HtmlInputElement>().ok())
+        .filter(|s| s.type_() == "range" && !s.id().is_empty())
+    else {
+        return;
+    };
+
+    BINDINGS.with(|b| {
+        let mut bindings = b.borrow_mut();
+        bindings.retain(|(bound, _)| *bound != knob);
+        bindings.push((knob.clone(), slider.id()));
+    });
+    store();
+    toggle_learning();
+    set_status(&format!("Knob {knob} drives {}", slider.id()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn control_changes_map_onto_sliders() {
+        assert_eq!(
+            control_change(&[0xB3, 21, 64]),
+            Some(("3:21".to_string(), 64))
+        );
+        assert_eq!(control_change(&[0x90, 60, 100]), None);
+        assert_eq!(control_change(&[0xB0, 7]), None);
+        assert_eq!(knob_to_slider(0, -4., 4.), -4.);
+        assert_eq!(knob_to_slider(127, -4., 4.), 4.);
+
+        let bindings = vec![("0:21".to_string(), "scale".to_string())];
+        assert_eq!(parse_bindings(&format_bindings(&bindings)), bindings);
+    }
+}
@@ -0,0 +1,141 @@
+//! Renders the current settings with consecutive seeds as a grid of thumbnails.
+
+use std::cell::LazyCell;
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{
+    CanvasRenderingContext2d, HtmlCanvasElement, HtmlElement, HtmlImageElement, HtmlInputElement,
+};
+
+use crate::{drawer::CANVAS, *};
+
+const THUMBNAIL_SIZE: u32 = 128;
+
+elements!(
+    (seed, HtmlInputElement),
+    (seed_control, HtmlElement),
+    (montage_count, HtmlInputElement),
+    (montage_count_display, HtmlElement),
+    (montage_render, HtmlElement),
+    (montage_status, HtmlElement),
+    (montage_image, HtmlImageElement),
+);
+define_closure!(montage_count_input, update_displays);
+define_closure!(montage_render_click, render);
+
+pub fn setup() {
+    add_callback!(montage_count, "input", montage_count_input);
+    add_callback!(montage_render, "click", montage_render_click);
+    update_displays();
+}
+
+fn update_displays() {
+    set_text!(
+        montage_count,
+        &parse_value!(montage_count, u32).to_string()
+    );
+}
+
+/// `count` seeds starting at `first`, wrapping around to `min` past `max` so every one of them
+/// is a value the seed slider accepts.
+fn seeds(first: u32, min: u32, max: u32, count: u32) -> Vec<u32> {
+    let span = max.saturating_sub(min) as u64 + 1;
+    (0..count as u64)
+        .map(|i| (min as u64 + (first.saturating_sub(min) as u64 + i) % span) as u32)
+        .collect()
+}
+
+/// Columns of the most square grid that fits `count` thumbnails.
+fn columns(count: u32) -> u32 {
+    (count as f64).sqrt().ceil().max(1.) as u32
+}
+
+fn context(canvas: &HtmlCanvasElement) -> Option<CanvasRenderingContext2d> {
+    canvas.get_context("2d").ok()??.dyn_into().ok()
+}
+
+/// Redraws the noise once per seed through the seed slider, copying every frame into the grid,
+/// then puts the slider back and redraws the original.
+fn render() {
+    if SEED_CONTROL.with(|c| c.hidden()) {
+        MONTAGE_STATUS.with(|s| s.set_inner_text("The selected noise has no seed"));
+        return;
+    }
+
+    let count = parse_value!(montage_count, u32);
+    let original = SEED.with(|s| s.value());
+    let seeds = seeds(
+        parse_value!(seed, u32),
+        SEED.with(|s| s.min()).parse().unwrap_or(0),
+        SEED.with(|s| s.max()).parse().unwrap_or(u32::MAX),
+        count,
+    );
+
+    let Some(montage) = DOCUMENT
+        .with(|d| d.create_element("canvas"))
+        .ok()
+        .and_then(|c| c.dyn_into::<HtmlCanvasElement>().ok())
+    else {
+        return;
+    };
+    let (columns, rows) = (columns(count), count.div_ceil(columns(count)));
+    montage.set_width(columns * THUMBNAIL_SIZE);
+    montage.set_height(rows * THUMBNAIL_SIZE);
+    let Some(context) = context(&montage) else {
+        return;
+    };
+    context.set_font("12px sans-serif");
+
+    for (i, seed) in seeds.iter().enumerate() {
+        SEED.with(|s| s.set_value(&seed.to_string()));
+        update_current_noise();
+
+        let x = (i as u32 % columns * THUMBNAIL_SIZE) as f64;
+        let y = (i as u32 / columns * THUMBNAIL_SIZE) as f64;
+        let size = THUMBNAIL_SIZE as f64;
+        let _ = CANVAS.with(|c| {
+            context.draw_image_with_html_canvas_element_and_dw_and_dh(c, x, y, size, size)
+        });
+        context.set_fill_style_str("rgba(255, 255, 255, 0.85)");
+        context.fill_rect(x, y, 64., 16.);
+        context.set_fill_style_str("#000000");
+        let _ = context.fill_text(&format!("seed {seed}"), x + 4., y + 12.);
+    }
+
+    SEED.with(|s| s.set_value(&original));
+    update_current_noise();
+
+    let Ok(url) = montage.to_data_url() else {
+        return;
+    };
+    MONTAGE_IMAGE.with(|image| {
+        image.set_src(&url);
+        image.set_hidden(false);
+    });
+    MONTAGE_STATUS.with(|s| {
+        s.set_inner_text(&format!(
+            "Seeds {} to {}",
+            seeds.first().unwrap_or(&0),
+            seeds.last().unwrap_or(&0)
+        ))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeds_wrap_inside_the_slider_range() {
+        assert_eq!(seeds(5, 0, 1000, 4), vec![5, 6, 7, 8]);
+        assert_eq!(seeds(998, 0, 1000, 4), vec![998, 999, 1000, 0]);
+        assert_eq!(seeds(9, 1, 10, 3), vec![9, 10, 1]);
+    }
+
+    #[test]
+    fn grid_is_as_square_as_possible() {
+        assert_eq!(columns(1), 1);
+        assert_eq!(columns(9), 3);
+        assert_eq!(columns(10), 4);
+    }
+}
@@ -0,0 +1,63 @@
+//! Interop with the [`noise`](https://docs.rs/noise) crate, enabled by the `noise-rs` feature:
+//!
+//! ```
+//! use noise::{Fbm, MultiFractal, NoiseFn, Perlin as NoisePerlin};
+//! use seeing_noise::{core::{Perlin, Sampler}, noise_rs::External};
+//!
+//! let ours = Perlin::new(42).octaves(4);
+//! let height = ours.get([0.5, 1.25]);
+//!
+//! let theirs = External(Fbm::<NoisePerlin>::new(42).set_octaves(4));
+//! let other = theirs.sample(0.5, 1.25);
+//! ```
+
+use noise::NoiseFn;
+
+use crate::core::{Anisotropic, Gabor, Perlin, Sampler, Simplex, Wavelet, Worley};
+
+macro_rules! impl_noise_fn {
+    ($($sampler:ty),* $(,)?) => {
+        $(
+            impl NoiseFn<f64, 2> for $sampler {
+                fn get(&self, [x, y]: [f64; 2]) -> f64 {
+                    self.sample(x, y)
+                }
+            }
+        )*
+    };
+}
+
+impl_noise_fn!(Perlin, Simplex, Wavelet, Gabor, Anisotropic, Worley);
+
+/// A 2D source of the `noise` crate sampled like the noises of this crate.
+pub struct External<N>(pub N);
+
+impl<N: NoiseFn<f64, 2> + Sync> Sampler for External<N> {
+    fn sample(&self, x: f64, y: f64) -> f64 {
+        self.0.get([x, y])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::noises::helpers::sample_points;
+
+    #[test]
+    fn noise_fn_matches_sampler() {
+        let perlin = Perlin::new(7).octaves(3);
+        let worley = Worley::new(7);
+        for (x, y) in sample_points(64, 20.0, 3) {
+            assert_eq!(perlin.get([x, y]), perlin.sample(x, y));
+            assert_eq!(worley.get([x, y]), worley.sample(x, y));
+        }
+    }
+
+    #[test]
+    fn external_round_trips() {
+        let external = External(Simplex::new(11));
+        for (x, y) in sample_points(64, 20.0, 5) {
+            assert_eq!(external.sample(x, y), external.0.sample(x, y));
+        }
+    }
+}
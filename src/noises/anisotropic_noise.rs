@@ -5,8 +5,11 @@ use web_sys::{HtmlElement, HtmlInputElement};
 
 use super::noise::Noise;
 use crate::{
-    drawer::{IMAGE_BYTES_COUNT, draw_arrow},
-    noises::helpers::{lerp, perlin_grad, shuffle},
+    core::{AnisotropicFractal, Sampler},
+    drawer::{draw_arrow},
+    noises::helpers::{BandLimit, lerp, octave_gain, perlin_grad, Source},
+    overlay::{self, Overlay},
+    field::{draw_field, draw_noise_grid, sample_field},
     *,
 };
 
@@ -15,11 +18,10 @@ struct AnisotropicNoiseImpl {
 }
 
 impl AnisotropicNoiseImpl {
-    pub fn new(seed: u32) -> Self {
-        let mut permutation: [usize; 256] = std::array::from_fn(|i| i);
-        shuffle(&mut permutation, seed);
-
-        AnisotropicNoiseImpl { permutation }
+    pub fn new(seed: u32, source: Source) -> Self {
+        AnisotropicNoiseImpl {
+            permutation: source.permutation(seed),
+        }
     }
 
     #[inline]
@@ -71,38 +73,17 @@ impl AnisotropicNoiseImpl {
         lerp(v, x1, x2)
     }
 
-    fn generate_coloring(&self, settings: AnisotropicNoiseSettings) -> Vec<u8> {
-        let mut v = Vec::with_capacity(IMAGE_BYTES_COUNT as usize);
-        let scale = settings.scale.value();
-
-        for y in 0..RESOLUTION {
-            for x in 0..RESOLUTION {
-                let nx = ((x as f64) - (HALF_RESOLUTION as f64)) / scale;
-                let ny = ((y as f64) - (HALF_RESOLUTION as f64)) / scale;
-
-                let noise_val = match settings.noise_type {
-                    NoiseType::Standard => self.fbm_standard(nx, ny, &settings),
-                    NoiseType::Turbulence => self.fbm_turbulence(nx, ny, &settings),
-                    NoiseType::Ridge => self.fbm_ridge(nx, ny, &settings),
-                    NoiseType::Directional => self.fbm_directional(nx, ny, &settings),
-                };
-
-                if noise_val < 0. {
-                    let t = noise_val + 1.;
-                    v.push(255);
-                    v.push(lerp(t, 0.0, 255.0) as u8);
-                    v.push(255);
-                    v.push(255);
-                } else {
-                    let t = noise_val;
-                    v.push(lerp(t, 255.0, 0.0) as u8);
-                    v.push(255);
-                    v.push(lerp(t, 255.0, 0.0) as u8);
-                    v.push(255);
-                }
-            }
+    fn generate_field(&self, settings: &AnisotropicNoiseSettings) -> Vec<f64> {
+        sample_field(settings.scale.value(), |nx, ny| self.sample(nx, ny, settings))
+    }
+
+    fn sample(&self, x: f64, y: f64, settings: &AnisotropicNoiseSettings) -> f64 {
+        match settings.noise_type {
+        NoiseType::Standard => self.fbm_standard(x, y, settings),
+        NoiseType::Turbulence => self.fbm_turbulence(x, y, settings),
+        NoiseType::Ridge => self.fbm_ridge(x, y, settings),
+        NoiseType::Directional => self.fbm_directional(x, y, settings),
         }
-        v
     }
 
     pub fn fbm_standard(&self, x: f64, y: f64, settings: &AnisotropicNoiseSettings) -> f64 {
@@ -116,6 +97,9 @@ impl AnisotropicNoiseImpl {
         let gain = settings.gain.value();
         let h_exponent = settings.h_exponent.value();
         let lacunarity = settings.lacunarity.value();
+        let band_limit = BandLimit::new(settings.scale.value(), settings.auto_band_limit.value());
+        let amplitude_falloff =
+            octave_gain(gain.powf(h_exponent), lacunarity, h_exponent, settings.spectral_h.value());
         let angle = settings.angle.value().to_radians();
         let anisotropy = settings.anisotropy.value();
         
@@ -133,10 +117,10 @@ impl AnisotropicNoiseImpl {
                 Visualization::AccumulatedOctaves => i <= show_octave,
             };
             if include {
-                total += noise_val * amplitude;
+                total += noise_val * amplitude * band_limit.weight(frequency);
                 max_value += amplitude;
             }
-            amplitude *= gain.powf(h_exponent);
+            amplitude *= amplitude_falloff;
             frequency *= lacunarity;
         }
 
@@ -153,6 +137,9 @@ impl AnisotropicNoiseImpl {
         let show_octave = settings.show_octave.value();
         let gain = settings.gain.value();
         let lacunarity = settings.lacunarity.value();
+        let band_limit = BandLimit::new(settings.scale.value(), settings.auto_band_limit.value());
+        let amplitude_falloff =
+            octave_gain(gain, lacunarity, settings.h_exponent.value(), settings.spectral_h.value());
         let angle = settings.angle.value().to_radians();
         let anisotropy = settings.anisotropy.value();
         
@@ -170,10 +157,10 @@ impl AnisotropicNoiseImpl {
                 Visualization::AccumulatedOctaves => i <= show_octave,
             };
             if include {
-                total += noise_val * amplitude;
+                total += noise_val * amplitude * band_limit.weight(frequency);
                 max_value += amplitude;
             }
-            amplitude *= gain;
+            amplitude *= amplitude_falloff;
             frequency *= lacunarity;
         }
 
@@ -191,6 +178,9 @@ impl AnisotropicNoiseImpl {
         let show_octave = settings.show_octave.value();
         let gain = settings.gain.value();
         let lacunarity = settings.lacunarity.value();
+        let band_limit = BandLimit::new(settings.scale.value(), settings.auto_band_limit.value());
+        let amplitude_falloff =
+            octave_gain(gain, lacunarity, settings.h_exponent.value(), settings.spectral_h.value());
         let angle = settings.angle.value().to_radians();
         let anisotropy = settings.anisotropy.value();
         
@@ -210,12 +200,12 @@ impl AnisotropicNoiseImpl {
             };
             if include {
                 let noise_val = noise_val * noise_val * weight;
-                total += noise_val * amplitude;
+                total += noise_val * amplitude * band_limit.weight(frequency);
                 max_value += amplitude;
             }
 
             weight = (noise_val * 2.0).clamp(0.0, 1.0);
-            amplitude *= gain;
+            amplitude *= amplitude_falloff;
             frequency *= lacunarity;
         }
 
@@ -232,6 +222,9 @@ impl AnisotropicNoiseImpl {
         let show_octave = settings.show_octave.value();
         let gain = settings.gain.value();
         let lacunarity = settings.lacunarity.value();
+        let band_limit = BandLimit::new(settings.scale.value(), settings.auto_band_limit.value());
+        let amplitude_falloff =
+            octave_gain(gain, lacunarity, settings.h_exponent.value(), settings.spectral_h.value());
         let base_angle = settings.angle.value().to_radians();
         let angle_step = settings.angle_step.value().to_radians();
         let anisotropy = settings.anisotropy.value();
@@ -252,10 +245,10 @@ impl AnisotropicNoiseImpl {
                 Visualization::AccumulatedOctaves => i <= show_octave,
             };
             if include {
-                total += noise_val * amplitude;
+                total += noise_val * amplitude * band_limit.weight(frequency);
                 max_value += amplitude;
             }
-            amplitude *= gain;
+            amplitude *= amplitude_falloff;
             frequency *= lacunarity;
         }
 
@@ -269,17 +262,20 @@ impl AnisotropicNoise {
     fn on_update() {
         let octaves = Octaves::parse().value();
         SHOW_OCTAVE.with(|e| e.set_max(format!("{octaves}").as_str()));
+
+        // The noise type radio hides H where only the gain is used, spectral H applies to every type.
+        if SpectralH::parse().value() {
+            set_hidden!(h_exponent_control, false);
+        }
     }
     
     fn generate_and_draw(settings: AnisotropicNoiseSettings) {
-        let anisotropic = AnisotropicNoiseImpl::new(settings.seed.value());
+        let anisotropic = AnisotropicNoiseImpl::new(settings.seed.value(), Source::page());
 
-        let coloring = anisotropic.generate_coloring(settings.clone());
-
-        draw_noise(coloring.as_slice());
+        draw_field(anisotropic.generate_field(&settings));
 
         if settings.show_grid.value() {
-            draw_grid(settings.scale.value(), "#000000");
+            draw_noise_grid(settings.scale.value(), &overlay::color(Overlay::Grid));
         }
 
         if settings.show_direction.value() {
@@ -295,7 +291,7 @@ impl AnisotropicNoise {
         
         let end_x = center_x + angle.cos() * length;
         let end_y = center_y + angle.sin() * length;
-        draw_arrow(center_x, center_y, end_x, end_y, 15.0, "#00ff00");
+        draw_arrow(center_x, center_y, end_x, end_y, 15.0, &overlay::color(Overlay::Direction));
         
         let perp_angle = angle + std::f64::consts::PI / 2.0;
         let anisotropy = settings.anisotropy.value();
@@ -315,9 +311,9 @@ define_noise!(anisotropic,
         (gain, f64, 0., 0.5, 1.),
         (h_exponent, f64, 0., 1., 2.),
         (ridge_offset, f64, 0., 1., 2.),
-        (angle, f64, 0.0, 0.0, 360.0),          
+        (angle, f64, 0.0, 0.0, 360.0, Degrees),          
         (anisotropy, f64, 0.1, 1.0, 5.0),     
-        (angle_step, f64, -90., 0.0, 90.),     
+        (angle_step, f64, -90., 0.0, 90., Degrees),     
         (show_octave, u32, 1., 1., 8.)
     ];
     radios:[
@@ -333,5 +329,96 @@ define_noise!(anisotropic,
             (directional, hide:[h_exponent, ridge_offset])
         )
     ];
-    checkboxes:[show_grid, show_direction];
+    checkboxes:[show_grid, show_direction, spectral_h, auto_band_limit];
+    dials:[angle];
+    pads:[(lacunarity, gain)];
+    cell_size: 1.0;
 );
+
+/// Anisotropic noise for use outside the page, see [`crate::core`].
+pub struct Anisotropic {
+    noise: AnisotropicNoiseImpl,
+    settings: AnisotropicNoiseSettings,
+    source: Source,
+}
+
+impl Anisotropic {
+    pub fn new(seed: u32) -> Self {
+        let mut settings = AnisotropicNoiseSettings::defaults();
+        settings.seed = Seed(seed);
+        let source = Source::default();
+        let noise = AnisotropicNoiseImpl::new(seed, source);
+        Anisotropic {
+            noise,
+            settings,
+            source,
+        }
+    }
+
+    fn rebuild(&mut self) {
+        self.noise = AnisotropicNoiseImpl::new(self.settings.seed.value(), self.source);
+    }
+
+    source_builder!();
+
+    pub fn fractal(mut self, fractal: AnisotropicFractal) -> Self {
+        self.settings.noise_type = match fractal {
+            AnisotropicFractal::Standard => NoiseType::Standard,
+            AnisotropicFractal::Turbulence => NoiseType::Turbulence,
+            AnisotropicFractal::Ridge => NoiseType::Ridge,
+            AnisotropicFractal::Directional => NoiseType::Directional,
+        };
+        self
+    }
+
+    settings_builder!(
+        octaves: u32 = 1..,
+        lacunarity: f64,
+        gain: f64,
+        h_exponent: f64,
+        ridge_offset: f64,
+        angle: f64,
+        anisotropy: f64,
+        angle_step: f64,
+        spectral_h: bool,
+        auto_band_limit: bool,
+    );
+
+    /// The defaults with a settings file's values applied, see [`crate::core::from_settings`].
+    pub fn from_pairs(pairs: &[(String, String)]) -> Result<Self, String> {
+        let settings = AnisotropicNoiseSettings::from_pairs(pairs)?;
+        let source = Source::default();
+        let noise = AnisotropicNoiseImpl::new(settings.seed.value(), source);
+        Ok(Anisotropic {
+            noise,
+            settings,
+            source,
+        })
+    }
+}
+
+impl Sampler for Anisotropic {
+    fn sample(&self, x: f64, y: f64) -> f64 {
+        self.noise.sample(x, y, &self.settings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::golden::assert_matches_golden;
+
+    #[test]
+    fn renders_like_golden() {
+        let noise = AnisotropicNoiseImpl::new(42, Source::default());
+        let mut settings = AnisotropicNoiseSettings::defaults();
+        settings.angle = Angle(30.0);
+        settings.anisotropy = Anisotropy(3.0);
+        assert_matches_golden("anisotropic", &noise.generate_field(&settings));
+
+        settings.octaves = Octaves(4);
+        settings.noise_type = NoiseType::Directional;
+        settings.angle_step = AngleStep(20.0);
+        assert_matches_golden("anisotropic_directional", &noise.generate_field(&settings));
+    }
+}
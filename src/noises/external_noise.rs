@@ -0,0 +1,95 @@
+use std::cell::{LazyCell, RefCell};
+
+use noise::{Fbm, MultiFractal, NoiseFn, OpenSimplex, Perlin, Value, Worley};
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlElement, HtmlInputElement};
+
+use super::noise::Noise;
+use crate::{
+    core::Sampler,
+    overlay::{self, Overlay},
+    field::{draw_field, draw_noise_grid, sample_field},
+    noise_rs::External,
+    *,
+};
+
+thread_local! {
+    /// The cellular source of every rayon thread and the seed it was built with.
+    static CELLULAR_SOURCE: RefCell<Option<(u32, Worley)>> = const { RefCell::new(None) };
+}
+
+/// `noise::Worley` keeps its distance function in an `Rc` and can not be shared between threads,
+/// so every thread samples a copy of its own.
+struct Cellular(u32);
+
+impl Sampler for Cellular {
+    fn sample(&self, x: f64, y: f64) -> f64 {
+        CELLULAR_SOURCE.with(|c| {
+            let mut cellular = c.borrow_mut();
+            if cellular.as_ref().is_none_or(|(seed, _)| *seed != self.0) {
+                *cellular = Some((self.0, Worley::new(self.0)));
+            }
+            cellular.as_ref().unwrap().1.get([x, y])
+        })
+    }
+}
+
+impl ExternalNoise {
+    /// The page lists the `noise` crate only in builds that include it.
+    fn on_setup() {
+        let option = DOCUMENT.with(|d| d.create_element("option")).unwrap();
+        let _ = option.set_attribute("value", "external");
+        option.set_text_content(Some("noise crate"));
+        let _ = NOISE_SELECT.with(|s| s.append_child(&option));
+    }
+
+    fn on_update() {}
+
+    fn generate_and_draw(settings: ExternalNoiseSettings) {
+        let seed = settings.seed.value();
+        let field = match settings.source {
+            Source::Gradient => Self::generate_field(External(Perlin::new(seed)), &settings),
+            Source::OpenSimplex => {
+                Self::generate_field(External(OpenSimplex::new(seed)), &settings)
+            }
+            Source::ValueNoise => Self::generate_field(External(Value::new(seed)), &settings),
+            Source::Cellular => Self::generate_field(Cellular(seed), &settings),
+            Source::Fbm => {
+                let fbm = Fbm::<Perlin>::new(seed)
+                    .set_octaves(settings.octaves.value() as usize)
+                    .set_lacunarity(settings.lacunarity.value())
+                    .set_persistence(settings.gain.value());
+                Self::generate_field(External(fbm), &settings)
+            }
+        };
+        draw_field(field);
+
+        if settings.show_grid.value() {
+            draw_noise_grid(settings.scale.value(), &overlay::color(Overlay::Grid));
+        }
+    }
+
+    fn generate_field(sampler: impl Sampler, settings: &ExternalNoiseSettings) -> Vec<f64> {
+        sample_field(settings.scale.value(), |x, y| sampler.sample(x, y))
+    }
+}
+
+define_noise!(external,
+    sliders:[
+        (seed, u32, 0., 42., 1000.),
+        (scale, f64, 1., 50., 200.),
+        (octaves, u32, 1., 4., 8.),
+        (lacunarity, f64, 1., 2., 4.),
+        (gain, f64, 0., 0.5, 1.)
+    ];
+    radios:[
+        (source,
+            (gradient, hide: [octaves, lacunarity, gain]),
+            (open_simplex, hide: [octaves, lacunarity, gain]),
+            (value_noise, hide: [octaves, lacunarity, gain]),
+            (cellular, hide: [octaves, lacunarity, gain]),
+            (fbm)
+        )
+    ];
+    checkboxes:[show_grid];
+);
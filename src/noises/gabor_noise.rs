@@ -1,26 +1,84 @@
 use std::cell::LazyCell;
 
-use rayon::prelude::*;
 use wasm_bindgen::{JsCast, prelude::Closure};
 use web_sys::{HtmlElement, HtmlInputElement};
 
 use super::noise::Noise;
 use crate::{
+    core::{GaborFractal, Sampler},
     drawer::{draw_arrow},
-    noises::helpers::{lerp, shuffle},
+    noises::helpers::{BandLimit, Source, octave_gain},
+    rng::Rng,
+    overlay::{self, Overlay},
+    field::{draw_field, draw_noise_grid, sample_field},
     *,
 };
 
+/// Streams of the impulse of a cell, see [`Rng::cell`].
+const POSITION_X_STREAM: u32 = 0;
+const POSITION_Y_STREAM: u32 = 1;
+const ORIENTATION_STREAM: u32 = 2;
+const PHASE_STREAM: u32 = 3;
+/// Streams of the Poisson layout: the number of impulses of a cell, then the four streams of
+/// every impulse.
+const COUNT_STREAM: u32 = 4;
+const FIRST_POISSON_STREAM: u32 = 5;
+/// Impulses of one cell never go beyond this, far above what the densest layout expects.
+const MAX_CELL_IMPULSES: u32 = 32;
+
+/// Number of events of a Poisson process expecting `mean` of them, from a uniform `u` by
+/// inverting the distribution.
+fn poisson(mean: f64, u: f64) -> u32 {
+    let mut probability = (-mean).exp();
+    let mut cumulative = probability;
+    let mut count = 0;
+    while u > cumulative && count < MAX_CELL_IMPULSES {
+        count += 1;
+        probability *= mean / count as f64;
+        cumulative += probability;
+    }
+    count
+}
+
+/// How the impulses are scattered over the cells, read from the settings once per sample.
+#[derive(Clone, Copy)]
+struct Scatter {
+    layout: ImpulseLayout,
+    density: f64,
+    independent_streams: bool,
+}
+
+impl Scatter {
+    fn new(settings: &GaborNoiseSettings) -> Self {
+        Scatter {
+            layout: settings.impulse_layout,
+            density: settings.impulse_density.value(),
+            independent_streams: settings.independent_streams.value(),
+        }
+    }
+}
+
+/// An impulse at (x, y) with the orientation and phase of its kernel in radians.
+struct Impulse {
+    x: f64,
+    y: f64,
+    orientation: f64,
+    phase: f64,
+}
+
 struct GaborNoiseImpl {
     permutation: [usize; 256],
+    seed: u32,
+    rng: &'static dyn Rng,
 }
 
 impl GaborNoiseImpl {
-    pub fn new(seed: u32) -> Self {
-        let mut permutation: [usize; 256] = std::array::from_fn(|i| i);
-        shuffle(&mut permutation, seed);
-
-        GaborNoiseImpl { permutation }
+    pub fn new(seed: u32, source: Source) -> Self {
+        GaborNoiseImpl {
+            permutation: source.permutation(seed),
+            seed,
+            rng: source.rng(),
+        }
     }
 
     #[inline]
@@ -30,9 +88,43 @@ impl GaborNoiseImpl {
         self.permutation[(self.permutation[xi] + yi) & 255]
     }
 
+    /// Random number in [0, 1] for one purpose, `stream`, of the impulse of a cell. With
+    /// `independent_streams` it comes from the full cell coordinates, otherwise from the cell's
+    /// permutation hash, so only 256 distinct impulses repeat over the plane.
     #[inline]
-    fn hash_to_float(&self, hash: usize, offset: u32) -> f64 {
-        squirrel_noise5::f32_zero_to_one_1d(hash as i32, offset as i32) as f64
+    fn impulse(&self, x: i32, y: i32, stream: u32, independent_streams: bool) -> f64 {
+        if independent_streams {
+            self.rng.cell(x, y, self.seed, stream)
+        } else {
+            self.rng.unit(self.hash(x, y) as u32, stream)
+        }
+    }
+
+    /// Calls `f` with every impulse of cell (cx, cy). One per cell jitters a single impulse around
+    /// the center of the cell, which leaves a faint grid at low bandwidths. The Poisson layout
+    /// puts a Poisson distributed number of impulses anywhere in the cell.
+    fn for_each_impulse(&self, cx: i32, cy: i32, scatter: Scatter, mut f: impl FnMut(Impulse)) {
+        let random = |stream| self.impulse(cx, cy, stream, scatter.independent_streams);
+        let angle = |stream| random(stream) * 2.0 * std::f64::consts::PI;
+        match scatter.layout {
+            ImpulseLayout::OnePerCell => f(Impulse {
+                x: cx as f64 + 0.5 + (random(POSITION_X_STREAM) - 0.5) * 0.8,
+                y: cy as f64 + 0.5 + (random(POSITION_Y_STREAM) - 0.5) * 0.8,
+                orientation: angle(ORIENTATION_STREAM),
+                phase: angle(PHASE_STREAM),
+            }),
+            ImpulseLayout::Poisson => {
+                for i in 0..poisson(scatter.density, random(COUNT_STREAM)) {
+                    let first = FIRST_POISSON_STREAM + 4 * i;
+                    f(Impulse {
+                        x: cx as f64 + random(first + POSITION_X_STREAM),
+                        y: cy as f64 + random(first + POSITION_Y_STREAM),
+                        orientation: angle(first + ORIENTATION_STREAM),
+                        phase: angle(first + PHASE_STREAM),
+                    });
+                }
+            }
+        }
     }
 
     fn sample_gabor_sparse(
@@ -42,6 +134,7 @@ impl GaborNoiseImpl {
         frequency: f64,
         bandwidth: f64,
         kernel_radius: u32,
+        scatter: Scatter,
     ) -> f64 {
         let kernel_radius = kernel_radius as f64;
         let mut sum = 0.0;
@@ -54,35 +147,27 @@ impl GaborNoiseImpl {
         
         for dy in -cell_radius..=cell_radius {
             for dx in -cell_radius..=cell_radius {
-                let cx = cell_x + dx;
-                let cy = cell_y + dy;
-                
-                let cell_hash = self.hash(cx, cy);
-                
-                let ix = cx as f64 + 0.5 + (self.hash_to_float(cell_hash, 0) - 0.5) * 0.8;
-                let iy = cy as f64 + 0.5 + (self.hash_to_float(cell_hash, 1) - 0.5) * 0.8;
-                
-                let dx = x - ix;
-                let dy = y - iy;
-                let dist_sq = dx * dx + dy * dy;
-                
-                let max_dist = kernel_radius * bandwidth;
-                if dist_sq > max_dist * max_dist {
-                    continue;
-                }
-                
-                let theta = self.hash_to_float(cell_hash, 2) * 2.0 * std::f64::consts::PI;
-                let phi = self.hash_to_float(cell_hash, 3) * 2.0 * std::f64::consts::PI;
-                
-                let gaussian_exp = -std::f64::consts::PI * dist_sq / (bandwidth * bandwidth);
-                let gaussian = gaussian_exp.exp();
-                
-                let u = dx * theta.cos() - dy * theta.sin();
-                let harmonic = (frequency * u + phi).cos();
-                
-                let kernel_value = gaussian * harmonic;
-                sum += kernel_value;
-                weight += gaussian;
+                self.for_each_impulse(cell_x + dx, cell_y + dy, scatter, |impulse| {
+                    let dx = x - impulse.x;
+                    let dy = y - impulse.y;
+                    let dist_sq = dx * dx + dy * dy;
+
+                    let max_dist = kernel_radius * bandwidth;
+                    if dist_sq > max_dist * max_dist {
+                        return;
+                    }
+
+                    let gaussian_exp = -std::f64::consts::PI * dist_sq / (bandwidth * bandwidth);
+                    let gaussian = gaussian_exp.exp();
+
+                    let theta = impulse.orientation;
+                    let u = dx * theta.cos() - dy * theta.sin();
+                    let harmonic = (frequency * u + impulse.phase).cos();
+
+                    let kernel_value = gaussian * harmonic;
+                    sum += kernel_value;
+                    weight += gaussian;
+                });
             }
         }
         
@@ -93,32 +178,17 @@ impl GaborNoiseImpl {
         }
     }
 
-    fn generate_coloring(&self, settings: GaborNoiseSettings) -> Vec<u8> {
-        let scale = settings.scale.value();
+    fn generate_field(&self, settings: &GaborNoiseSettings) -> Vec<f64> {
+        sample_field(settings.scale.value(), |nx, ny| self.sample(nx, ny, settings))
+    }
 
-        (0..(RESOLUTION * RESOLUTION) as usize)
-            .into_par_iter()
-            .flat_map(|i| {
-                let x = i % RESOLUTION as usize;
-                let y = i / RESOLUTION as usize;
-                let nx = ((x as f64) - (HALF_RESOLUTION as f64)) / scale;
-                let ny = ((y as f64) - (HALF_RESOLUTION as f64)) / scale;
-
-                let noise_val = match settings.noise_type {  // Removed clone()
-                    NoiseType::Standard => self.fbm_standard(nx, ny, &settings),
-                    NoiseType::Turbulence => self.fbm_turbulence(nx, ny, &settings),
-                    NoiseType::Anisotropic => self.fbm_anisotropic(nx, ny, &settings),
-                    NoiseType::DomainWarp => self.fbm_domain_warp(nx, ny, &settings),
-                };
-
-                if noise_val < 0.0 {
-                    let t = noise_val + 1.0;
-                    [255u8, lerp(t, 0.0, 255.0) as u8, 255, 255]
-                } else {
-                    [lerp(noise_val, 255.0, 0.0) as u8, 255, lerp(noise_val, 255.0, 0.0) as u8, 255]
-                }
-            })
-            .collect()
+    fn sample(&self, x: f64, y: f64, settings: &GaborNoiseSettings) -> f64 {
+        match settings.noise_type {
+        NoiseType::Standard => self.fbm_standard(x, y, settings),
+        NoiseType::Turbulence => self.fbm_turbulence(x, y, settings),
+        NoiseType::Anisotropic => self.fbm_anisotropic(x, y, settings),
+        NoiseType::DomainWarp => self.fbm_domain_warp(x, y, settings),
+        }
     }
 
     pub fn fbm_standard(&self, x: f64, y: f64, settings: &GaborNoiseSettings) -> f64 {
@@ -131,11 +201,22 @@ impl GaborNoiseImpl {
         let show_octave = settings.show_octave.value();
         let bandwidth = settings.bandwidth.value();
         let kernel_radius = settings.kernel_radius.value();
+        let scatter = Scatter::new(settings);
         let gain = settings.gain.value();
         let lacunarity = settings.lacunarity.value();
+        let band_limit = BandLimit::new(settings.scale.value(), settings.auto_band_limit.value());
+        let amplitude_falloff =
+            octave_gain(gain, lacunarity, settings.h_exponent.value(), settings.spectral_h.value());
 
         for i in 1..=octaves {
-            let noise_val = self.sample_gabor_sparse(x, y, frequency, bandwidth, kernel_radius);
+            let noise_val = self.sample_gabor_sparse(
+                x,
+                y,
+                frequency,
+                bandwidth,
+                kernel_radius,
+                scatter,
+            );
 
             let include = match settings.visualization {
                 Visualization::Final => true,
@@ -143,10 +224,10 @@ impl GaborNoiseImpl {
                 Visualization::AccumulatedOctaves => i <= show_octave,
             };
             if include {
-                total += noise_val * amplitude;
+                total += noise_val * amplitude * band_limit.weight(frequency / std::f64::consts::TAU);
                 max_value += amplitude;
             }
-            amplitude *= gain;
+            amplitude *= amplitude_falloff;
             frequency *= lacunarity;
         }
 
@@ -163,11 +244,17 @@ impl GaborNoiseImpl {
         let show_octave = settings.show_octave.value();
         let bandwidth = settings.bandwidth.value();
         let kernel_radius = settings.kernel_radius.value();
+        let scatter = Scatter::new(settings);
         let gain = settings.gain.value();
         let lacunarity = settings.lacunarity.value();
+        let band_limit = BandLimit::new(settings.scale.value(), settings.auto_band_limit.value());
+        let amplitude_falloff =
+            octave_gain(gain, lacunarity, settings.h_exponent.value(), settings.spectral_h.value());
 
         for i in 1..=octaves {
-            let noise_val = self.sample_gabor_sparse(x, y, frequency, bandwidth, kernel_radius).abs();
+            let noise_val = self
+                .sample_gabor_sparse(x, y, frequency, bandwidth, kernel_radius, scatter)
+                .abs();
 
             let include = match settings.visualization {
                 Visualization::Final => true,
@@ -175,10 +262,10 @@ impl GaborNoiseImpl {
                 Visualization::AccumulatedOctaves => i <= show_octave,
             };
             if include {
-                total += noise_val * amplitude;
+                total += noise_val * amplitude * band_limit.weight(frequency / std::f64::consts::TAU);
                 max_value += amplitude;
             }
-            amplitude *= gain;
+            amplitude *= amplitude_falloff;
             frequency *= lacunarity;
         }
 
@@ -195,15 +282,26 @@ impl GaborNoiseImpl {
         let show_octave = settings.show_octave.value();
         let bandwidth = settings.bandwidth.value();
         let kernel_radius = settings.kernel_radius.value();
+        let scatter = Scatter::new(settings);
         let gain = settings.gain.value();
         let lacunarity = settings.lacunarity.value();
+        let band_limit = BandLimit::new(settings.scale.value(), settings.auto_band_limit.value());
+        let amplitude_falloff =
+            octave_gain(gain, lacunarity, settings.h_exponent.value(), settings.spectral_h.value());
         let anisotropy = settings.anisotropy.value();
 
         for i in 1..=octaves {
             let aniso_x = x * anisotropy;
             let aniso_y = y / anisotropy;
             
-            let noise_val = self.sample_gabor_sparse(aniso_x, aniso_y, frequency, bandwidth, kernel_radius);
+            let noise_val = self.sample_gabor_sparse(
+                aniso_x,
+                aniso_y,
+                frequency,
+                bandwidth,
+                kernel_radius,
+                scatter,
+            );
 
             let include = match settings.visualization {
                 Visualization::Final => true,
@@ -211,10 +309,10 @@ impl GaborNoiseImpl {
                 Visualization::AccumulatedOctaves => i <= show_octave,
             };
             if include {
-                total += noise_val * amplitude;
+                total += noise_val * amplitude * band_limit.weight(frequency / std::f64::consts::TAU);
                 max_value += amplitude;
             }
-            amplitude *= gain;
+            amplitude *= amplitude_falloff;
             frequency *= lacunarity;
         }
 
@@ -235,6 +333,9 @@ impl GaborNoiseImpl {
 
     fn draw_impulse_locations(&self, settings: &GaborNoiseSettings) {
         let scale = settings.scale.value();
+        let scatter = Scatter::new(settings);
+        let style = overlay::color(Overlay::Vectors);
+        let length = overlay::vector_length();
 
         for i in 0..settings.octaves.value() {
             let octave_scale = scale / 2_f64.powi(i as i32);
@@ -242,20 +343,21 @@ impl GaborNoiseImpl {
 
             for x in -half_range..=half_range {
                 for y in -half_range..=half_range {
-                    let cell_hash = self.hash(x as i32, y as i32);
-                    
-                    let ix = x as f64 + 0.5 + (self.hash_to_float(cell_hash, 0) - 0.5) * 0.8;
-                    let iy = y as f64 + 0.5 + (self.hash_to_float(cell_hash, 1) - 0.5) * 0.8;
-                    
-                    let screen_x = HALF_RESOLUTION as f64 - ix * octave_scale;
-                    let screen_y = HALF_RESOLUTION as f64 - iy * octave_scale;
-                    
-                    let theta = self.hash_to_float(cell_hash, 2) * 2.0 * std::f64::consts::PI;
-                    let arrow_len = octave_scale / 3.0;
-                    let tx = screen_x + theta.cos() * arrow_len;
-                    let ty = screen_y + theta.sin() * arrow_len;
-                    
-                    draw_arrow(screen_x, screen_y, tx, ty, octave_scale / 8.0, "#ee0000");
+                    if !overlay::shows_vector(x as i32, y as i32) {
+                        continue;
+                    }
+                    self.for_each_impulse(x as i32, y as i32, scatter, |impulse| {
+                        let screen_x = HALF_RESOLUTION as f64 - impulse.x * octave_scale;
+                        let screen_y = HALF_RESOLUTION as f64 - impulse.y * octave_scale;
+
+                        let theta = impulse.orientation;
+                        let arrow_len = octave_scale / 3.0 * length;
+                        let tx = screen_x + theta.cos() * arrow_len;
+                        let ty = screen_y + theta.sin() * arrow_len;
+
+                        let head = octave_scale / 8.0 * length;
+                        draw_arrow(screen_x, screen_y, tx, ty, head, &style);
+                    });
                 }
             }
         }
@@ -268,17 +370,18 @@ impl GaborNoise {
     fn on_update() {
         let octaves = Octaves::parse().value();
         SHOW_OCTAVE.with(|e| e.set_max(format!("{octaves}").as_str()));
+
+        let hide_h_exponent = !SpectralH::parse().value();
+        set_hidden!(h_exponent_control, hide_h_exponent);
     }
     
     fn generate_and_draw(settings: GaborNoiseSettings) {
-        let gabor = GaborNoiseImpl::new(settings.seed.value());
-
-        let coloring = gabor.generate_coloring(settings.clone());
+        let gabor = GaborNoiseImpl::new(settings.seed.value(), Source::page());
 
-        draw_noise(coloring.as_slice());
+        draw_field(gabor.generate_field(&settings));
 
         if settings.show_grid.value() {
-            draw_grid(settings.scale.value(), "#000000");
+            draw_noise_grid(settings.scale.value(), &overlay::color(Overlay::Grid));
         }
 
         if settings.show_impulses.value() {
@@ -294,11 +397,13 @@ define_noise!(gabor,
         (octaves, u32, 1., 1., 8.),
         (lacunarity, f64, 1., 2., 4.),
         (gain, f64, 0., 0.5, 1.),
+        (h_exponent, f64, 0., 1., 2.),
         (base_frequency, f64, 1., 10.0, 50.),
         (bandwidth, f64, 0.1, 0.5, 2.),
         (kernel_radius, u32, 2., 3., 4.),
         (anisotropy, f64, 0.25, 1.0, 4.),
         (warp_amount, f64, 0., 4.0, 10.),
+        (impulse_density, f64, 0.25, 1., 4.),
         (show_octave, u32, 1., 1., 8.)
     ];
     radios:[
@@ -312,8 +417,120 @@ define_noise!(gabor,
             (turbulence, hide:[anisotropy, warp_amount]), 
             (anisotropic, hide:[warp_amount]), 
             (domain_warp, hide:[anisotropy])
+        ),
+        (impulse_layout,
+            (one_per_cell, hide: [impulse_density]),
+            (poisson)
         )
     ];
-    checkboxes:[show_grid, show_impulses];
+    checkboxes:[show_grid, show_impulses, spectral_h, auto_band_limit, independent_streams];
+    pads:[(lacunarity, gain)];
+    cell_size: 1.0;
 );
 
+/// Gabor noise for use outside the page, see [`crate::core`].
+pub struct Gabor {
+    noise: GaborNoiseImpl,
+    settings: GaborNoiseSettings,
+    source: Source,
+}
+
+impl Gabor {
+    pub fn new(seed: u32) -> Self {
+        let mut settings = GaborNoiseSettings::defaults();
+        settings.seed = Seed(seed);
+        let source = Source::default();
+        let noise = GaborNoiseImpl::new(seed, source);
+        Gabor {
+            noise,
+            settings,
+            source,
+        }
+    }
+
+    fn rebuild(&mut self) {
+        self.noise = GaborNoiseImpl::new(self.settings.seed.value(), self.source);
+    }
+
+    source_builder!();
+
+    pub fn fractal(mut self, fractal: GaborFractal) -> Self {
+        self.settings.noise_type = match fractal {
+            GaborFractal::Standard => NoiseType::Standard,
+            GaborFractal::Turbulence => NoiseType::Turbulence,
+            GaborFractal::Anisotropic => NoiseType::Anisotropic,
+            GaborFractal::DomainWarp => NoiseType::DomainWarp,
+        };
+        self
+    }
+
+    /// Scatters a Poisson distributed number of impulses, `density` per cell on average, instead
+    /// of one jittered impulse per cell.
+    pub fn poisson_impulses(mut self, density: f64) -> Self {
+        self.settings.impulse_layout = ImpulseLayout::Poisson;
+        self.settings.impulse_density = ImpulseDensity(density);
+        self
+    }
+
+    settings_builder!(
+        octaves: u32 = 1..,
+        lacunarity: f64,
+        gain: f64,
+        h_exponent: f64,
+        base_frequency: f64,
+        bandwidth: f64,
+        kernel_radius: u32,
+        anisotropy: f64,
+        warp_amount: f64,
+        spectral_h: bool,
+        auto_band_limit: bool,
+        independent_streams: bool,
+    );
+
+    /// The defaults with a settings file's values applied, see [`crate::core::from_settings`].
+    pub fn from_pairs(pairs: &[(String, String)]) -> Result<Self, String> {
+        let settings = GaborNoiseSettings::from_pairs(pairs)?;
+        let source = Source::default();
+        let noise = GaborNoiseImpl::new(settings.seed.value(), source);
+        Ok(Gabor {
+            noise,
+            settings,
+            source,
+        })
+    }
+}
+
+impl Sampler for Gabor {
+    fn sample(&self, x: f64, y: f64) -> f64 {
+        self.noise.sample(x, y, &self.settings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::golden::assert_matches_golden;
+
+    #[test]
+    fn renders_like_golden() {
+        let noise = GaborNoiseImpl::new(42, Source::default());
+        let mut settings = GaborNoiseSettings::defaults();
+        assert_matches_golden("gabor", &noise.generate_field(&settings));
+
+        settings.noise_type = NoiseType::Anisotropic;
+        settings.anisotropy = Anisotropy(3.0);
+        assert_matches_golden("gabor_anisotropic", &noise.generate_field(&settings));
+    }
+
+    #[test]
+    fn poisson_counts_average_the_density() {
+        for density in [0.25, 1., 4.] {
+            let samples = 10_000;
+            let total: u32 = (0..samples)
+                .map(|i| poisson(density, (i as f64 + 0.5) / samples as f64))
+                .sum();
+            let mean = total as f64 / samples as f64;
+            assert!((mean - density).abs() < 0.01, "{density}: {mean}");
+        }
+    }
+}
@@ -1,6 +1,75 @@
+use crate::{
+    permutation,
+    rng::{self, Generator, Rng},
+};
+
+/// The permutation table of Ken Perlin's reference implementation of improved noise.
+pub const PERLIN_PERMUTATION: [usize; 256] = [
+    151, 160, 137, 91, 90, 15, 131, 13, 201, 95, 96, 53, 194, 233, 7, 225, 140, 36, 103, 30, 69,
+    142, 8, 99, 37, 240, 21, 10, 23, 190, 6, 148, 247, 120, 234, 75, 0, 26, 197, 62, 94, 252, 219,
+    203, 117, 35, 11, 32, 57, 177, 33, 88, 237, 149, 56, 87, 174, 20, 125, 136, 171, 168, 68, 175,
+    74, 165, 71, 134, 139, 48, 27, 166, 77, 146, 158, 231, 83, 111, 229, 122, 60, 211, 133, 230,
+    220, 105, 92, 41, 55, 46, 245, 40, 244, 102, 143, 54, 65, 25, 63, 161, 1, 216, 80, 73, 209, 76,
+    132, 187, 208, 89, 18, 169, 200, 196, 135, 130, 116, 188, 159, 86, 164, 100, 109, 198, 173,
+    186, 3, 64, 52, 217, 226, 250, 124, 123, 5, 202, 38, 147, 118, 126, 255, 82, 85, 212, 207, 206,
+    59, 227, 47, 16, 58, 17, 182, 189, 28, 42, 223, 183, 170, 213, 119, 248, 152, 2, 44, 154, 163,
+    70, 221, 153, 101, 155, 167, 43, 172, 9, 129, 22, 39, 253, 19, 98, 108, 110, 79, 113, 224, 232,
+    178, 185, 112, 104, 218, 246, 97, 228, 251, 34, 242, 193, 238, 210, 144, 12, 191, 179, 162,
+    241, 81, 51, 145, 235, 249, 14, 239, 107, 49, 192, 214, 31, 181, 199, 106, 157, 184, 84, 204,
+    176, 115, 121, 50, 45, 127, 4, 150, 254, 138, 236, 205, 93, 222, 114, 67, 29, 24, 72, 243, 141,
+    128, 195, 78, 66, 215, 61, 156, 180,
+];
+
+/// The generator a noise draws its random numbers from and the table it hashes its cells with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Source {
+    pub generator: Generator,
+    /// Used instead of the shuffled table, whatever the seed.
+    pub table: Option<[usize; 256]>,
+}
+
+impl Default for Source {
+    fn default() -> Self {
+        Source {
+            generator: Generator::SquirrelNoise5,
+            table: None,
+        }
+    }
+}
+
+impl Source {
+    /// The generator picked on the page and the table set in the permutation panel.
+    pub fn page() -> Self {
+        Source {
+            generator: rng::generator(),
+            table: permutation::table(),
+        }
+    }
+
+    pub fn rng(&self) -> &'static dyn Rng {
+        self.generator.rng()
+    }
+
+    /// The table of `seed`, shuffled with the generator unless a table is set.
+    pub fn permutation(&self, seed: u32) -> [usize; 256] {
+        self.table.unwrap_or_else(|| {
+            let mut table = std::array::from_fn(|i| i);
+            shuffle_with(&mut table, seed, self.rng());
+            table
+        })
+    }
+}
+
+/// Fisher-Yates shuffle with the generator picked on the page, or the table set in the
+/// permutation panel, whatever the seed.
 pub fn shuffle(v: &mut [usize; 256], seed: u32) {
+    *v = Source::page().permutation(seed);
+}
+
+/// Fisher-Yates shuffle with the given generator.
+pub fn shuffle_with(v: &mut [usize; 256], seed: u32, rng: &dyn Rng) {
     for i in (1..256).rev() {
-        let r = squirrel_noise5::squirrel_noise5(i as u32, seed);
+        let r = rng.bits(i as u32, seed);
         let j = (r as usize) % (i + 1);
         v.swap(i, j);
     }
@@ -12,6 +81,68 @@ pub const fn perlin_grad(hash: usize, x: f64, y: f64) -> f64 {
     xm*x + ym*y
 }
 
+/// Amplitude ratio between two consecutive fBm octaves. With `spectral_h` it follows the
+/// textbook definition, where every octave is `lacunarity^-H` weaker than the previous one
+/// and the field has an amplitude spectrum falling off as 1/f^H. Otherwise `gain` is used as is.
+#[inline]
+pub fn octave_gain(gain: f64, lacunarity: f64, h_exponent: f64, spectral_h: bool) -> f64 {
+    if spectral_h {
+        lacunarity.powf(-h_exponent)
+    } else {
+        gain
+    }
+}
+
+/// Turn between consecutive octaves, the golden angle, which never brings an octave back in line
+/// with an earlier one.
+const OCTAVE_ROTATION: f64 = 2.399963229728653;
+/// Shift between consecutive octaves, the steps of the R2 sequence, so the lattice points of the
+/// octaves never meet.
+const OCTAVE_OFFSET: (f64, f64) = (0.7548776662466927, 0.5698402909980532);
+
+/// Coordinates of the `octave`th octave, counted from 0, with `decorrelate` turned and shifted by
+/// fixed irrational amounts. The octaves share the lattice of their noise at different scales, so
+/// the lattice points of octaves an integer lacunarity apart coincide and their artifacts add up,
+/// most visibly at the origin. The first octave stays put.
+#[inline]
+pub fn octave_domain(x: f64, y: f64, octave: u32, decorrelate: bool) -> (f64, f64) {
+    if !decorrelate || octave == 0 {
+        return (x, y);
+    }
+    let octave = octave as f64;
+    let (sin, cos) = (OCTAVE_ROTATION * octave).sin_cos();
+    (
+        x * cos - y * sin + OCTAVE_OFFSET.0 * octave,
+        x * sin + y * cos + OCTAVE_OFFSET.1 * octave,
+    )
+}
+
+/// Fades out the octaves too fine for the pixels of the canvas, the way mipmaps drop the texture
+/// detail a pixel cannot show. Without it the finest octaves alias into shimmer at small scales.
+#[derive(Clone, Copy)]
+pub struct BandLimit {
+    /// Pixels per noise unit, `None` when every octave is kept.
+    scale: Option<f64>,
+}
+
+impl BandLimit {
+    pub fn new(scale: f64, enabled: bool) -> Self {
+        BandLimit {
+            scale: enabled.then_some(scale),
+        }
+    }
+
+    /// Share kept of an octave with `frequency` periods per noise unit. A period needs two pixels
+    /// to show at all, octaves fade out as theirs shrinks from four pixels to two.
+    #[inline]
+    pub fn weight(&self, frequency: f64) -> f64 {
+        match self.scale {
+            Some(scale) => ((scale / frequency).log2() - 1.).clamp(0., 1.),
+            None => 1.,
+        }
+    }
+}
+
 #[inline]
 pub const fn lerp(t: f64, a: f64, b: f64) -> f64 {
     a + t * (b - a)
@@ -30,3 +161,81 @@ pub const fn get_perlin_vec(hash: usize) -> (f64, f64){
         _ => (1., -1.),
     }
 }
+
+/// Deterministic pseudo-random points in `[-range, range)²`, the inputs of the property tests.
+#[cfg(test)]
+pub fn sample_points(count: usize, range: f64, seed: u64) -> Vec<(f64, f64)> {
+    // splitmix64, so the samples do not depend on the hash under test.
+    let mut state = seed;
+    let mut next = move || {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        (z >> 11) as f64 / (1u64 << 53) as f64 * 2.0 * range - range
+    };
+    (0..count).map(|_| (next(), next())).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shuffle_is_a_permutation() {
+        for seed in 0..64 {
+            let mut v: [usize; 256] = std::array::from_fn(|i| i);
+            shuffle(&mut v, seed);
+
+            let mut seen = [false; 256];
+            for &x in &v {
+                assert!(!seen[x], "seed {seed} repeats {x}");
+                seen[x] = true;
+            }
+        }
+    }
+
+    #[test]
+    fn perlin_vectors_use_unit_components() {
+        for hash in 0..256 {
+            let (x, y) = get_perlin_vec(hash);
+            assert!([-1.0, 0.0, 1.0].contains(&x) && [-1.0, 0.0, 1.0].contains(&y));
+            assert!(x != 0.0 || y != 0.0);
+        }
+    }
+
+    #[test]
+    fn spectral_gain_follows_h() {
+        assert_eq!(octave_gain(0.3, 2.0, 1.0, true), 0.5);
+        assert_eq!(octave_gain(0.3, 2.0, 1.0, false), 0.3);
+    }
+
+    #[test]
+    fn decorrelated_octaves_keep_distances_and_move_the_origin() {
+        assert_eq!(octave_domain(0.3, 0.4, 0, true), (0.3, 0.4));
+        assert_eq!(octave_domain(0.3, 0.4, 2, false), (0.3, 0.4));
+        for octave in 1..8 {
+            let (ax, ay) = octave_domain(0., 0., octave, true);
+            let (bx, by) = octave_domain(3., 4., octave, true);
+            assert!(((bx - ax).hypot(by - ay) - 5.).abs() < 1e-12);
+            assert!(ax.fract() != 0. && ay.fract() != 0.);
+        }
+    }
+
+    #[test]
+    fn band_limit_fades_octaves_between_four_and_two_pixels() {
+        let limit = BandLimit::new(50., true);
+        assert_eq!(limit.weight(12.5), 1.);
+        assert_eq!(limit.weight(25.), 0.);
+        assert!((limit.weight(50. / 8f64.sqrt()) - 0.5).abs() < 1e-12);
+        assert_eq!(BandLimit::new(50., false).weight(1000.), 1.);
+    }
+
+    #[test]
+    fn sample_points_stay_in_range() {
+        for (x, y) in sample_points(1000, 8.0, 1) {
+            assert!((-8.0..8.0).contains(&x) && (-8.0..8.0).contains(&y));
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use std::cell::LazyCell;
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlElement, HtmlInputElement};
+
+use super::noise::Noise;
+use crate::{
+    core::Sampler,
+    drawer::RESOLUTION,
+    field::{draw_field, sample_field},
+    image_import::{self, ImageSampler},
+    *,
+};
+
+elements!((image_placeholder, HtmlElement));
+
+impl ImageNoise {
+    fn on_setup() {}
+
+    fn on_update() {}
+
+    /// Without an imported image the canvas stays blank and the page asks for one.
+    fn generate_and_draw(settings: ImageNoiseSettings) {
+        let image = image_import::image();
+        IMAGE_PLACEHOLDER.with(|p| p.set_hidden(image.is_some()));
+
+        let field = match image {
+            Some(pixels) => {
+                let sampler = ImageSampler::new(pixels);
+                sample_field(settings.scale.value(), |x, y| sampler.sample(x, y))
+            }
+            None => vec![0.; (RESOLUTION * RESOLUTION) as usize],
+        };
+        draw_field(field);
+    }
+}
+
+define_noise!(image,
+    sliders:[
+        (scale, f64, 1., 50., 200.)
+    ];
+    radios:[];
+    checkboxes:[];
+);
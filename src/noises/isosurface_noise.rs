@@ -0,0 +1,227 @@
+use std::cell::LazyCell;
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlElement, HtmlInputElement};
+
+use super::noise::Noise;
+use crate::{
+    camera::{OrbitCamera, Vec3, triangle_lighting},
+    drawer::{clear_canvas, draw_line, fill_polygon},
+    noises::volume::Perlin3d,
+    *,
+};
+
+/// Cube corners, indexed so that corners 0 and 6 span the main diagonal.
+const CUBE_CORNERS: [[usize; 3]; 8] = [
+    [0, 0, 0],
+    [1, 0, 0],
+    [1, 1, 0],
+    [0, 1, 0],
+    [0, 0, 1],
+    [1, 0, 1],
+    [1, 1, 1],
+    [0, 1, 1],
+];
+
+/// Six tetrahedra sharing the 0-6 diagonal. Marching cubes over them needs no case table.
+const CUBE_TETRAHEDRA: [[usize; 4]; 6] = [
+    [0, 5, 1, 6],
+    [0, 1, 2, 6],
+    [0, 2, 3, 6],
+    [0, 3, 7, 6],
+    [0, 7, 4, 6],
+    [0, 4, 5, 6],
+];
+
+const CAMERA_DISTANCE: f64 = 3.5;
+
+struct IsosurfaceNoiseImpl {
+    resolution: usize,
+    values: Vec<f64>,
+}
+
+impl IsosurfaceNoiseImpl {
+    /// Samples fBm on a `resolution`^3 lattice spanning the [-1, 1] cube.
+    pub fn new(settings: &IsosurfaceNoiseSettings) -> Self {
+        let noise = Perlin3d::new(settings.seed.value());
+        let resolution = settings.grid_resolution.value() as usize + 1;
+        let frequency = settings.volume_frequency.value();
+
+        let mut values = Vec::with_capacity(resolution * resolution * resolution);
+        for z in 0..resolution {
+            for y in 0..resolution {
+                for x in 0..resolution {
+                    let [px, py, pz] = Self::lattice_position(resolution, [x, y, z]);
+                    values.push(noise.fbm(
+                        px * frequency,
+                        py * frequency,
+                        pz * frequency,
+                        settings.octaves.value(),
+                        settings.lacunarity.value(),
+                        settings.gain.value(),
+                    ));
+                }
+            }
+        }
+
+        IsosurfaceNoiseImpl { resolution, values }
+    }
+
+    #[inline]
+    fn lattice_position(resolution: usize, [x, y, z]: [usize; 3]) -> Vec3 {
+        let to_unit = |v: usize| v as f64 / (resolution - 1) as f64 * 2.0 - 1.0;
+        [to_unit(x), to_unit(y), to_unit(z)]
+    }
+
+    #[inline]
+    fn value(&self, [x, y, z]: [usize; 3]) -> f64 {
+        self.values[(z * self.resolution + y) * self.resolution + x]
+    }
+
+    /// Extracts the surface where the volume crosses `threshold` as a triangle soup.
+    fn triangulate(&self, threshold: f64) -> Vec<[Vec3; 3]> {
+        let mut triangles = Vec::new();
+        let cells = self.resolution - 1;
+
+        for z in 0..cells {
+            for y in 0..cells {
+                for x in 0..cells {
+                    let corners = CUBE_CORNERS.map(|[dx, dy, dz]| [x + dx, y + dy, z + dz]);
+                    let positions = corners.map(|c| Self::lattice_position(self.resolution, c));
+                    let values = corners.map(|c| self.value(c));
+
+                    for tetrahedron in CUBE_TETRAHEDRA {
+                        Self::triangulate_tetrahedron(
+                            tetrahedron.map(|i| (positions[i], values[i])),
+                            threshold,
+                            &mut triangles,
+                        );
+                    }
+                }
+            }
+        }
+        triangles
+    }
+
+    fn triangulate_tetrahedron(
+        vertices: [(Vec3, f64); 4],
+        threshold: f64,
+        triangles: &mut Vec<[Vec3; 3]>,
+    ) {
+        let (inside, outside): (Vec<_>, Vec<_>) =
+            vertices.iter().partition(|(_, value)| *value > threshold);
+
+        let crossing = |(a, va): &(Vec3, f64), (b, vb): &(Vec3, f64)| -> Vec3 {
+            let t = (threshold - va) / (vb - va);
+            [
+                a[0] + t * (b[0] - a[0]),
+                a[1] + t * (b[1] - a[1]),
+                a[2] + t * (b[2] - a[2]),
+            ]
+        };
+
+        match (inside.len(), outside.len()) {
+            (1, 3) => triangles.push([0, 1, 2].map(|i| crossing(inside[0], outside[i]))),
+            (3, 1) => triangles.push([0, 1, 2].map(|i| crossing(inside[i], outside[0]))),
+            (2, 2) => {
+                let p0 = crossing(inside[0], outside[0]);
+                let p1 = crossing(inside[0], outside[1]);
+                let p2 = crossing(inside[1], outside[1]);
+                let p3 = crossing(inside[1], outside[0]);
+                triangles.push([p0, p1, p2]);
+                triangles.push([p0, p2, p3]);
+            }
+            _ => (),
+        }
+    }
+}
+
+impl IsosurfaceNoise {
+    fn on_setup() {}
+
+    fn on_update() {}
+
+    fn generate_and_draw(settings: IsosurfaceNoiseSettings) {
+        let volume = IsosurfaceNoiseImpl::new(&settings);
+        let triangles = volume.triangulate(settings.threshold.value());
+
+        let camera = OrbitCamera::from_degrees(
+            settings.camera_yaw.value(),
+            settings.camera_pitch.value(),
+            CAMERA_DISTANCE,
+        );
+
+        clear_canvas("#ffffff");
+        if settings.show_bounds.value() {
+            Self::draw_bounds(&camera, "#bbbbbb");
+        }
+
+        let mut faces: Vec<_> = triangles
+            .iter()
+            .map(|triangle| {
+                let view = triangle.map(|v| camera.to_view(v));
+                let depth = (view[0][2] + view[1][2] + view[2][2]) / 3.0;
+                (
+                    depth,
+                    triangle_lighting(&view),
+                    view.map(OrbitCamera::project),
+                )
+            })
+            .collect();
+        faces.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        for (_, light_amount, points) in faces {
+            let shade = 0.25 + 0.75 * light_amount;
+            let style = format!(
+                "rgb({}, {}, {})",
+                (110.0 * shade) as u8,
+                (200.0 * shade) as u8,
+                (110.0 * shade) as u8
+            );
+            fill_polygon(&points, &style);
+        }
+    }
+
+    fn draw_bounds(camera: &OrbitCamera, style: &str) {
+        let corner = |i: usize| {
+            let [x, y, z] = CUBE_CORNERS[i].map(|c| c as f64 * 2.0 - 1.0);
+            OrbitCamera::project(camera.to_view([x, y, z]))
+        };
+        let edges = [
+            (0, 1),
+            (1, 2),
+            (2, 3),
+            (3, 0),
+            (4, 5),
+            (5, 6),
+            (6, 7),
+            (7, 4),
+            (0, 4),
+            (1, 5),
+            (2, 6),
+            (3, 7),
+        ];
+        for (a, b) in edges {
+            let (ax, ay) = corner(a);
+            let (bx, by) = corner(b);
+            draw_line(ax, ay, bx, by, style);
+        }
+    }
+}
+
+define_noise!(isosurface,
+    sliders:[
+        (seed, u32, 0., 42., 1000.),
+        (volume_frequency, f64, 0.5, 2., 6.),
+        (octaves, u32, 1., 1., 4.),
+        (lacunarity, f64, 1., 2., 4.),
+        (gain, f64, 0., 0.5, 1.),
+        (threshold, f64, -0.6, 0., 0.6),
+        (grid_resolution, u32, 8., 24., 40.),
+        (camera_yaw, f64, 0., 35., 360., Degrees),
+        (camera_pitch, f64, -85., 25., 85., Degrees)
+    ];
+    radios:[];
+    checkboxes:[show_bounds];
+    dials:[camera_yaw];
+);
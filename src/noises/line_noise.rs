@@ -0,0 +1,173 @@
+use std::cell::LazyCell;
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlElement, HtmlInputElement};
+
+use super::noise::Noise;
+use crate::{
+    drawer::{RESOLUTION, clear_canvas, draw_line, draw_polyline},
+    noises::helpers::{lerp, shuffle},
+    *,
+};
+
+struct LineNoiseImpl {
+    permutation: [usize; 256],
+}
+
+impl LineNoiseImpl {
+    pub fn new(seed: u32) -> Self {
+        let mut permutation: [usize; 256] = std::array::from_fn(|i| i);
+        shuffle(&mut permutation, seed);
+
+        LineNoiseImpl { permutation }
+    }
+
+    #[inline]
+    fn fade(t: f64) -> f64 {
+        t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+    }
+
+    #[inline]
+    fn hash(&self, x: i32) -> usize {
+        self.permutation[(x & 255) as usize]
+    }
+
+    /// Maps a lattice point to a pseudo-random value in [-1, 1].
+    #[inline]
+    fn lattice_value(&self, x: i32) -> f64 {
+        self.hash(x) as f64 / 127.5 - 1.0
+    }
+
+    fn value_noise(&self, x: f64) -> f64 {
+        let xi = x.floor() as i32;
+        let t = Self::fade(x - xi as f64);
+        lerp(t, self.lattice_value(xi), self.lattice_value(xi + 1))
+    }
+
+    fn perlin_noise(&self, x: f64) -> f64 {
+        let xi = x.floor() as i32;
+        let xf = x - xi as f64;
+        let t = Self::fade(xf);
+
+        let g0 = self.lattice_value(xi);
+        let g1 = self.lattice_value(xi + 1);
+
+        // A 1D gradient noise peaks at |0.5|, so it is doubled to fill [-1, 1].
+        2.0 * lerp(t, g0 * xf, g1 * (xf - 1.0))
+    }
+
+    fn basis(&self, x: f64, basis: Basis) -> f64 {
+        match basis {
+            Basis::PerlinBasis => self.perlin_noise(x),
+            Basis::ValueBasis => self.value_noise(x),
+        }
+    }
+
+    pub fn fbm(&self, x: f64, settings: &LineNoiseSettings) -> f64 {
+        let mut total = 0.0;
+        let mut frequency = 1.0;
+        let mut amplitude = 1.0;
+        let mut max_value = 0.0;
+
+        let octaves = settings.octaves.value();
+        let show_octave = settings.show_octave.value();
+        let gain = settings.gain.value();
+        let lacunarity = settings.lacunarity.value();
+
+        for i in 1..=octaves {
+            let noise_val = self.basis(x * frequency, settings.basis);
+
+            let include = match settings.visualization {
+                Visualization::Final => true,
+                Visualization::SingleOctave => i == show_octave,
+                Visualization::AccumulatedOctaves => i <= show_octave,
+            };
+            if include {
+                total += noise_val * amplitude;
+                max_value += amplitude;
+            }
+            amplitude *= gain;
+            frequency *= lacunarity;
+        }
+
+        total / max_value
+    }
+
+    fn plot<F: Fn(f64) -> f64>(scale: f64, f: F) -> Vec<(f64, f64)> {
+        let amplitude = HALF_RESOLUTION as f64 * 0.8;
+        (0..=RESOLUTION)
+            .map(|x| {
+                let nx = (x as f64 - HALF_RESOLUTION as f64) / scale;
+                (x as f64, HALF_RESOLUTION as f64 - f(nx) * amplitude)
+            })
+            .collect()
+    }
+}
+
+impl LineNoise {
+    fn on_setup() {}
+
+    fn on_update() {
+        let octaves = Octaves::parse().value();
+        SHOW_OCTAVE.with(|e| e.set_max(format!("{octaves}").as_str()));
+    }
+
+    fn generate_and_draw(settings: LineNoiseSettings) {
+        let line = LineNoiseImpl::new(settings.seed.value());
+        let scale = settings.scale.value();
+
+        clear_canvas("#ffffff");
+
+        if settings.show_grid.value() {
+            Self::draw_lattice(scale);
+        }
+        let top = HALF_RESOLUTION as f64 * 0.2;
+        let bottom = RESOLUTION as f64 - top;
+        draw_line(0., HALF_RESOLUTION as f64, RESOLUTION as f64, HALF_RESOLUTION as f64, "#000000");
+        draw_line(0., top, RESOLUTION as f64, top, "#cccccc");
+        draw_line(0., bottom, RESOLUTION as f64, bottom, "#cccccc");
+
+        if settings.show_value_curve.value() {
+            let points = LineNoiseImpl::plot(scale, |x| line.value_noise(x));
+            draw_polyline(&points, "#0088ff", 1.5);
+        }
+        if settings.show_perlin_curve.value() {
+            let points = LineNoiseImpl::plot(scale, |x| line.perlin_noise(x));
+            draw_polyline(&points, "#ee0000", 1.5);
+        }
+
+        let points = LineNoiseImpl::plot(scale, |x| line.fbm(x, &settings));
+        draw_polyline(&points, "#00aa00", 2.5);
+    }
+
+    fn draw_lattice(scale: f64) {
+        let half_range = (HALF_RESOLUTION as f64 / scale).floor() as isize;
+        for i in -half_range..=half_range {
+            let x = HALF_RESOLUTION as f64 + i as f64 * scale;
+            draw_line(x, 0., x, RESOLUTION as f64, "#dddddd");
+        }
+    }
+}
+
+define_noise!(line,
+    sliders:[
+        (seed, u32, 0., 42., 1000.),
+        (scale, f64, 10., 50., 200.),
+        (octaves, u32, 1., 1., 8.),
+        (lacunarity, f64, 1., 2., 4.),
+        (gain, f64, 0., 0.5, 1.),
+        (show_octave, u32, 1., 1., 8.)
+    ];
+    radios:[
+        (visualization,
+            (final, hide: [show_octave]),
+            (single_octave),
+            (accumulated_octaves)
+        ),
+        (basis,
+            (perlin_basis),
+            (value_basis)
+        )
+    ];
+    checkboxes:[show_grid, show_value_curve, show_perlin_curve];
+);
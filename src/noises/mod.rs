@@ -4,7 +4,14 @@ pub mod wavelet_noise;
 pub mod gabor_noise;
 pub mod anisotropic_noise;
 pub mod worley_noise;
+pub mod line_noise;
+pub mod isosurface_noise;
+pub mod slices_noise;
+pub mod image_noise;
+#[cfg(feature = "noise-rs")]
+pub mod external_noise;
 
 pub mod noise;
 pub mod helpers;
+pub mod volume;
 
@@ -4,4 +4,9 @@ pub trait Noise {
     fn update();
     fn deselect();
     fn reset();
+
+    /// Noise units spanned by one lattice cell, for the noises driven by the shared scale slider.
+    fn cell_size() -> Option<f64> {
+        None
+    }
 }
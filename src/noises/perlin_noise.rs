@@ -1,25 +1,100 @@
-use std::cell::LazyCell;
+use std::cell::{Cell, LazyCell};
 
 use wasm_bindgen::{JsCast, prelude::Closure};
-use web_sys::{HtmlElement, HtmlInputElement};
+use web_sys::{HtmlCanvasElement, HtmlElement, HtmlInputElement, MouseEvent};
 
 use super::noise::Noise;
 use crate::{
-    drawer::{IMAGE_BYTES_COUNT, draw_arrow},
-    noises::helpers::{get_perlin_vec, lerp, perlin_grad, shuffle},
+    charts::Chart,
+    core::{Fractal, Lattice, Sampler},
+    drawer::{
+        CANVAS, canvas_coordinates, draw_arrow, draw_circle, draw_line, draw_patch, stroke_polygon,
+        stroke_rect, value_to_color,
+    },
+    noises::helpers::{
+        BandLimit, PERLIN_PERMUTATION, get_perlin_vec, lerp, octave_domain, octave_gain,
+        perlin_grad, Source,
+    },
+    overlay::{self, Overlay},
+    field::{canvas_position, draw_field, draw_noise_grid, noise_coordinates, sample_field},
     *,
 };
 
+/// Height of one row of the hexagonal and triangular lattices, whose neighbouring points are one
+/// unit apart.
+const ROW_HEIGHT: f64 = 0.866_025_403_784_438_6;
+/// Squared radius of the kernel around every point of the hexagonal lattice, it ends before the
+/// neighbouring points so the noise stays zero on the lattice.
+const HEX_KERNEL_RADIUS_SQUARED: f64 = 0.75;
+/// Brings the sum of the hexagonal kernels to roughly [-1, 1].
+const HEX_KERNEL_SCALE: f64 = 11.;
+
+/// The plane of Ken Perlin's reference the page samples, that of the canonical test of his Java
+/// ImprovedNoise, so (3.14, 42) reads the value it prints.
+const REFERENCE_PLANE: f64 = 7.;
+
+/// Rotates a point counterclockwise by `degrees` around the origin.
+#[inline]
+fn rotate(x: f64, y: f64, degrees: f64) -> (f64, f64) {
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    (x * cos - y * sin, x * sin + y * cos)
+}
+
+/// One octave of ridged fBm at a point.
+struct RidgeOctave {
+    /// What the previous octave handed down, the ridge value of the first octave counts fully.
+    weight: f64,
+    /// The octave's ridge value, `ridge_offset - |noise|`.
+    ridge: f64,
+    /// What the octave adds to the sum before the normalization: ridge² · weight · amplitude,
+    /// faded by the band limit.
+    contribution: f64,
+}
+
+/// Gradient of Ken Perlin's improved noise dotted with the offset (x, y, z). The 16 hash values
+/// pick the 12 edges of a cube, four of them twice.
+#[inline]
+fn reference_grad(hash: usize, x: f64, y: f64, z: f64) -> f64 {
+    let h = hash & 15;
+    let u = if h < 8 { x } else { y };
+    let v = if h < 4 {
+        y
+    } else if h == 12 || h == 14 {
+        x
+    } else {
+        z
+    };
+    (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+}
+
 struct PerlinNoiseImpl {
     permutation: [usize; 256],
+    /// Samples Ken Perlin's reference implementation instead of the lattices of the page.
+    reference: bool,
 }
 
 impl PerlinNoiseImpl {
-    pub fn new(seed: u32) -> Self {
-        let mut permutation: [usize; 256] = std::array::from_fn(|i| i);
-        shuffle(&mut permutation, seed);
+    pub fn new(seed: u32, source: Source) -> Self {
+        PerlinNoiseImpl {
+            permutation: source.permutation(seed),
+            reference: false,
+        }
+    }
 
-        PerlinNoiseImpl { permutation }
+    /// Ken Perlin's improved noise with his own table, which no seed changes.
+    pub fn reference() -> Self {
+        PerlinNoiseImpl {
+            permutation: PERLIN_PERMUTATION,
+            reference: true,
+        }
+    }
+
+    fn from_settings(settings: &PerlinNoiseSettings, source: Source) -> Self {
+        if settings.reference_mode.value() {
+            Self::reference()
+        } else {
+            Self::new(settings.seed.value(), source)
+        }
     }
 
     #[inline]
@@ -60,6 +135,125 @@ impl PerlinNoiseImpl {
         lerp(v, x1, x2)
     }
 
+    /// Ken Perlin's improved noise at (x, y, z), the same operations in the same order as his Java
+    /// reference, so the results agree to the last bit with every faithful port of it.
+    fn reference_noise(&self, x: f64, y: f64, z: f64) -> f64 {
+        let p = |i: usize| self.permutation[i & 255];
+        let cell = |t: f64| (t.floor() as i32 & 255) as usize;
+        let (xi, yi, zi) = (cell(x), cell(y), cell(z));
+        let (x, y, z) = (x - x.floor(), y - y.floor(), z - z.floor());
+        let (u, v, w) = (Self::fade(x), Self::fade(y), Self::fade(z));
+
+        let a = p(xi) + yi;
+        let (aa, ab) = (p(a) + zi, p(a + 1) + zi);
+        let b = p(xi + 1) + yi;
+        let (ba, bb) = (p(b) + zi, p(b + 1) + zi);
+
+        lerp(
+            w,
+            lerp(
+                v,
+                lerp(u, reference_grad(p(aa), x, y, z), reference_grad(p(ba), x - 1., y, z)),
+                lerp(
+                    u,
+                    reference_grad(p(ab), x, y - 1., z),
+                    reference_grad(p(bb), x - 1., y - 1., z),
+                ),
+            ),
+            lerp(
+                v,
+                lerp(
+                    u,
+                    reference_grad(p(aa + 1), x, y, z - 1.),
+                    reference_grad(p(ba + 1), x - 1., y, z - 1.),
+                ),
+                lerp(
+                    u,
+                    reference_grad(p(ab + 1), x, y - 1., z - 1.),
+                    reference_grad(p(bb + 1), x - 1., y - 1., z - 1.),
+                ),
+            ),
+        )
+    }
+
+    /// Lattice coordinates of a point, along the axes (1, 0) and (1/2, √3/2) of the hexagonal and
+    /// triangular lattices.
+    #[inline]
+    fn skewed(x: f64, y: f64) -> (f64, f64) {
+        let j = y / ROW_HEIGHT;
+        (x - j * 0.5, j)
+    }
+
+    /// Dot product of the gradient of a point of the hexagonal or triangular lattice with the
+    /// offset from that point.
+    #[inline]
+    fn skewed_dot_product(&self, x: f64, y: f64, i: i32, j: i32) -> f64 {
+        let corner_x = i as f64 + j as f64 * 0.5;
+        let corner_y = j as f64 * ROW_HEIGHT;
+        perlin_grad(self.hash(i, j), x - corner_x, y - corner_y)
+    }
+
+    /// Radial kernels around every point of the lattice, each point owns a hexagonal cell.
+    fn noise_hexagonal(&self, x: f64, y: f64) -> f64 {
+        let (i, j) = Self::skewed(x, y);
+        let (i0, j0) = (i.floor() as i32, j.floor() as i32);
+
+        let mut total = 0.;
+        for cj in j0 - 1..=j0 + 2 {
+            for ci in i0 - 1..=i0 + 2 {
+                let dx = x - (ci as f64 + cj as f64 * 0.5);
+                let dy = y - cj as f64 * ROW_HEIGHT;
+                let t = HEX_KERNEL_RADIUS_SQUARED - dx * dx - dy * dy;
+                if t > 0. {
+                    total += t.powi(4) * self.skewed_dot_product(x, y, ci, cj);
+                }
+            }
+        }
+        total * HEX_KERNEL_SCALE
+    }
+
+    /// Blends the three corners of the surrounding triangle with their faded barycentric weights.
+    fn noise_triangular(&self, x: f64, y: f64) -> f64 {
+        let (i, j) = Self::skewed(x, y);
+        let (fi, fj) = (i - i.floor(), j - j.floor());
+        let (i0, j0) = (i.floor() as i32, j.floor() as i32);
+
+        let corners = if fi + fj < 1. {
+            [((0, 0), 1. - fi - fj), ((1, 0), fi), ((0, 1), fj)]
+        } else {
+            [((1, 1), fi + fj - 1.), ((1, 0), 1. - fj), ((0, 1), 1. - fi)]
+        };
+        let (mut total, mut weights) = (0., 0.);
+        for ((di, dj), barycentric) in corners {
+            let weight = Self::fade(barycentric);
+            total += weight * self.skewed_dot_product(x, y, i0 + di, j0 + dj);
+            weights += weight;
+        }
+        total / weights
+    }
+
+    #[inline]
+    fn corner_dot_product(&self, xf: f64, yf: f64, corner_x: i32, corner_y: i32) -> f64 {
+        let hash = self.hash(corner_x, corner_y);
+        perlin_grad(hash, xf - corner_x as f64, yf - corner_y as f64)
+    }
+
+    fn explain_value(&self, xf: f64, yf: f64, step: usize) -> f64 {
+        match step {
+            2 => self.corner_dot_product(xf, yf, (xf >= 0.5) as i32, (yf >= 0.5) as i32),
+            3 => {
+                let u = Self::fade(xf);
+                let row = (yf >= 0.5) as i32;
+                lerp(
+                    u,
+                    self.corner_dot_product(xf, yf, 0, row),
+                    self.corner_dot_product(xf, yf, 1, row),
+                )
+            }
+            _ => self.noise_blend_full(xf, yf),
+        }
+    }
+
     #[inline]
     fn noise_blend_dot_products(&self, x: f64, y: f64) -> f64 {
         let xi = x.floor() as i32;
@@ -96,45 +290,50 @@ impl PerlinNoiseImpl {
         }
     }
 
-    fn generate_coloring(&self, settings: PerlinNoiseSettings) -> Vec<u8> {
-        let mut v = Vec::with_capacity(IMAGE_BYTES_COUNT as usize);
-        let scale = settings.scale.value();
+    fn generate_field(&self, settings: &PerlinNoiseSettings) -> Vec<f64> {
+        sample_field(settings.scale.value(), |nx, ny| self.sample(nx, ny, settings))
+    }
 
-        for y in 0..RESOLUTION {
-            for x in 0..RESOLUTION {
-                let nx = ((x as f64) - (HALF_RESOLUTION as f64)) / scale;
-                let ny = ((y as f64) - (HALF_RESOLUTION as f64)) / scale;
-
-                let noise_val = match settings.noise_type {
-                    NoiseType::Standard => self.fbm_standard(nx, ny, &settings),
-                    NoiseType::Turbulence => self.fbm_turbulence(nx, ny, &settings),
-                    NoiseType::Ridge => self.fbm_ridge(nx, ny, &settings),
-                    NoiseType::DomainWarp => self.fbm_domain_warp(nx, ny, &settings),
-                };
-
-                if noise_val < 0. {
-                    let t = noise_val + 1.;
-                    v.push(255);
-                    v.push(lerp(t, 0.0, 255.0) as u8);
-                    v.push(255);
-                    v.push(255);
-                } else {
-                    let t = noise_val;
-                    v.push(lerp(t, 255.0, 0.0) as u8);
-                    v.push(255);
-                    v.push(lerp(t, 255.0, 0.0) as u8);
-                    v.push(255);
-                }
-            }
+    fn sample(&self, x: f64, y: f64, settings: &PerlinNoiseSettings) -> f64 {
+        let (x, y) = rotate(x, y, self.domain_rotation(settings));
+        match settings.noise_type {
+            NoiseType::Standard => self.fbm_standard(x, y, settings),
+            NoiseType::Turbulence => self.fbm_turbulence(x, y, settings),
+            NoiseType::Ridge => self.fbm_ridge(x, y, settings),
+            NoiseType::DomainWarp => self.fbm_domain_warp(x, y, settings),
         }
-        v
     }
 
-    fn sample_noise(&self, x: f64, y: f64, use_dot_products: bool) -> f64 {
-        if use_dot_products {
-            self.noise_blend_dot_products(x, y)
+    // The reference samples the plain octaves, without the rotation, decorrelation and band
+    // limit of the page.
+    fn domain_rotation(&self, settings: &PerlinNoiseSettings) -> f64 {
+        if self.reference {
+            0.
         } else {
-            self.noise_blend_full(x, y)
+            settings.domain_rotation.value()
+        }
+    }
+
+    fn decorrelates(&self, settings: &PerlinNoiseSettings) -> bool {
+        settings.decorrelate_octaves.value() && !self.reference
+    }
+
+    fn band_limit(&self, settings: &PerlinNoiseSettings) -> BandLimit {
+        BandLimit::new(
+            settings.scale.value(),
+            settings.auto_band_limit.value() && !self.reference,
+        )
+    }
+
+    fn sample_noise(&self, x: f64, y: f64, lattice: LatticeType, use_dot_products: bool) -> f64 {
+        if self.reference {
+            return self.reference_noise(x, y, REFERENCE_PLANE);
+        }
+        match lattice {
+            LatticeType::SquareLattice if use_dot_products => self.noise_blend_dot_products(x, y),
+            LatticeType::SquareLattice => self.noise_blend_full(x, y),
+            LatticeType::HexagonalLattice => self.noise_hexagonal(x, y),
+            LatticeType::TriangularLattice => self.noise_triangular(x, y),
         }
     }
 
@@ -147,12 +346,18 @@ impl PerlinNoiseImpl {
         let octaves = settings.octaves.value();
         let show_octave = settings.show_octave.value();
         let use_dot_products = settings.show_dot_products.value();
+        let lattice = settings.lattice_type;
+        let decorrelate = self.decorrelates(settings);
         let gain = settings.gain.value();
         let h_exponent = settings.h_exponent.value();
         let lacunarity = settings.lacunarity.value();
+        let band_limit = self.band_limit(settings);
+        let amplitude_falloff =
+            octave_gain(gain.powf(h_exponent), lacunarity, h_exponent, settings.spectral_h.value());
 
         for i in 1..=octaves {
-            let noise_val = self.sample_noise(x * frequency, y * frequency, use_dot_products);
+            let (ox, oy) = octave_domain(x * frequency, y * frequency, i - 1, decorrelate);
+            let noise_val = self.sample_noise(ox, oy, lattice, use_dot_products);
 
             let include = match settings.visualization {
                 Visualization::Final => true,
@@ -160,10 +365,10 @@ impl PerlinNoiseImpl {
                 Visualization::AccumulatedOctaves => i <= show_octave,
             };
             if include {
-                total += noise_val * amplitude;
+                total += noise_val * amplitude * band_limit.weight(frequency);
                 max_value += amplitude;
             }
-            amplitude *= gain.powf(h_exponent);
+            amplitude *= amplitude_falloff;
             frequency *= lacunarity;
         }
 
@@ -179,13 +384,17 @@ impl PerlinNoiseImpl {
         let octaves = settings.octaves.value();
         let show_octave = settings.show_octave.value();
         let use_dot_products = settings.show_dot_products.value();
+        let lattice = settings.lattice_type;
+        let decorrelate = self.decorrelates(settings);
         let gain = settings.gain.value();
         let lacunarity = settings.lacunarity.value();
+        let band_limit = self.band_limit(settings);
+        let amplitude_falloff =
+            octave_gain(gain, lacunarity, settings.h_exponent.value(), settings.spectral_h.value());
 
         for i in 1..=octaves {
-            let noise_val = self
-                .sample_noise(x * frequency, y * frequency, use_dot_products)
-                .abs();
+            let (ox, oy) = octave_domain(x * frequency, y * frequency, i - 1, decorrelate);
+            let noise_val = self.sample_noise(ox, oy, lattice, use_dot_products).abs();
 
             let include = match settings.visualization {
                 Visualization::Final => true,
@@ -193,10 +402,10 @@ impl PerlinNoiseImpl {
                 Visualization::AccumulatedOctaves => i <= show_octave,
             };
             if include {
-                total += noise_val * amplitude;
+                total += noise_val * amplitude * band_limit.weight(frequency);
                 max_value += amplitude;
             }
-            amplitude *= gain;
+            amplitude *= amplitude_falloff;
             frequency *= lacunarity;
         }
 
@@ -213,12 +422,16 @@ impl PerlinNoiseImpl {
         let octaves = settings.octaves.value();
         let show_octave = settings.show_octave.value();
         let use_dot_products = settings.show_dot_products.value();
+        let lattice = settings.lattice_type;
+        let decorrelate = self.decorrelates(settings);
         let gain = settings.gain.value();
         let lacunarity = settings.lacunarity.value();
+        let band_limit = self.band_limit(settings);
+        let amplitude_falloff =
+            octave_gain(gain, lacunarity, settings.h_exponent.value(), settings.spectral_h.value());
         for i in 1..=octaves {
-            let noise_val = self
-                .sample_noise(x * frequency, y * frequency, use_dot_products)
-                .abs();
+            let (ox, oy) = octave_domain(x * frequency, y * frequency, i - 1, decorrelate);
+            let noise_val = self.sample_noise(ox, oy, lattice, use_dot_products).abs();
             let noise_val = settings.ridge_offset.value() - noise_val;
 
             let include = match settings.visualization {
@@ -228,18 +441,53 @@ impl PerlinNoiseImpl {
             };
             if include {
                 let noise_val = noise_val * noise_val * weight;
-                total += noise_val * amplitude;
+                total += noise_val * amplitude * band_limit.weight(frequency);
                 max_value += amplitude;
             }
 
             weight = (noise_val * 2.0).clamp(0.0, 1.0);
-            amplitude *= gain;
+            amplitude *= amplitude_falloff;
             frequency *= lacunarity;
         }
 
         total / max_value
     }
 
+    /// The octaves of `fbm_ridge` at (x, y) in the unrotated coordinates `sample` takes, with the
+    /// weight each one received.
+    fn ridge_octaves(&self, x: f64, y: f64, settings: &PerlinNoiseSettings) -> Vec<RidgeOctave> {
+        let (x, y) = rotate(x, y, self.domain_rotation(settings));
+        let use_dot_products = settings.show_dot_products.value();
+        let lacunarity = settings.lacunarity.value();
+        let amplitude_falloff = octave_gain(
+            settings.gain.value(),
+            lacunarity,
+            settings.h_exponent.value(),
+            settings.spectral_h.value(),
+        );
+        let (mut frequency, mut amplitude, mut weight) = (1.0, 1.0, 1.0);
+        let mut octaves = Vec::new();
+        let decorrelate = self.decorrelates(settings);
+        let band_limit = self.band_limit(settings);
+        for i in 0..settings.octaves.value() {
+            let (ox, oy) = octave_domain(x * frequency, y * frequency, i, decorrelate);
+            let noise_val = self
+                .sample_noise(ox, oy, settings.lattice_type, use_dot_products)
+                .abs();
+            let ridge = settings.ridge_offset.value() - noise_val;
+            octaves.push(RidgeOctave {
+                weight,
+                ridge,
+                contribution: ridge * ridge * weight * amplitude * band_limit.weight(frequency),
+            });
+
+            weight = (ridge * 2.0).clamp(0.0, 1.0);
+            amplitude *= amplitude_falloff;
+            frequency *= lacunarity;
+        }
+        octaves
+    }
+
     pub fn fbm_domain_warp(&self, x: f64, y: f64, settings: &PerlinNoiseSettings) -> f64 {
         let warp_amount = settings.warp_amount.value();
 
@@ -256,21 +504,90 @@ impl PerlinNoiseImpl {
         self.fbm_standard(rx, ry, &adjusted_settings)
     }
 }
+const EXPLAIN_CAPTIONS: [&str; 5] = [
+    "Step 1/5: every corner of the highlighted cell is assigned a pseudo-random gradient vector.",
+    "Step 2/5: distance vectors point from each corner to the sample point inside the cell.",
+    "Step 3/5: each gradient is dotted with its distance vector. Every quadrant shows the dot product of its nearest corner.",
+    "Step 4/5: the dot products are blended along x with the fade curve, giving the top row (upper half) and the bottom row (lower half).",
+    "Step 5/5: the two rows are blended along y, producing the final value of the first octave.",
+];
+const EXPLAIN_PROBE: (f64, f64) = (0.35, 0.65);
+
+thread_local! {
+    static EXPLAIN_STEP: Cell<usize> = const { Cell::new(0) };
+    /// Canvas pixel whose ridge weights are charted.
+    static RIDGE_PROBE: Cell<(f64, f64)> =
+        const { Cell::new((HALF_RESOLUTION as f64, HALF_RESOLUTION as f64)) };
+}
+elements!(
+    (explain_caption, HtmlElement),
+    (ridge_weights_control, HtmlElement),
+    (ridge_weights_chart, HtmlCanvasElement),
+);
+define_event_closure!(perlin_canvas_click, MouseEvent, PerlinNoise::on_canvas_click);
+
 impl PerlinNoise {
-    fn on_setup() {}
+    fn on_setup() {
+        add_callback!(canvas, "click", perlin_canvas_click);
+    }
     fn on_update() {
         let octaves = Octaves::parse().value();
         SHOW_OCTAVE.with(|e| e.set_max(format!("{octaves}").as_str()));
+
+        // The noise type radio hides H where only the gain is used, spectral H applies to every type.
+        if SpectralH::parse().value() {
+            set_hidden!(h_exponent_control, false);
+        }
+
+        let hide_explain = !Explain::parse().value();
+        set_hidden!(explain_next_control, hide_explain);
+
+        // The reference has a table of its own, no seed and only the square lattice.
+        let reference = ReferenceMode::parse().value();
+        set_hidden!(seed_control, reference);
+        set_hidden!(square_lattice_control, reference);
+        set_hidden!(hexagonal_lattice_control, reference);
+        set_hidden!(triangular_lattice_control, reference);
+
+        let hide_ridge_weights =
+            !ShowRidgeWeights::parse().value() || NoiseType::parse() != NoiseType::Ridge;
+        set_hidden!(ridge_weights_control, hide_ridge_weights);
+    }
+    fn on_canvas_click(event: MouseEvent) {
+        if PERLIN.with(|e| e.hidden())
+            || RIDGE_WEIGHTS_CONTROL.with(|e| e.hidden())
+            || !view::is_flat()
+        {
+            return;
+        }
+        RIDGE_PROBE.with(|p| p.set(canvas_coordinates(&event)));
+        Self::update();
+    }
+    fn on_explain_next() {
+        EXPLAIN_STEP.with(|s| s.set((s.get() + 1) % EXPLAIN_CAPTIONS.len()));
+        Self::update();
     }
     fn generate_and_draw(settings: PerlinNoiseSettings) {
-        let perlin = PerlinNoiseImpl::new(settings.seed.value());
+        let perlin = PerlinNoiseImpl::from_settings(&settings, Source::page());
 
-        let coloring = perlin.generate_coloring(settings.clone());
-
-        draw_noise(coloring.as_slice());
+        draw_field(perlin.generate_field(&settings));
 
         if settings.show_grid.value() {
-            draw_grid(settings.scale.value(), "#000000");
+            Self::draw_lattice(&settings);
+        }
+
+        if settings.show_ridge_weights.value() && settings.noise_type == NoiseType::Ridge {
+            Self::draw_ridge_weights(&settings, &perlin);
+        }
+
+        // The explanation and the vectors walk the square lattice with the gradients of the page,
+        // the radio hides them on the other lattices.
+        if settings.lattice_type != LatticeType::SquareLattice || settings.reference_mode.value() {
+            return;
+        }
+
+        if settings.explain.value() {
+            Self::draw_explanation(&settings, &perlin);
         }
 
         if settings.show_vectors.value() {
@@ -278,8 +595,173 @@ impl PerlinNoise {
         }
     }
 
+    /// Number of lattice rows or columns needed on each side of the origin to cover the canvas at
+    /// any rotation.
+    fn lattice_reach(scale: f64) -> i32 {
+        (HALF_RESOLUTION as f64 * std::f64::consts::SQRT_2 / scale / ROW_HEIGHT).ceil() as i32 + 1
+    }
+
+    /// Draws the cells of the lattice the noise is sampled on, turned back by the domain rotation
+    /// and placed like the view so they line up with the image.
+    fn draw_lattice(settings: &PerlinNoiseSettings) {
+        let scale = settings.scale.value();
+        let rotation = settings.domain_rotation.value();
+        let style = overlay::color(Overlay::Grid);
+        if settings.lattice_type == LatticeType::SquareLattice && rotation == 0. {
+            draw_noise_grid(scale, &style);
+            return;
+        }
+
+        let to_screen = |x: f64, y: f64| {
+            let (x, y) = rotate(x, y, -rotation);
+            canvas_position(x, y, scale)
+        };
+        let line = |(x0, y0): (f64, f64), (x1, y1): (f64, f64)| {
+            let ((sx0, sy0), (sx1, sy1)) = (to_screen(x0, y0), to_screen(x1, y1));
+            draw_line(sx0, sy0, sx1, sy1, &style);
+        };
+        let reach = Self::lattice_reach(scale);
+        let far = reach as f64 * 2.;
+
+        match settings.lattice_type {
+            LatticeType::SquareLattice => {
+                for i in -reach..=reach {
+                    line((i as f64, -far), (i as f64, far));
+                    line((-far, i as f64), (far, i as f64));
+                }
+            }
+            // The hexagonal cell around every lattice point, the area its kernel dominates.
+            LatticeType::HexagonalLattice => {
+                let radius = 1. / 3_f64.sqrt();
+                for j in -reach..=reach {
+                    for i in -reach - j.abs()..=reach + j.abs() {
+                        let center_x = i as f64 + j as f64 * 0.5;
+                        let center_y = j as f64 * ROW_HEIGHT;
+                        if center_x.abs() > far {
+                            continue;
+                        }
+                        let corners: Vec<(f64, f64)> = (0..6)
+                            .map(|k| {
+                                let angle = (30. + 60. * k as f64).to_radians();
+                                to_screen(
+                                    center_x + radius * angle.cos(),
+                                    center_y + radius * angle.sin(),
+                                )
+                            })
+                            .collect();
+                        stroke_polygon(&corners, &style);
+                    }
+                }
+            }
+            // The three families of lines that cut the plane into the triangles the corners are
+            // blended over.
+            LatticeType::TriangularLattice => {
+                for j in -reach..=reach {
+                    let y = j as f64 * ROW_HEIGHT;
+                    line((-far, y), (far, y));
+                }
+                for i in -reach * 2..=reach * 2 {
+                    for direction in [0.5, -0.5] {
+                        let (dx, dy) = (direction * far, ROW_HEIGHT * far);
+                        line((i as f64 - dx, -dy), (i as f64 + dx, dy));
+                    }
+                }
+            }
+        }
+    }
+
+    fn draw_explanation(settings: &PerlinNoiseSettings, noise: &PerlinNoiseImpl) {
+        let step = EXPLAIN_STEP.with(|s| s.get());
+        EXPLAIN_CAPTION.with(|c| c.set_inner_text(EXPLAIN_CAPTIONS[step]));
+
+        let scale = settings.scale.value();
+        let origin = HALF_RESOLUTION as f64;
+
+        if step >= 2 {
+            let size = (scale as u32).min(HALF_RESOLUTION);
+            let mut patch = Vec::with_capacity((size * size * 4) as usize);
+            for y in 0..size {
+                for x in 0..size {
+                    let value = noise.explain_value(x as f64 / scale, y as f64 / scale, step);
+                    patch.extend_from_slice(&value_to_color(value));
+                }
+            }
+            draw_patch(&patch, origin, origin, size, size);
+        }
+
+        stroke_rect(origin, origin, scale, scale, "#000000");
+
+        let (probe_x, probe_y) = (
+            origin + EXPLAIN_PROBE.0 * scale,
+            origin + EXPLAIN_PROBE.1 * scale,
+        );
+        let style = overlay::color(Overlay::Vectors);
+        for (corner_x, corner_y) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+            let xf = origin + corner_x as f64 * scale;
+            let yf = origin + corner_y as f64 * scale;
+
+            let (mx, my) = get_perlin_vec(noise.hash(corner_x, corner_y));
+            let offset = scale / 3.0;
+            draw_arrow(xf, yf, xf + mx * offset, yf + my * offset, scale / 8.0, &style);
+
+            if step == 1 {
+                draw_arrow(xf, yf, probe_x, probe_y, scale / 10.0, "#0000ee");
+            }
+        }
+        if step == 1 {
+            draw_circle(probe_x, probe_y, scale / 25.0, "#0000ee");
+        }
+    }
+
+    /// Marks the probed pixel and charts, octave by octave, the weight it received from the octave
+    /// before and the weighted ridge value it adds. A low ridge value, far from the ridges of its
+    /// octave, starves every later octave through the weight.
+    fn draw_ridge_weights(settings: &PerlinNoiseSettings, noise: &PerlinNoiseImpl) {
+        let (probe_x, probe_y) = RIDGE_PROBE.with(Cell::get);
+        let (x, y) = noise_coordinates(probe_x, probe_y, settings.scale.value());
+        let octaves = noise.ridge_octaves(x, y, settings);
+        if view::is_flat() {
+            draw_circle(probe_x, probe_y, 5., "#ffffff");
+            draw_circle(probe_x, probe_y, 3., "#1f77b4");
+        }
+
+        let chart = RIDGE_WEIGHTS_CHART.with(|c| Chart::new(c));
+        chart.clear("#ffffff");
+        let (margin, bottom) = (24., chart.height - 24.);
+        let plot_height = bottom - margin;
+        // Ridge values reach the squared offset, weights stay in [0, 1].
+        let top = octaves
+            .iter()
+            .map(|o| o.ridge * o.ridge * o.weight)
+            .fold(1f64, f64::max);
+        let slot = (chart.width - 2. * margin) / octaves.len().max(1) as f64;
+        let bar_width = slot * 0.35;
+        chart.line((margin, bottom), (chart.width - margin, bottom), "#888888");
+        for (i, octave) in octaves.iter().enumerate() {
+            let left = margin + i as f64 * slot + slot * 0.15;
+            let weighted = octave.ridge * octave.ridge * octave.weight;
+            for (k, (value, style)) in [(octave.weight, "#1f77b4"), (weighted, "#ff7f0e")]
+                .into_iter()
+                .enumerate()
+            {
+                let height = value / top * plot_height;
+                chart.rect(left + k as f64 * bar_width, bottom - height, bar_width, height, style);
+            }
+            chart.text(&format!("{}", i + 1), left + bar_width * 0.7, bottom + 14., "#444444");
+        }
+        chart.text("weight received", margin, 12., "#1f77b4");
+        chart.text("ridge² · weight", margin + 100., 12., "#ff7f0e");
+        let total: f64 = octaves.iter().map(|o| o.contribution).sum();
+        chart.text(&format!("sum {total:.3}"), chart.width - 70., 12., "#444444");
+    }
+
     fn draw_gradient_vectors(settings: &PerlinNoiseSettings, noise: PerlinNoiseImpl) {
         let scale = settings.scale.value();
+        // Positions and gradients are turned back by the domain rotation to match the image.
+        let rotation = -settings.domain_rotation.value();
+        let origin = HALF_RESOLUTION as f64;
+        let style = overlay::color(Overlay::Vectors);
+        let length = overlay::vector_length();
 
         for i in 0..settings.octaves.value() {
             let octave_scale = scale / 2_f64.powi(i as i32);
@@ -287,14 +769,19 @@ impl PerlinNoise {
 
             for x in -half_range..=half_range {
                 for y in -half_range..=half_range {
-                    let xf = HALF_RESOLUTION as f64 - x as f64 * octave_scale;
-                    let yf = HALF_RESOLUTION as f64 - y as f64 * octave_scale;
+                    if !overlay::shows_vector(x as i32, y as i32) {
+                        continue;
+                    }
+                    let (xf, yf) =
+                        rotate(-x as f64 * octave_scale, -y as f64 * octave_scale, rotation);
+                    let (xf, yf) = (origin + xf, origin + yf);
 
-                    let offset = octave_scale / 3.0;
+                    let offset = octave_scale / 3.0 * length;
                     let (mx, my) = get_perlin_vec(noise.hash(x as i32, y as i32));
+                    let (mx, my) = rotate(mx, my, rotation);
                     let (tx, ty) = (xf + mx * offset, yf + my * offset);
 
-                    draw_arrow(xf, yf, tx, ty, octave_scale / 5.0, "#ee0000");
+                    draw_arrow(xf, yf, tx, ty, octave_scale / 5.0 * length, &style);
                 }
             }
         }
@@ -304,14 +791,15 @@ impl PerlinNoise {
 define_noise!(perlin,
     sliders:[
         (seed, u32, 0., 42., 1000.),
-        (scale, f64, 10., 50., 200.),
+        (scale, f64, 1., 50., 200.),
         (octaves, u32, 1., 1., 8.),
         (lacunarity, f64, 1., 2., 4.),
         (gain, f64, 0., 0.5, 1.),
         (h_exponent, f64, 0., 1., 2.),
         (ridge_offset, f64, 0., 1., 2.),
         (warp_amount, f64, 0., 4.0, 10.),
-        (show_octave, u32, 1., 1., 8.)
+        (show_octave, u32, 1., 1., 8.),
+        (domain_rotation, f64, 0., 30., 90., Degrees)
     ];
     radios:[
         (visualization, 
@@ -320,11 +808,276 @@ define_noise!(perlin,
             (accumulated_octaves)
         ),
         (noise_type, 
-            (standard, hide: [ridge_offset, warp_amount]), 
-            (turbulence, hide:[h_exponent, ridge_offset, warp_amount]), 
-            (ridge, hide:[h_exponent, warp_amount]), 
-            (domain_warp, hide:[h_exponent, ridge_offset])
+            (standard, hide: [ridge_offset, warp_amount, warp_amount_scale_pad, show_ridge_weights]), 
+            (turbulence, hide:[h_exponent, ridge_offset, warp_amount, warp_amount_scale_pad, show_ridge_weights]), 
+            (ridge, hide:[h_exponent, warp_amount, warp_amount_scale_pad]), 
+            (domain_warp, hide:[h_exponent, ridge_offset, show_ridge_weights])
+        ),
+        (lattice_type,
+            (square_lattice),
+            (hexagonal_lattice, hide:[show_vectors, show_dot_products, explain]),
+            (triangular_lattice, hide:[show_vectors, show_dot_products, explain])
         )
     ];
-    checkboxes:[show_grid, show_vectors, show_dot_products];
+    checkboxes:[show_grid, show_vectors, show_dot_products, explain, spectral_h, auto_band_limit, show_ridge_weights, decorrelate_octaves, reference_mode];
+    buttons:[explain_next];
+    pads:[(lacunarity, gain), (warp_amount, scale)];
+    cell_size: 1.0;
 );
+
+impl PerlinNoise {
+    /// The shown settings in the form the shader and code exports bake in.
+    pub fn shader() -> Result<shader::Fbm, String> {
+        let settings = PerlinNoiseSettings::parse();
+        if settings.lattice_type != LatticeType::SquareLattice || settings.show_dot_products.value()
+        {
+            return Err(
+                "Only Perlin noise on the square lattice, without dot products, can be exported"
+                    .to_string(),
+            );
+        }
+        if settings.decorrelate_octaves.value() {
+            return Err("Octaves turned and shifted apart cannot be exported".to_string());
+        }
+        if settings.reference_mode.value() {
+            return Err("The reference mode cannot be exported".to_string());
+        }
+        let octaves = settings.octaves.value();
+        let shown = settings.show_octave.value().min(octaves);
+        Ok(shader::Fbm {
+            base: shader::Base::Perlin,
+            seed: settings.seed.value(),
+            fractal: match settings.noise_type {
+                NoiseType::Standard => Fractal::Standard,
+                NoiseType::Turbulence => Fractal::Turbulence,
+                NoiseType::Ridge => Fractal::Ridge,
+                NoiseType::DomainWarp => Fractal::DomainWarp,
+            },
+            octaves,
+            shown_octaves: match settings.visualization {
+                Visualization::Final => (1, octaves),
+                Visualization::SingleOctave => (shown, shown),
+                Visualization::AccumulatedOctaves => (1, shown),
+            },
+            lacunarity: settings.lacunarity.value(),
+            gain: settings.gain.value(),
+            h_exponent: settings.h_exponent.value(),
+            spectral_h: settings.spectral_h.value(),
+            ridge_offset: settings.ridge_offset.value(),
+            warp_amount: settings.warp_amount.value(),
+            rotation_degrees: settings.domain_rotation.value(),
+            scale: settings.scale.value(),
+        })
+    }
+}
+
+/// Perlin noise for use outside the page, see [`crate::core`].
+pub struct Perlin {
+    noise: PerlinNoiseImpl,
+    settings: PerlinNoiseSettings,
+    source: Source,
+}
+
+impl Perlin {
+    pub fn new(seed: u32) -> Self {
+        let mut settings = PerlinNoiseSettings::defaults();
+        settings.seed = Seed(seed);
+        let source = Source::default();
+        let noise = PerlinNoiseImpl::new(seed, source);
+        Perlin {
+            noise,
+            settings,
+            source,
+        }
+    }
+
+    pub fn fractal(mut self, fractal: Fractal) -> Self {
+        self.settings.noise_type = match fractal {
+            Fractal::Standard => NoiseType::Standard,
+            Fractal::Turbulence => NoiseType::Turbulence,
+            Fractal::Ridge => NoiseType::Ridge,
+            Fractal::DomainWarp => NoiseType::DomainWarp,
+        };
+        self
+    }
+
+    pub fn lattice(mut self, lattice: Lattice) -> Self {
+        self.settings.lattice_type = match lattice {
+            Lattice::Square => LatticeType::SquareLattice,
+            Lattice::Hexagonal => LatticeType::HexagonalLattice,
+            Lattice::Triangular => LatticeType::TriangularLattice,
+        };
+        self
+    }
+
+    /// Ken Perlin's improved noise to the last bit, with his table instead of the seed's.
+    pub fn reference_mode(mut self, reference: bool) -> Self {
+        self.settings.reference_mode = ReferenceMode(reference);
+        self.rebuild();
+        self
+    }
+
+    fn rebuild(&mut self) {
+        self.noise = PerlinNoiseImpl::from_settings(&self.settings, self.source);
+    }
+
+    source_builder!();
+
+    settings_builder!(
+        octaves: u32 = 1..,
+        lacunarity: f64,
+        gain: f64,
+        h_exponent: f64,
+        ridge_offset: f64,
+        warp_amount: f64,
+        domain_rotation: f64,
+        spectral_h: bool,
+        auto_band_limit: bool,
+        decorrelate_octaves: bool,
+    );
+
+    /// The defaults with a settings file's values applied, see [`crate::core::from_settings`].
+    pub fn from_pairs(pairs: &[(String, String)]) -> Result<Self, String> {
+        let settings = PerlinNoiseSettings::from_pairs(pairs)?;
+        let source = Source::default();
+        let noise = PerlinNoiseImpl::from_settings(&settings, source);
+        Ok(Perlin {
+            noise,
+            settings,
+            source,
+        })
+    }
+}
+
+impl Sampler for Perlin {
+    fn sample(&self, x: f64, y: f64) -> f64 {
+        self.noise.sample(x, y, &self.settings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::golden::assert_matches_golden;
+    use crate::noises::helpers::sample_points;
+
+    #[test]
+    fn zero_at_lattice_points() {
+        let noise = PerlinNoiseImpl::new(42, Source::default());
+        for x in -20..20 {
+            for y in -20..20 {
+                for use_dot_products in [false, true] {
+                    assert_eq!(
+                        noise.sample_noise(
+                            x as f64,
+                            y as f64,
+                            LatticeType::SquareLattice,
+                            use_dot_products
+                        ),
+                        0.0
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn zero_at_skewed_lattice_points() {
+        let noise = PerlinNoiseImpl::new(42, Source::default());
+        for i in -20..20 {
+            for j in -20..20 {
+                let (x, y) = (i as f64 + j as f64 * 0.5, j as f64 * ROW_HEIGHT);
+                for lattice in [LatticeType::HexagonalLattice, LatticeType::TriangularLattice] {
+                    let v = noise.sample_noise(x, y, lattice, false);
+                    assert!(v.abs() < 1e-9, "{v} at lattice point ({i}, {j})");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn stays_within_bounds() {
+        for seed in [0, 42, 999] {
+            let noise = PerlinNoiseImpl::new(seed, Source::default());
+            for (x, y) in sample_points(20_000, 300.0, seed as u64) {
+                let v = noise.sample_noise(x, y, LatticeType::SquareLattice, false);
+                assert!(v.abs() <= 1.0, "{v} at ({x}, {y})");
+                for lattice in [LatticeType::HexagonalLattice, LatticeType::TriangularLattice] {
+                    let v = noise.sample_noise(x, y, lattice, false);
+                    assert!(v.abs() <= 1.0, "{v} at ({x}, {y}) on a skewed lattice");
+                }
+                // A single corner's dot product with faded offsets, diagonal gradients reach 2.
+                let v = noise.sample_noise(x, y, LatticeType::SquareLattice, true);
+                assert!(v.abs() <= 2.0, "{v} at ({x}, {y}) blending dot products");
+            }
+        }
+    }
+
+    #[test]
+    // 3.14 is the point of the canonical test value, not an approximation of pi.
+    #[allow(clippy::approx_constant)]
+    fn reference_mode_matches_ken_perlins_implementation() {
+        let noise = PerlinNoiseImpl::reference();
+        // What his Java ImprovedNoise prints, and the value ports test themselves against.
+        assert_eq!(noise.reference_noise(3.14, 42., 7.), 0.13691995878400012);
+        for (x, y) in sample_points(1000, 300.0, 3) {
+            let v = noise.sample_noise(x, y, LatticeType::HexagonalLattice, true);
+            assert_eq!(v, noise.reference_noise(x, y, REFERENCE_PLANE));
+            assert!(v.abs() <= 1.0, "{v} at ({x}, {y})");
+            let corner = noise.reference_noise(x.floor(), y.floor(), REFERENCE_PLANE);
+            assert_eq!(corner, 0.);
+        }
+    }
+
+    #[test]
+    // 3.14 is the point of the canonical test value, not an approximation of pi.
+    #[allow(clippy::approx_constant)]
+    fn reference_mode_ignores_the_page_transforms() {
+        let mut settings = PerlinNoiseSettings::defaults();
+        settings.reference_mode = ReferenceMode(true);
+        let noise = PerlinNoiseImpl::from_settings(&settings, Source::default());
+        assert_eq!(noise.sample(3.14, 42., &settings), 0.13691995878400012);
+
+        settings.octaves = Octaves(4);
+        let plain = noise.sample(3.14, 42., &settings);
+        settings.decorrelate_octaves = DecorrelateOctaves(true);
+        settings.auto_band_limit = AutoBandLimit(true);
+        settings.scale = Scale(1.);
+        assert_eq!(noise.sample(3.14, 42., &settings), plain);
+    }
+
+    #[test]
+    fn ridge_octaves_add_up_to_ridged_fbm() {
+        let noise = PerlinNoiseImpl::new(42, Source::default());
+        let mut settings = PerlinNoiseSettings::defaults();
+        settings.octaves = Octaves(6);
+        settings.noise_type = NoiseType::Ridge;
+        let max_value: f64 = (0..6).map(|i| 0.5f64.powi(i)).sum();
+        // At scale 10 the band limit fades the finest octaves out.
+        for (band_limit, scale) in [(false, 50.), (true, 10.)] {
+            settings.auto_band_limit = AutoBandLimit(band_limit);
+            settings.scale = Scale(scale);
+            for (x, y) in sample_points(200, 20.0, 7) {
+                let octaves = noise.ridge_octaves(x, y, &settings);
+                assert_eq!(octaves[0].weight, 1.0);
+                for pair in octaves.windows(2) {
+                    assert_eq!(pair[1].weight, (pair[0].ridge * 2.0).clamp(0.0, 1.0));
+                }
+                let total: f64 = octaves.iter().map(|o| o.contribution).sum();
+                let v = noise.sample(x, y, &settings);
+                assert!((total / max_value - v).abs() < 1e-12, "{v} at ({x}, {y})");
+            }
+        }
+    }
+
+    #[test]
+    fn renders_like_golden() {
+        let noise = PerlinNoiseImpl::new(42, Source::default());
+        let mut settings = PerlinNoiseSettings::defaults();
+        assert_matches_golden("perlin", &noise.generate_field(&settings));
+
+        settings.octaves = Octaves(5);
+        settings.noise_type = NoiseType::Ridge;
+        assert_matches_golden("perlin_ridge", &noise.generate_field(&settings));
+    }
+}
@@ -1,28 +1,76 @@
-use std::cell::LazyCell;
+use std::{
+    cell::{Cell, LazyCell},
+    f64::consts::TAU,
+};
 
 use wasm_bindgen::{JsCast, prelude::Closure};
-use web_sys::{HtmlElement, HtmlInputElement};
+use web_sys::{HtmlElement, HtmlInputElement, MouseEvent};
 
 use super::noise::Noise;
 use crate::{
-    drawer::{IMAGE_BYTES_COUNT, draw_arrow},
-    noises::helpers::{lerp, perlin_grad, shuffle},
+    core::{Fractal, Gradients, Sampler},
+    drawer::{
+        CANVAS, canvas_coordinates, draw_arrow, draw_circle, draw_patch,
+        fill_polygon, stroke_polygon, value_to_color,
+    },
+    noises::helpers::{BandLimit, get_perlin_vec, octave_domain, octave_gain, Source},
+    overlay::{self, Overlay},
+    field::{canvas_position, draw_field, draw_noise_grid, noise_coordinates, sample_field},
     *,
 };
 
+/// Distance between two neighbouring simplex vertices once the skewed grid is unskewed, √(2/3).
+const LATTICE_EDGE: f64 = 0.816_496_580_927_726;
+
+/// The 12 gradients of 3D simplex noise, the midpoints of the edges of a cube, with their z
+/// dropped. Reference 2D implementations pick from these, so the axes come up twice as often as
+/// the diagonals.
+const GRADIENTS_12: [(f64, f64); 12] = [
+    (1., 1.),
+    (-1., 1.),
+    (1., -1.),
+    (-1., -1.),
+    (1., 0.),
+    (-1., 0.),
+    (1., 0.),
+    (-1., 0.),
+    (0., 1.),
+    (0., -1.),
+    (0., 1.),
+    (0., -1.),
+];
+
+/// The gradient a corner with this hash gets. The fewer directions a set has, the more its
+/// artifacts line up with them.
+fn gradient(set: GradientSet, hash: usize) -> (f64, f64) {
+    match set {
+        GradientSet::EightGradients => get_perlin_vec(hash),
+        GradientSet::TwelveGradients => GRADIENTS_12[hash % GRADIENTS_12.len()],
+        GradientSet::RandomGradients => {
+            let angle = TAU * (hash & 255) as f64 / 256.;
+            (angle.cos(), angle.sin())
+        }
+    }
+}
+
 struct SimplexNoiseImpl {
     permutation: [usize; 256],
+    /// Gradient of every hash value, looked up instead of computed for every corner.
+    gradients: [(f64, f64); 256],
 }
 
 impl SimplexNoiseImpl {
     const F2: f64 = 0.3660254037844386; // (sqrt(3) - 1) / 2 Because .sqrt() is not const. Why?!
     const G2: f64 = 0.21132486540518708; // (1 - 1/sqrt(3)) / 2
 
-    pub fn new(seed: u32) -> Self {
-        let mut permutation: [usize; 256] = std::array::from_fn(|i| i);
-        shuffle(&mut permutation, seed);
+    pub fn new(seed: u32, gradient_set: GradientSet, source: Source) -> Self {
+        let permutation = source.permutation(seed);
+        let gradients = std::array::from_fn(|hash| gradient(gradient_set, hash));
 
-        SimplexNoiseImpl { permutation }
+        SimplexNoiseImpl {
+            permutation,
+            gradients,
+        }
     }
 
     #[inline]
@@ -30,6 +78,17 @@ impl SimplexNoiseImpl {
         self.permutation[i & 255]
     }
 
+    #[inline]
+    fn gradient(&self, hash: usize) -> (f64, f64) {
+        self.gradients[hash & 255]
+    }
+
+    #[inline]
+    fn grad(&self, hash: usize, x: f64, y: f64) -> f64 {
+        let (gx, gy) = self.gradient(hash);
+        gx * x + gy * y
+    }
+
     fn noise_val(&self, x: f64, y: f64) -> f64 {
         let s = (x + y) * Self::F2;
         let i = (x + s).floor();
@@ -54,8 +113,8 @@ impl SimplexNoiseImpl {
         let x2 = x0 - 1.0 + 2.0 * Self::G2;
         let y2 = y0 - 1.0 + 2.0 * Self::G2;
 
-        let ii = i as i32 as usize;
-        let jj = j as i32 as usize;
+        let ii = (i as i32 & 255) as usize;
+        let jj = (j as i32 & 255) as usize;
 
         let gi0 = self.get_perm(ii + self.get_perm(jj));
         let gi1 = self.get_perm(ii + i1 + self.get_perm(jj + j1));
@@ -68,58 +127,35 @@ impl SimplexNoiseImpl {
         let t0 = 0.5 - x0 * x0 - y0 * y0;
         if t0 >= 0.0 {
             let t0_sq = t0 * t0;
-            n0 = t0_sq * t0_sq * perlin_grad(gi0, x0, y0);
+            n0 = t0_sq * t0_sq * self.grad(gi0, x0, y0);
         }
 
         let t1 = 0.5 - x1 * x1 - y1 * y1;
         if t1 >= 0.0 {
             let t1_sq = t1 * t1;
-            n1 = t1_sq * t1_sq * perlin_grad(gi1, x1, y1);
+            n1 = t1_sq * t1_sq * self.grad(gi1, x1, y1);
         }
 
         let t2 = 0.5 - x2 * x2 - y2 * y2;
         if t2 >= 0.0 {
             let t2_sq = t2 * t2;
-            n2 = t2_sq * t2_sq * perlin_grad(gi2, x2, y2);
+            n2 = t2_sq * t2_sq * self.grad(gi2, x2, y2);
         }
 
         70.0 * (n0 + n1 + n2)
     }
 
-    fn generate_coloring(
-        &self,
-        settings: &SimplexNoiseSettings,
-    ) -> Vec<u8> {
-        let scale = settings.scale.value();
+    fn generate_field(&self, settings: &SimplexNoiseSettings) -> Vec<f64> {
+        sample_field(settings.scale.value(), |nx, ny| self.sample(nx, ny, settings))
+    }
 
-        let mut v = Vec::with_capacity(IMAGE_BYTES_COUNT as usize);
-
-        for y in 0..RESOLUTION {
-            for x in 0..RESOLUTION {
-                let nx = (x as f64 - HALF_RESOLUTION as f64) / scale;
-                let ny = (y as f64 - HALF_RESOLUTION as f64) / scale;
-
-                let noise_val = match settings.noise_type {
-                    NoiseType::Standard => self.fbm_standard(nx, ny, settings),
-                    NoiseType::Turbulence => self.fbm_turbulence(nx, ny, settings),
-                    NoiseType::Ridge => self.fbm_ridge(nx, ny, settings),
-                    NoiseType::DomainWarp => self.fbm_domain_warp(nx, ny, settings),
-                };
-
-                let (r, g, b) = if noise_val < 0.0 {
-                    let t = noise_val + 1.0;
-                    (255, lerp(t, 0.0, 255.0) as u8, 255)
-                } else {
-                    let t = (noise_val + 1.0) * 0.5 - 0.5;
-                    let t = t * 2.0;
-                    let val = lerp(t, 255.0, 0.0) as u8;
-                    (val, 255, val)
-                };
-
-                v.extend_from_slice(&[r, g, b, 255]);
-            }
+    fn sample(&self, x: f64, y: f64, settings: &SimplexNoiseSettings) -> f64 {
+        match settings.noise_type {
+        NoiseType::Standard => self.fbm_standard(x, y, settings),
+        NoiseType::Turbulence => self.fbm_turbulence(x, y, settings),
+        NoiseType::Ridge => self.fbm_ridge(x, y, settings),
+        NoiseType::DomainWarp => self.fbm_domain_warp(x, y, settings),
         }
-        v
     }
 
     fn get_simplex_corners(&self, x: f64, y: f64) -> SimplexCorners {
@@ -136,8 +172,8 @@ impl SimplexNoiseImpl {
 
         let (i1, j1) = if x0 > y0 { (1, 0) } else { (0, 1) };
 
-        let ii = i as i32 as usize;
-        let jj = j as i32 as usize;
+        let ii = (i as i32 & 255) as usize;
+        let jj = (j as i32 & 255) as usize;
 
         let gi0 = self.get_perm(ii + self.get_perm(jj));
         let gi1 = self.get_perm(ii + i1 + self.get_perm(jj + j1));
@@ -152,6 +188,48 @@ impl SimplexNoiseImpl {
         }
     }
 
+    fn explain_point(&self, x: f64, y: f64) -> SimplexExplanation {
+        let s = (x + y) * Self::F2;
+        let i = (x + s).floor();
+        let j = (y + s).floor();
+
+        let corners = self.get_simplex_corners(x, y);
+        let skewed = [
+            (0., 0.),
+            (corners.i1 as f64, corners.j1 as f64),
+            (1., 1.),
+        ];
+        let gradients = [corners.gi0, corners.gi1, corners.gi2].map(|gi| self.gradient(gi));
+
+        let unskew = |(di, dj): (f64, f64)| {
+            let t = (i + di + j + dj) * Self::G2;
+            (i + di - t, j + dj - t)
+        };
+        let cell = [(0., 0.), (1., 0.), (1., 1.), (0., 1.)].map(unskew);
+        let corners = skewed.map(unskew);
+        let contributions = std::array::from_fn(|k| {
+            Self::corner_contribution(gradients[k], x - corners[k].0, y - corners[k].1)
+        });
+
+        SimplexExplanation {
+            cell,
+            corners,
+            gradients,
+            contributions,
+        }
+    }
+
+    #[inline]
+    fn corner_contribution((gx, gy): (f64, f64), dx: f64, dy: f64) -> f64 {
+        let t = 0.5 - dx * dx - dy * dy;
+        if t >= 0.0 {
+            let t_sq = t * t;
+            t_sq * t_sq * (gx * dx + gy * dy)
+        } else {
+            0.0
+        }
+    }
+
     pub fn fbm_standard(&self, x: f64, y: f64, settings: &SimplexNoiseSettings) -> f64 {
         let mut total = 0.0;
         let mut frequency = 1.0;
@@ -160,12 +238,17 @@ impl SimplexNoiseImpl {
 
         let octaves = settings.octaves.value();
         let show_octave = settings.show_octave.value();
+        let decorrelate = settings.decorrelate_octaves.value();
         let gain = settings.gain.value();
         let h_exponent = settings.h_exponent.value();
         let lacunarity = settings.lacunarity.value();
+        let band_limit = BandLimit::new(settings.scale.value(), settings.auto_band_limit.value());
+        let amplitude_falloff =
+            octave_gain(gain.powf(h_exponent), lacunarity, h_exponent, settings.spectral_h.value());
 
         for i in 1..=octaves {
-            let noise_val = self.noise_val(x * frequency, y * frequency);
+            let (ox, oy) = octave_domain(x * frequency, y * frequency, i - 1, decorrelate);
+            let noise_val = self.noise_val(ox, oy);
 
             let include = match settings.visualization {
                 Visualization::Final => true,
@@ -173,10 +256,10 @@ impl SimplexNoiseImpl {
                 Visualization::AccumulatedOctaves => i <= show_octave,
             };
             if include {
-                total += noise_val * amplitude;
+                total += noise_val * amplitude * band_limit.weight(frequency);
                 max_value += amplitude;
             }
-            amplitude *= gain.powf(h_exponent);
+            amplitude *= amplitude_falloff;
             frequency *= lacunarity;
         }
 
@@ -191,13 +274,16 @@ impl SimplexNoiseImpl {
 
         let octaves = settings.octaves.value();
         let show_octave = settings.show_octave.value();
+        let decorrelate = settings.decorrelate_octaves.value();
         let gain = settings.gain.value();
         let lacunarity = settings.lacunarity.value();
+        let band_limit = BandLimit::new(settings.scale.value(), settings.auto_band_limit.value());
+        let amplitude_falloff =
+            octave_gain(gain, lacunarity, settings.h_exponent.value(), settings.spectral_h.value());
 
         for i in 1..=octaves {
-            let noise_val = self
-                .noise_val(x * frequency, y * frequency)
-                .abs();
+            let (ox, oy) = octave_domain(x * frequency, y * frequency, i - 1, decorrelate);
+            let noise_val = self.noise_val(ox, oy).abs();
 
             let include = match settings.visualization {
                 Visualization::Final => true,
@@ -205,10 +291,10 @@ impl SimplexNoiseImpl {
                 Visualization::AccumulatedOctaves => i <= show_octave,
             };
             if include {
-                total += noise_val * amplitude;
+                total += noise_val * amplitude * band_limit.weight(frequency);
                 max_value += amplitude;
             }
-            amplitude *= gain;
+            amplitude *= amplitude_falloff;
             frequency *= lacunarity;
         }
 
@@ -224,12 +310,15 @@ impl SimplexNoiseImpl {
 
         let octaves = settings.octaves.value();
         let show_octave = settings.show_octave.value();
+        let decorrelate = settings.decorrelate_octaves.value();
         let gain = settings.gain.value();
         let lacunarity = settings.lacunarity.value();
+        let band_limit = BandLimit::new(settings.scale.value(), settings.auto_band_limit.value());
+        let amplitude_falloff =
+            octave_gain(gain, lacunarity, settings.h_exponent.value(), settings.spectral_h.value());
         for i in 1..=octaves {
-            let noise_val = self
-                .noise_val(x * frequency, y * frequency)
-                .abs();
+            let (ox, oy) = octave_domain(x * frequency, y * frequency, i - 1, decorrelate);
+            let noise_val = self.noise_val(ox, oy).abs();
             let noise_val = settings.ridge_offset.value() - noise_val;
 
             let include = match settings.visualization {
@@ -239,12 +328,12 @@ impl SimplexNoiseImpl {
             };
             if include {
                 let noise_val = noise_val * noise_val * weight;
-                total += noise_val * amplitude;
+                total += noise_val * amplitude * band_limit.weight(frequency);
                 max_value += amplitude;
             }
 
             weight = (noise_val * 2.0).clamp(0.0, 1.0);
-            amplitude *= gain;
+            amplitude *= amplitude_falloff;
             frequency *= lacunarity;
         }
 
@@ -276,26 +365,143 @@ struct SimplexCorners {
     gi2: usize,
 }
 
+struct SimplexExplanation {
+    cell: [(f64, f64); 4],
+    corners: [(f64, f64); 3],
+    gradients: [(f64, f64); 3],
+    contributions: [f64; 3],
+}
+
+const EXPLAIN_STEPS: usize = 6;
+
+thread_local! {
+    static EXPLAIN_STEP: Cell<usize> = const { Cell::new(0) };
+    static EXPLAIN_POINT: Cell<(f64, f64)> = const { Cell::new((0.3, 0.2)) };
+}
+elements!((explain_caption, HtmlElement));
+define_event_closure!(simplex_canvas_click, MouseEvent, SimplexNoise::on_canvas_click);
+
 impl SimplexNoise {
-    fn on_setup(){}
+    fn on_setup() {
+        add_callback!(canvas, "click", simplex_canvas_click);
+    }
     fn on_update() {
         let octaves = Octaves::parse().value();
         SHOW_OCTAVE.with(|e| e.set_max(format!("{octaves}").as_str()));
+
+        // The noise type radio hides H where only the gain is used, spectral H applies to every type.
+        if SpectralH::parse().value() {
+            set_hidden!(h_exponent_control, false);
+        }
+
+        let hide_explain = !Explain::parse().value();
+        set_hidden!(explain_next_control, hide_explain);
+    }
+    fn on_explain_next() {
+        EXPLAIN_STEP.with(|s| s.set((s.get() + 1) % EXPLAIN_STEPS));
+        Self::update();
+    }
+    fn on_canvas_click(event: MouseEvent) {
+        if SIMPLEX.with(|e| e.hidden()) || !Explain::parse().value() || !view::is_flat() {
+            return;
+        }
+        let scale = Scale::parse().value();
+        let (x, y) = canvas_coordinates(&event);
+        EXPLAIN_POINT.with(|p| p.set(noise_coordinates(x, y, scale)));
+        EXPLAIN_STEP.with(|s| s.set(0));
+        Self::update();
     }
     fn generate_and_draw(settings: SimplexNoiseSettings) {
-        let simplex = SimplexNoiseImpl::new(settings.seed.value());
+        let simplex =
+            SimplexNoiseImpl::new(settings.seed.value(), settings.gradient_set, Source::page());
 
-        let visualization = simplex.generate_coloring(&settings);
-
-        draw_noise(&visualization);
+        draw_field(simplex.generate_field(&settings));
 
         if settings.show_grid.value() {
-            draw_grid(settings.scale.value(), "#000000");
+            draw_noise_grid(settings.scale.value(), &overlay::color(Overlay::Grid));
         }
 
         if settings.show_vectors.value() {
             Self::draw_gradient_vectors(&simplex, &settings);
         }
+
+        if settings.explain.value() {
+            Self::draw_explanation(&simplex, &settings);
+        }
+    }
+
+    fn draw_explanation(simplex: &SimplexNoiseImpl, settings: &SimplexNoiseSettings) {
+        let step = EXPLAIN_STEP.with(|s| s.get());
+        let (x, y) = EXPLAIN_POINT.with(|p| p.get());
+        let explanation = simplex.explain_point(x, y);
+
+        let scale = settings.scale.value();
+        let to_screen = |(px, py): (f64, f64)| canvas_position(px, py, scale);
+        let point = to_screen((x, y));
+        let corners = explanation.corners.map(to_screen);
+
+        let caption = match step {
+            0 => "Step 1/6: the clicked point is skewed onto a square lattice. The highlighted rhombus is the skewed cell that contains it.".to_string(),
+            1 => "Step 2/6: the diagonal splits the cell into two triangles. Comparing the point's x and y offsets picks the triangle (simplex) to use.".to_string(),
+            2..=4 => {
+                let k = step - 2;
+                format!(
+                    "Step {}/6: corner {} contributes (0.5 - d²)⁴ · (g · d) = {:.4}. Its influence fades to zero at radius √0.5.",
+                    step + 1,
+                    k + 1,
+                    explanation.contributions[k]
+                )
+            }
+            _ => format!(
+                "Step 6/6: the three contributions are summed and scaled by 70, giving {:.4} for the first octave.",
+                70.0 * explanation.contributions.iter().sum::<f64>()
+            ),
+        };
+        EXPLAIN_CAPTION.with(|c| c.set_inner_text(&caption));
+
+        if let 2..=4 = step {
+            let k = step - 2;
+            let radius = 0.5_f64.sqrt() * scale;
+            let size = (2.0 * radius).ceil() as u32;
+            let (corner_x, corner_y) = explanation.corners[k];
+
+            let mut patch = Vec::with_capacity((size * size * 4) as usize);
+            for py in 0..size {
+                for px in 0..size {
+                    let dx = (px as f64 - radius) / scale;
+                    let dy = (py as f64 - radius) / scale;
+                    let value = SimplexNoiseImpl::corner_contribution(
+                        explanation.gradients[k],
+                        dx,
+                        dy,
+                    );
+                    patch.extend_from_slice(&value_to_color(70.0 * value));
+                }
+            }
+            let (screen_x, screen_y) = to_screen((corner_x, corner_y));
+            draw_patch(&patch, screen_x - radius, screen_y - radius, size, size);
+        }
+
+        stroke_polygon(&explanation.cell.map(to_screen), "#000000");
+        if step >= 1 {
+            fill_polygon(&corners, "rgba(0, 0, 238, 0.2)");
+            stroke_polygon(&corners, "#0000ee");
+        }
+
+        for (k, &(corner_x, corner_y)) in corners.iter().enumerate() {
+            let highlighted = step == 5 || step == k + 2;
+            if step >= 2 && !highlighted {
+                continue;
+            }
+            let (mx, my) = explanation.gradients[k];
+            let offset = scale / 3.0;
+            let (tx, ty) = (corner_x + mx * offset, corner_y + my * offset);
+            draw_arrow(corner_x, corner_y, tx, ty, scale / 8.0, &overlay::color(Overlay::Vectors));
+            if step >= 2 {
+                draw_arrow(corner_x, corner_y, point.0, point.1, scale / 10.0, "#0000ee");
+            }
+        }
+        draw_circle(point.0, point.1, 4.0, "#0000ee");
     }
 
     fn draw_gradient_vectors(
@@ -303,6 +509,7 @@ impl SimplexNoise {
         settings: &SimplexNoiseSettings,
     ) {
         let scale = settings.scale.value();
+        let length = overlay::vector_length();
 
         for octave in 0..settings.octaves.value() {
             let octave_scale = scale / 2_f64.powi(octave as i32);
@@ -310,6 +517,9 @@ impl SimplexNoise {
 
             for gx in -half_range..=half_range {
                 for gy in -half_range..=half_range {
+                    if !overlay::shows_vector(gx as i32, gy as i32) {
+                        continue;
+                    }
                     let world_x = gx as f64 * octave_scale;
                     let world_y = gy as f64 * octave_scale;
 
@@ -318,44 +528,37 @@ impl SimplexNoise {
 
                     let corners = simplex.get_simplex_corners(nx, ny);
 
-                    let offset = octave_scale / 3.0;
+                    let offset = octave_scale / 3.0 * length;
 
                     let screen_x = HALF_RESOLUTION as f64 + world_x;
                     let screen_y = HALF_RESOLUTION as f64 + world_y;
-                    Self::draw_gradient_arrow(screen_x, screen_y, corners.gi0, offset);
+                    let gradient = simplex.gradient(corners.gi0);
+                    Self::draw_gradient_arrow(screen_x, screen_y, gradient, offset);
 
                     let screen_x1 = screen_x + corners.i1 as f64 * octave_scale;
                     let screen_y1 = screen_y + corners.j1 as f64 * octave_scale;
-                    Self::draw_gradient_arrow(screen_x1, screen_y1, corners.gi1, offset);
+                    let gradient = simplex.gradient(corners.gi1);
+                    Self::draw_gradient_arrow(screen_x1, screen_y1, gradient, offset);
 
                     let screen_x2 = screen_x + octave_scale;
                     let screen_y2 = screen_y + octave_scale;
-                    Self::draw_gradient_arrow(screen_x2, screen_y2, corners.gi2, offset);
+                    let gradient = simplex.gradient(corners.gi2);
+                    Self::draw_gradient_arrow(screen_x2, screen_y2, gradient, offset);
                 }
             }
         }
     }
 
-    fn draw_gradient_arrow(xf: f64, yf: f64, gi: usize, offset: f64) {
-        let (tx, ty) = match gi & 7 {
-            0 => (xf - offset, yf - offset),
-            1 => (xf - offset, yf + offset),
-            2 => (xf + offset, yf - offset),
-            3 => (xf + offset, yf + offset), 
-            4 => (xf - offset, yf),
-            5 => (xf, yf + offset),
-            6 => (xf, yf - offset),
-            _ => (xf + offset, yf),
-        };
-
-        draw_arrow(xf, yf, tx, ty, offset / 2.0, "#ee0000");
+    fn draw_gradient_arrow(xf: f64, yf: f64, (gx, gy): (f64, f64), offset: f64) {
+        let (tx, ty) = (xf + gx * offset, yf + gy * offset);
+        draw_arrow(xf, yf, tx, ty, offset / 2.0, &overlay::color(Overlay::Vectors));
     }
 }
 
 define_noise!(simplex,
     sliders:[
         (seed, u32, 0., 42., 1000.),
-        (scale, f64, 10., 50., 200.),
+        (scale, f64, 1., 50., 200.),
         (octaves, u32, 1., 1., 8.),
         (lacunarity, f64, 1., 2., 4.),
         (gain, f64, 0., 0.5, 1.),
@@ -375,7 +578,206 @@ define_noise!(simplex,
             (turbulence, hide:[h_exponent, ridge_offset, warp_amount]), 
             (ridge, hide:[h_exponent, warp_amount]), 
             (domain_warp, hide:[h_exponent, ridge_offset])
+        ),
+        (gradient_set,
+            (eight_gradients),
+            (twelve_gradients),
+            (random_gradients)
         )
     ];
-    checkboxes:[show_grid, show_vectors];
+    checkboxes:[show_grid, show_vectors, explain, spectral_h, auto_band_limit, decorrelate_octaves];
+    buttons:[explain_next];
+    pads:[(lacunarity, gain)];
+    cell_size: LATTICE_EDGE;
 );
+
+impl SimplexNoise {
+    /// The shown settings in the form the shader and code exports bake in.
+    pub fn shader() -> Result<shader::Fbm, String> {
+        let settings = SimplexNoiseSettings::parse();
+        if settings.gradient_set != GradientSet::EightGradients {
+            return Err("Only Simplex noise with 8 gradients can be exported".to_string());
+        }
+        if settings.decorrelate_octaves.value() {
+            return Err("Octaves turned and shifted apart cannot be exported".to_string());
+        }
+        let octaves = settings.octaves.value();
+        let shown = settings.show_octave.value().min(octaves);
+        Ok(shader::Fbm {
+            base: shader::Base::Simplex,
+            seed: settings.seed.value(),
+            fractal: match settings.noise_type {
+                NoiseType::Standard => Fractal::Standard,
+                NoiseType::Turbulence => Fractal::Turbulence,
+                NoiseType::Ridge => Fractal::Ridge,
+                NoiseType::DomainWarp => Fractal::DomainWarp,
+            },
+            octaves,
+            shown_octaves: match settings.visualization {
+                Visualization::Final => (1, octaves),
+                Visualization::SingleOctave => (shown, shown),
+                Visualization::AccumulatedOctaves => (1, shown),
+            },
+            lacunarity: settings.lacunarity.value(),
+            gain: settings.gain.value(),
+            h_exponent: settings.h_exponent.value(),
+            spectral_h: settings.spectral_h.value(),
+            ridge_offset: settings.ridge_offset.value(),
+            warp_amount: settings.warp_amount.value(),
+            rotation_degrees: 0.,
+            scale: settings.scale.value(),
+        })
+    }
+}
+
+/// Simplex noise for use outside the page, see [`crate::core`].
+pub struct Simplex {
+    noise: SimplexNoiseImpl,
+    settings: SimplexNoiseSettings,
+    source: Source,
+}
+
+impl Simplex {
+    pub fn new(seed: u32) -> Self {
+        let mut settings = SimplexNoiseSettings::defaults();
+        settings.seed = Seed(seed);
+        let source = Source::default();
+        let noise = SimplexNoiseImpl::new(seed, settings.gradient_set, source);
+        Simplex {
+            noise,
+            settings,
+            source,
+        }
+    }
+
+    pub fn fractal(mut self, fractal: Fractal) -> Self {
+        self.settings.noise_type = match fractal {
+            Fractal::Standard => NoiseType::Standard,
+            Fractal::Turbulence => NoiseType::Turbulence,
+            Fractal::Ridge => NoiseType::Ridge,
+            Fractal::DomainWarp => NoiseType::DomainWarp,
+        };
+        self
+    }
+
+    pub fn gradients(mut self, gradients: Gradients) -> Self {
+        self.settings.gradient_set = match gradients {
+            Gradients::Eight => GradientSet::EightGradients,
+            Gradients::Twelve => GradientSet::TwelveGradients,
+            Gradients::Random => GradientSet::RandomGradients,
+        };
+        self.rebuild();
+        self
+    }
+
+    fn rebuild(&mut self) {
+        let (seed, gradient_set) = (self.settings.seed.value(), self.settings.gradient_set);
+        self.noise = SimplexNoiseImpl::new(seed, gradient_set, self.source);
+    }
+
+    source_builder!();
+
+    settings_builder!(
+        octaves: u32 = 1..,
+        lacunarity: f64,
+        gain: f64,
+        h_exponent: f64,
+        ridge_offset: f64,
+        warp_amount: f64,
+        spectral_h: bool,
+        auto_band_limit: bool,
+        decorrelate_octaves: bool,
+    );
+
+    /// The defaults with a settings file's values applied, see [`crate::core::from_settings`].
+    pub fn from_pairs(pairs: &[(String, String)]) -> Result<Self, String> {
+        let settings = SimplexNoiseSettings::from_pairs(pairs)?;
+        let source = Source::default();
+        let noise = SimplexNoiseImpl::new(settings.seed.value(), settings.gradient_set, source);
+        Ok(Simplex {
+            noise,
+            settings,
+            source,
+        })
+    }
+}
+
+impl Sampler for Simplex {
+    fn sample(&self, x: f64, y: f64) -> f64 {
+        self.noise.sample(x, y, &self.settings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::golden::assert_matches_golden;
+    use crate::noises::helpers::sample_points;
+
+    const GRADIENT_SETS: [GradientSet; 3] = [
+        GradientSet::EightGradients,
+        GradientSet::TwelveGradients,
+        GradientSet::RandomGradients,
+    ];
+
+    #[test]
+    fn zero_at_lattice_points() {
+        for set in GRADIENT_SETS {
+            let noise = SimplexNoiseImpl::new(42, set, Source::default());
+            for i in -20..20 {
+                for j in -20..20 {
+                    // Unskew the lattice point back into noise space.
+                    let t = (i + j) as f64 * SimplexNoiseImpl::G2;
+                    let v = noise.noise_val(i as f64 - t, j as f64 - t);
+                    assert!(v.abs() < 1e-12, "{v} at lattice point ({i}, {j})");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn stays_within_unit_range() {
+        for set in GRADIENT_SETS {
+            for seed in [0, 42, 999] {
+                let noise = SimplexNoiseImpl::new(seed, set, Source::default());
+                for (x, y) in sample_points(20_000, 300.0, seed as u64) {
+                    let v = noise.noise_val(x, y);
+                    assert!(v.abs() <= 1.0, "{v} at ({x}, {y})");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn gradient_sets_cover_their_directions() {
+        let directions = |set| {
+            let mut angles: Vec<i64> = (0..256)
+                .map(|hash| {
+                    let (x, y) = gradient(set, hash);
+                    (y.atan2(x).to_degrees() * 1000.).round() as i64
+                })
+                .collect();
+            angles.sort();
+            angles.dedup();
+            angles.len()
+        };
+        assert_eq!(directions(GradientSet::EightGradients), 8);
+        assert_eq!(directions(GradientSet::TwelveGradients), 8);
+        assert_eq!(directions(GradientSet::RandomGradients), 256);
+        for hash in 0..256 {
+            let (x, y) = gradient(GradientSet::RandomGradients, hash);
+            assert!((x.hypot(y) - 1.).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn renders_like_golden() {
+        let noise = SimplexNoiseImpl::new(42, GradientSet::EightGradients, Source::default());
+        let mut settings = SimplexNoiseSettings::defaults();
+        assert_matches_golden("simplex", &noise.generate_field(&settings));
+
+        settings.octaves = Octaves(4);
+        settings.noise_type = NoiseType::Turbulence;
+        assert_matches_golden("simplex_turbulence", &noise.generate_field(&settings));
+    }
+}
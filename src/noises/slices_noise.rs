@@ -0,0 +1,171 @@
+use std::cell::LazyCell;
+
+use rayon::prelude::*;
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlElement, HtmlInputElement};
+
+use super::noise::Noise;
+use crate::{
+    drawer::{RESOLUTION, draw_text},
+    field::draw_field,
+    noises::volume::Perlin3d,
+    scheduler, *,
+};
+
+const ANIMATION_NAME: &str = "slices";
+const ANIMATION_PERIOD_MS: i32 = 50;
+const ANIMATION_STEP: f64 = 0.01;
+
+struct SlicesNoiseImpl {
+    noise: Perlin3d,
+    frequency: f64,
+    octaves: u32,
+    lacunarity: f64,
+    gain: f64,
+}
+
+impl SlicesNoiseImpl {
+    pub fn new(settings: &SlicesNoiseSettings) -> Self {
+        SlicesNoiseImpl {
+            noise: Perlin3d::new(settings.seed.value()),
+            frequency: settings.volume_frequency.value(),
+            octaves: settings.octaves.value(),
+            lacunarity: settings.lacunarity.value(),
+            gain: settings.gain.value(),
+        }
+    }
+
+    /// Samples the volume spanning the [-1, 1] cube, the same one the isosurface viewer shows.
+    fn sample(&self, x: f64, y: f64, z: f64) -> f64 {
+        self.noise.fbm(
+            x * self.frequency,
+            y * self.frequency,
+            z * self.frequency,
+            self.octaves,
+            self.lacunarity,
+            self.gain,
+        )
+    }
+
+    /// Depth of slice `index` out of `count`, spread evenly from -1 to 1.
+    fn slice_depth(index: u32, count: u32) -> f64 {
+        if count <= 1 {
+            return 0.;
+        }
+        index as f64 / (count - 1) as f64 * 2. - 1.
+    }
+
+    fn grid_columns(count: u32) -> u32 {
+        (count as f64).sqrt().ceil() as u32
+    }
+
+    /// Lays all slices out as a flip-book grid, leaving a one pixel gutter between tiles.
+    fn generate_grid(&self, count: u32) -> Vec<f64> {
+        let columns = Self::grid_columns(count);
+        let tile = RESOLUTION / columns;
+
+        (0..(RESOLUTION * RESOLUTION) as usize)
+            .into_par_iter()
+            .map(|i| {
+                let x = i as u32 % RESOLUTION;
+                let y = i as u32 / RESOLUTION;
+                let (column, row) = (x / tile, y / tile);
+                let (local_x, local_y) = (x % tile, y % tile);
+                let index = row * columns + column;
+
+                if column >= columns || index >= count || local_x == tile - 1 || local_y == tile - 1
+                {
+                    return 0.;
+                }
+
+                let to_unit = |v: u32| v as f64 / (tile - 1) as f64 * 2. - 1.;
+                self.sample(
+                    to_unit(local_x),
+                    to_unit(local_y),
+                    Self::slice_depth(index, count),
+                )
+            })
+            .collect()
+    }
+
+    fn generate_slice(&self, depth: f64) -> Vec<f64> {
+        (0..(RESOLUTION * RESOLUTION) as usize)
+            .into_par_iter()
+            .map(|i| {
+                let to_unit = |v: usize| v as f64 / (RESOLUTION - 1) as f64 * 2. - 1.;
+                let x = i % RESOLUTION as usize;
+                let y = i / RESOLUTION as usize;
+                self.sample(to_unit(x), to_unit(y), depth)
+            })
+            .collect()
+    }
+}
+
+impl SlicesNoise {
+    fn on_setup() {}
+
+    fn on_update() {}
+
+    fn schedule_animation(settings: &SlicesNoiseSettings) {
+        if settings.layout == Layout::SliceScrub && settings.animate_slices.value() {
+            scheduler::start(ANIMATION_NAME, ANIMATION_PERIOD_MS, Self::advance_slice);
+        } else {
+            scheduler::stop(ANIMATION_NAME);
+        }
+    }
+
+    fn advance_slice() {
+        if SLICES.with(|e| e.hidden()) {
+            scheduler::stop(ANIMATION_NAME);
+            return;
+        }
+
+        SLICE_POSITION.with(|s| {
+            let next = s.value_as_number() + ANIMATION_STEP;
+            s.set_value_as_number(if next > 1. { 0. } else { next });
+        });
+        Self::update();
+    }
+
+    fn generate_and_draw(settings: SlicesNoiseSettings) {
+        Self::schedule_animation(&settings);
+        let volume = SlicesNoiseImpl::new(&settings);
+
+        match settings.layout {
+            Layout::SliceGrid => {
+                let count = settings.slice_count.value();
+                draw_field(volume.generate_grid(count));
+
+                let columns = SlicesNoiseImpl::grid_columns(count);
+                let tile = (RESOLUTION / columns) as f64;
+                for index in 0..count {
+                    let depth = SlicesNoiseImpl::slice_depth(index, count);
+                    let x = (index % columns) as f64 * tile;
+                    let y = (index / columns) as f64 * tile;
+                    draw_text(&format!("z = {depth:.2}"), x + 4., y + 14., "#000000");
+                }
+            }
+            Layout::SliceScrub => {
+                let depth = settings.slice_position.value() * 2. - 1.;
+                draw_field(volume.generate_slice(depth));
+                draw_text(&format!("z = {depth:.2}"), 6., 16., "#000000");
+            }
+        }
+    }
+}
+
+define_noise!(slices,
+    sliders:[
+        (seed, u32, 0., 42., 1000.),
+        (volume_frequency, f64, 0.5, 2., 6.),
+        (octaves, u32, 1., 1., 4.),
+        (lacunarity, f64, 1., 2., 4.),
+        (gain, f64, 0., 0.5, 1.),
+        (slice_count, u32, 4., 16., 36.),
+        (slice_position, f64, 0., 0.5, 1.)
+    ];
+    radios:[
+        (layout, (slice_grid, hide:[slice_position, animate_slices]), (slice_scrub, hide:[slice_count]))
+    ];
+    checkboxes:[animate_slices];
+);
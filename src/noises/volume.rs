@@ -0,0 +1,116 @@
+use crate::noises::helpers::{lerp, shuffle};
+
+/// Improved Perlin noise in three dimensions, shared by the volumetric modules.
+pub struct Perlin3d {
+    permutation: [usize; 256],
+}
+
+impl Perlin3d {
+    pub fn new(seed: u32) -> Self {
+        let mut permutation: [usize; 256] = std::array::from_fn(|i| i);
+        shuffle(&mut permutation, seed);
+
+        Perlin3d { permutation }
+    }
+
+    #[inline]
+    fn fade(t: f64) -> f64 {
+        t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+    }
+
+    #[inline]
+    fn hash(&self, x: i32, y: i32, z: i32) -> usize {
+        let xi = (x & 255) as usize;
+        let yi = (y & 255) as usize;
+        let zi = (z & 255) as usize;
+        self.permutation[(self.permutation[(self.permutation[xi] + yi) & 255] + zi) & 255]
+    }
+
+    /// Dot product with one of the 12 cube-edge gradients of the reference implementation.
+    #[inline]
+    fn grad(hash: usize, x: f64, y: f64, z: f64) -> f64 {
+        match hash % 12 {
+            0 => x + y,
+            1 => -x + y,
+            2 => x - y,
+            3 => -x - y,
+            4 => x + z,
+            5 => -x + z,
+            6 => x - z,
+            7 => -x - z,
+            8 => y + z,
+            9 => -y + z,
+            10 => y - z,
+            _ => -y - z,
+        }
+    }
+
+    pub fn noise(&self, x: f64, y: f64, z: f64) -> f64 {
+        let xi = x.floor() as i32;
+        let yi = y.floor() as i32;
+        let zi = z.floor() as i32;
+
+        let xf = x - xi as f64;
+        let yf = y - yi as f64;
+        let zf = z - zi as f64;
+
+        let u = Self::fade(xf);
+        let v = Self::fade(yf);
+        let w = Self::fade(zf);
+
+        let corner = |dx: i32, dy: i32, dz: i32| {
+            Self::grad(
+                self.hash(xi + dx, yi + dy, zi + dz),
+                xf - dx as f64,
+                yf - dy as f64,
+                zf - dz as f64,
+            )
+        };
+
+        let x00 = lerp(u, corner(0, 0, 0), corner(1, 0, 0));
+        let x10 = lerp(u, corner(0, 1, 0), corner(1, 1, 0));
+        let x01 = lerp(u, corner(0, 0, 1), corner(1, 0, 1));
+        let x11 = lerp(u, corner(0, 1, 1), corner(1, 1, 1));
+
+        lerp(w, lerp(v, x00, x10), lerp(v, x01, x11))
+    }
+
+    pub fn fbm(&self, x: f64, y: f64, z: f64, octaves: u32, lacunarity: f64, gain: f64) -> f64 {
+        let mut total = 0.0;
+        let mut frequency = 1.0;
+        let mut amplitude = 1.0;
+        let mut max_value = 0.0;
+
+        for _ in 0..octaves {
+            total += self.noise(x * frequency, y * frequency, z * frequency) * amplitude;
+            max_value += amplitude;
+            amplitude *= gain;
+            frequency *= lacunarity;
+        }
+
+        total / max_value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_at_lattice_points() {
+        let noise = Perlin3d::new(42);
+        for x in -6..6 {
+            for y in -6..6 {
+                for z in -6..6 {
+                    assert_eq!(noise.noise(x as f64, y as f64, z as f64), 0.0);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn octaves_leave_lattice_points_at_zero() {
+        let noise = Perlin3d::new(7);
+        assert_eq!(noise.fbm(3.0, -2.0, 5.0, 4, 2.0, 0.5), 0.0);
+    }
+}
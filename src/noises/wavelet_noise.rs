@@ -1,32 +1,97 @@
-use std::cell::LazyCell;
+use std::cell::{LazyCell, RefCell};
+use std::sync::Arc;
 
 use wasm_bindgen::{JsCast, prelude::Closure};
 use web_sys::{HtmlElement, HtmlInputElement};
 
 use super::noise::Noise;
 use crate::{
-    drawer::IMAGE_BYTES_COUNT,
-    noises::helpers::lerp,
+    core::{Fractal, Interpolation, Sampler},
+    drawer::{RESOLUTION, draw_patch, draw_text, stroke_rect, value_to_color},
+    noises::helpers::{BandLimit, Source, lerp, octave_gain},
+    overlay::{self, Overlay},
+    field::{draw_field, draw_noise_grid, sample_field},
+    rng::{Generator, Rng},
     *,
 };
 
-const WAVELET_TILE_SIZE: usize = 128;
+elements!((wavelet_tile_readout, HtmlElement));
+
+/// The last built tile, rebuilt only when its seed, generator or size changes.
+struct WaveletTile {
+    seed: u32,
+    generator: Generator,
+    size: usize,
+    values: Arc<Vec<f64>>,
+    build_ms: f64,
+}
+
+thread_local! {
+    static TILE_CACHE: RefCell<Option<WaveletTile>> = const { RefCell::new(None) };
+}
 
 struct WaveletNoiseImpl {
-    noise_tile: Vec<f64>,
+    noise_tile: Arc<Vec<f64>>,
+    tile_size: usize,
+    sampling: TileSampling,
+    rng: &'static dyn Rng,
 }
 
 impl WaveletNoiseImpl {
-    pub fn new(seed: u32) -> Self {
-        let mut noise_tile = vec![0.0; WAVELET_TILE_SIZE * WAVELET_TILE_SIZE];
-        Self::generate_noise_tile(&mut noise_tile, seed);
+    pub fn new(seed: u32, tile_size: usize, sampling: TileSampling, source: Source) -> Self {
+        let generator = source.generator;
+        let noise_tile = TILE_CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            let stale = |tile: &WaveletTile| {
+                tile.seed != seed || tile.generator != generator || tile.size != tile_size
+            };
+            if cache.as_ref().is_none_or(stale) {
+                let start = js_sys::Date::now();
+                let mut values = vec![0.0; tile_size * tile_size];
+                Self::generate_noise_tile(&mut values, seed, tile_size, source.rng());
+                cache.replace(WaveletTile {
+                    seed,
+                    generator,
+                    size: tile_size,
+                    values: Arc::new(values),
+                    build_ms: js_sys::Date::now() - start,
+                });
+            }
+            cache.as_ref().unwrap().values.clone()
+        });
+
+        WaveletNoiseImpl { noise_tile, tile_size, sampling, rng: source.rng() }
+    }
+
+    /// Builds the tile without going through the cache, whose timing needs a browser.
+    fn uncached(seed: u32, tile_size: usize, sampling: TileSampling, source: Source) -> Self {
+        let mut values = vec![0.0; tile_size * tile_size];
+        Self::generate_noise_tile(&mut values, seed, tile_size, source.rng());
+        WaveletNoiseImpl { noise_tile: Arc::new(values), tile_size, sampling, rng: source.rng() }
+    }
+
+    /// Describes what the current tile costs to keep and to build.
+    fn tile_cost() -> String {
+        TILE_CACHE.with(|cache| match cache.borrow().as_ref() {
+            Some(tile) => format!(
+                "Tile {size}x{size}: {kib} KiB of memory, built in {ms:.0} ms. The pattern repeats every {size} noise units.",
+                size = tile.size,
+                kib = tile.values.len() * size_of::<f64>() / 1024,
+                ms = tile.build_ms,
+            ),
+            None => String::new(),
+        })
+    }
 
-        WaveletNoiseImpl { noise_tile }
+    fn generate_noise_tile(noise_tile: &mut [f64], seed: u32, tile_size: usize, rng: &dyn Rng) {
+        Self::random_tile(noise_tile, seed, rng);
+        Self::wavelet_decompose_2d(noise_tile, tile_size);
     }
 
-    fn generate_noise_tile(noise_tile: &mut [f64], seed: u32) {
+    /// Zero mean white noise, the input of the decomposition.
+    fn random_tile(noise_tile: &mut [f64], seed: u32, rng: &dyn Rng) {
         for (i, p) in noise_tile.iter_mut().enumerate() {
-            *p = squirrel_noise5::f32_neg_one_to_one_1d(i as i32, seed as i32) as f64;
+            *p = rng.signed(i as u32, seed);
         }
 
         let sum: f64 = noise_tile.iter().sum();
@@ -34,12 +99,33 @@ impl WaveletNoiseImpl {
         for val in noise_tile.iter_mut() {
             *val -= mean;
         }
+    }
 
-        Self::wavelet_decompose_2d(noise_tile);
+    /// The random tile split into its 2x2 block averages (what the Haar step keeps as low
+    /// frequencies) and the remainder, plus the transformed tile that the noise samples.
+    fn tile_bands(&self, seed: u32) -> [(&'static str, Vec<f64>); 4] {
+        let size = self.tile_size;
+        let mut random = vec![0.0; size * size];
+        Self::random_tile(&mut random, seed, self.rng);
+
+        let low_pass: Vec<f64> = (0..size * size)
+            .map(|i| {
+                let (x, y) = ((i % size) & !1, (i / size) & !1);
+                let at = |dx: usize, dy: usize| random[(y + dy) * size + x + dx];
+                (at(0, 0) + at(1, 0) + at(0, 1) + at(1, 1)) * 0.25
+            })
+            .collect();
+        let high_pass = random.iter().zip(&low_pass).map(|(r, l)| r - l).collect();
+
+        [
+            ("random", random),
+            ("low-pass", low_pass),
+            ("high-pass", high_pass),
+            ("used tile", self.noise_tile.to_vec()),
+        ]
     }
 
-    fn wavelet_decompose_2d(data: &mut [f64]) {
-        let sz = WAVELET_TILE_SIZE;
+    fn wavelet_decompose_2d(data: &mut [f64], sz: usize) {
         let mut temp = vec![0.0; sz];
 
         for y in 0..sz {
@@ -83,6 +169,26 @@ impl WaveletNoiseImpl {
         ((x % n + n) % n) as usize
     }
 
+    #[inline]
+    fn tile_value(&self, x: i32, y: i32) -> f64 {
+        let x = Self::mod_fast(x, self.tile_size);
+        let y = Self::mod_fast(y, self.tile_size);
+        self.noise_tile[y * self.tile_size + x]
+    }
+
+    #[inline]
+    fn fade(t: f64) -> f64 {
+        t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+    }
+
+    /// Catmull-Rom spline through four evenly spaced samples, evaluated between `p1` and `p2`.
+    #[inline]
+    fn cubic(t: f64, p0: f64, p1: f64, p2: f64, p3: f64) -> f64 {
+        p1 + 0.5
+            * t
+            * (p2 - p0 + t * (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3 + t * (3.0 * (p1 - p2) + p3 - p0)))
+    }
+
     #[inline]
     fn noise(&self, x: f64, y: f64) -> f64 {
         let xi = x.floor() as i32;
@@ -91,53 +197,44 @@ impl WaveletNoiseImpl {
         let fx = x - x.floor();
         let fy = y - y.floor();
 
-        let x0 = Self::mod_fast(xi, WAVELET_TILE_SIZE);
-        let x1 = Self::mod_fast(xi + 1, WAVELET_TILE_SIZE);
-        let y0 = Self::mod_fast(yi, WAVELET_TILE_SIZE);
-        let y1 = Self::mod_fast(yi + 1, WAVELET_TILE_SIZE);
+        match self.sampling {
+            TileSampling::Bilinear | TileSampling::Quintic => {
+                let (tx, ty) = if self.sampling == TileSampling::Quintic {
+                    (Self::fade(fx), Self::fade(fy))
+                } else {
+                    (fx, fy)
+                };
 
-        let v00 = self.noise_tile[y0 * WAVELET_TILE_SIZE + x0];
-        let v10 = self.noise_tile[y0 * WAVELET_TILE_SIZE + x1];
-        let v01 = self.noise_tile[y1 * WAVELET_TILE_SIZE + x0];
-        let v11 = self.noise_tile[y1 * WAVELET_TILE_SIZE + x1];
+                let v00 = self.tile_value(xi, yi);
+                let v10 = self.tile_value(xi + 1, yi);
+                let v01 = self.tile_value(xi, yi + 1);
+                let v11 = self.tile_value(xi + 1, yi + 1);
 
-        let v0 = lerp(fx, v00, v10);
-        let v1 = lerp(fx, v01, v11);
-        lerp(fy, v0, v1)
+                let v0 = lerp(tx, v00, v10);
+                let v1 = lerp(tx, v01, v11);
+                lerp(ty, v0, v1)
+            }
+            TileSampling::Bicubic => {
+                let row = |dy: i32| {
+                    let v = |dx: i32| self.tile_value(xi + dx, yi + dy);
+                    Self::cubic(fx, v(-1), v(0), v(1), v(2))
+                };
+                Self::cubic(fy, row(-1), row(0), row(1), row(2))
+            }
+        }
     }
 
-    fn generate_coloring(&self, settings: WaveletNoiseSettings) -> Vec<u8> {
-        let mut v = Vec::with_capacity(IMAGE_BYTES_COUNT as usize);
-        let scale = settings.scale.value();
-
-        for y in 0..RESOLUTION {
-            for x in 0..RESOLUTION {
-                let nx = ((x as f64) - (HALF_RESOLUTION as f64)) / scale;
-                let ny = ((y as f64) - (HALF_RESOLUTION as f64)) / scale;
-
-                let noise_val = match settings.noise_type {
-                    NoiseType::Standard => self.fbm_standard(nx, ny, &settings),
-                    NoiseType::Turbulence => self.fbm_turbulence(nx, ny, &settings),
-                    NoiseType::Ridge => self.fbm_ridge(nx, ny, &settings),
-                    NoiseType::DomainWarp => self.fbm_domain_warp(nx, ny, &settings),
-                };
+    fn generate_field(&self, settings: &WaveletNoiseSettings) -> Vec<f64> {
+        sample_field(settings.scale.value(), |nx, ny| self.sample(nx, ny, settings))
+    }
 
-                if noise_val < 0. {
-                    let t = noise_val + 1.;
-                    v.push(255);
-                    v.push(lerp(t, 0.0, 255.0) as u8);
-                    v.push(255);
-                    v.push(255);
-                } else {
-                    let t = noise_val;
-                    v.push(lerp(t, 255.0, 0.0) as u8);
-                    v.push(255);
-                    v.push(lerp(t, 255.0, 0.0) as u8);
-                    v.push(255);
-                }
-            }
+    fn sample(&self, x: f64, y: f64, settings: &WaveletNoiseSettings) -> f64 {
+        match settings.noise_type {
+        NoiseType::Standard => self.fbm_standard(x, y, settings),
+        NoiseType::Turbulence => self.fbm_turbulence(x, y, settings),
+        NoiseType::Ridge => self.fbm_ridge(x, y, settings),
+        NoiseType::DomainWarp => self.fbm_domain_warp(x, y, settings),
         }
-        v
     }
 
     pub fn fbm_standard(&self, x: f64, y: f64, settings: &WaveletNoiseSettings) -> f64 {
@@ -151,6 +248,9 @@ impl WaveletNoiseImpl {
         let gain = settings.gain.value();
         let h_exponent = settings.h_exponent.value();
         let lacunarity = settings.lacunarity.value();
+        let band_limit = BandLimit::new(settings.scale.value(), settings.auto_band_limit.value());
+        let amplitude_falloff =
+            octave_gain(gain.powf(h_exponent), lacunarity, h_exponent, settings.spectral_h.value());
 
         for i in 1..=octaves {
             let noise_val = self.noise(x * frequency, y * frequency);
@@ -161,10 +261,10 @@ impl WaveletNoiseImpl {
                 Visualization::AccumulatedOctaves => i <= show_octave,
             };
             if include {
-                total += noise_val * amplitude;
+                total += noise_val * amplitude * band_limit.weight(frequency);
                 max_value += amplitude;
             }
-            amplitude *= gain.powf(h_exponent);
+            amplitude *= amplitude_falloff;
             frequency *= lacunarity;
         }
 
@@ -181,6 +281,9 @@ impl WaveletNoiseImpl {
         let show_octave = settings.show_octave.value();
         let gain = settings.gain.value();
         let lacunarity = settings.lacunarity.value();
+        let band_limit = BandLimit::new(settings.scale.value(), settings.auto_band_limit.value());
+        let amplitude_falloff =
+            octave_gain(gain, lacunarity, settings.h_exponent.value(), settings.spectral_h.value());
 
         for i in 1..=octaves {
             let noise_val = self.noise(x * frequency, y * frequency).abs();
@@ -191,10 +294,10 @@ impl WaveletNoiseImpl {
                 Visualization::AccumulatedOctaves => i <= show_octave,
             };
             if include {
-                total += noise_val * amplitude;
+                total += noise_val * amplitude * band_limit.weight(frequency);
                 max_value += amplitude;
             }
-            amplitude *= gain;
+            amplitude *= amplitude_falloff;
             frequency *= lacunarity;
         }
 
@@ -212,6 +315,9 @@ impl WaveletNoiseImpl {
         let show_octave = settings.show_octave.value();
         let gain = settings.gain.value();
         let lacunarity = settings.lacunarity.value();
+        let band_limit = BandLimit::new(settings.scale.value(), settings.auto_band_limit.value());
+        let amplitude_falloff =
+            octave_gain(gain, lacunarity, settings.h_exponent.value(), settings.spectral_h.value());
 
         for i in 1..=octaves {
             let noise_val = self.noise(x * frequency, y * frequency).abs();
@@ -224,12 +330,12 @@ impl WaveletNoiseImpl {
             };
             if include {
                 let noise_val = noise_val * noise_val * weight;
-                total += noise_val * amplitude;
+                total += noise_val * amplitude * band_limit.weight(frequency);
                 max_value += amplitude;
             }
 
             weight = (noise_val * 2.0).clamp(0.0, 1.0);
-            amplitude *= gain;
+            amplitude *= amplitude_falloff;
             frequency *= lacunarity;
         }
 
@@ -260,17 +366,56 @@ impl WaveletNoise {
     fn on_update() {
         let octaves = Octaves::parse().value();
         SHOW_OCTAVE.with(|e| e.set_max(format!("{octaves}").as_str()));
+
+        // The noise type radio hides H where only the gain is used, spectral H applies to every type.
+        if SpectralH::parse().value() {
+            set_hidden!(h_exponent_control, false);
+        }
     }
 
     fn generate_and_draw(settings: WaveletNoiseSettings) {
-        let wavelet = WaveletNoiseImpl::new(settings.seed.value());
-
-        let coloring = wavelet.generate_coloring(settings.clone());
-
-        draw_noise(coloring.as_slice());
+        let tile_size = 1 << settings.tile_size_power.value();
+        let wavelet = WaveletNoiseImpl::new(
+            settings.seed.value(),
+            tile_size,
+            settings.tile_sampling,
+            Source::page(),
+        );
+        set_text!(tile_size_power, &format!("{tile_size}"));
+        WAVELET_TILE_READOUT.with(|r| r.set_inner_text(&WaveletNoiseImpl::tile_cost()));
+
+        draw_field(wavelet.generate_field(&settings));
 
         if settings.show_grid.value() {
-            draw_grid(settings.scale.value(), "#000000");
+            draw_noise_grid(settings.scale.value(), &overlay::color(Overlay::Grid));
+        }
+        if settings.show_tile.value() {
+            Self::draw_tile_bands(&wavelet, settings.seed.value());
+        }
+    }
+
+    /// Draws every band as a small panel along the bottom of the canvas, each normalized
+    /// to its own peak so that the weak bands stay visible.
+    fn draw_tile_bands(wavelet: &WaveletNoiseImpl, seed: u32) {
+        const PANEL: u32 = 92;
+        const GAP: f64 = 6.;
+        let size = wavelet.tile_size;
+        let top = (RESOLUTION - PANEL) as f64 - GAP;
+
+        for (i, (label, band)) in wavelet.tile_bands(seed).iter().enumerate() {
+            let peak = band.iter().fold(0.0, |m: f64, v| m.max(v.abs())).max(1e-12);
+            let pixels: Vec<u8> = (0..(PANEL * PANEL) as usize)
+                .flat_map(|p| {
+                    let x = p % PANEL as usize * size / PANEL as usize;
+                    let y = p / PANEL as usize * size / PANEL as usize;
+                    value_to_color(band[y * size + x] / peak)
+                })
+                .collect();
+
+            let left = GAP + i as f64 * (PANEL as f64 + GAP);
+            draw_patch(&pixels, left, top, PANEL, PANEL);
+            stroke_rect(left, top, PANEL as f64, PANEL as f64, "#000000");
+            draw_text(label, left + 3., top + 13., "#000000");
         }
     }
 }
@@ -285,7 +430,8 @@ define_noise!(wavelet,
         (h_exponent, f64, 0., 1., 2.),
         (ridge_offset, f64, 0., 1., 2.),
         (warp_amount, f64, 0., 4.0, 10.),
-        (show_octave, u32, 1., 1., 8.)
+        (show_octave, u32, 1., 1., 8.),
+        (tile_size_power, u32, 6., 7., 8.)
     ];
     radios:[
         (visualization, 
@@ -298,8 +444,148 @@ define_noise!(wavelet,
             (turbulence, hide:[h_exponent, ridge_offset, warp_amount]), 
             (ridge, hide:[h_exponent, warp_amount]), 
             (domain_warp, hide:[h_exponent, ridge_offset])
-        )
+        ),
+        (tile_sampling, (bilinear), (quintic), (bicubic))
     ];
-    checkboxes:[show_grid];
+    checkboxes:[show_grid, show_tile, spectral_h, auto_band_limit];
+    pads:[(lacunarity, gain)];
+    cell_size: 1.0;
 );
 
+/// Wavelet noise for use outside the page, see [`crate::core`].
+pub struct Wavelet {
+    noise: WaveletNoiseImpl,
+    settings: WaveletNoiseSettings,
+    source: Source,
+}
+
+impl Wavelet {
+    pub fn new(seed: u32) -> Self {
+        let mut settings = WaveletNoiseSettings::defaults();
+        settings.seed = Seed(seed);
+        let source = Source::default();
+        let noise = WaveletNoiseImpl::uncached(
+            seed,
+            1 << settings.tile_size_power.value(),
+            settings.tile_sampling,
+            source,
+        );
+        Wavelet {
+            noise,
+            settings,
+            source,
+        }
+    }
+
+    fn rebuild(&mut self) {
+        self.noise = WaveletNoiseImpl::uncached(
+            self.settings.seed.value(),
+            1 << self.settings.tile_size_power.value(),
+            self.settings.tile_sampling,
+            self.source,
+        );
+    }
+
+    source_builder!();
+
+    pub fn fractal(mut self, fractal: Fractal) -> Self {
+        self.settings.noise_type = match fractal {
+            Fractal::Standard => NoiseType::Standard,
+            Fractal::Turbulence => NoiseType::Turbulence,
+            Fractal::Ridge => NoiseType::Ridge,
+            Fractal::DomainWarp => NoiseType::DomainWarp,
+        };
+        self
+    }
+
+    /// Side of the repeating tile is `2^power` noise units.
+    pub fn tile_size_power(mut self, power: u32) -> Self {
+        self.settings.tile_size_power = TileSizePower(power);
+        self.rebuild();
+        self
+    }
+
+    pub fn interpolation(mut self, interpolation: Interpolation) -> Self {
+        self.settings.tile_sampling = match interpolation {
+            Interpolation::Bilinear => TileSampling::Bilinear,
+            Interpolation::Quintic => TileSampling::Quintic,
+            Interpolation::Bicubic => TileSampling::Bicubic,
+        };
+        self.noise.sampling = self.settings.tile_sampling;
+        self
+    }
+
+    settings_builder!(
+        octaves: u32 = 1..,
+        lacunarity: f64,
+        gain: f64,
+        h_exponent: f64,
+        ridge_offset: f64,
+        warp_amount: f64,
+        spectral_h: bool,
+        auto_band_limit: bool,
+    );
+
+    /// The defaults with a settings file's values applied, see [`crate::core::from_settings`].
+    pub fn from_pairs(pairs: &[(String, String)]) -> Result<Self, String> {
+        let settings = WaveletNoiseSettings::from_pairs(pairs)?;
+        let source = Source::default();
+        let noise = WaveletNoiseImpl::uncached(
+            settings.seed.value(),
+            1 << settings.tile_size_power.value(),
+            settings.tile_sampling,
+            source,
+        );
+        Ok(Wavelet {
+            noise,
+            settings,
+            source,
+        })
+    }
+}
+
+impl Sampler for Wavelet {
+    fn sample(&self, x: f64, y: f64) -> f64 {
+        self.noise.sample(x, y, &self.settings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::golden::assert_matches_golden;
+
+    fn wavelet(tile_size: usize, sampling: TileSampling) -> WaveletNoiseImpl {
+        WaveletNoiseImpl::uncached(42, tile_size, sampling, Source::default())
+    }
+
+    #[test]
+    fn tile_wraps_exactly() {
+        for sampling in [TileSampling::Bilinear, TileSampling::Quintic, TileSampling::Bicubic] {
+            for tile_size in [64, 128] {
+                let noise = wavelet(tile_size, sampling);
+                let period = tile_size as f64;
+                // Eighths are exact in binary, so shifting by a period does not round the fraction.
+                for i in -100..100 {
+                    let (x, y) = (i as f64 * 0.875, i as f64 * -0.625);
+                    let v = noise.noise(x, y);
+                    assert_eq!(v, noise.noise(x + period, y));
+                    assert_eq!(v, noise.noise(x, y - period));
+                    assert_eq!(v, noise.noise(x - 3.0 * period, y + 2.0 * period));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn renders_like_golden() {
+        let mut settings = WaveletNoiseSettings::defaults();
+        let noise = wavelet(128, settings.tile_sampling);
+        assert_matches_golden("wavelet", &noise.generate_field(&settings));
+
+        settings.octaves = Octaves(4);
+        settings.tile_sampling = TileSampling::Bicubic;
+        let noise = wavelet(128, settings.tile_sampling);
+        assert_matches_golden("wavelet_bicubic", &noise.generate_field(&settings));
+    }
+}
@@ -1,29 +1,51 @@
 use std::cell::LazyCell;
 
 use wasm_bindgen::{JsCast, prelude::Closure};
-use web_sys::{HtmlElement, HtmlInputElement};
+use web_sys::{HtmlElement, HtmlInputElement, MouseEvent};
 
 use super::noise::Noise;
 use crate::{
-    drawer::{draw_circle, IMAGE_BYTES_COUNT},
-    noises::helpers::{lerp, shuffle},
+    core::{WorleyFeature, Metric, Sampler},
+    drawer::{
+        CANVAS, canvas_coordinates, draw_circle, draw_line, restore_frame,
+        save_frame, stroke_polygon,
+    },
+    noises::helpers::{BandLimit, Source, octave_gain},
+    overlay::{self, Overlay},
+    field::{canvas_position, draw_field, draw_noise_grid, noise_coordinates, sample_field},
+    rng::Rng,
     *,
 };
 
+/// Streams of the feature point of a cell, see [`Rng::cell`].
+const OFFSET_X_STREAM: u32 = 0;
+const OFFSET_Y_STREAM: u32 = 1;
+
 struct WorleyNoiseImpl {
     permutation: [usize; 256],
+    seed: u32,
+    rng: &'static dyn Rng,
 }
 
 impl WorleyNoiseImpl {
-    pub fn new(seed: u32) -> Self {
-        let mut permutation: [usize; 256] = std::array::from_fn(|i| i);
-        shuffle(&mut permutation, seed);
-
-        WorleyNoiseImpl { permutation }
+    pub fn new(seed: u32, source: Source) -> Self {
+        WorleyNoiseImpl {
+            permutation: source.permutation(seed),
+            seed,
+            rng: source.rng(),
+        }
     }
 
+    /// Offset of the feature point of a cell. With `independent_streams` both coordinates get
+    /// their own random number, otherwise they are derived from one hash of the cell and correlate.
     #[inline]
-    fn hash2d(&self, x: i32, y: i32) -> (f64, f64) {
+    fn hash2d(&self, x: i32, y: i32, independent_streams: bool) -> (f64, f64) {
+        if independent_streams {
+            return (
+                self.rng.cell(x, y, self.seed, OFFSET_X_STREAM),
+                self.rng.cell(x, y, self.seed, OFFSET_Y_STREAM),
+            );
+        }
         let xi = (x & 255) as usize;
         let yi = (y & 255) as usize;
         let h = self.permutation[(self.permutation[xi] + yi) & 255];
@@ -35,7 +57,50 @@ impl WorleyNoiseImpl {
     }
 
     #[inline]
-    fn worley_distance(&self, x: f64, y: f64, distance_metric: DistanceMetric) -> (f64, f64) {
+    fn distance(dx: f64, dy: f64, distance_metric: DistanceMetric) -> f64 {
+        match distance_metric {
+            DistanceMetric::Euclidean => (dx * dx + dy * dy).sqrt(),
+            DistanceMetric::Manhattan => dx.abs() + dy.abs(),
+            DistanceMetric::Chebyshev => dx.abs().max(dy.abs()),
+            DistanceMetric::Minkowski => {
+                let p = 3.0;
+                (dx.abs().powf(p) + dy.abs().powf(p)).powf(1.0 / p)
+            }
+        }
+    }
+
+    /// Returns every candidate feature point of the 3x3 searched neighbourhood with its distance to (x, y).
+    fn search_neighborhood(
+        &self,
+        x: f64,
+        y: f64,
+        distance_metric: DistanceMetric,
+        independent_streams: bool,
+    ) -> Vec<(f64, f64, f64)> {
+        let xi = x.floor() as i32;
+        let yi = y.floor() as i32;
+
+        let mut candidates = Vec::with_capacity(9);
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let (offset_x, offset_y) = self.hash2d(xi + dx, yi + dy, independent_streams);
+                let point_x = (xi + dx) as f64 + offset_x;
+                let point_y = (yi + dy) as f64 + offset_y;
+                let dist = Self::distance(point_x - x, point_y - y, distance_metric);
+                candidates.push((point_x, point_y, dist));
+            }
+        }
+        candidates
+    }
+
+    #[inline]
+    fn worley_distance(
+        &self,
+        x: f64,
+        y: f64,
+        distance_metric: DistanceMetric,
+        independent_streams: bool,
+    ) -> (f64, f64) {
         let xi = x.floor() as i32;
         let yi = y.floor() as i32;
         let xf = x - xi as f64;
@@ -49,29 +114,11 @@ impl WorleyNoiseImpl {
                 let cell_x = xi + dx;
                 let cell_y = yi + dy;
                 
-                let (offset_x, offset_y) = self.hash2d(cell_x, cell_y);
+                let (offset_x, offset_y) = self.hash2d(cell_x, cell_y, independent_streams);
                 let point_x = dx as f64 + offset_x;
                 let point_y = dy as f64 + offset_y;
 
-                let dist = match distance_metric {
-                    DistanceMetric::Euclidean => {
-                        let dx = point_x - xf;
-                        let dy = point_y - yf;
-                        (dx * dx + dy * dy).sqrt()
-                    }
-                    DistanceMetric::Manhattan => {
-                        (point_x - xf).abs() + (point_y - yf).abs()
-                    }
-                    DistanceMetric::Chebyshev => {
-                        (point_x - xf).abs().max((point_y - yf).abs())
-                    }
-                    DistanceMetric::Minkowski => {
-                        let p = 3.0; 
-                        let dx = (point_x - xf).abs();
-                        let dy = (point_y - yf).abs();
-                        (dx.powf(p) + dy.powf(p)).powf(1.0 / p)
-                    }
-                };
+                let dist = Self::distance(point_x - xf, point_y - yf, distance_metric);
 
                 if dist < min_dist1 {
                     min_dist2 = min_dist1;
@@ -85,40 +132,17 @@ impl WorleyNoiseImpl {
         (min_dist1, min_dist2)
     }
 
-    fn generate_coloring(&self, settings: WorleyNoiseSettings) -> Vec<u8> {
-        let mut v = Vec::with_capacity(IMAGE_BYTES_COUNT as usize);
-        let scale = settings.scale.value();
+    fn generate_field(&self, settings: &WorleyNoiseSettings) -> Vec<f64> {
+        sample_field(settings.scale.value(), |nx, ny| self.sample(nx, ny, settings))
+    }
 
-        for y in 0..RESOLUTION {
-            for x in 0..RESOLUTION {
-                let nx = ((x as f64) - (HALF_RESOLUTION as f64)) / scale;
-                let ny = ((y as f64) - (HALF_RESOLUTION as f64)) / scale;
-
-                let noise_val = match settings.noise_type {
-                    NoiseType::F1 => self.fbm_f1(nx, ny, &settings),
-                    NoiseType::F2MinusF1 => self.fbm_f2_minus_f1(nx, ny, &settings),
-                    NoiseType::Crackle => self.fbm_crackle(nx, ny, &settings),
-                    NoiseType::DomainWarp => self.fbm_domain_warp(nx, ny, &settings),
-                };
-
-                let normalized = noise_val.clamp(-1.0, 1.0);
-
-                if normalized < 0. {
-                    let t = normalized + 1.;
-                    v.push(255);
-                    v.push(lerp(t, 0.0, 255.0) as u8);
-                    v.push(255);
-                    v.push(255);
-                } else {
-                    let t = normalized;
-                    v.push(lerp(t, 255.0, 0.0) as u8);
-                    v.push(255);
-                    v.push(lerp(t, 255.0, 0.0) as u8);
-                    v.push(255);
-                }
-            }
+    fn sample(&self, x: f64, y: f64, settings: &WorleyNoiseSettings) -> f64 {
+        match settings.noise_type {
+        NoiseType::F1 => self.fbm_f1(x, y, settings),
+        NoiseType::F2MinusF1 => self.fbm_f2_minus_f1(x, y, settings),
+        NoiseType::Crackle => self.fbm_crackle(x, y, settings),
+        NoiseType::DomainWarp => self.fbm_domain_warp(x, y, settings),
         }
-        v
     }
 
     pub fn fbm_f1(&self, x: f64, y: f64, settings: &WorleyNoiseSettings) -> f64 {
@@ -131,13 +155,17 @@ impl WorleyNoiseImpl {
         let show_octave = settings.show_octave.value();
         let gain = settings.gain.value();
         let lacunarity = settings.lacunarity.value();
+        let band_limit = BandLimit::new(settings.scale.value(), settings.auto_band_limit.value());
+        let amplitude_falloff =
+            octave_gain(gain, lacunarity, settings.h_exponent.value(), settings.spectral_h.value());
         let distance_metric = settings.distance_metric;
 
         for i in 1..=octaves {
             let (f1, _) = self.worley_distance(
                 x * frequency, 
                 y * frequency, 
-                distance_metric
+                distance_metric,
+                settings.independent_streams.value(),
             );
 
             let include = match settings.visualization {
@@ -148,11 +176,11 @@ impl WorleyNoiseImpl {
             
             if include {
                 let noise_val = 1.0 - f1.min(1.0);
-                total += noise_val * amplitude;
+                total += noise_val * amplitude * band_limit.weight(frequency);
                 max_value += amplitude;
             }
             
-            amplitude *= gain;
+            amplitude *= amplitude_falloff;
             frequency *= lacunarity;
         }
 
@@ -169,13 +197,17 @@ impl WorleyNoiseImpl {
         let show_octave = settings.show_octave.value();
         let gain = settings.gain.value();
         let lacunarity = settings.lacunarity.value();
+        let band_limit = BandLimit::new(settings.scale.value(), settings.auto_band_limit.value());
+        let amplitude_falloff =
+            octave_gain(gain, lacunarity, settings.h_exponent.value(), settings.spectral_h.value());
         let distance_metric = settings.distance_metric;
 
         for i in 1..=octaves {
             let (f1, f2) = self.worley_distance(
                 x * frequency, 
                 y * frequency, 
-                distance_metric
+                distance_metric,
+                settings.independent_streams.value(),
             );
 
             let include = match settings.visualization {
@@ -186,11 +218,11 @@ impl WorleyNoiseImpl {
             
             if include {
                 let noise_val = (f2 - f1).min(1.0);
-                total += noise_val * amplitude;
+                total += noise_val * amplitude * band_limit.weight(frequency);
                 max_value += amplitude;
             }
             
-            amplitude *= gain;
+            amplitude *= amplitude_falloff;
             frequency *= lacunarity;
         }
 
@@ -207,6 +239,9 @@ impl WorleyNoiseImpl {
         let show_octave = settings.show_octave.value();
         let gain = settings.gain.value();
         let lacunarity = settings.lacunarity.value();
+        let band_limit = BandLimit::new(settings.scale.value(), settings.auto_band_limit.value());
+        let amplitude_falloff =
+            octave_gain(gain, lacunarity, settings.h_exponent.value(), settings.spectral_h.value());
         let distance_metric = settings.distance_metric;
         let crackle_power = settings.crackle_power.value();
 
@@ -214,7 +249,8 @@ impl WorleyNoiseImpl {
             let (f1, _) = self.worley_distance(
                 x * frequency, 
                 y * frequency, 
-                distance_metric
+                distance_metric,
+                settings.independent_streams.value(),
             );
 
             let include = match settings.visualization {
@@ -225,11 +261,11 @@ impl WorleyNoiseImpl {
             
             if include {
                 let noise_val = f1.min(1.0).powf(crackle_power);
-                total += noise_val * amplitude;
+                total += noise_val * amplitude * band_limit.weight(frequency);
                 max_value += amplitude;
             }
             
-            amplitude *= gain;
+            amplitude *= amplitude_falloff;
             frequency *= lacunarity;
         }
 
@@ -254,32 +290,104 @@ impl WorleyNoiseImpl {
     }
 }
 
+define_event_closure!(worley_canvas_move, MouseEvent, WorleyNoise::on_canvas_move);
+define_closure!(worley_canvas_leave, WorleyNoise::on_canvas_leave);
+
 impl WorleyNoise {
-    fn on_setup() {}
+    fn on_setup() {
+        add_callback!(canvas, "mousemove", worley_canvas_move);
+        add_callback!(canvas, "mouseleave", worley_canvas_leave);
+    }
+
+    fn is_search_shown() -> bool {
+        !WORLEY.with(|e| e.hidden()) && ShowSearch::parse().value() && view::is_flat()
+    }
+
+    fn on_canvas_leave() {
+        if Self::is_search_shown() {
+            restore_frame();
+        }
+    }
+
+    fn on_canvas_move(event: MouseEvent) {
+        if !Self::is_search_shown() {
+            return;
+        }
+        restore_frame();
+
+        let seed = Seed::parse().value();
+        let scale = Scale::parse().value();
+        let distance_metric = DistanceMetric::parse();
+        let (screen_x, screen_y) = canvas_coordinates(&event);
+        let (x, y) = noise_coordinates(screen_x, screen_y, scale);
+
+        // A repeated domain wraps the point into its first tile, the search is drawn back under
+        // the mouse.
+        let (origin_x, origin_y) = canvas_position(x, y, scale);
+        let to_screen = |nx: f64, ny: f64| {
+            let (px, py) = canvas_position(nx, ny, scale);
+            (px - origin_x + screen_x, py - origin_y + screen_y)
+        };
+        let (cell_x, cell_y) = (x.floor(), y.floor());
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let style = if dx == 0 && dy == 0 { "#000000" } else { "#777777" };
+                let (left, top) = (cell_x + dx as f64, cell_y + dy as f64);
+                let corners = [(0., 0.), (1., 0.), (1., 1.), (0., 1.)]
+                    .map(|(cx, cy)| to_screen(left + cx, top + cy));
+                stroke_polygon(&corners, style);
+            }
+        }
+
+        let independent_streams = IndependentStreams::parse().value();
+        let mut candidates = WorleyNoiseImpl::new(seed, Source::page()).search_neighborhood(
+            x,
+            y,
+            distance_metric,
+            independent_streams,
+        );
+        candidates.sort_by(|a, b| a.2.total_cmp(&b.2));
+        let style = overlay::color(Overlay::Points);
+        for &(point_x, point_y, _) in &candidates {
+            let (px, py) = to_screen(point_x, point_y);
+            draw_circle(px, py, scale / 15.0, &style);
+        }
+        let (f2_x, f2_y) = to_screen(candidates[1].0, candidates[1].1);
+        draw_line(screen_x, screen_y, f2_x, f2_y, "#0088ff");
+        let (f1_x, f1_y) = to_screen(candidates[0].0, candidates[0].1);
+        draw_line(screen_x, screen_y, f1_x, f1_y, "#00aa00");
+        draw_circle(screen_x, screen_y, 3.0, "#000000");
+    }
     
     fn on_update() {
         let octaves = Octaves::parse().value();
         SHOW_OCTAVE.with(|e| e.set_max(format!("{octaves}").as_str()));
+
+        let hide_h_exponent = !SpectralH::parse().value();
+        set_hidden!(h_exponent_control, hide_h_exponent);
     }
     
     fn generate_and_draw(settings: WorleyNoiseSettings) {
-        let worley = WorleyNoiseImpl::new(settings.seed.value());
-
-        let coloring = worley.generate_coloring(settings.clone());
+        let worley = WorleyNoiseImpl::new(settings.seed.value(), Source::page());
 
-        draw_noise(coloring.as_slice());
+        draw_field(worley.generate_field(&settings));
 
         if settings.show_grid.value() {
-            draw_grid(settings.scale.value(), "#000000");
+            draw_noise_grid(settings.scale.value(), &overlay::color(Overlay::Grid));
         }
 
         if settings.show_points.value() {
             Self::draw_feature_points(&settings, worley);
         }
+
+        if settings.show_search.value() {
+            save_frame();
+        }
     }
 
     fn draw_feature_points(settings: &WorleyNoiseSettings, noise: WorleyNoiseImpl) {
         let scale = settings.scale.value();
+        let style = overlay::color(Overlay::Points);
 
         for i in 0..settings.octaves.value() {
             let octave_scale = scale / 2_f64.powi(i as i32);
@@ -287,13 +395,14 @@ impl WorleyNoise {
 
             for x in -half_range..=half_range {
                 for y in -half_range..=half_range {
-                    let (offset_x, offset_y) = noise.hash2d(x as i32, y as i32);
+                    let (offset_x, offset_y) =
+                        noise.hash2d(x as i32, y as i32, settings.independent_streams.value());
                     
                     let xf = HALF_RESOLUTION as f64 - (x as f64 + offset_x) * octave_scale;
                     let yf = HALF_RESOLUTION as f64 - (y as f64 + offset_y) * octave_scale;
 
                     let radius = octave_scale / 10.0;
-                    draw_circle(xf, yf, radius, "#ee0000");
+                    draw_circle(xf, yf, radius, &style);
                 }
             }
         }
@@ -303,10 +412,11 @@ impl WorleyNoise {
 define_noise!(worley,
     sliders:[
         (seed, u32, 0., 42., 1000.),
-        (scale, f64, 10., 50., 200.),
+        (scale, f64, 1., 50., 200.),
         (octaves, u32, 1., 1., 8.),
         (lacunarity, f64, 1., 2., 4.),
         (gain, f64, 0., 0.5, 1.),
+        (h_exponent, f64, 0., 1., 2.),
         (crackle_power, f64, 0.5, 2.0, 4.0),
         (warp_amount, f64, 0.1, 1.0, 2.),
         (show_octave, u32, 1., 1., 8.)
@@ -330,6 +440,121 @@ define_noise!(worley,
             (minkowski)
         )
     ];
-    checkboxes:[show_grid, show_points];
+    checkboxes:[show_grid, show_points, show_search, spectral_h, auto_band_limit, independent_streams];
+    pads:[(lacunarity, gain)];
+    cell_size: 1.0;
 );
 
+/// Worley noise for use outside the page, see [`crate::core`].
+pub struct Worley {
+    noise: WorleyNoiseImpl,
+    settings: WorleyNoiseSettings,
+    source: Source,
+}
+
+impl Worley {
+    pub fn new(seed: u32) -> Self {
+        let mut settings = WorleyNoiseSettings::defaults();
+        settings.seed = Seed(seed);
+        let source = Source::default();
+        let noise = WorleyNoiseImpl::new(seed, source);
+        Worley {
+            noise,
+            settings,
+            source,
+        }
+    }
+
+    fn rebuild(&mut self) {
+        self.noise = WorleyNoiseImpl::new(self.settings.seed.value(), self.source);
+    }
+
+    source_builder!();
+
+    pub fn feature(mut self, feature: WorleyFeature) -> Self {
+        self.settings.noise_type = match feature {
+            WorleyFeature::F1 => NoiseType::F1,
+            WorleyFeature::F2MinusF1 => NoiseType::F2MinusF1,
+            WorleyFeature::Crackle => NoiseType::Crackle,
+            WorleyFeature::DomainWarp => NoiseType::DomainWarp,
+        };
+        self
+    }
+
+    pub fn metric(mut self, metric: Metric) -> Self {
+        self.settings.distance_metric = match metric {
+            Metric::Euclidean => DistanceMetric::Euclidean,
+            Metric::Manhattan => DistanceMetric::Manhattan,
+            Metric::Chebyshev => DistanceMetric::Chebyshev,
+            Metric::Minkowski => DistanceMetric::Minkowski,
+        };
+        self
+    }
+
+    settings_builder!(
+        octaves: u32 = 1..,
+        lacunarity: f64,
+        gain: f64,
+        h_exponent: f64,
+        crackle_power: f64,
+        warp_amount: f64,
+        spectral_h: bool,
+        auto_band_limit: bool,
+        independent_streams: bool,
+    );
+
+    /// The defaults with a settings file's values applied, see [`crate::core::from_settings`].
+    pub fn from_pairs(pairs: &[(String, String)]) -> Result<Self, String> {
+        let settings = WorleyNoiseSettings::from_pairs(pairs)?;
+        let source = Source::default();
+        let noise = WorleyNoiseImpl::new(settings.seed.value(), source);
+        Ok(Worley {
+            noise,
+            settings,
+            source,
+        })
+    }
+}
+
+impl Sampler for Worley {
+    fn sample(&self, x: f64, y: f64) -> f64 {
+        self.noise.sample(x, y, &self.settings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::golden::assert_matches_golden;
+
+    #[test]
+    fn renders_like_golden() {
+        let noise = WorleyNoiseImpl::new(42, Source::default());
+        let mut settings = WorleyNoiseSettings::defaults();
+        assert_matches_golden("worley", &noise.generate_field(&settings));
+
+        settings.octaves = Octaves(3);
+        settings.noise_type = NoiseType::Crackle;
+        settings.distance_metric = DistanceMetric::Manhattan;
+        assert_matches_golden("worley_crackle", &noise.generate_field(&settings));
+    }
+
+    #[test]
+    fn independent_streams_give_every_cell_its_own_point() {
+        let noise = WorleyNoiseImpl::new(42, Source::default());
+        let distinct_points = |independent_streams| {
+            let mut points: Vec<(u64, u64)> = (0..64)
+                .flat_map(|x| (0..64).map(move |y| (x, y)))
+                .map(|(x, y)| {
+                    let (fx, fy) = noise.hash2d(x, y, independent_streams);
+                    (fx.to_bits(), fy.to_bits())
+                })
+                .collect();
+            points.sort();
+            points.dedup();
+            points.len()
+        };
+        assert!(distinct_points(false) <= 256);
+        assert!(distinct_points(true) > 4000);
+    }
+}
@@ -0,0 +1,132 @@
+//! Overlay colors, vector density and length, and drawing the overlays without the field.
+
+use std::cell::{Cell, LazyCell, RefCell};
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlElement, HtmlInputElement};
+
+use crate::*;
+
+#[derive(Clone, Copy)]
+pub enum Overlay {
+    /// Gradient vectors and impulse orientations.
+    Vectors,
+    /// Lattice cells.
+    Grid,
+    /// Feature points.
+    Points,
+    /// The main direction of anisotropic noise.
+    Direction,
+}
+
+/// Colors before anything is picked, the ones the overlays always had.
+const DEFAULTS: [&str; 4] = ["#ee0000", "#000000", "#ee0000", "#00ff00"];
+
+/// Rgba the field is replaced by when only the overlays are shown, light enough for every default
+/// overlay color.
+pub const BACKGROUND: [u8; 4] = [236, 236, 236, 255];
+
+thread_local! {
+    /// Picked colors, indexed by `Overlay`.
+    static COLORS: RefCell<[String; 4]> = RefCell::new(DEFAULTS.map(String::from));
+    /// Vectors are drawn at every `STRIDE`th lattice point along each axis.
+    static STRIDE: Cell<i32> = const { Cell::new(1) };
+    /// Factor on the length the overlays give their arrows.
+    static LENGTH_FACTOR: Cell<f64> = const { Cell::new(1.) };
+    /// Whether the field is left out and only the overlays are drawn.
+    static HIDE_FIELD: Cell<bool> = const { Cell::new(false) };
+}
+
+elements!(
+    (overlay_vectors_color, HtmlInputElement),
+    (overlay_grid_color, HtmlInputElement),
+    (overlay_points_color, HtmlInputElement),
+    (overlay_direction_color, HtmlInputElement),
+    (vector_stride, HtmlInputElement),
+    (vector_stride_display, HtmlElement),
+    (vector_length, HtmlInputElement),
+    (vector_length_display, HtmlElement),
+    (overlays_only, HtmlInputElement),
+);
+define_closure!(overlay_change, on_overlay_change);
+
+pub fn setup() {
+    add_callback!(overlay_vectors_color, "input", overlay_change);
+    add_callback!(overlay_grid_color, "input", overlay_change);
+    add_callback!(overlay_points_color, "input", overlay_change);
+    add_callback!(overlay_direction_color, "input", overlay_change);
+    add_callback!(vector_stride, "input", overlay_change);
+    add_callback!(vector_length, "input", overlay_change);
+    add_callback!(overlays_only, "input", overlay_change);
+    read_controls();
+}
+
+fn on_overlay_change() {
+    read_controls();
+    update_current_noise();
+}
+
+/// Picks up the colors and vector settings from the page, also after a saved state has been
+/// applied.
+pub fn read_controls() {
+    let picked = [
+        parse_value!(overlay_vectors_color, String),
+        parse_value!(overlay_grid_color, String),
+        parse_value!(overlay_points_color, String),
+        parse_value!(overlay_direction_color, String),
+    ];
+    COLORS.with(|c| c.replace(picked));
+
+    let stride = parse_value!(vector_stride, i32).max(1);
+    let length = parse_value!(vector_length, f64);
+    STRIDE.with(|s| s.set(stride));
+    LENGTH_FACTOR.with(|l| l.set(length));
+    set_text!(
+        vector_stride,
+        &match stride {
+            1 => "every cell".to_string(),
+            _ => format!("every {stride} cells"),
+        }
+    );
+    set_text!(vector_length, &format!("{length:.2}\u{d7}"));
+
+    HIDE_FIELD.with(|o| o.set(is_checked!(overlays_only)));
+}
+
+/// The color `overlay` is drawn in.
+pub fn color(overlay: Overlay) -> String {
+    COLORS.with(|c| c.borrow()[overlay as usize].clone())
+}
+
+/// Whether the lattice point (x, y) gets a vector. The point at the origin always does, so the
+/// vectors left stay where they were.
+pub fn shows_vector(x: i32, y: i32) -> bool {
+    let stride = STRIDE.with(Cell::get);
+    x.rem_euclid(stride) == 0 && y.rem_euclid(stride) == 0
+}
+
+/// Factor on the arrow lengths of the vector overlays.
+pub fn vector_length() -> f64 {
+    LENGTH_FACTOR.with(Cell::get)
+}
+
+/// Whether the field is hidden behind `BACKGROUND`. It is still computed and kept, the analysis
+/// and the views work on it as before.
+pub fn overlays_only() -> bool {
+    HIDE_FIELD.with(Cell::get)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn thinned_vectors_keep_the_origin() {
+        assert!(shows_vector(-3, 5));
+        STRIDE.with(|s| s.set(3));
+        assert!(shows_vector(0, 0));
+        assert!(shows_vector(-3, 6));
+        assert!(!shows_vector(-2, 6));
+        assert!(!shows_vector(3, 1));
+    }
+}
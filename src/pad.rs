@@ -0,0 +1,91 @@
+//! Square controls that set two sliders at once, generated by `define_noise!` for its `pads`.
+
+use web_sys::{HtmlCanvasElement, HtmlInputElement, MouseEvent};
+
+use crate::charts::Chart;
+
+fn range(slider: &HtmlInputElement) -> (f64, f64) {
+    (
+        slider.min().parse().unwrap_or(0.),
+        slider.max().parse().unwrap_or(100.),
+    )
+}
+
+/// Position of `value` along the slider, 0 at its minimum and 1 at its maximum.
+fn to_fraction(value: f64, (min, max): (f64, f64)) -> f64 {
+    if max <= min {
+        return 0.;
+    }
+    ((value - min) / (max - min)).clamp(0., 1.)
+}
+
+fn from_fraction(fraction: f64, (min, max): (f64, f64)) -> f64 {
+    min + fraction.clamp(0., 1.) * (max - min)
+}
+
+/// Moves both sliders to the cursor while the left button is held, returns whether either moved.
+pub fn drag(
+    pad: &HtmlCanvasElement,
+    x_slider: &HtmlInputElement,
+    y_slider: &HtmlInputElement,
+    event: &MouseEvent,
+) -> bool {
+    if event.buttons() & 1 == 0 {
+        return false;
+    }
+    let x = event.offset_x() as f64 / pad.client_width().max(1) as f64;
+    let y = 1. - event.offset_y() as f64 / pad.client_height().max(1) as f64;
+
+    let previous = (x_slider.value_as_number(), y_slider.value_as_number());
+    x_slider.set_value_as_number(from_fraction(x, range(x_slider)));
+    y_slider.set_value_as_number(from_fraction(y, range(y_slider)));
+    (x_slider.value_as_number(), y_slider.value_as_number()) != previous
+}
+
+pub fn draw(pad: &HtmlCanvasElement, x_slider: &HtmlInputElement, y_slider: &HtmlInputElement) {
+    let chart = Chart::new(pad);
+    let (width, height) = (chart.width, chart.height);
+
+    chart.clear("#ffffff");
+    for quarter in 1..4 {
+        let t = quarter as f64 / 4.;
+        chart.line((t * width, 0.), (t * width, height), "#eeeeee");
+        chart.line((0., t * height), (width, t * height), "#eeeeee");
+    }
+    chart.polyline(
+        &[
+            (0.5, 0.5),
+            (width - 0.5, 0.5),
+            (width - 0.5, height - 0.5),
+            (0.5, height - 0.5),
+            (0.5, 0.5),
+        ],
+        "#888888",
+    );
+
+    let point = (
+        to_fraction(x_slider.value_as_number(), range(x_slider)) * width,
+        (1. - to_fraction(y_slider.value_as_number(), range(y_slider))) * height,
+    );
+    chart.line((point.0, 0.), (point.0, height), "#cccccc");
+    chart.line((0., point.1), (width, point.1), "#cccccc");
+    chart.circle(point, 5., "#1f77b4");
+
+    chart.text(&format!("{} →", x_slider.id()), width - 90., height - 6., "#444444");
+    chart.text(&format!("↑ {}", y_slider.id()), 4., 12., "#444444");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fractions_map_onto_the_slider_range() {
+        let range = (1., 4.);
+        assert_eq!(to_fraction(2.5, range), 0.5);
+        assert_eq!(from_fraction(0.5, range), 2.5);
+        assert_eq!(to_fraction(10., range), 1.);
+        assert_eq!(from_fraction(-1., range), 1.);
+        assert_eq!(to_fraction(3., (2., 2.)), 0.);
+    }
+}
@@ -0,0 +1,195 @@
+//! Shows the seed's permutation table and swaps in a generated, reference or edited one.
+
+use std::cell::{Cell, LazyCell};
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlElement, HtmlInputElement, HtmlSelectElement, HtmlTextAreaElement};
+
+use crate::{
+    noises::helpers::{PERLIN_PERMUTATION, shuffle_with},
+    rng::{self, Generator},
+    *,
+};
+
+thread_local! {
+    /// The table every noise uses instead of shuffling its own.
+    static OVERRIDE: Cell<Option<[usize; 256]>> = const { Cell::new(None) };
+}
+
+elements!(
+    (permutation_override, HtmlInputElement),
+    (permutation_table_text, HtmlTextAreaElement),
+    (permutation_generator, HtmlSelectElement),
+    (permutation_seed, HtmlInputElement),
+    (permutation_shuffle, HtmlElement),
+    (permutation_reference, HtmlElement),
+    (permutation_status, HtmlElement),
+    (seed, HtmlInputElement),
+);
+define_closure!(permutation_override_change, on_override_change);
+define_closure!(permutation_table_text_input, on_table_input);
+define_closure!(permutation_shuffle_click, on_shuffle);
+define_closure!(permutation_reference_click, on_reference);
+
+pub fn setup() {
+    add_callback!(permutation_override, "input", permutation_override_change);
+    add_callback!(permutation_table_text, "input", permutation_table_text_input);
+    add_callback!(permutation_shuffle, "click", permutation_shuffle_click);
+    add_callback!(permutation_reference, "click", permutation_reference_click);
+}
+
+/// The table set in the panel, `None` while every noise shuffles its own.
+pub fn table() -> Option<[usize; 256]> {
+    OVERRIDE.with(Cell::get)
+}
+
+/// Replaces the table set in the panel, without touching the panel itself.
+pub fn set_table(table: Option<[usize; 256]>) {
+    OVERRIDE.with(|o| o.set(table));
+}
+
+fn set_status(text: &str) {
+    PERMUTATION_STATUS.with(|s| s.set_inner_text(text));
+}
+
+/// The entries sixteen to a line, separated by commas so the table pastes into code as it is.
+fn format_table(table: &[usize; 256]) -> String {
+    table
+        .chunks(16)
+        .map(|row| {
+            let row: Vec<String> = row.iter().map(|p| p.to_string()).collect();
+            row.join(", ")
+        })
+        .collect::<Vec<_>>()
+        .join(",\n")
+}
+
+/// Reads a table back from entries separated by commas or whitespace. Every number from 0 to 255
+/// has to appear exactly once.
+fn parse_table(text: &str) -> Result<[usize; 256], String> {
+    let entries = text
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|word| !word.is_empty())
+        .map(|word| match word.parse::<usize>() {
+            Ok(entry) if entry < 256 => Ok(entry),
+            _ => Err(format!("{word} is not a number from 0 to 255")),
+        })
+        .collect::<Result<Vec<usize>, String>>()?;
+    let table: [usize; 256] = entries
+        .as_slice()
+        .try_into()
+        .map_err(|_| format!("{} entries, a table has 256", entries.len()))?;
+
+    let mut counts = [0; 256];
+    for &entry in &table {
+        counts[entry] += 1;
+    }
+    if let Some(repeated) = counts.iter().position(|&c| c > 1) {
+        let missing = counts.iter().position(|&c| c == 0).unwrap_or(0);
+        return Err(format!(
+            "{repeated} appears {} times, {missing} is missing",
+            counts[repeated]
+        ));
+    }
+    Ok(table)
+}
+
+fn shuffled(generator: Generator, seed: u32) -> [usize; 256] {
+    let mut table: [usize; 256] = std::array::from_fn(|i| i);
+    shuffle_with(&mut table, seed, generator.rng());
+    table
+}
+
+/// Puts `table` in place of the shuffled tables and redraws.
+fn use_table(table: [usize; 256], status: &str) {
+    set_table(Some(table));
+    PERMUTATION_OVERRIDE.with(|o| o.set_checked(true));
+    set_status(status);
+    update_current_noise();
+}
+
+fn show_table(table: &[usize; 256]) {
+    let text = format_table(table);
+    PERMUTATION_TABLE_TEXT.with(|t| {
+        // Setting the same text again would move the cursor of someone editing it.
+        if t.value() != text {
+            t.set_value(&text);
+        }
+    });
+}
+
+fn on_override_change() {
+    if !is_checked!(permutation_override) {
+        set_table(None);
+        set_status("Every noise shuffles its own table");
+        update_current_noise();
+        return;
+    }
+    match parse_table(&PERMUTATION_TABLE_TEXT.with(|t| t.value())) {
+        Ok(table) => use_table(table, "Every noise uses the table below"),
+        Err(error) => {
+            PERMUTATION_OVERRIDE.with(|o| o.set_checked(false));
+            set_status(&error);
+        }
+    }
+}
+
+/// Edits take effect as soon as the text is a whole table again.
+fn on_table_input() {
+    match parse_table(&PERMUTATION_TABLE_TEXT.with(|t| t.value())) {
+        Ok(table) => use_table(table, "Every noise uses the edited table"),
+        Err(error) => set_status(&error),
+    }
+}
+
+fn on_shuffle() {
+    let generator = Generator::from_name(&PERMUTATION_GENERATOR.with(|g| g.value()));
+    let seed = PERMUTATION_SEED.with(|s| s.value().parse().unwrap_or(0));
+    let table = shuffled(generator, seed);
+    show_table(&table);
+    use_table(
+        table,
+        &format!("Every noise uses the table of {generator:?} with seed {seed}"),
+    );
+}
+
+fn on_reference() {
+    show_table(&PERLIN_PERMUTATION);
+    use_table(
+        PERLIN_PERMUTATION,
+        "Every noise uses Ken Perlin's reference table",
+    );
+}
+
+/// Keeps the table of the seed on screen in view while no table is set.
+pub fn on_frame_drawn() {
+    if table().is_some() {
+        return;
+    }
+    let seed = SEED.with(|s| s.value().parse().unwrap_or(0));
+    show_table(&shuffled(rng::generator(), seed));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tables_round_trip_and_bad_ones_are_refused() {
+        assert_eq!(
+            parse_table(&format_table(&PERLIN_PERMUTATION)),
+            Ok(PERLIN_PERMUTATION)
+        );
+        let table = shuffled(Generator::Lcg, 3);
+        assert_eq!(parse_table(&format_table(&table)), Ok(table));
+
+        let mut twice = PERLIN_PERMUTATION;
+        twice[1] = twice[0];
+        assert_eq!(
+            parse_table(&format_table(&twice)),
+            Err("151 appears 2 times, 160 is missing".to_string())
+        );
+        assert!(parse_table("1, 2, 3").is_err());
+        assert!(parse_table(&format_table(&PERLIN_PERMUTATION).replace("151", "256")).is_err());
+    }
+}
@@ -0,0 +1,134 @@
+//! Just enough of PNG for 8-bit RGBA images, stored uncompressed.
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+const MAX_STORED_BLOCK: usize = 0xffff;
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffff_u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (a, b) = bytes.iter().fold((1_u32, 0_u32), |(a, b), &byte| {
+        let a = (a + byte as u32) % 65521;
+        (a, (b + a) % 65521)
+    });
+    (b << 16) | a
+}
+
+fn chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend((data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend(kind);
+    out.extend(data);
+    let crc = crc32(&out[start..]);
+    out.extend(crc.to_be_bytes());
+}
+
+pub fn encode(width: u32, height: u32, rgba: &[u8]) -> Vec<u8> {
+    let row = width as usize * 4;
+    let mut raw = Vec::with_capacity((row + 1) * height as usize);
+    for line in rgba.chunks_exact(row) {
+        raw.push(0);
+        raw.extend(line);
+    }
+
+    let mut zlib = vec![0x78, 0x01];
+    let blocks = raw.chunks(MAX_STORED_BLOCK).collect::<Vec<_>>();
+    for (i, block) in blocks.iter().enumerate() {
+        zlib.push((i + 1 == blocks.len()) as u8);
+        let len = block.len() as u16;
+        zlib.extend(len.to_le_bytes());
+        zlib.extend((!len).to_le_bytes());
+        zlib.extend(*block);
+    }
+    zlib.extend(adler32(&raw).to_be_bytes());
+
+    let mut header = Vec::new();
+    header.extend(width.to_be_bytes());
+    header.extend(height.to_be_bytes());
+    header.extend([8, 6, 0, 0, 0]);
+
+    let mut out = SIGNATURE.to_vec();
+    chunk(&mut out, b"IHDR", &header);
+    chunk(&mut out, b"IDAT", &zlib);
+    chunk(&mut out, b"IEND", &[]);
+    out
+}
+
+#[cfg(test)]
+pub fn decode(bytes: &[u8]) -> Result<(u32, u32, Vec<u8>), String> {
+    let rest = bytes.strip_prefix(&SIGNATURE).ok_or("not a png")?;
+
+    let (mut width, mut height, mut zlib) = (0, 0, Vec::new());
+    let mut at = 0;
+    while at + 12 <= rest.len() {
+        let len = u32::from_be_bytes(rest[at..at + 4].try_into().unwrap()) as usize;
+        let kind = &rest[at + 4..at + 8];
+        let data = rest.get(at + 8..at + 8 + len).ok_or("truncated chunk")?;
+        match kind {
+            b"IHDR" => {
+                width = u32::from_be_bytes(data[0..4].try_into().unwrap());
+                height = u32::from_be_bytes(data[4..8].try_into().unwrap());
+                if data[8..13] != [8, 6, 0, 0, 0] {
+                    return Err("only 8-bit non-interlaced RGBA is supported".into());
+                }
+            }
+            b"IDAT" => zlib.extend(data),
+            _ => (),
+        }
+        at += len + 12;
+    }
+
+    let mut raw = Vec::new();
+    let mut at = 2;
+    loop {
+        let header = *zlib.get(at).ok_or("truncated zlib stream")?;
+        if header & 0b110 != 0 {
+            return Err("compressed images are not supported, rewrite it with BLESS=1".into());
+        }
+        let len = u16::from_le_bytes(zlib[at + 1..at + 3].try_into().unwrap()) as usize;
+        raw.extend(zlib.get(at + 5..at + 5 + len).ok_or("truncated block")?);
+        at += 5 + len;
+        if header & 1 == 1 {
+            break;
+        }
+    }
+
+    let row = width as usize * 4;
+    let mut rgba = Vec::with_capacity(row * height as usize);
+    for line in raw.chunks_exact(row + 1) {
+        if line[0] != 0 {
+            return Err("only unfiltered rows are supported".into());
+        }
+        rgba.extend(&line[1..]);
+    }
+    Ok((width, height, rgba))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips() {
+        let rgba: Vec<u8> = (0..300 * 200 * 4).map(|i| (i * 7 % 251) as u8).collect();
+        assert_eq!(decode(&encode(300, 200, &rgba)), Ok((300, 200, rgba)));
+    }
+
+    #[test]
+    fn checksums_match_reference_values() {
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    }
+}
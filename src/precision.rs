@@ -0,0 +1,216 @@
+//! Samples with f32 coordinates, to show what single precision loses far from the origin.
+
+use std::cell::Cell;
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlElement, HtmlInputElement};
+
+use crate::{
+    drawer::{HALF_RESOLUTION, RESOLUTION, draw_line, draw_text},
+    *,
+};
+
+/// How the canvas pixels are turned into noise coordinates, captured once per frame so the
+/// parallel sampling does not touch the page.
+#[derive(Clone, Copy)]
+pub struct Precision {
+    /// Whether the pixel coordinates and the sampled values are carried in f32.
+    pub single: bool,
+    /// Noise units the view is moved right and down by.
+    pub offset: f64,
+    /// Whether the difference to the f64 field is shown instead of the noise.
+    pub difference: bool,
+    /// Whether the left half of the canvas is sampled in f32 and the right half in f64.
+    pub split: bool,
+}
+
+thread_local! {
+    static PRECISION: Cell<Precision> = const {
+        Cell::new(Precision { single: false, offset: 0., difference: false, split: false })
+    };
+}
+
+elements!(
+    (precision_f32, HtmlInputElement),
+    (precision_offset, HtmlInputElement),
+    (precision_offset_display, HtmlElement),
+    (precision_difference, HtmlInputElement),
+    (precision_split, HtmlInputElement),
+    (precision_spacing, HtmlElement),
+    (precision_status, HtmlElement),
+);
+define_closure!(precision_input_change, on_input_change);
+
+pub fn setup() {
+    add_callback!(precision_f32, "input", precision_input_change);
+    add_callback!(precision_offset, "input", precision_input_change);
+    add_callback!(precision_difference, "input", precision_input_change);
+    add_callback!(precision_split, "input", precision_input_change);
+    show_offset(offset());
+}
+
+/// The slider picks the power of ten of the offset, its left end leaves the view at the origin.
+fn offset() -> f64 {
+    let power = parse_value!(precision_offset, f64);
+    if power == 0. { 0. } else { 10f64.powf(power) }
+}
+
+fn show_offset(offset: f64) {
+    set_text!(precision_offset, &format!("{offset:.0}"));
+    let text = if offset == 0. {
+        String::new()
+    } else {
+        let (single, double) = spacing(offset);
+        format!(
+            "Around the offset f32 coordinates step by {single} units, f64 ones by {double:.1e}"
+        )
+    };
+    PRECISION_SPACING.with(|s| s.set_inner_text(&text));
+}
+
+/// The distance from `offset` to the next larger f32 and f64.
+fn spacing(offset: f64) -> (f64, f64) {
+    let single = offset as f32;
+    let single = f32::from_bits(single.to_bits() + 1) as f64 - single as f64;
+    let double = f64::from_bits(offset.to_bits() + 1) - offset;
+    (single, double)
+}
+
+fn on_input_change() {
+    read_controls();
+    update_current_noise();
+}
+
+/// Picks up the controls from the page, also after a saved state has been applied.
+pub fn read_controls() {
+    let offset = offset();
+    show_offset(offset);
+    let single = is_checked!(precision_f32);
+    let split = is_checked!(precision_split);
+    let difference = single && !split && is_checked!(precision_difference);
+    PRECISION.with(|p| {
+        p.set(Precision {
+            single,
+            offset,
+            difference,
+            split,
+        })
+    });
+    if !difference {
+        PRECISION_STATUS.with(|s| s.set_inner_text(""));
+    }
+}
+
+impl Precision {
+    fn single_at(&self, x: usize) -> bool {
+        if self.split {
+            x < HALF_RESOLUTION as usize
+        } else {
+            self.single
+        }
+    }
+
+    pub fn current() -> Self {
+        PRECISION.with(Cell::get)
+    }
+
+    /// The same view sampled in f64, the reference of the difference view.
+    pub fn double(self) -> Self {
+        Precision {
+            single: false,
+            difference: false,
+            split: false,
+            ..self
+        }
+    }
+
+    /// Noise coordinates of the canvas position (x, y), in pixels, with `scale` pixels per noise
+    /// unit.
+    pub fn coordinates(&self, x: f64, y: f64, scale: f64) -> (f64, f64) {
+        if self.single_at(x as usize) {
+            let (half, scale, offset) = (HALF_RESOLUTION as f32, scale as f32, self.offset as f32);
+            let nx = (x as f32 - half) / scale + offset;
+            let ny = (y as f32 - half) / scale + offset;
+            (nx as f64, ny as f64)
+        } else {
+            let half = HALF_RESOLUTION as f64;
+            (
+                (x - half) / scale + self.offset,
+                (y - half) / scale + self.offset,
+            )
+        }
+    }
+
+    /// The sampled value of a pixel in column `x`, carried at the precision of that column.
+    pub fn value(&self, x: usize, value: f64) -> f64 {
+        if self.single_at(x) {
+            value as f32 as f64
+        } else {
+            value
+        }
+    }
+}
+
+/// Marks the halves of the split comparison.
+pub fn on_frame_drawn() {
+    if !Precision::current().split || !view::is_flat() {
+        return;
+    }
+    let (half, bottom) = (HALF_RESOLUTION as f64, RESOLUTION as f64);
+    draw_line(half, 0., half, bottom, "#000000");
+    draw_text("f32", 6., bottom - 6., "#000000");
+    draw_text("f64", half + 6., bottom - 6., "#000000");
+}
+
+/// `field` minus `reference`, stretched so the largest difference reaches ±1. The largest
+/// difference itself is shown below the controls.
+pub fn difference(field: &mut [f64], reference: &[f64]) {
+    for (value, reference) in field.iter_mut().zip(reference) {
+        *value -= reference;
+    }
+    let largest = field.iter().fold(0f64, |m, d| m.max(d.abs()));
+    PRECISION_STATUS
+        .with(|s| s.set_inner_text(&format!("Largest difference from f64: {largest:.3e}")));
+    if largest != 0. {
+        field.iter_mut().for_each(|d| *d /= largest);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn f32_merges_neighbouring_pixels_far_from_the_origin() {
+        let near = Precision {
+            single: true,
+            offset: 0.,
+            difference: false,
+            split: false,
+        };
+        let far = Precision {
+            offset: 1e7,
+            ..near
+        };
+        assert_ne!(
+            near.coordinates(10., 0., 50.),
+            near.coordinates(11., 0., 50.)
+        );
+        assert_eq!(far.coordinates(10., 0., 50.), far.coordinates(11., 0., 50.));
+        assert_ne!(
+            far.double().coordinates(10., 0., 50.),
+            far.double().coordinates(11., 0., 50.)
+        );
+
+        let split = Precision { split: true, ..far };
+        assert_eq!(
+            split.coordinates(10., 0., 50.),
+            split.coordinates(11., 0., 50.)
+        );
+        assert_ne!(
+            split.coordinates(300., 0., 50.),
+            split.coordinates(301., 0., 50.)
+        );
+        assert_eq!(spacing(1e7).0, 1.);
+    }
+}
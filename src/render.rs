@@ -0,0 +1,241 @@
+//! Offline rendering of flat TOML or JSON settings files, for the `render` binary.
+
+use std::{iter::Peekable, str::Chars};
+
+use crate::{
+    core::{Viewport, from_settings, scale},
+    drawer::RESOLUTION,
+    field::colorize,
+    png,
+};
+
+/// Keys of a settings file that size the image rather than set up the noise.
+const SIZE_KEYS: [&str; 2] = ["width", "height"];
+
+/// Splits a settings file into `(key, value)` pairs, with string quotes removed.
+///
+/// Either format is flat, one value per key and no nesting. TOML files hold `key = value` lines,
+/// with `#` starting a comment and no `[tables]`. JSON files hold a single object whose values
+/// are strings, numbers or booleans, never objects or arrays. Besides the ids of the noise's
+/// controls, `width` and `height` size the image, `RESOLUTION` pixels each by default.
+pub fn parse_settings(text: &str) -> Result<Vec<(String, String)>, String> {
+    let unquote = |s: &str| s.trim().trim_matches('"').to_string();
+    let text = text.trim();
+
+    if let Some(object) = text.strip_prefix('{') {
+        let object = object.strip_suffix('}').ok_or("Unterminated JSON object")?;
+        parse_json_members(object)
+    } else {
+        text.lines()
+            .map(|line| line.split('#').next().unwrap().trim())
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                if line.starts_with('[') {
+                    return Err(format!("Tables are not supported, got {line}"));
+                }
+                let (key, value) = line
+                    .split_once('=')
+                    .ok_or_else(|| format!("Expected key = value, got {line}"))?;
+                Ok((unquote(key), unquote(value)))
+            })
+            .collect()
+    }
+}
+
+/// The members of a flat JSON object, without its braces.
+fn parse_json_members(object: &str) -> Result<Vec<(String, String)>, String> {
+    let mut chars = object.chars().peekable();
+    let skip_whitespace = |chars: &mut Peekable<Chars>| {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+    };
+    let mut pairs = Vec::new();
+
+    skip_whitespace(&mut chars);
+    if chars.peek().is_none() {
+        return Ok(pairs);
+    }
+    loop {
+        skip_whitespace(&mut chars);
+        if chars.next() != Some('"') {
+            return Err("Expected a quoted key in the JSON object".to_string());
+        }
+        let key = parse_json_string(&mut chars)?;
+        skip_whitespace(&mut chars);
+        if chars.next() != Some(':') {
+            return Err(format!("Expected : after \"{key}\""));
+        }
+        skip_whitespace(&mut chars);
+        let value = match chars.peek() {
+            Some('"') => {
+                chars.next();
+                parse_json_string(&mut chars)?
+            }
+            Some('{' | '[') => {
+                return Err(format!(
+                    "{key}: nested objects and arrays are not supported"
+                ));
+            }
+            _ => {
+                let mut value = String::new();
+                while let Some(c) = chars.next_if(|&c| c != ',' && !c.is_whitespace()) {
+                    value.push(c);
+                }
+                if value.is_empty() || value.contains(['"', '{', '}', '[', ']', ':']) {
+                    return Err(format!("{key}: expected a string, number or boolean"));
+                }
+                value
+            }
+        };
+        pairs.push((key, value));
+        skip_whitespace(&mut chars);
+        match chars.next() {
+            None => return Ok(pairs),
+            Some(',') => {}
+            Some(c) => return Err(format!("Expected , between members, got {c}")),
+        }
+    }
+}
+
+/// A JSON string after its opening quote, up to and including the closing one.
+fn parse_json_string(chars: &mut impl Iterator<Item = char>) -> Result<String, String> {
+    let mut string = String::new();
+    loop {
+        match chars.next().ok_or("Unterminated JSON string")? {
+            '"' => return Ok(string),
+            '\\' => string.push(match chars.next().ok_or("Unterminated JSON string")? {
+                c @ ('"' | '\\' | '/') => c,
+                'n' => '\n',
+                't' => '\t',
+                c => return Err(format!("Unsupported escape \\{c} in a JSON string")),
+            }),
+            c => string.push(c),
+        }
+    }
+}
+
+/// The size of the image the settings ask for, `RESOLUTION` on a side by default.
+fn size(pairs: &[(String, String)]) -> Result<(u32, u32), String> {
+    let side = |key: &str| match pairs.iter().find(|(k, _)| k == key) {
+        Some((_, value)) => value
+            .parse()
+            .ok()
+            .filter(|&side| side > 0)
+            .ok_or_else(|| format!("Invalid value {value} for {key}")),
+        None => Ok(RESOLUTION),
+    };
+    Ok((side("width")?, side("height")?))
+}
+
+/// The field the page would draw for the settings, one value per pixel in row-major order, with
+/// the width and height of the image.
+pub fn render(pairs: &[(String, String)]) -> Result<(u32, u32, Vec<f64>), String> {
+    let (width, height) = size(pairs)?;
+    let noise_pairs: Vec<_> = pairs
+        .iter()
+        .filter(|(key, _)| !SIZE_KEYS.contains(&key.as_str()))
+        .cloned()
+        .collect();
+    let sampler = from_settings(&noise_pairs)?;
+    let scale = scale(&noise_pairs)?;
+    let (w, h) = (width as f64 / scale, height as f64 / scale);
+    let view = Viewport::new(-w / 2., -h / 2., w, h, width as usize, height as usize);
+    let mut field = vec![0.; view.columns * view.rows];
+    sampler.fill(&mut field, &view);
+    Ok((width, height, field))
+}
+
+/// Colored the same way as on the canvas.
+pub fn to_png(width: u32, height: u32, field: &[f64]) -> Vec<u8> {
+    png::encode(width, height, &colorize(field))
+}
+
+/// Raw noise values as little-endian `f32`s, for tools that want the data rather than an image.
+pub fn to_raw(field: &[f64]) -> Vec<u8> {
+    field
+        .iter()
+        .flat_map(|&v| (v as f32).to_le_bytes())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(list: &[(&str, &str)]) -> Vec<(String, String)> {
+        list.iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn parses_toml_and_json_alike() {
+        let toml = "# ridged perlin\nnoise = \"perlin\"\noctaves = 5\nnoise_type = \"ridge\"\n";
+        let json = r#"{ "noise": "perlin", "octaves": 5, "noise_type": "ridge" }"#;
+        let expected = pairs(&[
+            ("noise", "perlin"),
+            ("octaves", "5"),
+            ("noise_type", "ridge"),
+        ]);
+        assert_eq!(parse_settings(toml), Ok(expected.clone()));
+        assert_eq!(parse_settings(json), Ok(expected));
+        assert_eq!(parse_settings("{ }"), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn rejects_json_that_is_not_flat() {
+        for json in [
+            r#"{ "noise": "perlin", "octaves": { "count": 5 } }"#,
+            r#"{ "noise": "perlin", "octaves": [5] }"#,
+            r#"{ "noise": "perlin", "name": "a, b" "#,
+            r#"{ "noise": "perlin", }"#,
+            r#"{ "noise": "perlin" "octaves": 5 }"#,
+            r#"{ noise: "perlin" }"#,
+        ] {
+            assert!(parse_settings(json).is_err(), "{json}");
+        }
+        assert_eq!(
+            parse_settings(r#"{ "noise": "perlin", "name": "a, \"b\"" }"#),
+            Ok(pairs(&[("noise", "perlin"), ("name", "a, \"b\"")]))
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_settings() {
+        assert!(render(&pairs(&[("noise", "perlin"), ("sharpness", "2")])).is_err());
+        assert!(render(&pairs(&[("noise", "perlin"), ("noise_type", "billow")])).is_err());
+        assert!(render(&pairs(&[("octaves", "2")])).is_err());
+        assert!(render(&pairs(&[("noise", "perlin"), ("width", "0")])).is_err());
+    }
+
+    #[test]
+    fn renders_a_full_canvas() {
+        let (width, height, field) =
+            render(&pairs(&[("noise", "simplex"), ("seed", "3")])).unwrap();
+        assert_eq!((width, height), (RESOLUTION, RESOLUTION));
+        assert_eq!(field.len(), (RESOLUTION * RESOLUTION) as usize);
+        assert_eq!(to_raw(&field).len(), field.len() * 4);
+    }
+
+    #[test]
+    fn renders_the_asked_size_around_the_same_center() {
+        let settings = [("noise", "perlin"), ("seed", "3")];
+        let (_, _, full) = render(&pairs(&settings)).unwrap();
+        let (width, height, field) = render(&pairs(&[
+            settings[0],
+            settings[1],
+            ("width", "40"),
+            ("height", "20"),
+        ]))
+        .unwrap();
+        assert_eq!((width, height, field.len()), (40, 20, 40 * 20));
+        // The smaller image is the middle of the full canvas.
+        let (left, top) = ((RESOLUTION - 40) / 2, (RESOLUTION - 20) / 2);
+        for row in 0..20 {
+            for column in 0..40 {
+                let full_index = ((top + row) * RESOLUTION + left + column) as usize;
+                let value = field[(row * 40 + column) as usize];
+                assert!((value - full[full_index]).abs() < 1e-9);
+            }
+        }
+    }
+}
@@ -0,0 +1,211 @@
+//! Exports both compare captures side by side as one png, with their analysis underneath.
+
+use std::cell::LazyCell;
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlCanvasElement, HtmlElement};
+
+use crate::{
+    analysis::{
+        fft::{SPECTRUM_SIZE, power_spectrum},
+        histogram::{BINS, histogram},
+        similarity::{max_abs_diff, rmse, ssim},
+    },
+    charts::Chart,
+    compare::{self, Capture},
+    drawer::RESOLUTION,
+    *,
+};
+
+const FILE_NAME: &str = "noise_comparison.png";
+const MARGIN: f64 = 20.;
+const HEADER_HEIGHT: f64 = 24.;
+const HISTOGRAM_HEIGHT: f64 = 120.;
+const LINE_HEIGHT: f64 = 14.;
+const LABELS: [&str; 2] = ["A", "B"];
+/// Settings that differ between the two captures.
+const CHANGED_STYLE: &str = "#d62728";
+
+elements!(
+    (compare_export, HtmlElement),
+    (compare_export_status, HtmlElement),
+);
+define_closure!(compare_export_click, export);
+
+pub fn setup() {
+    add_callback!(compare_export, "click", compare_export_click);
+}
+
+fn set_status(text: &str) {
+    COMPARE_EXPORT_STATUS.with(|s| s.set_inner_text(text));
+}
+
+/// Log power spectrum as grayscale rgba, with the zero frequency moved to the center and the
+/// strongest frequency white.
+fn spectrum_pixels(field: &[f64]) -> Vec<u8> {
+    let spectrum: Vec<f64> = power_spectrum(field).iter().map(|p| p.ln_1p()).collect();
+    let max = spectrum.iter().copied().fold(0., f64::max).max(1e-12);
+    let half = SPECTRUM_SIZE / 2;
+    (0..SPECTRUM_SIZE * SPECTRUM_SIZE)
+        .flat_map(|i| {
+            let (x, y) = (i % SPECTRUM_SIZE, i / SPECTRUM_SIZE);
+            let (x, y) = ((x + half) % SPECTRUM_SIZE, (y + half) % SPECTRUM_SIZE);
+            let gray = (spectrum[y * SPECTRUM_SIZE + x] / max * 255.) as u8;
+            [gray, gray, gray, 255]
+        })
+        .collect()
+}
+
+/// Draws the histogram as a step outline in a frame, `max` sets the common vertical scale.
+fn draw_histogram(chart: &Chart, bins: &[f64; BINS], max: f64, left: f64, top: f64) {
+    let width = RESOLUTION as f64;
+    chart.polyline(
+        &[
+            (left, top),
+            (left + width, top),
+            (left + width, top + HISTOGRAM_HEIGHT),
+            (left, top + HISTOGRAM_HEIGHT),
+            (left, top),
+        ],
+        "#888888",
+    );
+
+    let bin_width = width / BINS as f64;
+    let bottom = top + HISTOGRAM_HEIGHT;
+    let mut points = vec![(left, bottom)];
+    for (i, fraction) in bins.iter().enumerate() {
+        let y = bottom - fraction / max * (HISTOGRAM_HEIGHT - LINE_HEIGHT);
+        let x = left + i as f64 * bin_width;
+        points.extend([(x, y), (x + bin_width, y)]);
+    }
+    points.push((left + width, bottom));
+    chart.polyline(&points, "#1f77b4");
+    chart.text("-1", left + 4., top + 12., "#444444");
+    chart.text("1", left + width - 12., top + 12., "#444444");
+}
+
+fn draw_column(chart: &Chart, slot: usize, capture: &Capture, other: &Capture, max: f64) {
+    let left = MARGIN + slot as f64 * (RESOLUTION as f64 + MARGIN);
+    let mut top = MARGIN;
+
+    chart.text(
+        &format!("{}: {}", LABELS[slot], capture.noise),
+        left,
+        top + 12.,
+        "#000000",
+    );
+    top += HEADER_HEIGHT;
+
+    chart.image(&capture.pixels, RESOLUTION, RESOLUTION, left, top);
+    top += RESOLUTION as f64 + MARGIN;
+
+    draw_histogram(chart, &histogram(&capture.field), max, left, top);
+    top += HISTOGRAM_HEIGHT + MARGIN;
+
+    let spectrum_left = left + (RESOLUTION as f64 - SPECTRUM_SIZE as f64) / 2.;
+    chart.image(
+        &spectrum_pixels(&capture.field),
+        SPECTRUM_SIZE as u32,
+        SPECTRUM_SIZE as u32,
+        spectrum_left,
+        top,
+    );
+    top += SPECTRUM_SIZE as f64 + MARGIN;
+
+    for (id, value) in &capture.parameters {
+        let changed = !other.parameters.contains(&(id.clone(), value.clone()));
+        let style = if changed { CHANGED_STYLE } else { "#000000" };
+        top += LINE_HEIGHT;
+        chart.text(&format!("{id} = {value}"), left, top, style);
+    }
+}
+
+/// Lays both captures out on a detached canvas, the download link takes it as a data url.
+fn render(a: &Capture, b: &Capture) -> Option<HtmlCanvasElement> {
+    let rows = a.parameters.len().max(b.parameters.len()) as f64;
+    let width = 2. * RESOLUTION as f64 + 3. * MARGIN;
+    // Header, image, histogram, spectrum and settings of the columns, then the summary line.
+    let column = HEADER_HEIGHT + RESOLUTION as f64 + HISTOGRAM_HEIGHT + SPECTRUM_SIZE as f64;
+    let height = column + rows * LINE_HEIGHT + LINE_HEIGHT + 6. * MARGIN;
+
+    let canvas = DOCUMENT
+        .with(|d| d.create_element("canvas"))
+        .ok()?
+        .dyn_into::<HtmlCanvasElement>()
+        .ok()?;
+    canvas.set_width(width as u32);
+    canvas.set_height(height as u32);
+    let chart = Chart::new(&canvas);
+    chart.clear("#ffffff");
+
+    let comparable = a.field.len() == b.field.len() && !a.field.is_empty();
+    let max = if comparable {
+        histogram(&a.field)
+            .iter()
+            .chain(&histogram(&b.field))
+            .copied()
+            .fold(0., f64::max)
+            .max(1e-12)
+    } else {
+        1.
+    };
+    draw_column(&chart, 0, a, b, max);
+    draw_column(&chart, 1, b, a, max);
+
+    let summary = if comparable {
+        format!(
+            "RMSE {:.4}, largest difference {:.4}, SSIM {:.4}. Settings in red differ between A and B.",
+            rmse(&a.field, &b.field),
+            max_abs_diff(&a.field, &b.field),
+            ssim(&a.field, &b.field)
+        )
+    } else {
+        "Settings in red differ between A and B.".to_string()
+    };
+    chart.text(&summary, MARGIN, height - MARGIN, "#000000");
+    Some(canvas)
+}
+
+fn export() {
+    let Some(url) = compare::with_captures(render)
+        .flatten()
+        .and_then(|canvas| canvas.to_data_url().ok())
+    else {
+        set_status("Set A and B first");
+        return;
+    };
+
+    let Some(link) = DOCUMENT
+        .with(|d| d.create_element("a"))
+        .ok()
+        .and_then(|a| a.dyn_into::<HtmlElement>().ok())
+    else {
+        return;
+    };
+    let _ = link.set_attribute("href", &url);
+    let _ = link.set_attribute("download", FILE_NAME);
+    link.click();
+    set_status(&format!("Saved as {FILE_NAME}"));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn spectrum_peaks_at_the_wave_frequency() {
+        let size = RESOLUTION as usize;
+        let cycles = 8.;
+        let field: Vec<f64> = (0..size * size)
+            .map(|i| (2. * PI * cycles * (i % size) as f64 / SPECTRUM_SIZE as f64).cos())
+            .collect();
+
+        let pixels = spectrum_pixels(&field);
+        let brightness = |x: usize, y: usize| pixels[(y * SPECTRUM_SIZE + x) * 4];
+        let center = SPECTRUM_SIZE / 2;
+        assert!(brightness(center + cycles as usize, center) >= 250);
+        assert!(brightness(center - cycles as usize, center) >= 250);
+        assert!(brightness(center, center + cycles as usize) < 128);
+    }
+}
@@ -0,0 +1,190 @@
+//! The random generators behind the noises, picked on the page and used counter-style.
+
+use std::cell::Cell;
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::HtmlSelectElement;
+
+use crate::*;
+
+pub trait Rng: Sync {
+    /// 32 random bits for `index` of the stream `seed`.
+    fn bits(&self, index: u32, seed: u32) -> u32;
+
+    /// A random number in [0, 1].
+    fn unit(&self, index: u32, seed: u32) -> f64 {
+        self.bits(index, seed) as f64 / u32::MAX as f64
+    }
+
+    /// A random number in [-1, 1].
+    fn signed(&self, index: u32, seed: u32) -> f64 {
+        self.unit(index, seed) * 2. - 1.
+    }
+
+    /// A random number in [0, 1] for one purpose, `stream`, of lattice cell (x, y). The numbers
+    /// of different streams of a cell are independent, unlike numbers derived from one hash of it.
+    fn cell(&self, x: i32, y: i32, seed: u32, stream: u32) -> f64 {
+        let row = self.bits(y as u32, seed);
+        let cell = self.bits(x as u32, row);
+        self.unit(stream, cell)
+    }
+}
+
+/// Squirrel Eiserloh's noise based hash, what the noises always used.
+pub struct SquirrelNoise5;
+
+impl Rng for SquirrelNoise5 {
+    fn bits(&self, index: u32, seed: u32) -> u32 {
+        squirrel_noise5::squirrel_noise5(index, seed)
+    }
+
+    fn unit(&self, index: u32, seed: u32) -> f64 {
+        squirrel_noise5::f32_zero_to_one_1d(index as i32, seed as i32) as f64
+    }
+
+    fn signed(&self, index: u32, seed: u32) -> f64 {
+        squirrel_noise5::f32_neg_one_to_one_1d(index as i32, seed as i32) as f64
+    }
+}
+
+/// One step of PCG32 from a state holding the seed and the index, with its XSH RR output.
+pub struct Pcg32;
+
+impl Rng for Pcg32 {
+    fn bits(&self, index: u32, seed: u32) -> u32 {
+        let state = ((seed as u64) << 32 | index as u64)
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        let xorshifted = (((state >> 18) ^ state) >> 27) as u32;
+        xorshifted.rotate_right((state >> 59) as u32)
+    }
+}
+
+/// xoshiro128** started from the index and the seed, its second output.
+pub struct Xoshiro128;
+
+impl Rng for Xoshiro128 {
+    fn bits(&self, index: u32, seed: u32) -> u32 {
+        let mut s = [
+            index ^ 0x9e37_79b9,
+            seed ^ 0x243f_6a88,
+            index.rotate_left(16) ^ 0x85a3_08d3,
+            seed.rotate_left(16) ^ 0x1319_8a2e,
+        ];
+        let mut next = || {
+            let result = s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+            let t = s[1] << 9;
+            s[2] ^= s[0];
+            s[3] ^= s[1];
+            s[1] ^= s[2];
+            s[0] ^= s[3];
+            s[2] ^= t;
+            s[3] = s[3].rotate_left(11);
+            result
+        };
+        next();
+        next()
+    }
+}
+
+/// One step of the Numerical Recipes LCG. Its low bits cycle with short periods, which
+/// `shuffle` turns into visibly regular permutation tables.
+pub struct Lcg;
+
+impl Rng for Lcg {
+    fn bits(&self, index: u32, seed: u32) -> u32 {
+        index
+            .wrapping_add(seed.wrapping_mul(0x9e37_79b9))
+            .wrapping_mul(1_664_525)
+            .wrapping_add(1_013_904_223)
+    }
+}
+
+/// The random number generators a noise can draw from, SquirrelNoise5 by default.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Generator {
+    SquirrelNoise5,
+    Pcg32,
+    Xoshiro128,
+    Lcg,
+}
+
+impl Generator {
+    pub fn rng(self) -> &'static dyn Rng {
+        match self {
+            Generator::SquirrelNoise5 => &SquirrelNoise5,
+            Generator::Pcg32 => &Pcg32,
+            Generator::Xoshiro128 => &Xoshiro128,
+            Generator::Lcg => &Lcg,
+        }
+    }
+
+    /// The generator of an option of the generator selects, SquirrelNoise5 for anything else.
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "pcg32" => Generator::Pcg32,
+            "xoshiro128" => Generator::Xoshiro128,
+            "lcg" => Generator::Lcg,
+            _ => Generator::SquirrelNoise5,
+        }
+    }
+}
+
+thread_local! {
+    static GENERATOR: Cell<Generator> = const { Cell::new(Generator::SquirrelNoise5) };
+}
+
+pub fn generator() -> Generator {
+    GENERATOR.with(Cell::get)
+}
+
+pub fn set_generator(generator: Generator) {
+    GENERATOR.with(|g| g.set(generator));
+}
+
+/// The generator picked on the page.
+pub fn current() -> &'static dyn Rng {
+    generator().rng()
+}
+
+elements!((rng_generator, HtmlSelectElement));
+define_closure!(rng_generator_change, on_generator_change);
+
+pub fn setup() {
+    add_callback!(rng_generator, "input", rng_generator_change);
+}
+
+fn on_generator_change() {
+    read_controls();
+    update_current_noise();
+}
+
+/// Picks up the generator from the page, also after a saved state has been applied.
+pub fn read_controls() {
+    set_generator(Generator::from_name(&parse_value!(rng_generator, String)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generators_are_reproducible_and_in_range() {
+        for generator in [
+            Generator::SquirrelNoise5,
+            Generator::Pcg32,
+            Generator::Xoshiro128,
+            Generator::Lcg,
+        ] {
+            let rng = generator.rng();
+            let distinct: std::collections::HashSet<u32> =
+                (0..1000).map(|i| rng.bits(i, 42)).collect();
+            assert!(distinct.len() > 990, "{generator:?} repeats itself");
+            for i in 0..1000 {
+                assert_eq!(rng.bits(i, 7), rng.bits(i, 7));
+                assert!((0.0..=1.0).contains(&rng.unit(i, 7)));
+                assert!((-1.0..=1.0).contains(&rng.signed(i, 7)));
+            }
+        }
+    }
+}
@@ -0,0 +1,94 @@
+//! Turns the sampled window about the center of the canvas, for every noise alike.
+
+use std::cell::Cell;
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlElement, HtmlInputElement};
+
+use crate::{drawer::HALF_RESOLUTION, *};
+
+/// The turn of the view, captured once per frame so the parallel sampling does not touch the
+/// page.
+#[derive(Clone, Copy)]
+pub struct Rotation {
+    sin: f64,
+    cos: f64,
+}
+
+thread_local! {
+    static CURRENT_ROTATION: Cell<Rotation> = const { Cell::new(Rotation { sin: 0., cos: 1. }) };
+}
+
+elements!(
+    (rotation, HtmlInputElement),
+    (rotation_display, HtmlElement),
+);
+define_closure!(rotation_change, on_rotation_change);
+
+pub fn setup() {
+    add_callback!(rotation, "input", rotation_change);
+    read_controls();
+}
+
+fn on_rotation_change() {
+    read_controls();
+    update_current_noise();
+}
+
+/// Picks up the angle from the page, also after a saved state has been applied.
+pub fn read_controls() {
+    let degrees = parse_value!(rotation, f64);
+    set_text!(rotation, &format!("{degrees:.0}°"));
+    CURRENT_ROTATION.with(|r| r.set(Rotation::degrees(degrees)));
+}
+
+impl Rotation {
+    pub fn degrees(degrees: f64) -> Self {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        Rotation { sin, cos }
+    }
+
+    pub fn current() -> Self {
+        CURRENT_ROTATION.with(Cell::get)
+    }
+
+    pub fn is_identity(&self) -> bool {
+        self.sin == 0. && self.cos == 1.
+    }
+
+    /// The canvas position whose sample shows up at (x, y), in pixels, when the view is turned
+    /// counterclockwise on the screen.
+    pub fn apply(&self, x: f64, y: f64) -> (f64, f64) {
+        let half = HALF_RESOLUTION as f64;
+        let (x, y) = (x - half, y - half);
+        (
+            half + x * self.cos - y * self.sin,
+            half + x * self.sin + y * self.cos,
+        )
+    }
+
+    /// Where the sample of the canvas position (x, y) shows up, undoing `apply`.
+    pub fn invert(&self, x: f64, y: f64) -> (f64, f64) {
+        Rotation {
+            sin: -self.sin,
+            cos: self.cos,
+        }
+        .apply(x, y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn turns_about_the_canvas_center_and_back() {
+        let half = HALF_RESOLUTION as f64;
+        let rotation = Rotation::degrees(90.);
+        let (x, y) = rotation.apply(half + 10., half);
+        assert!((x - half).abs() < 1e-9 && (y - half - 10.).abs() < 1e-9);
+        let (x, y) = rotation.invert(x, y);
+        assert!((x - half - 10.).abs() < 1e-9 && (y - half).abs() < 1e-9);
+        assert!(Rotation::degrees(0.).is_identity());
+    }
+}
@@ -0,0 +1,101 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use js_sys::Object;
+use wasm_bindgen::{JsCast, JsValue, prelude::Closure};
+use web_sys::AbortController;
+
+use crate::*;
+
+struct Interval {
+    id: i32,
+    _tick: Closure<dyn Fn()>,
+}
+
+thread_local! {
+    static INTERVALS: RefCell<HashMap<&'static str, Interval>> = RefCell::new(HashMap::new());
+    /// The controllers of the renders still running, by name.
+    static RENDERS: RefCell<HashMap<&'static str, AbortController>> = RefCell::new(HashMap::new());
+}
+
+/// Calls `tick` every `period_ms` milliseconds until `stop(name)` is called.
+/// Starting an already running interval does nothing.
+pub fn start(name: &'static str, period_ms: i32, tick: impl Fn() + 'static) {
+    if is_running(name) {
+        return;
+    }
+
+    let tick = Closure::<dyn Fn()>::new(tick);
+    let id = web_sys::window()
+        .unwrap()
+        .set_interval_with_callback_and_timeout_and_arguments_0(
+            tick.as_ref().unchecked_ref(),
+            period_ms,
+        )
+        .map_err(|_| console_log!("Failed to start interval {name}"))
+        .unwrap();
+
+    INTERVALS.with(|i| i.borrow_mut().insert(name, Interval { id, _tick: tick }));
+}
+
+pub fn stop(name: &'static str) {
+    if let Some(interval) = INTERVALS.with(|i| i.borrow_mut().remove(name)) {
+        web_sys::window()
+            .unwrap()
+            .clear_interval_with_handle(interval.id);
+    }
+}
+
+pub fn is_running(name: &'static str) -> bool {
+    INTERVALS.with(|i| i.borrow().contains_key(name))
+}
+
+/// Calls `run` once after `delay_ms` milliseconds, letting the browser handle its events first
+/// even when the delay is 0.
+pub fn after(delay_ms: i32, run: impl FnOnce() + 'static) {
+    let run = Closure::once_into_js(run);
+    if web_sys::window()
+        .unwrap()
+        .set_timeout_with_callback_and_timeout_and_arguments_0(run.unchecked_ref(), delay_ms)
+        .is_err()
+    {
+        console_log!("Failed to schedule a timeout");
+    }
+}
+
+/// Renders `settings` with [`generate::generate`] and hands the image, or why there is none, to
+/// `done`. Starting another render under the same name aborts this one, its `done` is then never
+/// called.
+pub fn render(
+    name: &'static str,
+    settings: &Object,
+    width: u32,
+    height: u32,
+    done: impl FnOnce(Result<JsValue, JsValue>) + 'static,
+) {
+    let Ok(controller) = AbortController::new() else {
+        console_log!("Failed to create an abort controller for {name}");
+        return;
+    };
+    let signal = controller.signal();
+    if let Some(previous) = RENDERS.with(|r| r.borrow_mut().insert(name, controller)) {
+        previous.abort();
+    }
+    let promise = generate::generate(settings, width, height, Some(signal.clone()));
+
+    let done = RefCell::new(Some(done));
+    let settle = Rc::new(move |result: Result<JsValue, JsValue>| {
+        if signal.aborted() {
+            return;
+        }
+        RENDERS.with(|r| r.borrow_mut().remove(name));
+        if let Some(done) = done.borrow_mut().take() {
+            done(result);
+        }
+    });
+    let on_reject = settle.clone();
+    let resolved = Closure::once(move |image: JsValue| settle(Ok(image)));
+    let rejected = Closure::once(move |error: JsValue| on_reject(Err(error)));
+    let _ = promise.then2(&resolved, &rejected);
+    // Freed by the garbage collector once the promise lets go of them.
+    let _ = (resolved.into_js_value(), rejected.into_js_value());
+}
@@ -0,0 +1,110 @@
+//! JSON Schema of every noise's parameters, built from the `define_noise!` declarations.
+
+use wasm_bindgen::prelude::wasm_bindgen;
+
+#[cfg(feature = "noise-rs")]
+use crate::noises::external_noise::ExternalNoise;
+use crate::{
+    analysis::hash_quality::HashQualityNoise,
+    demos::{
+        biomes::BiomesNoise, brownian_motion::BrownianMotionNoise, caves::CavesNoise,
+        clouds::CloudsNoise, determinism::DeterminismNoise, low_discrepancy::LowDiscrepancyNoise,
+        quilting::QuiltingNoise, reaction_diffusion::ReactionDiffusionNoise, smoke::SmokeNoise,
+    },
+    noises::{
+        anisotropic_noise::AnisotropicNoise, gabor_noise::GaborNoise, image_noise::ImageNoise,
+        isosurface_noise::IsosurfaceNoise, line_noise::LineNoise, perlin_noise::PerlinNoise,
+        simplex_noise::SimplexNoise, slices_noise::SlicesNoise, wavelet_noise::WaveletNoise,
+        worley_noise::WorleyNoise,
+    },
+};
+
+const DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// A slider, integer sliders only take whole numbers.
+pub fn slider(name: &str, integer: bool, min: f64, default: f64, max: f64) -> String {
+    let kind = if integer { "integer" } else { "number" };
+    format!(
+        r#""{name}": {{ "type": "{kind}", "minimum": {min}, "maximum": {max}, "default": {default} }}"#
+    )
+}
+
+/// A radio group, taking the id of one of its options. The first option is the default.
+pub fn radio(name: &str, options: &[&str]) -> String {
+    let quoted: Vec<String> = options.iter().map(|o| format!("\"{o}\"")).collect();
+    format!(
+        r#""{name}": {{ "enum": [{}], "default": {} }}"#,
+        quoted.join(", "),
+        quoted[0]
+    )
+}
+
+pub fn checkbox(name: &str) -> String {
+    format!(r#""{name}": {{ "type": "boolean", "default": false }}"#)
+}
+
+/// The settings of one noise. Everything but `noise` may be left out, leaving it at its default.
+pub fn noise(name: &str, properties: &[String]) -> String {
+    let mut all = vec![format!(r#""noise": {{ "const": "{name}" }}"#)];
+    all.extend_from_slice(properties);
+    format!(
+        "{{\n      \"title\": \"{name}\",\n      \"type\": \"object\",\n      \"properties\": {{\n        {}\n      }},\n      \"required\": [\"noise\"],\n      \"additionalProperties\": false\n    }}",
+        all.join(",\n        ")
+    )
+}
+
+/// The schema of a settings object for any of the noises.
+#[wasm_bindgen]
+pub fn parameter_schema() -> String {
+    let noises = [
+        PerlinNoise::schema(),
+        SimplexNoise::schema(),
+        WaveletNoise::schema(),
+        GaborNoise::schema(),
+        AnisotropicNoise::schema(),
+        WorleyNoise::schema(),
+        LineNoise::schema(),
+        IsosurfaceNoise::schema(),
+        SlicesNoise::schema(),
+        ReactionDiffusionNoise::schema(),
+        BrownianMotionNoise::schema(),
+        LowDiscrepancyNoise::schema(),
+        HashQualityNoise::schema(),
+        ImageNoise::schema(),
+        QuiltingNoise::schema(),
+        SmokeNoise::schema(),
+        CloudsNoise::schema(),
+        CavesNoise::schema(),
+        BiomesNoise::schema(),
+        DeterminismNoise::schema(),
+        #[cfg(feature = "noise-rs")]
+        ExternalNoise::schema(),
+    ];
+    format!(
+        "{{\n  \"$schema\": \"{DIALECT}\",\n  \"title\": \"Seeing Noise settings\",\n  \"oneOf\": [\n    {}\n  ]\n}}\n",
+        noises.join(",\n    ")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn schema_lists_every_control_of_a_noise() {
+        let schema = parameter_schema();
+        assert_eq!(schema.matches('{').count(), schema.matches('}').count());
+        assert_eq!(schema.matches('[').count(), schema.matches(']').count());
+        assert!(schema.contains(r#""noise": { "const": "perlin" }"#));
+        assert!(schema.contains(
+            r#""octaves": { "type": "integer", "minimum": 1, "maximum": 8, "default": 1 }"#
+        ));
+        assert!(schema.contains(
+            r#""gain": { "type": "number", "minimum": 0, "maximum": 1, "default": 0.5 }"#
+        ));
+        assert!(schema.contains(
+            r#""noise_type": { "enum": ["standard", "turbulence", "ridge", "domain_warp"], "default": "standard" }"#
+        ));
+        assert!(schema.contains(r#""spectral_h": { "type": "boolean", "default": false }"#));
+    }
+}
@@ -0,0 +1,114 @@
+//! Moves the sampled window over the noise field, from the offset inputs or a middle-button drag.
+
+use std::cell::{Cell, LazyCell};
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlInputElement, MouseEvent};
+
+use crate::{
+    axis_scale::AxisScale,
+    drawer::{CANVAS, canvas_coordinates},
+    *,
+};
+
+const MIDDLE_BUTTON: i16 = 1;
+
+/// Noise units the sampled window is moved right and down by, captured once per frame so the
+/// parallel sampling does not touch the page.
+#[derive(Clone, Copy)]
+pub struct Scroll {
+    pub x: f64,
+    pub y: f64,
+}
+
+thread_local! {
+    static SCROLL: Cell<Scroll> = const { Cell::new(Scroll { x: 0., y: 0. }) };
+    /// Canvas position of the middle button drag at the last mouse move.
+    static DRAG: Cell<Option<(f64, f64)>> = const { Cell::new(None) };
+}
+
+elements!(
+    (scroll_x, HtmlInputElement),
+    (scroll_y, HtmlInputElement),
+    (scale, HtmlInputElement),
+);
+define_closure!(scroll_change, on_scroll_change);
+define_event_closure!(scroll_press, MouseEvent, on_press);
+define_event_closure!(scroll_drag, MouseEvent, on_drag);
+define_event_closure!(scroll_release, MouseEvent, on_release);
+
+pub fn setup() {
+    add_callback!(scroll_x, "input", scroll_change);
+    add_callback!(scroll_y, "input", scroll_change);
+    add_callback!(canvas, "mousedown", scroll_press);
+    add_callback!(canvas, "mousemove", scroll_drag);
+    add_callback!(canvas, "mouseup", scroll_release);
+    add_callback!(canvas, "mouseleave", scroll_release);
+    read_controls();
+}
+
+fn on_scroll_change() {
+    read_controls();
+    update_current_noise();
+}
+
+/// An empty or half typed input counts as zero.
+fn offset(input: &HtmlInputElement) -> f64 {
+    input.value().parse().unwrap_or(0.)
+}
+
+/// Picks up the offsets from the page, also after a saved state has been applied.
+pub fn read_controls() {
+    let x = SCROLL_X.with(|s| offset(s));
+    let y = SCROLL_Y.with(|s| offset(s));
+    SCROLL.with(|s| s.set(Scroll { x, y }));
+}
+
+fn on_press(event: MouseEvent) {
+    if event.button() != MIDDLE_BUTTON {
+        return;
+    }
+    // Keeps the browser from starting its own middle button autoscroll.
+    event.prevent_default();
+    DRAG.with(|d| d.set(Some(canvas_coordinates(&event))));
+}
+
+/// Drags the field along with the mouse, so the point under the cursor stays under it.
+fn on_drag(event: MouseEvent) {
+    let Some((last_x, last_y)) = DRAG.with(Cell::get) else {
+        return;
+    };
+    let (x, y) = canvas_coordinates(&event);
+    DRAG.with(|d| d.set(Some((x, y))));
+
+    let scale = parse_value!(scale, f64);
+    let axis_scale = AxisScale::current();
+    let scroll = Scroll::current();
+    let (dx, dy) = (
+        (x - last_x) / (scale * axis_scale.x),
+        (y - last_y) / (scale * axis_scale.y),
+    );
+    let scroll = Scroll {
+        x: scroll.x - dx,
+        y: scroll.y - dy,
+    };
+    // The inputs show rounded offsets, the drag keeps going from the exact ones.
+    SCROLL_X.with(|o| o.set_value(&format!("{:.3}", scroll.x)));
+    SCROLL_Y.with(|o| o.set_value(&format!("{:.3}", scroll.y)));
+    SCROLL.with(|s| s.set(scroll));
+    update_current_noise();
+}
+
+fn on_release(_: MouseEvent) {
+    DRAG.with(|d| d.set(None));
+}
+
+impl Scroll {
+    pub fn current() -> Self {
+        SCROLL.with(Cell::get)
+    }
+
+    pub fn apply(&self, x: f64, y: f64) -> (f64, f64) {
+        (x + self.x, y + self.y)
+    }
+}
@@ -0,0 +1,221 @@
+use std::cell::LazyCell;
+
+use js_sys::{Float32Array, Object, Reflect};
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::HtmlElement;
+
+use crate::*;
+
+/// Name of the render of the case being checked, a new run aborts the one before it.
+const RENDER_NAME: &str = "self_test";
+
+/// Canonical parameter sets, as the settings files of the `render` binary spell them.
+const CASES: &[(&str, &[(&str, &str)])] = &[
+    ("perlin", &[("noise", "perlin")]),
+    (
+        "perlin ridge",
+        &[
+            ("noise", "perlin"),
+            ("noise_type", "ridge"),
+            ("octaves", "5"),
+        ],
+    ),
+    (
+        "perlin domain warp",
+        &[
+            ("noise", "perlin"),
+            ("noise_type", "domain_warp"),
+            ("octaves", "3"),
+            ("seed", "7"),
+        ],
+    ),
+    ("simplex", &[("noise", "simplex")]),
+    (
+        "simplex turbulence",
+        &[
+            ("noise", "simplex"),
+            ("noise_type", "turbulence"),
+            ("octaves", "4"),
+        ],
+    ),
+    ("wavelet", &[("noise", "wavelet")]),
+    (
+        "wavelet small tile",
+        &[
+            ("noise", "wavelet"),
+            ("tile_size_power", "6"),
+            ("octaves", "4"),
+        ],
+    ),
+    ("gabor", &[("noise", "gabor")]),
+    (
+        "anisotropic",
+        &[
+            ("noise", "anisotropic"),
+            ("angle", "30"),
+            ("anisotropy", "3"),
+        ],
+    ),
+    ("worley", &[("noise", "worley")]),
+    (
+        "worley crackle",
+        &[
+            ("noise", "worley"),
+            ("noise_type", "crackle"),
+            ("octaves", "3"),
+        ],
+    ),
+];
+
+/// Known good hashes of the fields produced by `CASES`, as `generate` hands them out in `f32`.
+const GOLDEN_HASHES: &[(&str, u64)] = &[
+    ("perlin", 0xabec71b7aab27d4e),
+    ("perlin ridge", 0xa8207d9a2f29d29d),
+    ("perlin domain warp", 0x0f24afd110ba63ff),
+    ("simplex", 0xd49ece33f7921bdf),
+    ("simplex turbulence", 0x049263c8eeaa9439),
+    ("wavelet", 0x8341cd5b7ba3ef81),
+    ("wavelet small tile", 0x05de6a71397a7d76),
+    ("gabor", 0x2efe54c023f48fad),
+    ("anisotropic", 0x96206781185bb8ae),
+    ("worley", 0x73e317f26c2a4ade),
+    ("worley crackle", 0x89a9974dd2401d27),
+];
+
+/// Every case samples the canvas area at the default scale, on a coarser grid to stay quick.
+const CASE_SIZE: usize = 128;
+const CASE_SCALE: f64 = 50.;
+
+/// Values are rounded to 24 fractional bits before hashing, so the last bits of transcendental
+/// functions, which differ between math libraries, do not count as regressions.
+const QUANTIZATION: f64 = (1 << 24) as f64;
+
+elements!(
+    (self_test_control, HtmlElement),
+    (self_test_run, HtmlElement),
+    (self_test_readout, HtmlElement),
+);
+define_closure!(self_test_click, run);
+
+/// The self-test only shows up in dev mode, when the page is opened with `?dev`.
+pub fn setup() {
+    let search = web_sys::window()
+        .and_then(|w| w.location().search().ok())
+        .unwrap_or_default();
+    if search.contains("dev") {
+        SELF_TEST_CONTROL.with(|c| c.set_hidden(false));
+        add_callback!(self_test_run, "click", self_test_click);
+    }
+}
+
+/// FNV-1a over the quantized field.
+fn hash_field(field: &[f32]) -> u64 {
+    field.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &value| {
+        let quantized = (value as f64 * QUANTIZATION).round() as i64;
+        quantized.to_le_bytes().iter().fold(hash, |hash, &byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+        })
+    })
+}
+
+/// Whether `name` hashed to its known hash, as a line of the report.
+fn check_case(name: &str, hash: Result<u64, String>) -> Result<String, String> {
+    let golden = GOLDEN_HASHES
+        .iter()
+        .find(|(golden_name, _)| *golden_name == name)
+        .map(|(_, golden)| *golden);
+    match (hash, golden) {
+        (Ok(hash), Some(golden)) if hash == golden => Ok(format!("ok       {name}")),
+        (Ok(hash), Some(golden)) => Err(format!(
+            "FAILED   {name}: expected {golden:#018x}, got {hash:#018x}"
+        )),
+        (Ok(hash), None) => Err(format!(
+            "FAILED   {name}: no known hash, got (\"{name}\", {hash:#018x}),"
+        )),
+        (Err(error), _) => Err(format!("FAILED   {name}: {error}")),
+    }
+}
+
+fn run() {
+    SELF_TEST_READOUT.with(|r| r.set_inner_text("Running..."));
+    run_case(0, Vec::new(), 0);
+}
+
+/// Renders case `index` the way `generate` renders for scripts, then moves on to the next case.
+/// `generate` builds its sampler with `core::from_settings`, which draws from SquirrelNoise5 and
+/// shuffles its own tables whatever generator and permutation table the page has picked.
+fn run_case(index: usize, mut report: Vec<String>, failures: usize) {
+    let Some(&(name, settings)) = CASES.get(index) else {
+        report.push(if failures == 0 {
+            "All known hashes match".to_string()
+        } else {
+            format!("{failures} case(s) failed")
+        });
+        SELF_TEST_READOUT.with(|r| r.set_inner_text(&report.join("\n")));
+        return;
+    };
+    let object = Object::new();
+    let _ = Reflect::set(&object, &"scale".into(), &CASE_SCALE.into());
+    for (key, value) in settings {
+        let _ = Reflect::set(&object, &(*key).into(), &(*value).into());
+    }
+    let size = CASE_SIZE as u32;
+    scheduler::render(RENDER_NAME, &object, size, size, move |image| {
+        let hash = image
+            .and_then(|image| Reflect::get(&image, &"values".into()))
+            .map(|values| hash_field(&Float32Array::from(values).to_vec()))
+            .map_err(|error| error.as_string().unwrap_or_else(|| format!("{error:?}")));
+        let (line, failed) = match check_case(name, hash) {
+            Ok(line) => (line, false),
+            Err(line) => (line, true),
+        };
+        report.push(line);
+        run_case(index + 1, report, failures + failed as usize);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        core::{Viewport, from_settings},
+        drawer::RESOLUTION,
+        tiles::sample_tile,
+    };
+
+    /// What the page's run hashes for `settings`, sampled here without a browser.
+    fn case_hash(settings: &[(&str, &str)]) -> Result<u64, String> {
+        let pairs: Vec<_> = settings
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        let sampler = from_settings(&pairs)?;
+        let side = RESOLUTION as f64 / CASE_SCALE;
+        let view = Viewport::new(-side / 2., -side / 2., side, side, CASE_SIZE, CASE_SIZE);
+        let size = CASE_SIZE as u32;
+        Ok(hash_field(&sample_tile(sampler.as_ref(), &view, (0, 0, size, size))))
+    }
+
+    fn failures() -> Vec<String> {
+        CASES
+            .iter()
+            .filter_map(|(name, settings)| check_case(name, case_hash(settings)).err())
+            .collect()
+    }
+
+    #[test]
+    fn every_case_matches_its_known_hash() {
+        let failures = failures();
+        assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+    }
+
+    #[test]
+    fn the_page_generator_and_table_do_not_change_the_hashes() {
+        rng::set_generator(rng::Generator::Xoshiro128);
+        permutation::set_table(Some(std::array::from_fn(|i| 255 - i)));
+        let failures = failures();
+        rng::set_generator(rng::Generator::SquirrelNoise5);
+        permutation::set_table(None);
+        assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+    }
+}
@@ -0,0 +1,263 @@
+//! Records the control changes with their timing and plays them back in the same rhythm.
+
+use std::{
+    cell::{LazyCell, RefCell},
+    collections::HashSet,
+};
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlElement, HtmlTextAreaElement};
+
+use crate::*;
+
+const REPLAY_NAME: &str = "session_replay";
+const REPLAY_PERIOD_MS: i32 = 20;
+
+/// Changes of the controls at one moment of a session.
+#[derive(Debug, PartialEq)]
+struct Step {
+    /// Milliseconds since the session started.
+    at: f64,
+    /// Serialized controls, a full state when the step selects another noise.
+    changes: String,
+}
+
+struct Recording {
+    started_at: f64,
+    /// Serialized controls after the last recorded step.
+    last: String,
+}
+
+struct Replay {
+    started_at: f64,
+    steps: Vec<Step>,
+    next: usize,
+}
+
+thread_local! {
+    static RECORDING: RefCell<Option<Recording>> = const { RefCell::new(None) };
+    static REPLAY: RefCell<Option<Replay>> = const { RefCell::new(None) };
+}
+
+elements!(
+    (session_record, HtmlElement),
+    (session_replay, HtmlElement),
+    (session_script, HtmlTextAreaElement),
+    (session_status, HtmlElement),
+);
+define_closure!(session_record_click, toggle_recording);
+define_closure!(session_replay_click, toggle_replay);
+define_closure!(session_input, on_input);
+
+pub fn setup() {
+    add_callback!(session_record, "click", session_record_click);
+    add_callback!(session_replay, "click", session_replay_click);
+    add_callback!(document, "input", session_input);
+}
+
+fn set_status(text: &str) {
+    SESSION_STATUS.with(|s| s.set_inner_text(text));
+}
+
+fn format_step(step: &Step) -> String {
+    format!("@{:.2}\n{}", step.at / 1000., step.changes)
+}
+
+/// Reads the steps back from a recording. Lines before the first `@` line belong to a step at 0.
+fn parse(script: &str) -> Vec<Step> {
+    let mut steps: Vec<Step> = Vec::new();
+    for line in script.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        if let Some(seconds) = line.strip_prefix('@') {
+            let at = seconds.trim().parse::<f64>().unwrap_or(0.) * 1000.;
+            steps.push(Step {
+                at,
+                changes: String::new(),
+            });
+            continue;
+        }
+        if steps.is_empty() {
+            steps.push(Step {
+                at: 0.,
+                changes: String::new(),
+            });
+        }
+        if let Some(step) = steps.last_mut() {
+            step.changes.push_str(line);
+            step.changes.push('\n');
+        }
+    }
+    steps.retain(|s| !s.changes.is_empty());
+    steps.sort_by(|a, b| a.at.total_cmp(&b.at));
+    steps
+}
+
+/// Lines of the serialized controls `after` that differ from `before`.
+fn changed_lines(before: &str, after: &str) -> String {
+    let before: HashSet<&str> = before.lines().collect();
+    after
+        .lines()
+        .filter(|line| !before.contains(line))
+        .map(|line| format!("{line}\n"))
+        .collect()
+}
+
+fn selects_noise(changes: &str) -> bool {
+    changes.lines().any(|l| l.starts_with("noise_select="))
+}
+
+fn toggle_recording() {
+    if RECORDING.with(|r| r.borrow().is_some()) {
+        RECORDING.with(|r| r.take());
+        SESSION_RECORD.with(|b| b.set_inner_text("Record"));
+        let steps = parse(&SESSION_SCRIPT.with(|s| s.value()));
+        let seconds = steps.last().map_or(0., |s| s.at / 1000.);
+        set_status(&format!(
+            "Recorded {} steps over {seconds:.1} s",
+            steps.len()
+        ));
+        return;
+    }
+
+    stop_replay();
+    let state = state::serialize();
+    SESSION_SCRIPT.with(|s| {
+        s.set_value(&format_step(&Step {
+            at: 0.,
+            changes: state.clone(),
+        }))
+    });
+    RECORDING.with(|r| {
+        r.replace(Some(Recording {
+            started_at: js_sys::Date::now(),
+            last: state,
+        }))
+    });
+    SESSION_RECORD.with(|b| b.set_inner_text("Stop recording"));
+    set_status("Recording, every change of the controls is kept");
+}
+
+/// Adds the controls changed since the last step. A step that selects another noise keeps every
+/// control, since selecting a noise resets its controls first.
+fn on_input() {
+    let step = RECORDING.with(|r| {
+        let mut recording = r.borrow_mut();
+        let recording = recording.as_mut()?;
+        let state = state::serialize();
+        let mut changes = changed_lines(&recording.last, &state);
+        if changes.is_empty() {
+            return None;
+        }
+        if selects_noise(&changes) {
+            changes = state.clone();
+        }
+        recording.last = state;
+        Some(Step {
+            at: js_sys::Date::now() - recording.started_at,
+            changes,
+        })
+    });
+    if let Some(step) = step {
+        SESSION_SCRIPT.with(|s| s.set_value(&(s.value() + &format_step(&step))));
+    }
+}
+
+fn toggle_replay() {
+    if REPLAY.with(|r| r.borrow().is_some()) {
+        stop_replay();
+        set_status("Replay stopped");
+        return;
+    }
+
+    let steps = parse(&SESSION_SCRIPT.with(|s| s.value()));
+    if steps.is_empty() {
+        set_status("Record a session or paste one first");
+        return;
+    }
+    if RECORDING.with(|r| r.borrow().is_some()) {
+        toggle_recording();
+    }
+    REPLAY.with(|r| {
+        r.replace(Some(Replay {
+            started_at: js_sys::Date::now(),
+            steps,
+            next: 0,
+        }))
+    });
+    SESSION_REPLAY.with(|b| b.set_inner_text("Stop replay"));
+    set_status("Replaying");
+    scheduler::start(REPLAY_NAME, REPLAY_PERIOD_MS, tick);
+}
+
+fn stop_replay() {
+    scheduler::stop(REPLAY_NAME);
+    REPLAY.with(|r| r.take());
+    SESSION_REPLAY.with(|b| b.set_inner_text("Replay"));
+}
+
+/// Applies every step whose time has come, the changes of one step in a single redraw.
+fn tick() {
+    let elapsed = REPLAY.with(|r| {
+        r.borrow()
+            .as_ref()
+            .map(|r| js_sys::Date::now() - r.started_at)
+    });
+    let Some(elapsed) = elapsed else {
+        return;
+    };
+    loop {
+        // Applying a step redraws the noise, the replay is not borrowed meanwhile.
+        let step = REPLAY.with(|r| {
+            let mut replay = r.borrow_mut();
+            let replay = replay.as_mut()?;
+            let step = replay.steps.get(replay.next)?;
+            if step.at > elapsed {
+                return None;
+            }
+            replay.next += 1;
+            Some(step.changes.clone())
+        });
+        let Some(changes) = step else {
+            break;
+        };
+        if selects_noise(&changes) {
+            state::apply(&changes);
+        } else {
+            state::apply_changes(&changes);
+        }
+    }
+
+    let finished = REPLAY.with(|r| r.borrow().as_ref().is_some_and(|r| r.next == r.steps.len()));
+    if finished {
+        let count = REPLAY.with(|r| r.borrow().as_ref().map_or(0, |r| r.steps.len()));
+        stop_replay();
+        state::save();
+        set_status(&format!("Replayed {count} steps"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steps_round_trip_through_text() {
+        let steps = [
+            Step {
+                at: 0.,
+                changes: "noise_select=perlin\nscale=2\n".to_string(),
+            },
+            Step {
+                at: 1250.,
+                changes: changed_lines("scale=2\nseed=0\n", "scale=3\nseed=0\n"),
+            },
+        ];
+        assert_eq!(steps[1].changes, "scale=3\n");
+        let script: String = steps.iter().map(format_step).collect();
+        assert_eq!(parse(&script), steps);
+        assert!(selects_noise(&steps[0].changes) && !selects_noise(&steps[1].changes));
+
+        let written = parse("octaves=4\n@2\n  \n@0.5\nseed=7\n");
+        assert_eq!(written.len(), 2);
+        assert_eq!(written[1].at, 500.);
+    }
+}
@@ -0,0 +1,563 @@
+//! Writes the shown noise out as a self-contained GLSL or WGSL function.
+
+use std::{cell::LazyCell, fmt::Write};
+
+use wasm_bindgen::{JsCast, JsValue, prelude::Closure};
+use web_sys::{HtmlElement, HtmlTextAreaElement};
+
+use crate::{
+    core::Fractal,
+    drawer::HALF_RESOLUTION,
+    noises::{
+        helpers::{octave_gain, shuffle},
+        perlin_noise::PerlinNoise,
+        simplex_noise::SimplexNoise,
+    },
+    *,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Base {
+    Perlin,
+    Simplex,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Language {
+    Glsl,
+    Wgsl,
+}
+
+/// Everything the exported function needs, in the units of the page's sliders.
+pub struct Fbm {
+    pub base: Base,
+    pub seed: u32,
+    pub fractal: Fractal,
+    pub octaves: u32,
+    /// First and last octave added up, the others only advance the frequency.
+    pub shown_octaves: (u32, u32),
+    pub lacunarity: f64,
+    pub gain: f64,
+    pub h_exponent: f64,
+    pub spectral_h: bool,
+    pub ridge_offset: f64,
+    pub warp_amount: f64,
+    pub rotation_degrees: f64,
+    pub scale: f64,
+}
+
+impl Fbm {
+    /// Amplitude ratio between octaves, worked out the way each fBm loop of the page does.
+    pub fn falloff(&self) -> f64 {
+        match self.fractal {
+            Fractal::Standard => octave_gain(
+                self.gain.powf(self.h_exponent),
+                self.lacunarity,
+                self.h_exponent,
+                self.spectral_h,
+            ),
+            Fractal::Turbulence | Fractal::Ridge => {
+                octave_gain(self.gain, self.lacunarity, self.h_exponent, self.spectral_h)
+            }
+            // Domain warping runs the standard loop with H fixed to 1.
+            Fractal::DomainWarp => octave_gain(self.gain, self.lacunarity, 1., self.spectral_h),
+        }
+    }
+}
+
+thread_local! {
+    static COPY_SUCCEEDED: LazyCell<Closure<dyn FnMut(JsValue)>> = LazyCell::new(|| {
+        Closure::new(|_| set_status("Copied to clipboard"))
+    });
+    static COPY_FAILED: LazyCell<Closure<dyn FnMut(JsValue)>> = LazyCell::new(|| {
+        Closure::new(|error: JsValue| {
+            console_log!("Failed to copy the shader: {error:?}");
+            set_status("Could not copy, select the code below and copy it by hand");
+        })
+    });
+}
+
+elements!(
+    (shader_glsl, HtmlElement),
+    (shader_wgsl, HtmlElement),
+    (shader_status, HtmlElement),
+    (shader_code, HtmlTextAreaElement),
+);
+define_closure!(shader_glsl_click, export_glsl);
+define_closure!(shader_wgsl_click, export_wgsl);
+
+pub fn setup() {
+    add_callback!(shader_glsl, "click", shader_glsl_click);
+    add_callback!(shader_wgsl, "click", shader_wgsl_click);
+}
+
+fn set_status(text: &str) {
+    SHADER_STATUS.with(|s| s.set_inner_text(text));
+}
+
+/// A float literal every exported language reads as a float, `2` would be an integer.
+pub fn float(value: f64) -> String {
+    format!("{value:?}")
+}
+
+/// The 256 entries of the permutation table, sixteen to a line.
+pub fn table(seed: u32) -> String {
+    let mut permutation: [usize; 256] = std::array::from_fn(|i| i);
+    shuffle(&mut permutation, seed);
+    permutation
+        .chunks(16)
+        .map(|row| {
+            let row: Vec<String> = row.iter().map(|p| p.to_string()).collect();
+            format!("    {}", row.join(", "))
+        })
+        .collect::<Vec<_>>()
+        .join(",\n")
+}
+
+pub fn generate(fbm: &Fbm, language: Language) -> String {
+    let mut code = String::new();
+    let name = match fbm.base {
+        Base::Perlin => "Perlin",
+        Base::Simplex => "Simplex",
+    };
+    let fractal = match fbm.fractal {
+        Fractal::Standard => "standard fBm",
+        Fractal::Turbulence => "turbulence",
+        Fractal::Ridge => "ridged fBm",
+        Fractal::DomainWarp => "domain warped fBm",
+    };
+    let _ = writeln!(
+        code,
+        "// {name} noise, {fractal}, seed {}, exported from Seeing Noise.\n\
+         // seeing_noise(p) gives the value the page shows at p in noise space, roughly in [-1, 1].\n\
+         // The page samples pixel (x, y) of its canvas at p = (vec2(x, y) - {}) / SEEING_NOISE_SCALE,\n\
+         // with y growing downwards, and seeing_noise_color(v) gives the colour it paints.\n",
+        fbm.seed,
+        float(HALF_RESOLUTION as f64),
+    );
+
+    let constants = [
+        ("SCALE", float(fbm.scale)),
+        ("LACUNARITY", float(fbm.lacunarity)),
+        ("FALLOFF", float(fbm.falloff())),
+        ("RIDGE_OFFSET", float(fbm.ridge_offset)),
+        ("WARP_AMOUNT", float(fbm.warp_amount)),
+        ("ROTATION", float(fbm.rotation_degrees.to_radians())),
+    ];
+    let integers = [
+        ("OCTAVES", fbm.octaves),
+        ("FIRST_OCTAVE", fbm.shown_octaves.0),
+        ("LAST_OCTAVE", fbm.shown_octaves.1),
+    ];
+    match language {
+        Language::Glsl => {
+            for (name, value) in constants {
+                let _ = writeln!(code, "const float SEEING_NOISE_{name} = {value};");
+            }
+            for (name, value) in integers {
+                let _ = writeln!(code, "const int SEEING_NOISE_{name} = {value};");
+            }
+            let _ = writeln!(
+                code,
+                "\nconst int SEEING_NOISE_PERM[256] = int[256](\n{}\n);\n",
+                table(fbm.seed)
+            );
+            code.push_str(GLSL_COMMON);
+            code.push_str(match fbm.base {
+                Base::Perlin => GLSL_PERLIN,
+                Base::Simplex => GLSL_SIMPLEX,
+            });
+            code.push_str(&glsl_fractal(fbm.fractal));
+            code.push_str(GLSL_ENTRY);
+        }
+        Language::Wgsl => {
+            for (name, value) in constants {
+                let _ = writeln!(code, "const SEEING_NOISE_{name}: f32 = {value};");
+            }
+            for (name, value) in integers {
+                let _ = writeln!(code, "const SEEING_NOISE_{name}: i32 = {value};");
+            }
+            let _ = writeln!(
+                code,
+                "\nvar<private> SEEING_NOISE_PERM: array<i32, 256> = array<i32, 256>(\n{}\n);\n",
+                table(fbm.seed)
+            );
+            code.push_str(WGSL_COMMON);
+            code.push_str(match fbm.base {
+                Base::Perlin => WGSL_PERLIN,
+                Base::Simplex => WGSL_SIMPLEX,
+            });
+            code.push_str(&wgsl_fractal(fbm.fractal));
+            code.push_str(WGSL_ENTRY);
+        }
+    }
+    code
+}
+
+const GLSL_COMMON: &str = "\
+int seeing_noise_perm(int i) {
+    return SEEING_NOISE_PERM[i & 255];
+}
+
+float seeing_noise_grad(int hash, vec2 d) {
+    const vec2 GRADIENTS[8] = vec2[8](
+        vec2(1.0, 0.0), vec2(1.0, 1.0), vec2(0.0, 1.0), vec2(-1.0, 1.0),
+        vec2(-1.0, 0.0), vec2(-1.0, -1.0), vec2(0.0, -1.0), vec2(1.0, -1.0)
+    );
+    return dot(GRADIENTS[hash & 7], d);
+}
+
+";
+
+const GLSL_PERLIN: &str = "\
+int seeing_noise_hash(int x, int y) {
+    return seeing_noise_perm(seeing_noise_perm(x) + (y & 255));
+}
+
+float seeing_noise_base(vec2 p) {
+    vec2 cell = floor(p);
+    ivec2 c = ivec2(cell);
+    vec2 f = p - cell;
+    vec2 u = f * f * f * (f * (f * 6.0 - 15.0) + 10.0);
+    float aa = seeing_noise_grad(seeing_noise_hash(c.x, c.y), f);
+    float ba = seeing_noise_grad(seeing_noise_hash(c.x + 1, c.y), f - vec2(1.0, 0.0));
+    float ab = seeing_noise_grad(seeing_noise_hash(c.x, c.y + 1), f - vec2(0.0, 1.0));
+    float bb = seeing_noise_grad(seeing_noise_hash(c.x + 1, c.y + 1), f - vec2(1.0, 1.0));
+    return mix(mix(aa, ba, u.x), mix(ab, bb, u.x), u.y);
+}
+
+";
+
+const GLSL_SIMPLEX: &str = "\
+float seeing_noise_corner(int hash, vec2 d) {
+    float t = 0.5 - dot(d, d);
+    if (t < 0.0) {
+        return 0.0;
+    }
+    t *= t;
+    return t * t * seeing_noise_grad(hash, d);
+}
+
+float seeing_noise_base(vec2 p) {
+    const float F2 = 0.3660254037844386;
+    const float G2 = 0.21132486540518708;
+    vec2 cell = floor(p + (p.x + p.y) * F2);
+    vec2 d0 = p - (cell - (cell.x + cell.y) * G2);
+    ivec2 o = d0.x > d0.y ? ivec2(1, 0) : ivec2(0, 1);
+    vec2 d1 = d0 - vec2(o) + G2;
+    vec2 d2 = d0 - 1.0 + 2.0 * G2;
+    int i = int(cell.x) & 255;
+    int j = int(cell.y) & 255;
+    int g0 = seeing_noise_perm(i + seeing_noise_perm(j));
+    int g1 = seeing_noise_perm(i + o.x + seeing_noise_perm(j + o.y));
+    int g2 = seeing_noise_perm(i + 1 + seeing_noise_perm(j + 1));
+    return 70.0 * (seeing_noise_corner(g0, d0) + seeing_noise_corner(g1, d1)
+        + seeing_noise_corner(g2, d2));
+}
+
+";
+
+/// The fBm loop of one fractal type, domain warping adds a wrapper around the standard loop.
+fn glsl_fractal(fractal: Fractal) -> String {
+    let ridge = fractal == Fractal::Ridge;
+    let octave = match fractal {
+        Fractal::Standard | Fractal::DomainWarp => "seeing_noise_base(p * frequency)",
+        Fractal::Turbulence => "abs(seeing_noise_base(p * frequency))",
+        Fractal::Ridge => "SEEING_NOISE_RIDGE_OFFSET - abs(seeing_noise_base(p * frequency))",
+    };
+    // Ridges weigh every octave by the one before, so they only show up along its ridges.
+    let (weight, contribution, next_weight) = if ridge {
+        (
+            "    float weight = 1.0;\n",
+            "n * n * weight",
+            "        weight = clamp(n * 2.0, 0.0, 1.0);\n",
+        )
+    } else {
+        ("", "n", "")
+    };
+    let mut code = format!(
+        "\
+float seeing_noise_fbm(vec2 p) {{
+    float total = 0.0;
+    float max_value = 0.0;
+    float amplitude = 1.0;
+    float frequency = 1.0;
+{weight}    for (int i = 1; i <= SEEING_NOISE_OCTAVES; i++) {{
+        float n = {octave};
+        if (i >= SEEING_NOISE_FIRST_OCTAVE && i <= SEEING_NOISE_LAST_OCTAVE) {{
+            total += {contribution} * amplitude;
+            max_value += amplitude;
+        }}
+{next_weight}        amplitude *= SEEING_NOISE_FALLOFF;
+        frequency *= SEEING_NOISE_LACUNARITY;
+    }}
+    return total / max_value;
+}}
+
+"
+    );
+    code.push_str(if fractal == Fractal::DomainWarp {
+        "\
+float seeing_noise_fractal(vec2 p) {
+    vec2 q = vec2(seeing_noise_fbm(p), seeing_noise_fbm(p + vec2(5.2, 1.3)));
+    return seeing_noise_fbm(p + SEEING_NOISE_WARP_AMOUNT * q);
+}
+
+"
+    } else {
+        "\
+float seeing_noise_fractal(vec2 p) {
+    return seeing_noise_fbm(p);
+}
+
+"
+    });
+    code
+}
+
+const GLSL_ENTRY: &str = "\
+float seeing_noise(vec2 p) {
+    float c = cos(SEEING_NOISE_ROTATION);
+    float s = sin(SEEING_NOISE_ROTATION);
+    return seeing_noise_fractal(vec2(p.x * c - p.y * s, p.x * s + p.y * c));
+}
+
+vec3 seeing_noise_color(float v) {
+    v = clamp(v, -1.0, 1.0);
+    return v < 0.0 ? vec3(1.0, v + 1.0, 1.0) : vec3(1.0 - v, 1.0, 1.0 - v);
+}
+";
+
+const WGSL_COMMON: &str = "\
+fn seeing_noise_perm(i: i32) -> i32 {
+    return SEEING_NOISE_PERM[i & 255];
+}
+
+fn seeing_noise_grad(hash: i32, d: vec2<f32>) -> f32 {
+    var gradients = array<vec2<f32>, 8>(
+        vec2(1.0, 0.0), vec2(1.0, 1.0), vec2(0.0, 1.0), vec2(-1.0, 1.0),
+        vec2(-1.0, 0.0), vec2(-1.0, -1.0), vec2(0.0, -1.0), vec2(1.0, -1.0)
+    );
+    return dot(gradients[hash & 7], d);
+}
+
+";
+
+const WGSL_PERLIN: &str = "\
+fn seeing_noise_hash(x: i32, y: i32) -> i32 {
+    return seeing_noise_perm(seeing_noise_perm(x) + (y & 255));
+}
+
+fn seeing_noise_base(p: vec2<f32>) -> f32 {
+    let cell = floor(p);
+    let c = vec2<i32>(cell);
+    let f = p - cell;
+    let u = f * f * f * (f * (f * 6.0 - 15.0) + 10.0);
+    let aa = seeing_noise_grad(seeing_noise_hash(c.x, c.y), f);
+    let ba = seeing_noise_grad(seeing_noise_hash(c.x + 1, c.y), f - vec2(1.0, 0.0));
+    let ab = seeing_noise_grad(seeing_noise_hash(c.x, c.y + 1), f - vec2(0.0, 1.0));
+    let bb = seeing_noise_grad(seeing_noise_hash(c.x + 1, c.y + 1), f - vec2(1.0, 1.0));
+    return mix(mix(aa, ba, u.x), mix(ab, bb, u.x), u.y);
+}
+
+";
+
+const WGSL_SIMPLEX: &str = "\
+fn seeing_noise_corner(hash: i32, d: vec2<f32>) -> f32 {
+    var t = 0.5 - dot(d, d);
+    if (t < 0.0) {
+        return 0.0;
+    }
+    t *= t;
+    return t * t * seeing_noise_grad(hash, d);
+}
+
+fn seeing_noise_base(p: vec2<f32>) -> f32 {
+    let F2 = 0.3660254037844386;
+    let G2 = 0.21132486540518708;
+    let cell = floor(p + (p.x + p.y) * F2);
+    let d0 = p - (cell - (cell.x + cell.y) * G2);
+    let o = select(vec2<i32>(0, 1), vec2<i32>(1, 0), d0.x > d0.y);
+    let d1 = d0 - vec2<f32>(o) + G2;
+    let d2 = d0 - 1.0 + 2.0 * G2;
+    let i = i32(cell.x) & 255;
+    let j = i32(cell.y) & 255;
+    let g0 = seeing_noise_perm(i + seeing_noise_perm(j));
+    let g1 = seeing_noise_perm(i + o.x + seeing_noise_perm(j + o.y));
+    let g2 = seeing_noise_perm(i + 1 + seeing_noise_perm(j + 1));
+    return 70.0 * (seeing_noise_corner(g0, d0) + seeing_noise_corner(g1, d1)
+        + seeing_noise_corner(g2, d2));
+}
+
+";
+
+fn wgsl_fractal(fractal: Fractal) -> String {
+    let ridge = fractal == Fractal::Ridge;
+    let octave = match fractal {
+        Fractal::Standard | Fractal::DomainWarp => "seeing_noise_base(p * frequency)",
+        Fractal::Turbulence => "abs(seeing_noise_base(p * frequency))",
+        Fractal::Ridge => "SEEING_NOISE_RIDGE_OFFSET - abs(seeing_noise_base(p * frequency))",
+    };
+    // Ridges weigh every octave by the one before, so they only show up along its ridges.
+    let (weight, contribution, next_weight) = if ridge {
+        (
+            "    var weight = 1.0;\n",
+            "n * n * weight",
+            "        weight = clamp(n * 2.0, 0.0, 1.0);\n",
+        )
+    } else {
+        ("", "n", "")
+    };
+    let mut code = format!(
+        "\
+fn seeing_noise_fbm(p: vec2<f32>) -> f32 {{
+    var total = 0.0;
+    var max_value = 0.0;
+    var amplitude = 1.0;
+    var frequency = 1.0;
+{weight}    for (var i = 1; i <= SEEING_NOISE_OCTAVES; i++) {{
+        let n = {octave};
+        if (i >= SEEING_NOISE_FIRST_OCTAVE && i <= SEEING_NOISE_LAST_OCTAVE) {{
+            total += {contribution} * amplitude;
+            max_value += amplitude;
+        }}
+{next_weight}        amplitude *= SEEING_NOISE_FALLOFF;
+        frequency *= SEEING_NOISE_LACUNARITY;
+    }}
+    return total / max_value;
+}}
+
+"
+    );
+    code.push_str(if fractal == Fractal::DomainWarp {
+        "\
+fn seeing_noise_fractal(p: vec2<f32>) -> f32 {
+    let q = vec2(seeing_noise_fbm(p), seeing_noise_fbm(p + vec2(5.2, 1.3)));
+    return seeing_noise_fbm(p + SEEING_NOISE_WARP_AMOUNT * q);
+}
+
+"
+    } else {
+        "\
+fn seeing_noise_fractal(p: vec2<f32>) -> f32 {
+    return seeing_noise_fbm(p);
+}
+
+"
+    });
+    code
+}
+
+const WGSL_ENTRY: &str = "\
+fn seeing_noise(p: vec2<f32>) -> f32 {
+    let c = cos(SEEING_NOISE_ROTATION);
+    let s = sin(SEEING_NOISE_ROTATION);
+    return seeing_noise_fractal(vec2(p.x * c - p.y * s, p.x * s + p.y * c));
+}
+
+fn seeing_noise_color(value: f32) -> vec3<f32> {
+    let v = clamp(value, -1.0, 1.0);
+    return select(vec3(1.0 - v, 1.0, 1.0 - v), vec3(1.0, v + 1.0, 1.0), v < 0.0);
+}
+";
+
+fn export_glsl() {
+    export(Language::Glsl);
+}
+
+fn export_wgsl() {
+    export(Language::Wgsl);
+}
+
+/// Settings of the shown noise, when it is one the exporters can write out.
+pub fn current() -> Result<Fbm, String> {
+    match CURRENT_NOISE.lock().unwrap().as_str() {
+        "perlin" => PerlinNoise::shader(),
+        "simplex" => SimplexNoise::shader(),
+        _ => Err("Only Perlin and simplex noise can be exported".to_string()),
+    }
+}
+
+fn export(language: Language) {
+    let code = match current() {
+        Ok(fbm) => generate(&fbm, language),
+        Err(message) => {
+            set_status(&message);
+            set_hidden!(shader_code, true);
+            return;
+        }
+    };
+
+    SHADER_CODE.with(|c| c.set_value(&code));
+    set_hidden!(shader_code, false);
+    let written = web_sys::window()
+        .unwrap()
+        .navigator()
+        .clipboard()
+        .write_text(&code);
+    COPY_SUCCEEDED.with(|succeeded| {
+        COPY_FAILED.with(|failed| {
+            let _ = written.then2(succeeded, failed);
+        })
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fbm(base: Base, fractal: Fractal) -> Fbm {
+        Fbm {
+            base,
+            seed: 42,
+            fractal,
+            octaves: 5,
+            shown_octaves: (1, 5),
+            lacunarity: 2.,
+            gain: 0.5,
+            h_exponent: 1.,
+            spectral_h: false,
+            ridge_offset: 1.,
+            warp_amount: 4.,
+            rotation_degrees: 30.,
+            scale: 50.,
+        }
+    }
+
+    #[test]
+    fn shaders_bake_in_the_settings() {
+        for language in [Language::Glsl, Language::Wgsl] {
+            for base in [Base::Perlin, Base::Simplex] {
+                for fractal in [
+                    Fractal::Standard,
+                    Fractal::Turbulence,
+                    Fractal::Ridge,
+                    Fractal::DomainWarp,
+                ] {
+                    let code = generate(&fbm(base, fractal), language);
+                    assert_eq!(code.matches('{').count(), code.matches('}').count());
+                    assert_eq!(code.matches('(').count(), code.matches(')').count());
+                    assert!(code.contains("SEEING_NOISE_SCALE"));
+                    assert!(code.contains("= 50.0;"));
+                    assert_eq!(
+                        code.contains("SEEING_NOISE_WARP_AMOUNT * q"),
+                        fractal == Fractal::DomainWarp
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn falloff_follows_the_fbm_loops() {
+        let mut settings = fbm(Base::Perlin, Fractal::Standard);
+        settings.gain = 0.6;
+        settings.h_exponent = 2.;
+        assert!((settings.falloff() - 0.36).abs() < 1e-12);
+        settings.fractal = Fractal::Ridge;
+        assert_eq!(settings.falloff(), 0.6);
+        settings.spectral_h = true;
+        settings.fractal = Fractal::DomainWarp;
+        assert_eq!(settings.falloff(), 0.5);
+    }
+}
@@ -0,0 +1,131 @@
+//! Randomize and A/B buttons added under every slider.
+
+use std::{cell::RefCell, collections::HashMap};
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{Element, HtmlInputElement, MouseEvent};
+
+use crate::*;
+
+const SLIDERS_SELECTOR: &str = ".left-column .slider-group input[type=range][id]";
+
+/// The two values a slider's A/B button flips between. Whichever slot is active takes the slider
+/// value when the button is pressed, so tweaks made in between are kept.
+#[derive(Default)]
+struct Slots {
+    a: Option<f64>,
+    b: Option<f64>,
+    on_b: bool,
+}
+
+impl Slots {
+    /// Stores `current` in the active slot and switches to the other one, returns the value the
+    /// slider should jump to. The first press only remembers A, the slider stays until B is set.
+    fn toggle(&mut self, current: f64) -> Option<f64> {
+        let (active, other) = if self.on_b {
+            (&mut self.b, self.a)
+        } else {
+            (&mut self.a, self.b)
+        };
+        *active = Some(current);
+        self.on_b = !self.on_b;
+        other
+    }
+
+    fn label(&self) -> &'static str {
+        if self.on_b { "B" } else { "A" }
+    }
+}
+
+thread_local! {
+    static SLOTS: RefCell<HashMap<String, Slots>> = RefCell::new(HashMap::new());
+}
+
+define_event_closure!(slider_button_click, MouseEvent, on_click);
+
+pub fn setup() {
+    let Ok(sliders) = DOCUMENT.with(|d| d.query_selector_all(SLIDERS_SELECTOR)) else {
+        return;
+    };
+    for slider in (0..sliders.length()).filter_map(|i| sliders.item(i)?.dyn_into::<Element>().ok())
+    {
+        let Some(group) = slider.parent_element() else {
+            continue;
+        };
+        let buttons = create("div", "slider-buttons");
+        for (action, label, title) in [
+            ("randomize", "⚄", "Random value"),
+            ("ab", "A", "Flip between two remembered values"),
+        ] {
+            let button = create("button", "");
+            button.set_text_content(Some(label));
+            let _ = button.set_attribute("title", title);
+            let _ = button.set_attribute("data-slider-action", action);
+            let _ = button.set_attribute("data-slider", &slider.id());
+            let _ = buttons.append_child(&button);
+        }
+        let _ = group.append_child(&buttons);
+    }
+    add_callback!(document, "click", slider_button_click);
+}
+
+fn create(tag: &str, class: &str) -> Element {
+    let element = DOCUMENT.with(|d| d.create_element(tag)).unwrap();
+    element.set_class_name(class);
+    element
+}
+
+fn on_click(event: MouseEvent) {
+    let Some(button) = event.target().and_then(|t| t.dyn_into::<Element>().ok()) else {
+        return;
+    };
+    let (Some(action), Some(id)) = (
+        button.get_attribute("data-slider-action"),
+        button.get_attribute("data-slider"),
+    ) else {
+        return;
+    };
+    let Some(slider) = DOCUMENT
+        .with(|d| d.get_element_by_id(&id))
+        .and_then(|s| s.dyn_into::<HtmlInputElement>().ok())
+    else {
+        return;
+    };
+
+    let value = match action.as_str() {
+        "randomize" => {
+            let min: f64 = slider.min().parse().unwrap_or(0.);
+            let max: f64 = slider.max().parse().unwrap_or(100.);
+            Some(min + js_sys::Math::random() * (max - min))
+        }
+        "ab" => SLOTS.with(|s| {
+            let mut slots = s.borrow_mut();
+            let slots = slots.entry(id).or_default();
+            let value = slots.toggle(slider.value_as_number());
+            button.set_text_content(Some(slots.label()));
+            value
+        }),
+        _ => None,
+    };
+    let Some(value) = value else {
+        return;
+    };
+
+    state::set_slider(&slider, value);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ab_flips_between_the_remembered_values() {
+        let mut slots = Slots::default();
+        assert_eq!(slots.toggle(1.), None);
+        assert_eq!(slots.label(), "B");
+        assert_eq!(slots.toggle(5.), Some(1.));
+        assert_eq!(slots.toggle(1.5), Some(5.));
+        assert_eq!(slots.toggle(5.), Some(1.5));
+        assert_eq!(slots.label(), "A");
+    }
+}
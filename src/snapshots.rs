@@ -0,0 +1,201 @@
+use std::cell::LazyCell;
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{
+    CanvasRenderingContext2d, HtmlCanvasElement, HtmlElement, HtmlInputElement, MouseEvent,
+};
+
+use crate::{drawer::CANVAS, *};
+
+const STORAGE_KEY: &str = "seeing_noise_snapshots";
+const THUMBNAIL_SIZE: u32 = 64;
+/// Data urls and serialized states never contain ASCII control characters, so these separate
+/// snapshots and their fields without any escaping.
+const RECORD_SEPARATOR: char = '\u{1e}';
+const FIELD_SEPARATOR: char = '\u{1f}';
+
+struct Snapshot {
+    name: String,
+    /// Milliseconds since the Unix epoch.
+    saved_at: f64,
+    /// Downscaled canvas as a png data url.
+    thumbnail: String,
+    state: String,
+}
+
+elements!(
+    (snapshot_name, HtmlInputElement),
+    (snapshot_save, HtmlElement),
+    (snapshot_gallery, HtmlElement),
+);
+define_closure!(snapshot_save_click, save_current);
+define_event_closure!(snapshot_gallery_click, MouseEvent, on_gallery_click);
+
+pub fn setup() {
+    add_callback!(snapshot_save, "click", snapshot_save_click);
+    add_callback!(snapshot_gallery, "click", snapshot_gallery_click);
+    render(&load());
+}
+
+fn load() -> Vec<Snapshot> {
+    let Some(stored) = state::storage().and_then(|s| s.get_item(STORAGE_KEY).ok().flatten()) else {
+        return Vec::new();
+    };
+    stored
+        .split(RECORD_SEPARATOR)
+        .filter_map(|record| {
+            let mut fields = record.split(FIELD_SEPARATOR);
+            Some(Snapshot {
+                name: fields.next()?.to_string(),
+                saved_at: fields.next()?.parse().ok()?,
+                thumbnail: fields.next()?.to_string(),
+                state: fields.next()?.to_string(),
+            })
+        })
+        .collect()
+}
+
+fn store(snapshots: &[Snapshot]) {
+    let Some(storage) = state::storage() else {
+        return;
+    };
+    let stored = snapshots
+        .iter()
+        .map(|s| {
+            [
+                s.name.as_str(),
+                &s.saved_at.to_string(),
+                &s.thumbnail,
+                &s.state,
+            ]
+            .join(&FIELD_SEPARATOR.to_string())
+        })
+        .collect::<Vec<_>>()
+        .join(&RECORD_SEPARATOR.to_string());
+    if storage.set_item(STORAGE_KEY, &stored).is_err() {
+        console_log!("Failed to save snapshots to local storage, the storage is probably full");
+    }
+}
+
+fn thumbnail() -> Option<String> {
+    let canvas = DOCUMENT
+        .with(|d| d.create_element("canvas"))
+        .ok()?
+        .dyn_into::<HtmlCanvasElement>()
+        .ok()?;
+    canvas.set_width(THUMBNAIL_SIZE);
+    canvas.set_height(THUMBNAIL_SIZE);
+    let context = canvas
+        .get_context("2d")
+        .ok()??
+        .dyn_into::<CanvasRenderingContext2d>()
+        .ok()?;
+    CANVAS
+        .with(|c| {
+            context.draw_image_with_html_canvas_element_and_dw_and_dh(
+                c,
+                0.0,
+                0.0,
+                THUMBNAIL_SIZE as f64,
+                THUMBNAIL_SIZE as f64,
+            )
+        })
+        .ok()?;
+    canvas.to_data_url().ok()
+}
+
+fn save_current() {
+    let noise = parse_value!(noise_select, String);
+    if CURRENT_NOISE.lock().unwrap().is_empty() {
+        return;
+    }
+
+    let name = SNAPSHOT_NAME.with(|n| n.value().trim().to_string());
+    let name = if name.is_empty() { noise } else { name };
+    let name = name.replace([RECORD_SEPARATOR, FIELD_SEPARATOR], "");
+
+    let mut snapshots = load();
+    snapshots.push(Snapshot {
+        name,
+        saved_at: js_sys::Date::now(),
+        thumbnail: thumbnail().unwrap_or_default(),
+        state: state::serialize(),
+    });
+    store(&snapshots);
+    render(&snapshots);
+    SNAPSHOT_NAME.with(|n| n.set_value(""));
+}
+
+fn on_gallery_click(event: MouseEvent) {
+    let Some(target) = event.target().and_then(|t| t.dyn_into::<Element>().ok()) else {
+        return;
+    };
+    let (Some(action), Some(index)) = (
+        target.get_attribute("data-action"),
+        target
+            .get_attribute("data-index")
+            .and_then(|i| i.parse::<usize>().ok()),
+    ) else {
+        return;
+    };
+
+    let mut snapshots = load();
+    if index >= snapshots.len() {
+        return;
+    }
+    match action.as_str() {
+        "load" => {
+            state::apply(&snapshots[index].state);
+            state::save();
+            history::push();
+        }
+        "delete" => {
+            snapshots.remove(index);
+            store(&snapshots);
+            render(&snapshots);
+        }
+        _ => (),
+    }
+}
+
+fn create(tag: &str, class: &str) -> Element {
+    let element = DOCUMENT.with(|d| d.create_element(tag)).unwrap();
+    element.set_class_name(class);
+    element
+}
+
+fn render(snapshots: &[Snapshot]) {
+    SNAPSHOT_GALLERY.with(|gallery| {
+        gallery.set_inner_html("");
+        for (index, snapshot) in snapshots.iter().enumerate() {
+            let card = create("div", "snapshot");
+
+            let image = create("img", "snapshot-thumbnail");
+            let _ = image.set_attribute("src", &snapshot.thumbnail);
+            let _ = image.set_attribute("alt", &snapshot.name);
+
+            let name = create("div", "snapshot-name");
+            name.set_text_content(Some(&snapshot.name));
+
+            let date = create("div", "snapshot-date");
+            let saved_at = js_sys::Date::new(&snapshot.saved_at.into());
+            date.set_text_content(Some(&String::from(
+                saved_at.to_locale_string("default", &wasm_bindgen::JsValue::UNDEFINED),
+            )));
+
+            let buttons = create("div", "snapshot-buttons");
+            for (action, label) in [("load", "Load"), ("delete", "Delete")] {
+                let button = create("button", "");
+                button.set_text_content(Some(label));
+                let _ = button.set_attribute("data-action", action);
+                let _ = button.set_attribute("data-index", &index.to_string());
+                let _ = buttons.append_child(&button);
+            }
+
+            for child in [&image, &name, &date, &buttons] {
+                let _ = card.append_child(child);
+            }
+            let _ = gallery.append_child(&card);
+        }
+    });
+}
@@ -0,0 +1,519 @@
+//! Writes the shown noise out as a standalone Rust or C source file.
+
+use std::{cell::LazyCell, fmt::Write};
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::HtmlElement;
+
+use crate::{
+    core::Fractal,
+    drawer::HALF_RESOLUTION,
+    shader::{Base, Fbm, float, table},
+    *,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Language {
+    Rust,
+    C,
+}
+
+impl Language {
+    fn file_name(self) -> &'static str {
+        match self {
+            Language::Rust => "seeing_noise.rs",
+            Language::C => "seeing_noise.c",
+        }
+    }
+}
+
+elements!(
+    (snippet_rust, HtmlElement),
+    (snippet_c, HtmlElement),
+    (snippet_status, HtmlElement),
+);
+define_closure!(snippet_rust_click, download_rust);
+define_closure!(snippet_c_click, download_c);
+
+pub fn setup() {
+    add_callback!(snippet_rust, "click", snippet_rust_click);
+    add_callback!(snippet_c, "click", snippet_c_click);
+}
+
+fn set_status(text: &str) {
+    SNIPPET_STATUS.with(|s| s.set_inner_text(text));
+}
+
+/// The float constants the loops read, the others would only draw unused warnings.
+fn constants(fbm: &Fbm) -> Vec<(&'static str, String)> {
+    let mut constants = vec![
+        ("HALF_RESOLUTION", float(HALF_RESOLUTION as f64)),
+        ("SCALE", float(fbm.scale)),
+        ("LACUNARITY", float(fbm.lacunarity)),
+        ("FALLOFF", float(fbm.falloff())),
+        ("ROTATION", float(fbm.rotation_degrees.to_radians())),
+    ];
+    match fbm.fractal {
+        Fractal::Ridge => constants.push(("RIDGE_OFFSET", float(fbm.ridge_offset))),
+        Fractal::DomainWarp => constants.push(("WARP_AMOUNT", float(fbm.warp_amount))),
+        Fractal::Standard | Fractal::Turbulence => {}
+    }
+    constants
+}
+
+pub fn generate(fbm: &Fbm, language: Language) -> String {
+    let name = match fbm.base {
+        Base::Perlin => "Perlin",
+        Base::Simplex => "Simplex",
+    };
+    let mut code = format!(
+        "// {name} noise, seed {}, exported from Seeing Noise.\n\
+         // seeing_noise(x, y) gives the value the page shows at (x, y) in noise space, roughly in\n\
+         // [-1, 1], and seeing_noise_pixel(px, py) the value at pixel (px, py) of its canvas, with\n\
+         // y growing downwards. seeing_noise_color gives the colour the page paints a value.\n\n",
+        fbm.seed,
+    );
+    let integers = [
+        ("OCTAVES", fbm.octaves),
+        ("FIRST_OCTAVE", fbm.shown_octaves.0),
+        ("LAST_OCTAVE", fbm.shown_octaves.1),
+    ];
+    match language {
+        Language::Rust => {
+            for (name, value) in constants(fbm) {
+                let _ = writeln!(code, "const {name}: f64 = {value};");
+            }
+            for (name, value) in integers {
+                let _ = writeln!(code, "const {name}: u32 = {value};");
+            }
+            let _ = writeln!(
+                code,
+                "\nconst PERM: [usize; 256] = [\n{}\n];\n",
+                table(fbm.seed)
+            );
+            code.push_str(RUST_COMMON);
+            code.push_str(match fbm.base {
+                Base::Perlin => RUST_PERLIN,
+                Base::Simplex => RUST_SIMPLEX,
+            });
+            code.push_str(&rust_fractal(fbm.fractal));
+            code.push_str(RUST_ENTRY);
+        }
+        Language::C => {
+            code.push_str("#include <math.h>\n\n");
+            for (name, value) in constants(fbm) {
+                let _ = writeln!(code, "static const double SEEING_NOISE_{name} = {value};");
+            }
+            for (name, value) in integers {
+                let _ = writeln!(code, "static const int SEEING_NOISE_{name} = {value};");
+            }
+            let _ = writeln!(
+                code,
+                "\nstatic const int SEEING_NOISE_PERM[256] = {{\n{}\n}};\n",
+                table(fbm.seed)
+            );
+            code.push_str(C_COMMON);
+            code.push_str(match fbm.base {
+                Base::Perlin => C_PERLIN,
+                Base::Simplex => C_SIMPLEX,
+            });
+            code.push_str(&c_fractal(fbm.fractal));
+            code.push_str(C_ENTRY);
+        }
+    }
+    code
+}
+
+const RUST_COMMON: &str = "\
+fn perm(i: usize) -> usize {
+    PERM[i & 255]
+}
+
+fn grad(hash: usize, x: f64, y: f64) -> f64 {
+    const GRADIENTS: [(f64, f64); 8] = [
+        (1.0, 0.0), (1.0, 1.0), (0.0, 1.0), (-1.0, 1.0),
+        (-1.0, 0.0), (-1.0, -1.0), (0.0, -1.0), (1.0, -1.0),
+    ];
+    let (gx, gy) = GRADIENTS[hash & 7];
+    gx * x + gy * y
+}
+
+fn lerp(t: f64, a: f64, b: f64) -> f64 {
+    a + t * (b - a)
+}
+
+";
+
+const RUST_PERLIN: &str = "\
+fn fade(t: f64) -> f64 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn hash(x: i32, y: i32) -> usize {
+    perm(perm((x & 255) as usize) + (y & 255) as usize)
+}
+
+fn base(x: f64, y: f64) -> f64 {
+    let (xi, yi) = (x.floor() as i32, y.floor() as i32);
+    let (xf, yf) = (x - xi as f64, y - yi as f64);
+    let (u, v) = (fade(xf), fade(yf));
+    let x1 = lerp(u, grad(hash(xi, yi), xf, yf), grad(hash(xi + 1, yi), xf - 1.0, yf));
+    let x2 = lerp(
+        u,
+        grad(hash(xi, yi + 1), xf, yf - 1.0),
+        grad(hash(xi + 1, yi + 1), xf - 1.0, yf - 1.0),
+    );
+    lerp(v, x1, x2)
+}
+
+";
+
+const RUST_SIMPLEX: &str = "\
+const F2: f64 = 0.3660254037844386;
+const G2: f64 = 0.21132486540518708;
+
+fn corner(hash: usize, x: f64, y: f64) -> f64 {
+    let t = 0.5 - x * x - y * y;
+    if t < 0.0 {
+        return 0.0;
+    }
+    let t = t * t;
+    t * t * grad(hash, x, y)
+}
+
+fn base(x: f64, y: f64) -> f64 {
+    let s = (x + y) * F2;
+    let (i, j) = ((x + s).floor(), (y + s).floor());
+    let t = (i + j) * G2;
+    let (x0, y0) = (x - (i - t), y - (j - t));
+    let (i1, j1) = if x0 > y0 { (1, 0) } else { (0, 1) };
+    let (x1, y1) = (x0 - i1 as f64 + G2, y0 - j1 as f64 + G2);
+    let (x2, y2) = (x0 - 1.0 + 2.0 * G2, y0 - 1.0 + 2.0 * G2);
+    let (ii, jj) = ((i as i32 & 255) as usize, (j as i32 & 255) as usize);
+    70.0 * (corner(perm(ii + perm(jj)), x0, y0)
+        + corner(perm(ii + i1 + perm(jj + j1)), x1, y1)
+        + corner(perm(ii + 1 + perm(jj + 1)), x2, y2))
+}
+
+";
+
+/// The fBm loop of one fractal type, domain warping adds a wrapper around the standard loop.
+fn rust_fractal(fractal: Fractal) -> String {
+    let octave = match fractal {
+        Fractal::Standard | Fractal::DomainWarp => "base(x * frequency, y * frequency)",
+        Fractal::Turbulence => "base(x * frequency, y * frequency).abs()",
+        Fractal::Ridge => "RIDGE_OFFSET - base(x * frequency, y * frequency).abs()",
+    };
+    // Ridges weigh every octave by the one before, so they only show up along its ridges.
+    let (weight, contribution, next_weight) = if fractal == Fractal::Ridge {
+        (
+            "    let mut weight = 1.0;\n",
+            "n * n * weight",
+            "        weight = (n * 2.0).clamp(0.0, 1.0);\n",
+        )
+    } else {
+        ("", "n", "")
+    };
+    let mut code = format!(
+        "\
+fn fbm(x: f64, y: f64) -> f64 {{
+    let mut total = 0.0;
+    let mut max_value = 0.0;
+    let mut amplitude = 1.0;
+    let mut frequency = 1.0;
+{weight}    for i in 1..=OCTAVES {{
+        let n = {octave};
+        if (FIRST_OCTAVE..=LAST_OCTAVE).contains(&i) {{
+            total += {contribution} * amplitude;
+            max_value += amplitude;
+        }}
+{next_weight}        amplitude *= FALLOFF;
+        frequency *= LACUNARITY;
+    }}
+    total / max_value
+}}
+
+"
+    );
+    code.push_str(if fractal == Fractal::DomainWarp {
+        "\
+fn fractal(x: f64, y: f64) -> f64 {
+    let (qx, qy) = (fbm(x, y), fbm(x + 5.2, y + 1.3));
+    fbm(x + WARP_AMOUNT * qx, y + WARP_AMOUNT * qy)
+}
+
+"
+    } else {
+        "\
+fn fractal(x: f64, y: f64) -> f64 {
+    fbm(x, y)
+}
+
+"
+    });
+    code
+}
+
+const RUST_ENTRY: &str = "\
+pub fn seeing_noise(x: f64, y: f64) -> f64 {
+    let (sin, cos) = ROTATION.sin_cos();
+    fractal(x * cos - y * sin, x * sin + y * cos)
+}
+
+pub fn seeing_noise_pixel(px: f64, py: f64) -> f64 {
+    seeing_noise((px - HALF_RESOLUTION) / SCALE, (py - HALF_RESOLUTION) / SCALE)
+}
+
+pub fn seeing_noise_color(v: f64) -> [u8; 3] {
+    if v < 0.0 {
+        [255, lerp(v + 1.0, 0.0, 255.0) as u8, 255]
+    } else {
+        let other = lerp(v, 255.0, 0.0) as u8;
+        [other, 255, other]
+    }
+}
+";
+
+const C_COMMON: &str = "\
+static int seeing_noise_perm(int i) {
+    return SEEING_NOISE_PERM[i & 255];
+}
+
+static double seeing_noise_grad(int hash, double x, double y) {
+    static const double GRADIENTS[8][2] = {
+        {1.0, 0.0}, {1.0, 1.0}, {0.0, 1.0}, {-1.0, 1.0},
+        {-1.0, 0.0}, {-1.0, -1.0}, {0.0, -1.0}, {1.0, -1.0}
+    };
+    return GRADIENTS[hash & 7][0] * x + GRADIENTS[hash & 7][1] * y;
+}
+
+static double seeing_noise_lerp(double t, double a, double b) {
+    return a + t * (b - a);
+}
+
+";
+
+const C_PERLIN: &str = "\
+static double seeing_noise_fade(double t) {
+    return t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+}
+
+static int seeing_noise_hash(int x, int y) {
+    return seeing_noise_perm(seeing_noise_perm(x) + (y & 255));
+}
+
+static double seeing_noise_base(double x, double y) {
+    int xi = (int)floor(x);
+    int yi = (int)floor(y);
+    double xf = x - xi;
+    double yf = y - yi;
+    double u = seeing_noise_fade(xf);
+    double v = seeing_noise_fade(yf);
+    double x1 = seeing_noise_lerp(u, seeing_noise_grad(seeing_noise_hash(xi, yi), xf, yf),
+        seeing_noise_grad(seeing_noise_hash(xi + 1, yi), xf - 1.0, yf));
+    double x2 = seeing_noise_lerp(u, seeing_noise_grad(seeing_noise_hash(xi, yi + 1), xf, yf - 1.0),
+        seeing_noise_grad(seeing_noise_hash(xi + 1, yi + 1), xf - 1.0, yf - 1.0));
+    return seeing_noise_lerp(v, x1, x2);
+}
+
+";
+
+const C_SIMPLEX: &str = "\
+static double seeing_noise_corner(int hash, double x, double y) {
+    double t = 0.5 - x * x - y * y;
+    if (t < 0.0) {
+        return 0.0;
+    }
+    t *= t;
+    return t * t * seeing_noise_grad(hash, x, y);
+}
+
+static double seeing_noise_base(double x, double y) {
+    const double F2 = 0.3660254037844386;
+    const double G2 = 0.21132486540518708;
+    double s = (x + y) * F2;
+    double i = floor(x + s);
+    double j = floor(y + s);
+    double t = (i + j) * G2;
+    double x0 = x - (i - t);
+    double y0 = y - (j - t);
+    int i1 = x0 > y0 ? 1 : 0;
+    int j1 = 1 - i1;
+    double x1 = x0 - i1 + G2;
+    double y1 = y0 - j1 + G2;
+    double x2 = x0 - 1.0 + 2.0 * G2;
+    double y2 = y0 - 1.0 + 2.0 * G2;
+    int ii = (int)i & 255;
+    int jj = (int)j & 255;
+    return 70.0 * (seeing_noise_corner(seeing_noise_perm(ii + seeing_noise_perm(jj)), x0, y0)
+        + seeing_noise_corner(seeing_noise_perm(ii + i1 + seeing_noise_perm(jj + j1)), x1, y1)
+        + seeing_noise_corner(seeing_noise_perm(ii + 1 + seeing_noise_perm(jj + 1)), x2, y2));
+}
+
+";
+
+fn c_fractal(fractal: Fractal) -> String {
+    let octave = match fractal {
+        Fractal::Standard | Fractal::DomainWarp => {
+            "seeing_noise_base(x * frequency, y * frequency)"
+        }
+        Fractal::Turbulence => "fabs(seeing_noise_base(x * frequency, y * frequency))",
+        Fractal::Ridge => {
+            "SEEING_NOISE_RIDGE_OFFSET - fabs(seeing_noise_base(x * frequency, y * frequency))"
+        }
+    };
+    // Ridges weigh every octave by the one before, so they only show up along its ridges.
+    let (weight, contribution, next_weight) = if fractal == Fractal::Ridge {
+        (
+            "    double weight = 1.0;\n",
+            "n * n * weight",
+            "        weight = fmin(fmax(n * 2.0, 0.0), 1.0);\n",
+        )
+    } else {
+        ("", "n", "")
+    };
+    let mut code = format!(
+        "\
+static double seeing_noise_fbm(double x, double y) {{
+    double total = 0.0;
+    double max_value = 0.0;
+    double amplitude = 1.0;
+    double frequency = 1.0;
+{weight}    for (int i = 1; i <= SEEING_NOISE_OCTAVES; i++) {{
+        double n = {octave};
+        if (i >= SEEING_NOISE_FIRST_OCTAVE && i <= SEEING_NOISE_LAST_OCTAVE) {{
+            total += {contribution} * amplitude;
+            max_value += amplitude;
+        }}
+{next_weight}        amplitude *= SEEING_NOISE_FALLOFF;
+        frequency *= SEEING_NOISE_LACUNARITY;
+    }}
+    return total / max_value;
+}}
+
+"
+    );
+    code.push_str(if fractal == Fractal::DomainWarp {
+        "\
+static double seeing_noise_fractal(double x, double y) {
+    double qx = seeing_noise_fbm(x, y);
+    double qy = seeing_noise_fbm(x + 5.2, y + 1.3);
+    return seeing_noise_fbm(x + SEEING_NOISE_WARP_AMOUNT * qx, y + SEEING_NOISE_WARP_AMOUNT * qy);
+}
+
+"
+    } else {
+        "\
+static double seeing_noise_fractal(double x, double y) {
+    return seeing_noise_fbm(x, y);
+}
+
+"
+    });
+    code
+}
+
+const C_ENTRY: &str = "\
+double seeing_noise(double x, double y) {
+    double c = cos(SEEING_NOISE_ROTATION);
+    double s = sin(SEEING_NOISE_ROTATION);
+    return seeing_noise_fractal(x * c - y * s, x * s + y * c);
+}
+
+double seeing_noise_pixel(double px, double py) {
+    return seeing_noise((px - SEEING_NOISE_HALF_RESOLUTION) / SEEING_NOISE_SCALE,
+        (py - SEEING_NOISE_HALF_RESOLUTION) / SEEING_NOISE_SCALE);
+}
+
+void seeing_noise_color(double v, unsigned char rgb[3]) {
+    if (v < 0.0) {
+        rgb[0] = 255;
+        rgb[1] = (unsigned char)fmin(fmax(seeing_noise_lerp(v + 1.0, 0.0, 255.0), 0.0), 255.0);
+        rgb[2] = 255;
+    } else {
+        unsigned char other =
+            (unsigned char)fmin(fmax(seeing_noise_lerp(v, 255.0, 0.0), 0.0), 255.0);
+        rgb[0] = other;
+        rgb[1] = 255;
+        rgb[2] = other;
+    }
+}
+";
+
+fn download_rust() {
+    download(Language::Rust);
+}
+
+fn download_c() {
+    download(Language::C);
+}
+
+fn download(language: Language) {
+    let code = match shader::current() {
+        Ok(fbm) => generate(&fbm, language),
+        Err(message) => {
+            set_status(&message);
+            return;
+        }
+    };
+
+    let Some(link) = DOCUMENT
+        .with(|d| d.create_element("a"))
+        .ok()
+        .and_then(|a| a.dyn_into::<HtmlElement>().ok())
+    else {
+        return;
+    };
+    let url = format!(
+        "data:text/plain;charset=utf-8,{}",
+        js_sys::encode_uri_component(&code)
+    );
+    let file_name = language.file_name();
+    let _ = link.set_attribute("href", &url);
+    let _ = link.set_attribute("download", file_name);
+    link.click();
+    set_status(&format!("Saved as {file_name}"));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snippets_only_declare_the_constants_they_use() {
+        for language in [Language::Rust, Language::C] {
+            for base in [Base::Perlin, Base::Simplex] {
+                for fractal in [
+                    Fractal::Standard,
+                    Fractal::Turbulence,
+                    Fractal::Ridge,
+                    Fractal::DomainWarp,
+                ] {
+                    let fbm = Fbm {
+                        base,
+                        seed: 42,
+                        fractal,
+                        octaves: 5,
+                        shown_octaves: (1, 5),
+                        lacunarity: 2.,
+                        gain: 0.5,
+                        h_exponent: 1.,
+                        spectral_h: false,
+                        ridge_offset: 1.,
+                        warp_amount: 4.,
+                        rotation_degrees: 0.,
+                        scale: 50.,
+                    };
+                    let code = generate(&fbm, language);
+                    assert_eq!(code.matches('{').count(), code.matches('}').count());
+                    assert_eq!(code.matches('(').count(), code.matches(')').count());
+                    for (name, _) in constants(&fbm) {
+                        assert!(code.matches(name).count() >= 2, "{name} is never read");
+                    }
+                    assert_eq!(code.contains("WARP_AMOUNT"), fractal == Fractal::DomainWarp);
+                    assert_eq!(code.contains("RIDGE_OFFSET"), fractal == Fractal::Ridge);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use std::cell::LazyCell;
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{Event, HtmlElement, HtmlInputElement, HtmlSelectElement, Storage};
+
+use crate::*;
+
+const STORAGE_KEY: &str = "seeing_noise_state";
+
+/// Every control that makes up a configuration. Sound stays off after a reload, browsers refuse
+/// to start audio without a click anyway, the snapshot, compare, montage, demo, annotation and
+/// permutation panels are not part of any configuration and file inputs can not be set from a
+/// script.
+const CONTROLS_SELECTOR: &str = concat!(
+    ".left-column input[id]:not([id^=audio_]):not([id^=snapshot_]):not([id^=compare_])",
+    ":not([id^=montage_]):not([id^=kiosk_]):not([id^=annotation_])",
+    ":not(#permutation_override):not(#permutation_seed):not([type=file]), ",
+    ".left-column select[id]:not([id^=annotation_])"
+);
+
+define_closure!(save_state, save);
+
+/// Input events bubble up to the document after the control's own handler has redrawn the noise,
+/// so a single listener there sees every change.
+pub fn setup() {
+    add_callback!(document, "input", save_state);
+}
+
+pub fn storage() -> Option<Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+fn controls() -> Vec<Element> {
+    let Ok(nodes) = DOCUMENT.with(|d| d.query_selector_all(CONTROLS_SELECTOR)) else {
+        return Vec::new();
+    };
+    (0..nodes.length())
+        .filter_map(|i| nodes.item(i)?.dyn_into::<Element>().ok())
+        .collect()
+}
+
+fn value(control: &Element) -> Option<String> {
+    if let Some(input) = control.dyn_ref::<HtmlInputElement>() {
+        match input.type_().as_str() {
+            "checkbox" | "radio" => Some(input.checked().to_string()),
+            _ => Some(input.value()),
+        }
+    } else {
+        control.dyn_ref::<HtmlSelectElement>().map(|s| s.value())
+    }
+}
+
+/// Every control value as `id=value` lines, the same format snapshots keep.
+pub fn serialize() -> String {
+    let mut state = String::new();
+    for control in controls() {
+        let Some(value) = value(&control) else {
+            continue;
+        };
+        state.push_str(&format!("{}={value}\n", control.id()));
+    }
+    state
+}
+
+/// Ids and values of the controls on screen, the settings that shape the current noise. Radio
+/// buttons are listed only when they are the checked one.
+pub fn visible_parameters() -> Vec<(String, String)> {
+    controls()
+        .into_iter()
+        .filter(|control| {
+            // Hidden elements and everything inside them have no offset parent.
+            control
+                .dyn_ref::<HtmlElement>()
+                .is_some_and(|c| c.offset_parent().is_some())
+        })
+        .filter(|control| {
+            control
+                .dyn_ref::<HtmlInputElement>()
+                .is_none_or(|i| i.type_() != "radio" || i.checked())
+        })
+        .filter_map(|control| Some((control.id(), value(&control)?)))
+        .collect()
+}
+
+pub fn save() {
+    let Some(storage) = storage() else {
+        return;
+    };
+    if storage.set_item(STORAGE_KEY, &serialize()).is_err() {
+        console_log!("Failed to save the state to local storage");
+    }
+}
+
+/// Moves a slider the way dragging it would. The slider snaps the value to its step and the input
+/// event reaches whichever noise or panel listens to it.
+pub fn set_slider(slider: &HtmlInputElement, value: f64) {
+    slider.set_value_as_number(value);
+    if let Ok(input) = Event::new("input") {
+        let _ = slider.dispatch_event(&input);
+    }
+    save();
+}
+
+/// Selects the noise that was shown before the reload, then puts every control back the way it was.
+pub fn restore() {
+    if let Some(state) = storage().and_then(|s| s.get_item(STORAGE_KEY).ok().flatten()) {
+        apply(&state);
+    }
+}
+
+/// Selects the noise recorded in a serialized state and sets every control it lists.
+pub fn apply(state: &str) {
+    let Some((_, noise)) = state
+        .lines()
+        .filter_map(|l| l.split_once('='))
+        .find(|(id, _)| *id == "noise_select")
+    else {
+        return;
+    };
+    NOISE_SELECT.with(|s| s.set_value(noise));
+    if NOISE_SELECT.with(|s| s.value()) != noise {
+        return;
+    }
+    // Selecting a noise resets its controls to their defaults, so the saved values go in afterwards.
+    change_noise();
+    apply_changes(state);
+}
+
+/// Sets the controls listed in a serialized state and redraws, leaving the selected noise and the
+/// controls it does not list as they are.
+pub fn apply_changes(state: &str) {
+    for (id, value) in state.lines().filter_map(|l| l.split_once('=')) {
+        if id == "noise_select" {
+            continue;
+        }
+        let Some(control) = DOCUMENT.with(|d| d.get_element_by_id(id)) else {
+            continue;
+        };
+        if let Some(input) = control.dyn_ref::<HtmlInputElement>() {
+            match input.type_().as_str() {
+                "checkbox" | "radio" => input.set_checked(value == "true"),
+                _ => input.set_value(value),
+            }
+        } else if let Some(select) = control.dyn_ref::<HtmlSelectElement>() {
+            select.set_value(value);
+        }
+    }
+
+    view::update_controls();
+    precision::read_controls();
+    rng::read_controls();
+    overlay::read_controls();
+    supersampling::read_controls();
+    field::read_controls();
+    domain_repeat::read_controls();
+    axis_scale::read_controls();
+    scroll::read_controls();
+    rotation::read_controls();
+    channels::read_controls();
+    update_current_noise();
+}
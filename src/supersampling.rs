@@ -0,0 +1,101 @@
+//! Averages several samples per pixel on a rotated grid, and times the frames.
+
+use std::cell::Cell;
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlElement, HtmlSelectElement};
+
+use crate::*;
+
+/// Frames slower than this no longer keep up with a slider drag.
+pub const SMOOTH_FRAME_MS: f64 = 1000. / 30.;
+
+thread_local! {
+    /// Samples along each side of a pixel.
+    static GRID: Cell<usize> = const { Cell::new(1) };
+    static FRAME_STARTED_AT: Cell<f64> = const { Cell::new(0.) };
+    static FRAME_MS: Cell<f64> = const { Cell::new(0.) };
+}
+
+elements!(
+    (supersampling, HtmlSelectElement),
+    (frame_time_readout, HtmlElement),
+);
+define_closure!(supersampling_change, on_supersampling_change);
+
+pub fn setup() {
+    add_callback!(supersampling, "input", supersampling_change);
+}
+
+fn on_supersampling_change() {
+    read_controls();
+    update_current_noise();
+}
+
+/// Picks up the setting from the page, also after a saved state has been applied.
+pub fn read_controls() {
+    let grid = parse_value!(supersampling, usize).clamp(1, 4);
+    GRID.with(|g| g.set(grid));
+}
+
+pub fn grid() -> usize {
+    GRID.with(Cell::get)
+}
+
+/// Positions of the samples of an `n`×`n` rotated grid in pixels, relative to where a single
+/// sample would go, which stays their mean.
+pub fn offsets(n: usize) -> Vec<(f64, f64)> {
+    let count = (n * n) as f64;
+    (0..n)
+        .flat_map(|i| {
+            (0..n).map(move |j| ((i * n + j) as f64 + 0.5, (j * n + n - 1 - i) as f64 + 0.5))
+        })
+        .map(|(x, y)| (x / count - 0.5, y / count - 0.5))
+        .collect()
+}
+
+pub fn frame_started() {
+    FRAME_STARTED_AT.with(|s| s.set(js_sys::Date::now()));
+}
+
+/// How long the last frame took, in milliseconds.
+pub fn frame_ms() -> f64 {
+    FRAME_MS.with(Cell::get)
+}
+
+/// Shows how long the frame took, with a warning when supersampling makes it too slow to drag.
+pub fn on_frame_drawn() {
+    let elapsed = js_sys::Date::now() - FRAME_STARTED_AT.with(Cell::get);
+    FRAME_MS.with(|f| f.set(elapsed));
+    let grid = grid();
+    let mut text = format!(
+        "Last frame: {elapsed:.0} ms, {} samples per pixel",
+        grid * grid
+    );
+    if grid > 1 && elapsed > SMOOTH_FRAME_MS {
+        text.push_str(". Too slow for smooth dragging, lower the supersampling while exploring");
+    }
+    FRAME_TIME_READOUT.with(|r| r.set_inner_text(&text));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotated_grid_covers_every_row_and_column_once() {
+        assert_eq!(offsets(1), vec![(0., 0.)]);
+        for n in [2, 4] {
+            let offsets = offsets(n);
+            let mut xs: Vec<f64> = offsets.iter().map(|o| o.0).collect();
+            let mut ys: Vec<f64> = offsets.iter().map(|o| o.1).collect();
+            xs.sort_by(f64::total_cmp);
+            ys.sort_by(f64::total_cmp);
+            let step = 1. / (n * n) as f64;
+            for (k, (x, y)) in xs.iter().zip(&ys).enumerate() {
+                let expected = (k as f64 + 0.5) * step - 0.5;
+                assert!((x - expected).abs() < 1e-12 && (y - expected).abs() < 1e-12);
+            }
+        }
+    }
+}
@@ -0,0 +1,167 @@
+//! Renders larger than the canvas, tile by tile, for `tile_worker.js` to run off the page.
+
+use js_sys::{Array, Float32Array, Function, Object, Reflect};
+use wasm_bindgen::{JsValue, prelude::wasm_bindgen};
+
+use crate::{
+    core::{Sampler, Viewport, from_settings, scale},
+    drawer::RESOLUTION,
+};
+
+/// Left, top, width and height of a tile in pixels.
+pub type Tile = (u32, u32, u32, u32);
+
+/// The noise space a `width` x `height` image shows. Its longer side spans what the canvas spans
+/// at `scale`, so an image as large as the canvas shows what the canvas does.
+fn view(width: u32, height: u32, scale: f64) -> Viewport {
+    let unit = RESOLUTION as f64 / scale / width.max(height) as f64;
+    let (w, h) = (width as f64 * unit, height as f64 * unit);
+    Viewport::new(-w / 2., -h / 2., w, h, width as usize, height as usize)
+}
+
+/// Tiles covering the image in row-major order, the last row and column take what is left.
+fn tiles(width: u32, height: u32, tile_size: u32) -> Vec<Tile> {
+    (0..height)
+        .step_by(tile_size as usize)
+        .flat_map(|top| {
+            (0..width).step_by(tile_size as usize).map(move |left| {
+                (
+                    left,
+                    top,
+                    tile_size.min(width - left),
+                    tile_size.min(height - top),
+                )
+            })
+        })
+        .collect()
+}
+
+/// The part of the view one tile shows.
+fn tile_view(view: &Viewport, (left, top, width, height): Tile) -> Viewport {
+    let (x, y) = view.point(left as usize, top as usize);
+    Viewport::new(
+        x,
+        y,
+        width as f64 * view.width / view.columns as f64,
+        height as f64 * view.height / view.rows as f64,
+        width as usize,
+        height as usize,
+    )
+}
+
+/// The keys and values of a settings object, the way a settings file spells them.
+fn settings_pairs(settings: &Object) -> Vec<(String, String)> {
+    Object::entries(settings)
+        .iter()
+        .filter_map(|entry| {
+            let entry = Array::from(&entry);
+            let key = entry.get(0).as_string()?;
+            let value = entry.get(1);
+            let value = value
+                .as_string()
+                .or_else(|| value.as_f64().map(|v| v.to_string()))
+                .or_else(|| value.as_bool().map(|v| v.to_string()))?;
+            Some((key, value))
+        })
+        .collect()
+}
+
+/// The noise `settings` describe and the part of it a `width` x `height` image shows.
+pub fn prepare(
+    settings: &Object,
+    width: u32,
+    height: u32,
+) -> Result<(Box<dyn Sampler>, Viewport), JsValue> {
+    if width == 0 || height == 0 {
+        return Err("The image needs a size".into());
+    }
+    let pairs = settings_pairs(settings);
+    let sampler = from_settings(&pairs)?;
+    Ok((sampler, view(width, height, scale(&pairs)?)))
+}
+
+/// The values of one tile row by row.
+pub fn sample_tile(sampler: &dyn Sampler, view: &Viewport, tile: Tile) -> Vec<f32> {
+    let viewport = tile_view(view, tile);
+    let mut values = vec![0.; viewport.columns * viewport.rows];
+    sampler.fill(&mut values, &viewport);
+    values.iter().map(|&v| v as f32).collect()
+}
+
+/// One tile as `{ x, y, width, height, values }`, with the values row by row.
+fn render_tile(sampler: &dyn Sampler, view: &Viewport, tile: Tile) -> Object {
+    let values = sample_tile(sampler, view, tile);
+
+    let chunk = Object::new();
+    for (key, value) in [
+        ("x", JsValue::from(tile.0)),
+        ("y", JsValue::from(tile.1)),
+        ("width", JsValue::from(tile.2)),
+        ("height", JsValue::from(tile.3)),
+        ("values", Float32Array::from(values.as_slice()).into()),
+    ] {
+        let _ = Reflect::set(&chunk, &key.into(), &value);
+    }
+    chunk
+}
+
+/// Renders a `width` x `height` image of the noise `settings` describe, a flat object with the
+/// same keys as the `render` binary's settings files. `on_tile` gets every finished tile as
+/// `{ x, y, width, height, values }` with the values in a `Float32Array`, and `on_progress` the
+/// number of tiles done and in total after each one. The render stops early when `on_progress`
+/// returns `false`.
+#[wasm_bindgen]
+pub fn render_tiles(
+    settings: &Object,
+    width: u32,
+    height: u32,
+    tile_size: u32,
+    on_tile: &Function,
+    on_progress: &Function,
+) -> Result<(), JsValue> {
+    if tile_size == 0 {
+        return Err("The tiles need a size".into());
+    }
+    let (sampler, view) = prepare(settings, width, height)?;
+    let tiles = tiles(width, height, tile_size);
+    let total = tiles.len() as u32;
+    for (done, tile) in (1u32..).zip(tiles) {
+        on_tile.call1(&JsValue::NULL, &render_tile(sampler.as_ref(), &view, tile))?;
+        let go_on = on_progress.call2(&JsValue::NULL, &done.into(), &total.into())?;
+        if go_on == JsValue::FALSE {
+            break;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::drawer::HALF_RESOLUTION;
+
+    #[test]
+    fn tiles_cover_the_image_once_and_match_the_canvas() {
+        let (width, height) = (1000, 700);
+        let mut covered = vec![0; width * height];
+        for (left, top, w, h) in tiles(width as u32, height as u32, 256) {
+            for y in top..top + h {
+                for x in left..left + w {
+                    covered[y as usize * width + x as usize] += 1;
+                }
+            }
+        }
+        assert!(covered.iter().all(|&c| c == 1));
+
+        // At the canvas size the pixels land where the canvas samples them.
+        let scale = 40.;
+        let canvas = view(RESOLUTION, RESOLUTION, scale);
+        let tile = tile_view(&canvas, (128, 64, 128, 128));
+        let (x, y) = tile.point(3, 5);
+        let expected = (
+            (131. - HALF_RESOLUTION as f64) / scale,
+            (69. - HALF_RESOLUTION as f64) / scale,
+        );
+        assert!((x - expected.0).abs() < 1e-12 && (y - expected.1).abs() < 1e-12);
+    }
+}
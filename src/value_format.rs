@@ -0,0 +1,55 @@
+//! How slider values are written next to their sliders.
+
+/// Non-zero values closer to 0 than this are written in scientific notation, a fixed number of
+/// decimals would round them away.
+const SCIENTIFIC_BELOW: f64 = 1e-3;
+/// Decimals `Auto` rounds to, enough for every slider step on the page and few enough to hide
+/// the float error that stepping a slider accumulates.
+const AUTO_DECIMALS: usize = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ValueFormat {
+    /// Trailing zeros trimmed, so integers have no decimal point.
+    Auto,
+    /// A fixed number of decimals.
+    Decimals(usize),
+    /// An angle in degrees, written like `Auto` with a degree sign.
+    Degrees,
+}
+
+fn trim_zeros(text: String) -> String {
+    if !text.contains('.') {
+        return text;
+    }
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+pub fn format_value(value: impl Into<f64>, format: ValueFormat) -> String {
+    let value = value.into();
+    if value != 0. && value.abs() < SCIENTIFIC_BELOW && format != ValueFormat::Degrees {
+        return format!("{value:.2e}");
+    }
+    match format {
+        ValueFormat::Auto => trim_zeros(format!("{value:.AUTO_DECIMALS$}")),
+        ValueFormat::Decimals(places) => format!("{value:.places$}"),
+        ValueFormat::Degrees => format!("{}°", trim_zeros(format!("{value:.1}"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_are_written_per_format() {
+        assert_eq!(format_value(42_u32, ValueFormat::Auto), "42");
+        assert_eq!(format_value(0.1 + 0.2, ValueFormat::Auto), "0.3");
+        assert_eq!(format_value(-0.25, ValueFormat::Auto), "-0.25");
+        assert_eq!(format_value(0.055, ValueFormat::Decimals(3)), "0.055");
+        assert_eq!(format_value(2., ValueFormat::Decimals(2)), "2.00");
+        assert_eq!(format_value(45., ValueFormat::Degrees), "45°");
+        assert_eq!(format_value(12.5, ValueFormat::Degrees), "12.5°");
+        assert_eq!(format_value(0.0005, ValueFormat::Auto), "5.00e-4");
+        assert_eq!(format_value(0., ValueFormat::Decimals(1)), "0.0");
+    }
+}
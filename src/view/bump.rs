@@ -0,0 +1,115 @@
+use std::cell::{Cell, LazyCell};
+
+use rayon::prelude::*;
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlElement, HtmlInputElement, MouseEvent};
+
+use crate::{
+    camera::{Vec3, dot, normalize, sub},
+    drawer::{CANVAS, RESOLUTION, canvas_coordinates, draw_circle, draw_noise},
+    field::FIELD,
+    *,
+};
+
+const AMBIENT: f64 = 0.15;
+const SHININESS: i32 = 32;
+const ALBEDO: [f64; 3] = [200., 190., 175.];
+
+elements!(
+    (bump_strength, HtmlInputElement),
+    (bump_strength_display, HtmlElement),
+    (bump_light_height, HtmlInputElement),
+    (bump_light_height_display, HtmlElement),
+);
+define_closure!(bump_change, super::on_view_change);
+define_event_closure!(bump_canvas_move, MouseEvent, on_canvas_move);
+
+thread_local! {
+    /// Canvas position of the point light, dragged around with the mouse.
+    static LIGHT: Cell<(f64, f64)> = Cell::new((RESOLUTION as f64 * 0.3, RESOLUTION as f64 * 0.3));
+}
+
+pub fn setup() {
+    add_callback!(bump_strength, "input", bump_change);
+    add_callback!(bump_light_height, "input", bump_change);
+    add_callback!(canvas, "mousedown", bump_canvas_move);
+    add_callback!(canvas, "mousemove", bump_canvas_move);
+}
+
+pub fn update_displays() {
+    set_text!(bump_strength, &parse_value!(bump_strength, f64).to_string());
+    set_text!(
+        bump_light_height,
+        &parse_value!(bump_light_height, f64).to_string()
+    );
+}
+
+/// Moves the light while the left button is held, relighting the retained field without
+/// sampling the noise again.
+fn on_canvas_move(event: MouseEvent) {
+    if super::current() != super::ViewMode::Bump || event.buttons() & 1 == 0 {
+        return;
+    }
+    LIGHT.with(|l| l.set(canvas_coordinates(&event)));
+
+    let field = FIELD.with(|f| f.borrow().clone());
+    if field.len() == (RESOLUTION * RESOLUTION) as usize {
+        draw(&field);
+    }
+}
+
+/// Reads the field with clamped edges.
+#[inline]
+fn at(field: &[f64], x: isize, y: isize) -> f64 {
+    let size = RESOLUTION as isize;
+    field[(y.clamp(0, size - 1) * size + x.clamp(0, size - 1)) as usize]
+}
+
+/// Surface normal of every pixel, with the field as the height of a flat quad. `strength` is the
+/// height in pixels of a step from -1 to 1.
+fn normals(field: &[f64], strength: f64) -> Vec<Vec3> {
+    (0..(RESOLUTION * RESOLUTION) as usize)
+        .into_par_iter()
+        .map(|i| {
+            let x = (i % RESOLUTION as usize) as isize;
+            let y = (i / RESOLUTION as usize) as isize;
+            let height = strength / 2.;
+            let dx = (at(field, x + 1, y) - at(field, x - 1, y)) / 2. * height;
+            let dy = (at(field, x, y + 1) - at(field, x, y - 1)) / 2. * height;
+            normalize([-dx, -dy, 1.])
+        })
+        .collect()
+}
+
+/// Blinn-Phong lighting of the bumped quad under a point light at `light`, seen from straight
+/// above.
+fn shade(normals: &[Vec3], light: Vec3) -> Vec<u8> {
+    normals
+        .par_iter()
+        .enumerate()
+        .flat_map_iter(|(i, &normal)| {
+            let position = [
+                (i % RESOLUTION as usize) as f64,
+                (i / RESOLUTION as usize) as f64,
+                0.,
+            ];
+            let to_light = normalize(sub(light, position));
+            let half = normalize([to_light[0], to_light[1], to_light[2] + 1.]);
+            let diffuse = dot(normal, to_light).max(0.);
+            let specular = dot(normal, half).max(0.).powi(SHININESS);
+            ALBEDO
+                .map(|albedo| (albedo * (AMBIENT + diffuse) + 255. * specular * 0.5) as u8)
+                .into_iter()
+                .chain([255])
+        })
+        .collect()
+}
+
+pub fn draw(field: &[f64]) {
+    let strength = parse_value!(bump_strength, f64);
+    let (light_x, light_y) = LIGHT.with(|l| l.get());
+    let light = [light_x, light_y, parse_value!(bump_light_height, f64)];
+
+    draw_noise(&shade(&normals(field, strength), light));
+    draw_circle(light_x, light_y, 5., "#ffdd55");
+}
@@ -0,0 +1,72 @@
+use rayon::prelude::*;
+
+use crate::{
+    drawer::{RESOLUTION, draw_noise},
+    field::colorize,
+};
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum Filter {
+    GradientMagnitude,
+    Laplacian,
+    Emboss,
+}
+
+/// Reads the field with clamped edges.
+#[inline]
+fn at(field: &[f64], x: isize, y: isize) -> f64 {
+    let size = RESOLUTION as isize;
+    field[(y.clamp(0, size - 1) * size + x.clamp(0, size - 1)) as usize]
+}
+
+fn apply(field: &[f64], filter: Filter) -> Vec<f64> {
+    (0..(RESOLUTION * RESOLUTION) as usize)
+        .into_par_iter()
+        .map(|i| {
+            let x = (i % RESOLUTION as usize) as isize;
+            let y = (i / RESOLUTION as usize) as isize;
+            let f = |dx: isize, dy: isize| at(field, x + dx, y + dy);
+            match filter {
+                Filter::GradientMagnitude => {
+                    let gx =
+                        f(1, -1) + 2. * f(1, 0) + f(1, 1) - f(-1, -1) - 2. * f(-1, 0) - f(-1, 1);
+                    let gy =
+                        f(-1, 1) + 2. * f(0, 1) + f(1, 1) - f(-1, -1) - 2. * f(0, -1) - f(1, -1);
+                    (gx * gx + gy * gy).sqrt()
+                }
+                Filter::Laplacian => f(-1, 0) + f(1, 0) + f(0, -1) + f(0, 1) - 4. * f(0, 0),
+                Filter::Emboss => f(1, 1) - f(-1, -1),
+            }
+        })
+        .collect()
+}
+
+/// Scales the filter output so the 99th percentile of its magnitude maps to 1. Using the
+/// maximum instead would let a single crease (Worley cell edges) wash out the whole image.
+fn normalize(values: &mut [f64]) {
+    let mut magnitudes: Vec<f64> = values.iter().map(|v| v.abs()).collect();
+    let index = magnitudes.len() * 99 / 100;
+    let (_, &mut percentile, _) = magnitudes.select_nth_unstable_by(index, f64::total_cmp);
+    let percentile = percentile.max(1e-12);
+    values
+        .iter_mut()
+        .for_each(|v| *v = (*v / percentile).clamp(-1., 1.));
+}
+
+pub fn draw(field: &[f64], filter: Filter) {
+    let mut filtered = apply(field, filter);
+    normalize(&mut filtered);
+
+    let pixels = match filter {
+        Filter::GradientMagnitude | Filter::Laplacian => colorize(&filtered),
+        // Emboss reads best as a gray relief lit from the top left.
+        Filter::Emboss => filtered
+            .iter()
+            .flat_map(|v| {
+                let gray = (127.5 - v * 127.5) as u8;
+                [gray, gray, gray, 255]
+            })
+            .collect(),
+    };
+    draw_noise(&pixels);
+}
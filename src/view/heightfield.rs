@@ -0,0 +1,124 @@
+use std::cell::LazyCell;
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlElement, HtmlInputElement};
+
+use crate::{
+    camera::{OrbitCamera, Vec3, triangle_lighting},
+    drawer::{RESOLUTION, clear_canvas, draw_polyline, fill_polygon, value_to_color},
+    value_format::{ValueFormat, format_value},
+    *,
+};
+
+/// Number of field samples along each side of the terrain mesh.
+const HEIGHTFIELD_SAMPLES: usize = 64;
+const CAMERA_DISTANCE: f64 = 3.5;
+
+elements!(
+    (view_yaw, HtmlInputElement),
+    (view_yaw_display, HtmlElement),
+    (view_pitch, HtmlInputElement),
+    (view_pitch_display, HtmlElement),
+    (view_height, HtmlInputElement),
+    (view_height_display, HtmlElement),
+    (view_solid, HtmlInputElement),
+);
+define_closure!(heightfield_change, super::on_view_change);
+
+pub fn setup() {
+    add_callback!(view_yaw, "input", heightfield_change);
+    add_callback!(view_pitch, "input", heightfield_change);
+    add_callback!(view_height, "input", heightfield_change);
+    add_callback!(view_solid, "input", heightfield_change);
+}
+
+pub fn update_displays() {
+    let degrees = |value: f64| format_value(value, ValueFormat::Degrees);
+    set_text!(view_yaw, &degrees(parse_value!(view_yaw, f64)));
+    set_text!(view_pitch, &degrees(parse_value!(view_pitch, f64)));
+    set_text!(view_height, &parse_value!(view_height, f64).to_string());
+}
+
+/// Samples the retained field on a coarse grid, as terrain spanning [-1, 1] on the ground plane.
+fn terrain_vertices(field: &[f64], height: f64) -> Vec<Vec<(Vec3, f64)>> {
+    let step = (RESOLUTION - 1) as f64 / (HEIGHTFIELD_SAMPLES - 1) as f64;
+    (0..HEIGHTFIELD_SAMPLES)
+        .map(|row| {
+            (0..HEIGHTFIELD_SAMPLES)
+                .map(|column| {
+                    let px = (column as f64 * step).round() as usize;
+                    let py = (row as f64 * step).round() as usize;
+                    let value = field[py * RESOLUTION as usize + px];
+                    let to_unit = |v: usize| v as f64 / (HEIGHTFIELD_SAMPLES - 1) as f64 * 2. - 1.;
+                    ([to_unit(column), value * height * 0.5, to_unit(row)], value)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+pub fn draw(field: &[f64]) {
+    let camera = OrbitCamera::from_degrees(
+        parse_value!(view_yaw, f64),
+        parse_value!(view_pitch, f64),
+        CAMERA_DISTANCE,
+    );
+    let vertices = terrain_vertices(field, parse_value!(view_height, f64));
+
+    clear_canvas("#ffffff");
+    if is_checked!(view_solid) {
+        draw_solid(&camera, &vertices);
+    } else {
+        draw_wireframe(&camera, &vertices);
+    }
+}
+
+fn draw_wireframe(camera: &OrbitCamera, vertices: &[Vec<(Vec3, f64)>]) {
+    let project = |(position, _): &(Vec3, f64)| OrbitCamera::project(camera.to_view(*position));
+
+    for row in vertices {
+        let points: Vec<_> = row.iter().map(project).collect();
+        draw_polyline(&points, "#333333", 1.0);
+    }
+    for column in 0..HEIGHTFIELD_SAMPLES {
+        let points: Vec<_> = vertices.iter().map(|row| project(&row[column])).collect();
+        draw_polyline(&points, "#333333", 1.0);
+    }
+}
+
+/// Flat shaded terrain drawn back to front, colored with the same palette as the flat view.
+fn draw_solid(camera: &OrbitCamera, vertices: &[Vec<(Vec3, f64)>]) {
+    let mut faces = Vec::with_capacity(HEIGHTFIELD_SAMPLES * HEIGHTFIELD_SAMPLES * 2);
+    for row in 0..HEIGHTFIELD_SAMPLES - 1 {
+        for column in 0..HEIGHTFIELD_SAMPLES - 1 {
+            let a = vertices[row][column];
+            let b = vertices[row][column + 1];
+            let c = vertices[row + 1][column + 1];
+            let d = vertices[row + 1][column];
+            for triangle in [[a, b, c], [a, c, d]] {
+                let view = triangle.map(|(position, _)| camera.to_view(position));
+                let depth = (view[0][2] + view[1][2] + view[2][2]) / 3.;
+                let value = (triangle[0].1 + triangle[1].1 + triangle[2].1) / 3.;
+                faces.push((
+                    depth,
+                    value,
+                    triangle_lighting(&view),
+                    view.map(OrbitCamera::project),
+                ));
+            }
+        }
+    }
+    faces.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    for (_, value, light_amount, points) in faces {
+        let shade = 0.35 + 0.65 * light_amount;
+        let [r, g, b, _] = value_to_color(value.clamp(-1., 1.));
+        let style = format!(
+            "rgb({}, {}, {})",
+            (r as f64 * shade) as u8,
+            (g as f64 * shade) as u8,
+            (b as f64 * shade) as u8
+        );
+        fill_polygon(&points, &style);
+    }
+}
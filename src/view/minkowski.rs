@@ -0,0 +1,147 @@
+//! The Minkowski functionals of the thresholded field: area, perimeter and Euler characteristic.
+
+use std::cell::LazyCell;
+
+use rayon::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{HtmlCanvasElement, HtmlElement};
+
+use crate::{charts::Chart, drawer::RESOLUTION, *};
+
+const SIZE: usize = RESOLUTION as usize;
+/// Thresholds sampled across [-1, 1] for the chart.
+const LEVELS: usize = 41;
+const AREA_COLOR: &str = "#1f77b4";
+const BOUNDARY_COLOR: &str = "#d62728";
+const EULER_COLOR: &str = "#2ca02c";
+
+elements!(
+    (minkowski_chart, HtmlCanvasElement),
+    (minkowski_readout, HtmlElement),
+);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Functionals {
+    /// Share of the pixels above the threshold.
+    area: f64,
+    /// Pixel sides between covered and uncovered pixels, per pixel of the canvas.
+    boundary: f64,
+    /// Pieces minus holes, with pixels joined through their sides.
+    euler: i64,
+}
+
+/// Counts every 2x2 window of the covered pixels, with the canvas padded by uncovered ones. By
+/// Gray's bit quads, windows with one covered pixel add a quarter to the Euler characteristic,
+/// windows with three take a quarter away and the two diagonal ones add a half.
+fn functionals(field: &[f64], threshold: f64) -> Functionals {
+    let covered = |x: isize, y: isize| {
+        (0..SIZE as isize).contains(&x)
+            && (0..SIZE as isize).contains(&y)
+            && field[y as usize * SIZE + x as usize] > threshold
+    };
+    let (mut area, mut boundary, mut quarters) = (0, 0, 0);
+    for y in -1..SIZE as isize {
+        for x in -1..SIZE as isize {
+            let window = [
+                covered(x, y),
+                covered(x + 1, y),
+                covered(x, y + 1),
+                covered(x + 1, y + 1),
+            ];
+            area += window[3] as usize;
+            // Every pixel side is counted once, as the right or the bottom side of a window.
+            boundary += (window[1] != window[3]) as usize + (window[2] != window[3]) as usize;
+            quarters += match window.iter().filter(|&&c| c).count() {
+                1 => 1,
+                3 => -1,
+                2 if window[0] == window[3] => 2,
+                _ => 0,
+            };
+        }
+    }
+    let pixels = (SIZE * SIZE) as f64;
+    Functionals {
+        area: area as f64 / pixels,
+        boundary: boundary as f64 / pixels,
+        euler: quarters / 4,
+    }
+}
+
+/// Charts the functionals against the threshold, each stretched to the height of the chart, and
+/// marks the threshold of the view.
+pub fn draw(field: &[f64], threshold: f64) {
+    let levels: Vec<f64> = (0..LEVELS)
+        .map(|i| -1. + 2. * i as f64 / (LEVELS - 1) as f64)
+        .collect();
+    let curves: Vec<Functionals> = levels
+        .par_iter()
+        .map(|&level| functionals(field, level))
+        .collect();
+    let current = functionals(field, threshold);
+
+    let chart = MINKOWSKI_CHART.with(|c| Chart::new(c));
+    chart.clear("#ffffff");
+    let to_x = |level: f64| (level + 1.) / 2. * chart.width;
+    // Values from -1 at the bottom to 1 at the top, so a negative Euler characteristic fits.
+    let to_y = |value: f64| (1. - value) / 2. * chart.height;
+    chart.line((0., to_y(0.)), (chart.width, to_y(0.)), "#cccccc");
+    let longest = curves.iter().fold(0f64, |m, f| m.max(f.boundary));
+    let most = curves.iter().fold(0f64, |m, f| m.max(f.euler.abs() as f64));
+    let stretch = |value: f64, largest: f64| if largest > 0. { value / largest } else { 0. };
+    let curve = |value: &dyn Fn(&Functionals) -> f64| -> Vec<(f64, f64)> {
+        levels
+            .iter()
+            .zip(&curves)
+            .map(|(&level, f)| (to_x(level), to_y(value(f))))
+            .collect()
+    };
+    chart.polyline(&curve(&|f| f.area), AREA_COLOR);
+    chart.polyline(&curve(&|f| stretch(f.boundary, longest)), BOUNDARY_COLOR);
+    chart.polyline(&curve(&|f| stretch(f.euler as f64, most)), EULER_COLOR);
+    chart.line(
+        (to_x(threshold), 0.),
+        (to_x(threshold), chart.height),
+        "#000000",
+    );
+    chart.text("area", 4., 12., AREA_COLOR);
+    chart.text("boundary", 4., 24., BOUNDARY_COLOR);
+    chart.text("Euler", 4., 36., EULER_COLOR);
+
+    MINKOWSKI_READOUT.with(|r| {
+        r.set_inner_text(&format!(
+            "Area fraction {:.3}, boundary length {:.4} per pixel, Euler characteristic {}",
+            current.area, current.boundary, current.euler
+        ))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn square_and_ring() {
+        let square = |x: usize, y: usize, from: usize, to: usize| {
+            (from..to).contains(&x) && (from..to).contains(&y)
+        };
+        let field = |inside: &dyn Fn(usize, usize) -> bool| -> Vec<f64> {
+            (0..SIZE * SIZE)
+                .map(|i| if inside(i % SIZE, i / SIZE) { 1. } else { -1. })
+                .collect()
+        };
+        let pixels = (SIZE * SIZE) as f64;
+
+        let solid = functionals(&field(&|x, y| square(x, y, 100, 150)), 0.);
+        assert_eq!(solid.area, 2500. / pixels);
+        assert_eq!(solid.boundary, 200. / pixels);
+        assert_eq!(solid.euler, 1);
+
+        let ring = functionals(
+            &field(&|x, y| square(x, y, 100, 150) && !square(x, y, 110, 130)),
+            0.,
+        );
+        assert_eq!(ring.euler, 0);
+        assert_eq!(functionals(&field(&|_, _| true), 0.).euler, 1);
+        assert_eq!(functionals(&field(&|_, _| false), 0.).euler, 0);
+    }
+}
@@ -0,0 +1,116 @@
+use std::cell::LazyCell;
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlElement, HtmlSelectElement};
+
+use crate::{drawer::RESOLUTION, field::FIELD, view::filters::Filter, *};
+
+mod bump;
+mod filters;
+mod heightfield;
+mod minkowski;
+mod scatter;
+mod sdf;
+mod stipple;
+mod threshold;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum ViewMode {
+    Flat,
+    Heightfield,
+    Stipple,
+    Scatter,
+    Bump,
+    Sdf,
+    Threshold,
+    Filtered(Filter),
+}
+
+elements!(
+    (view_mode, HtmlSelectElement),
+    (heightfield_control, HtmlElement),
+    (stipple_control, HtmlElement),
+    (scatter_control, HtmlElement),
+    (bump_control, HtmlElement),
+    (sdf_control, HtmlElement),
+    (threshold_view_control, HtmlElement),
+    (filter_control, HtmlElement),
+);
+define_closure!(view_change, on_view_change);
+
+pub fn setup() {
+    add_callback!(view_mode, "input", view_change);
+    heightfield::setup();
+    stipple::setup();
+    scatter::setup();
+    bump::setup();
+    sdf::setup();
+    threshold::setup();
+    update_controls();
+}
+
+pub fn current() -> ViewMode {
+    match parse_value!(view_mode, String).as_str() {
+        "heightfield" => ViewMode::Heightfield,
+        "stipple" => ViewMode::Stipple,
+        "scatter" => ViewMode::Scatter,
+        "bump" => ViewMode::Bump,
+        "sdf" => ViewMode::Sdf,
+        "threshold" => ViewMode::Threshold,
+        "gradient" => ViewMode::Filtered(Filter::GradientMagnitude),
+        "laplacian" => ViewMode::Filtered(Filter::Laplacian),
+        "emboss" => ViewMode::Filtered(Filter::Emboss),
+        _ => ViewMode::Flat,
+    }
+}
+
+/// Canvas overlays that map pixels back to noise coordinates only make sense in the flat view.
+pub fn is_flat() -> bool {
+    current() == ViewMode::Flat
+}
+
+/// Called after every noise redraw, replaces the flat image with the selected view of the retained field.
+pub fn on_frame_drawn() {
+    let mode = current();
+    if mode == ViewMode::Flat {
+        return;
+    }
+
+    let field = FIELD.with(|f| f.borrow().clone());
+    if field.len() != (RESOLUTION * RESOLUTION) as usize {
+        return;
+    }
+
+    match mode {
+        ViewMode::Flat => (),
+        ViewMode::Heightfield => heightfield::draw(&field),
+        ViewMode::Stipple => stipple::draw(&field),
+        ViewMode::Scatter => scatter::draw(&field),
+        ViewMode::Bump => bump::draw(&field),
+        ViewMode::Sdf => sdf::draw(&field),
+        ViewMode::Threshold => threshold::draw(&field),
+        ViewMode::Filtered(filter) => filters::draw(&field, filter),
+    }
+}
+
+fn on_view_change() {
+    update_controls();
+    update_current_noise();
+}
+
+pub fn update_controls() {
+    let mode = current();
+    HEIGHTFIELD_CONTROL.with(|c| c.set_hidden(mode != ViewMode::Heightfield));
+    STIPPLE_CONTROL.with(|c| c.set_hidden(mode != ViewMode::Stipple));
+    SCATTER_CONTROL.with(|c| c.set_hidden(mode != ViewMode::Scatter));
+    BUMP_CONTROL.with(|c| c.set_hidden(mode != ViewMode::Bump));
+    SDF_CONTROL.with(|c| c.set_hidden(mode != ViewMode::Sdf));
+    THRESHOLD_VIEW_CONTROL.with(|c| c.set_hidden(mode != ViewMode::Threshold));
+    FILTER_CONTROL.with(|c| c.set_hidden(!matches!(mode, ViewMode::Filtered(_))));
+    heightfield::update_displays();
+    stipple::update_displays();
+    scatter::update_displays();
+    bump::update_displays();
+    sdf::update_displays();
+    threshold::update_displays();
+}
@@ -0,0 +1,117 @@
+use std::cell::LazyCell;
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlElement, HtmlInputElement, HtmlSelectElement};
+
+use crate::{
+    drawer::{RESOLUTION, clear_canvas, draw_circle, fill_polygon},
+    *,
+};
+
+/// Fixed seed for the jitter, so objects stay in place while the threshold changes.
+const JITTER_SEED: i32 = 4242;
+
+elements!(
+    (scatter_threshold, HtmlInputElement),
+    (scatter_threshold_display, HtmlElement),
+    (scatter_jitter, HtmlInputElement),
+    (scatter_jitter_display, HtmlElement),
+    (scatter_spacing, HtmlInputElement),
+    (scatter_spacing_display, HtmlElement),
+    (scatter_sprite, HtmlSelectElement),
+    (scatter_show_field, HtmlInputElement),
+);
+define_closure!(scatter_change, super::on_view_change);
+
+pub fn setup() {
+    add_callback!(scatter_threshold, "input", scatter_change);
+    add_callback!(scatter_jitter, "input", scatter_change);
+    add_callback!(scatter_spacing, "input", scatter_change);
+    add_callback!(scatter_sprite, "input", scatter_change);
+    add_callback!(scatter_show_field, "input", scatter_change);
+}
+
+pub fn update_displays() {
+    set_text!(
+        scatter_threshold,
+        &parse_value!(scatter_threshold, f64).to_string()
+    );
+    set_text!(
+        scatter_jitter,
+        &parse_value!(scatter_jitter, f64).to_string()
+    );
+    set_text!(
+        scatter_spacing,
+        &parse_value!(scatter_spacing, u32).to_string()
+    );
+}
+
+/// One object per grid cell, moved randomly inside its cell by up to `jitter` of the spacing,
+/// and kept only where the field at its final position is above `threshold`.
+/// Returned with the field value at the object, sorted top to bottom for painter's order.
+fn place_objects(field: &[f64], spacing: u32, jitter: f64, threshold: f64) -> Vec<(f64, f64, f64)> {
+    let cells = (RESOLUTION / spacing) as i32;
+    let mut objects: Vec<_> = (0..cells * cells)
+        .filter_map(|i| {
+            let offset = |channel: i32| {
+                squirrel_noise5::f32_neg_one_to_one_1d(i * 2 + channel, JITTER_SEED) as f64
+                    * 0.5
+                    * jitter
+            };
+            let x = ((i % cells) as f64 + 0.5 + offset(0)) * spacing as f64;
+            let y = ((i / cells) as f64 + 0.5 + offset(1)) * spacing as f64;
+            let pixel = |v: f64| (v as usize).min(RESOLUTION as usize - 1);
+            let value = field[pixel(y) * RESOLUTION as usize + pixel(x)];
+            (value > threshold).then_some((x, y, value))
+        })
+        .collect();
+    objects.sort_by(|a, b| a.1.total_cmp(&b.1));
+    objects
+}
+
+fn draw_tree(x: f64, y: f64, size: f64) {
+    let trunk = size * 0.15;
+    fill_polygon(
+        &[
+            (x - trunk, y),
+            (x + trunk, y),
+            (x + trunk, y + size * 0.3),
+            (x - trunk, y + size * 0.3),
+        ],
+        "#6b4423",
+    );
+    fill_polygon(
+        &[
+            (x, y - size),
+            (x + size * 0.5, y + size * 0.1),
+            (x - size * 0.5, y + size * 0.1),
+        ],
+        "#1e6b2a",
+    );
+}
+
+fn draw_bush(x: f64, y: f64, size: f64) {
+    draw_circle(x, y, size * 0.5, "#2e8b3a");
+    draw_circle(x - size * 0.15, y - size * 0.15, size * 0.2, "#4caf50");
+}
+
+pub fn draw(field: &[f64]) {
+    let spacing = parse_value!(scatter_spacing, u32).max(2);
+    let threshold = parse_value!(scatter_threshold, f64);
+    let objects = place_objects(field, spacing, parse_value!(scatter_jitter, f64), threshold);
+
+    if !is_checked!(scatter_show_field) {
+        clear_canvas("#e8dcb5");
+    }
+
+    let sprite = parse_value!(scatter_sprite, String);
+    for (x, y, value) in objects {
+        // Objects grow with how far the field is above the threshold.
+        let size = spacing as f64
+            * (0.6 + 0.6 * ((value - threshold) / (1. - threshold).max(1e-6)).min(1.));
+        match sprite.as_str() {
+            "circles" => draw_bush(x, y, size),
+            _ => draw_tree(x, y, size),
+        }
+    }
+}
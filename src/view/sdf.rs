@@ -0,0 +1,91 @@
+use std::cell::LazyCell;
+
+use rayon::prelude::*;
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlElement, HtmlInputElement};
+
+use crate::{
+    drawer::{RESOLUTION, draw_noise},
+    *,
+};
+
+/// Number of contour bands across one distance unit, the width of the canvas.
+const BANDS_PER_UNIT: f64 = 25.;
+const OUTSIDE_COLOR: [f64; 3] = [0.9, 0.6, 0.3];
+const INSIDE_COLOR: [f64; 3] = [0.65, 0.85, 1.];
+
+elements!(
+    (sdf_threshold, HtmlInputElement),
+    (sdf_threshold_display, HtmlElement),
+    (sdf_readout, HtmlElement),
+);
+define_closure!(sdf_change, super::on_view_change);
+
+pub fn setup() {
+    add_callback!(sdf_threshold, "input", sdf_change);
+}
+
+pub fn update_displays() {
+    set_text!(sdf_threshold, &parse_value!(sdf_threshold, f64).to_string());
+}
+
+/// Reads the field with clamped edges.
+#[inline]
+fn at(field: &[f64], x: isize, y: isize) -> f64 {
+    let size = RESOLUTION as isize;
+    field[(y.clamp(0, size - 1) * size + x.clamp(0, size - 1)) as usize]
+}
+
+/// Length of the gradient of every pixel, with distances measured in canvas widths. A true signed
+/// distance field has a gradient of length one everywhere.
+fn gradient_norms(field: &[f64]) -> Vec<f64> {
+    (0..(RESOLUTION * RESOLUTION) as usize)
+        .into_par_iter()
+        .map(|i| {
+            let x = (i % RESOLUTION as usize) as isize;
+            let y = (i / RESOLUTION as usize) as isize;
+            let dx = (at(field, x + 1, y) - at(field, x - 1, y)) / 2. * RESOLUTION as f64;
+            let dy = (at(field, x, y + 1) - at(field, x, y - 1)) / 2. * RESOLUTION as f64;
+            (dx * dx + dy * dy).sqrt()
+        })
+        .collect()
+}
+
+/// The usual signed distance palette: orange outside, blue inside, darker towards the surface,
+/// with rings every band and a white line along the zero contour.
+fn distance_color(distance: f64) -> [u8; 4] {
+    let base = if distance > 0. {
+        OUTSIDE_COLOR
+    } else {
+        INSIDE_COLOR
+    };
+    let falloff = 1. - (-6. * distance.abs()).exp();
+    let rings = 0.8 + 0.2 * (distance * BANDS_PER_UNIT * std::f64::consts::TAU).cos();
+    // Half a pixel either side of zero is drawn as the contour itself.
+    let contour = 1. - (distance.abs() * RESOLUTION as f64 - 0.5).clamp(0., 1.);
+    let channel = |c: f64| ((c * falloff * rings) * (1. - contour) + contour) * 255.;
+    [
+        channel(base[0]) as u8,
+        channel(base[1]) as u8,
+        channel(base[2]) as u8,
+        255,
+    ]
+}
+
+pub fn draw(field: &[f64]) {
+    let threshold = parse_value!(sdf_threshold, f64);
+    let pixels: Vec<u8> = field
+        .iter()
+        .flat_map(|value| distance_color(value - threshold))
+        .collect();
+    draw_noise(&pixels);
+
+    let norms = gradient_norms(field);
+    let deviation = norms.iter().map(|n| (n - 1.).abs()).sum::<f64>() / norms.len() as f64;
+    let mean = norms.iter().sum::<f64>() / norms.len() as f64;
+    SDF_READOUT.with(|r| {
+        r.set_inner_text(&format!(
+            "Mean gradient length {mean:.3}, mean deviation from 1 is {deviation:.3}"
+        ))
+    });
+}
@@ -0,0 +1,118 @@
+use std::cell::LazyCell;
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlElement, HtmlInputElement, HtmlSelectElement};
+
+use crate::{
+    drawer::{RESOLUTION, clear_canvas, draw_circle},
+    *,
+};
+
+/// Fixed seed for rejection sampling, so the dots do not flicker while other controls change.
+const REJECTION_SEED: i32 = 1337;
+
+elements!(
+    (stipple_spacing, HtmlInputElement),
+    (stipple_spacing_display, HtmlElement),
+    (stipple_method, HtmlSelectElement),
+);
+define_closure!(stipple_change, super::on_view_change);
+
+pub fn setup() {
+    add_callback!(stipple_spacing, "input", stipple_change);
+    add_callback!(stipple_method, "input", stipple_change);
+}
+
+pub fn update_displays() {
+    set_text!(
+        stipple_spacing,
+        &parse_value!(stipple_spacing, u32).to_string()
+    );
+}
+
+/// Maps a noise value to the probability of a dot, so the highest values are fully covered.
+#[inline]
+fn density(value: f64) -> f64 {
+    ((value + 1.) * 0.5).clamp(0., 1.)
+}
+
+/// Averages the field over square cells of `spacing` pixels.
+fn cell_densities(field: &[f64], spacing: u32) -> (usize, Vec<f64>) {
+    let cells = (RESOLUTION / spacing) as usize;
+    let mut densities = vec![0.; cells * cells];
+    for (i, d) in densities.iter_mut().enumerate() {
+        let (cx, cy) = ((i % cells) as u32 * spacing, (i / cells) as u32 * spacing);
+        let mut sum = 0.;
+        for y in cy..cy + spacing {
+            for x in cx..cx + spacing {
+                sum += density(field[(y * RESOLUTION + x) as usize]);
+            }
+        }
+        *d = sum / (spacing * spacing) as f64;
+    }
+    (cells, densities)
+}
+
+/// Floyd-Steinberg dithering of the cell densities, places one dot per cell that rounds up.
+fn error_diffusion(field: &[f64], spacing: u32) -> Vec<(f64, f64)> {
+    let (cells, mut densities) = cell_densities(field, spacing);
+    let mut dots = Vec::new();
+
+    for y in 0..cells {
+        for x in 0..cells {
+            let old = densities[y * cells + x];
+            let new = if old >= 0.5 { 1. } else { 0. };
+            let error = old - new;
+            if new == 1. {
+                let half = spacing as f64 * 0.5;
+                dots.push((
+                    x as f64 * spacing as f64 + half,
+                    y as f64 * spacing as f64 + half,
+                ));
+            }
+
+            let mut spread = |dx: isize, dy: usize, weight: f64| {
+                let nx = x as isize + dx;
+                let ny = y + dy;
+                if nx >= 0 && (nx as usize) < cells && ny < cells {
+                    densities[ny * cells + nx as usize] += error * weight;
+                }
+            };
+            spread(1, 0, 7. / 16.);
+            spread(-1, 1, 3. / 16.);
+            spread(0, 1, 5. / 16.);
+            spread(1, 1, 1. / 16.);
+        }
+    }
+    dots
+}
+
+/// Throws one random candidate per cell and keeps it with probability equal to the local density.
+fn rejection_sampling(field: &[f64], spacing: u32) -> Vec<(f64, f64)> {
+    let cells = (RESOLUTION / spacing) as i32;
+    (0..cells * cells)
+        .filter_map(|i| {
+            let random = |channel: i32| {
+                squirrel_noise5::f32_zero_to_one_1d(i * 3 + channel, REJECTION_SEED) as f64
+            };
+            let x = random(0) * (RESOLUTION - 1) as f64;
+            let y = random(1) * (RESOLUTION - 1) as f64;
+            let value = field[y as usize * RESOLUTION as usize + x as usize];
+            (random(2) < density(value)).then_some((x, y))
+        })
+        .collect()
+}
+
+pub fn draw(field: &[f64]) {
+    let spacing = parse_value!(stipple_spacing, u32).max(1);
+    let dots = match parse_value!(stipple_method, String).as_str() {
+        "rejection" => rejection_sampling(field, spacing),
+        _ => error_diffusion(field, spacing),
+    };
+
+    clear_canvas("#ffffff");
+    let radius = spacing as f64 * 0.4;
+    for (x, y) in dots {
+        draw_circle(x, y, radius, "#000000");
+    }
+}
@@ -0,0 +1,188 @@
+use std::cell::LazyCell;
+
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlElement, HtmlInputElement};
+
+use crate::{
+    drawer::{RESOLUTION, draw_noise},
+    *,
+};
+
+const SIZE: usize = RESOLUTION as usize;
+const COLOR_SEED: u32 = 0xc0c;
+const EMPTY_COLOR: [u8; 4] = [24, 24, 24, 255];
+/// Components spanning the canvas are drawn in white so they stand out from the rest.
+const SPANNING_COLOR: [u8; 4] = [255, 255, 255, 255];
+
+elements!(
+    (threshold_level, HtmlInputElement),
+    (threshold_level_display, HtmlElement),
+    (threshold_readout, HtmlElement),
+);
+define_closure!(threshold_change, super::on_view_change);
+
+pub fn setup() {
+    add_callback!(threshold_level, "input", threshold_change);
+}
+
+pub fn update_displays() {
+    set_text!(
+        threshold_level,
+        &parse_value!(threshold_level, f64).to_string()
+    );
+}
+
+/// The regions of the field above a threshold, joined through the four side neighbours of every
+/// pixel.
+struct Components {
+    /// Component of every pixel, none below the threshold.
+    labels: Vec<Option<usize>>,
+    /// Pixels in every component.
+    sizes: Vec<usize>,
+    /// Components touching both the left and the right edge.
+    spans_across: Vec<bool>,
+    /// Components touching both the top and the bottom edge.
+    spans_down: Vec<bool>,
+}
+
+fn components(field: &[f64], threshold: f64) -> Components {
+    let mut labels = vec![None; field.len()];
+    let mut sizes = Vec::new();
+    let (mut spans_across, mut spans_down) = (Vec::new(), Vec::new());
+    let mut stack = Vec::new();
+    for start in 0..field.len() {
+        if labels[start].is_some() || field[start] <= threshold {
+            continue;
+        }
+        let label = sizes.len();
+        let (mut size, mut edges) = (0, [false; 4]);
+        labels[start] = Some(label);
+        stack.push(start);
+        while let Some(i) = stack.pop() {
+            let (x, y) = (i % SIZE, i / SIZE);
+            size += 1;
+            edges[0] |= x == 0;
+            edges[1] |= x == SIZE - 1;
+            edges[2] |= y == 0;
+            edges[3] |= y == SIZE - 1;
+            let neighbours = [
+                (x > 0).then(|| i - 1),
+                (x < SIZE - 1).then(|| i + 1),
+                (y > 0).then(|| i - SIZE),
+                (y < SIZE - 1).then(|| i + SIZE),
+            ];
+            for n in neighbours.into_iter().flatten() {
+                if labels[n].is_none() && field[n] > threshold {
+                    labels[n] = Some(label);
+                    stack.push(n);
+                }
+            }
+        }
+        sizes.push(size);
+        spans_across.push(edges[0] && edges[1]);
+        spans_down.push(edges[2] && edges[3]);
+    }
+    Components {
+        labels,
+        sizes,
+        spans_across,
+        spans_down,
+    }
+}
+
+/// Number of components in every size class, the classes doubling from single pixels up.
+fn size_classes(sizes: &[usize]) -> Vec<usize> {
+    let mut classes = Vec::new();
+    for &size in sizes {
+        let class = size.ilog2() as usize;
+        if classes.len() <= class {
+            classes.resize(class + 1, 0);
+        }
+        classes[class] += 1;
+    }
+    classes
+}
+
+fn readout(components: &Components) -> String {
+    let covered: usize = components.sizes.iter().sum();
+    let coverage = covered as f64 / (SIZE * SIZE) as f64 * 100.;
+    let count = components.sizes.len();
+    let largest = components.sizes.iter().max().copied().unwrap_or(0);
+    let share = if covered > 0 {
+        largest as f64 / covered as f64 * 100.
+    } else {
+        0.
+    };
+    let across = components.spans_across.contains(&true);
+    let down = components.spans_down.contains(&true);
+    let percolation = match (across, down) {
+        (true, true) => "percolates left to right and top to bottom",
+        (true, false) => "percolates left to right only",
+        (false, true) => "percolates top to bottom only",
+        (false, false) => "does not percolate",
+    };
+    let classes: Vec<String> = size_classes(&components.sizes)
+        .iter()
+        .enumerate()
+        .filter(|(_, n)| **n > 0)
+        .map(|(class, n)| match class {
+            0 => format!("1 px: {n}"),
+            _ => format!("{}-{} px: {n}", 1 << class, (2 << class) - 1),
+        })
+        .collect();
+    format!(
+        "Coverage {coverage:.1}%, {count} components, the largest holds {share:.1}% of the \
+         covered pixels and the field {percolation}. Sizes: {}",
+        classes.join(", ")
+    )
+}
+
+/// Paints every component above the threshold in its own color, and the ones that span the
+/// canvas in white.
+pub fn draw(field: &[f64]) {
+    let threshold = parse_value!(threshold_level, f64);
+    let components = components(field, threshold);
+    let rng = rng::current();
+    let pixels: Vec<u8> = components
+        .labels
+        .iter()
+        .flat_map(|label| match *label {
+            None => EMPTY_COLOR,
+            Some(l) if components.spans_across[l] || components.spans_down[l] => SPANNING_COLOR,
+            Some(l) => {
+                let [r, g, b, _] = rng.bits(l as u32, COLOR_SEED).to_le_bytes();
+                [64 + r / 2, 64 + g / 2, 64 + b / 2, 255]
+            }
+        })
+        .collect();
+    draw_noise(&pixels);
+    THRESHOLD_READOUT.with(|r| r.set_inner_text(&readout(&components)));
+    super::minkowski::draw(field, threshold);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stripes_span_and_dots_do_not() {
+        // A vertical bar three pixels wide and a single pixel apart from it.
+        let field: Vec<f64> = (0..SIZE * SIZE)
+            .map(|i| {
+                let (x, y) = (i % SIZE, i / SIZE);
+                if (10..13).contains(&x) || (x, y) == (50, 50) {
+                    1.
+                } else {
+                    -1.
+                }
+            })
+            .collect();
+        let components = components(&field, 0.);
+        assert_eq!(components.sizes.len(), 2);
+        assert_eq!(components.sizes[0], 3 * SIZE);
+        assert_eq!(components.spans_down, [true, false]);
+        assert_eq!(components.spans_across, [false, false]);
+        assert_eq!(size_classes(&components.sizes)[0], 1);
+        assert!(readout(&components).contains("top to bottom only"));
+    }
+}
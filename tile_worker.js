@@ -0,0 +1,28 @@
+// Renders images larger than the canvas off the page's thread, the page keeps responding while
+// the rayon workers compute the tiles.
+//
+// const worker = new Worker("./tile_worker.js", { type: "module" });
+// worker.onmessage = ({ data }) => { /* { tile } | { done, total } | { error } */ };
+// worker.postMessage({ settings: { noise: "perlin", octaves: 6 }, width: 8192, height: 8192 });
+//
+// Every tile comes as { x, y, width, height, values } with the values row by row in a
+// Float32Array, followed by the number of tiles done and in total.
+import init, { initThreadPool, render_tiles } from "./pkg/seeing_noise.js";
+
+const ready = init().then(() => initThreadPool(navigator.hardwareConcurrency));
+
+onmessage = async ({ data }) => {
+  await ready;
+  try {
+    render_tiles(
+      data.settings,
+      data.width,
+      data.height,
+      data.tileSize ?? 512,
+      (tile) => postMessage({ tile }, [tile.values.buffer]),
+      (done, total) => postMessage({ done, total }),
+    );
+  } catch (error) {
+    postMessage({ error: String(error) });
+  }
+};